@@ -0,0 +1,105 @@
+use crate::analysis::indexer::Indexer;
+use crate::core::database::ArborDatabase;
+use std::path::PathBuf;
+
+/// Finds indexed source files whose content no longer matches the hash
+/// recorded in the symbol index, and the analyzed functions they affect.
+pub struct ChangedFilesDetector;
+
+impl ChangedFilesDetector {
+    /// Files that were modified or deleted since they were last indexed,
+    /// in sorted order.
+    pub fn detect(db: &ArborDatabase) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = db
+            .symbol_index
+            .file_hashes
+            .iter()
+            .filter(|(path, stored_hash)| match Indexer::hash_file(path) {
+                Ok(hash) => &hash != *stored_hash,
+                Err(_) => true,
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        changed.sort();
+        changed
+    }
+
+    /// Analyzed functions defined in, or raising from, any of `changed`, or
+    /// a file inside a changed archive.
+    pub fn affected_functions(db: &ArborDatabase, changed: &[PathBuf]) -> Vec<String> {
+        let is_changed = |file: &PathBuf| changed.iter().any(|path| file.starts_with(path));
+
+        let mut functions: Vec<String> = db
+            .functions
+            .values()
+            .filter(|analysis| {
+                is_changed(&analysis.location.file)
+                    || analysis.raises.iter().any(|r| is_changed(&r.raise_location.file))
+            })
+            .map(|analysis| analysis.function_id.clone())
+            .collect();
+
+        functions.sort();
+        functions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::Environment;
+    use crate::core::types::{CodeLocation, FunctionAnalysis, RaiseStatement};
+
+    #[test]
+    fn test_detect_changed_files_and_affected_functions() {
+        let dir = tempfile::tempdir().unwrap();
+        let unchanged = dir.path().join("stable.py");
+        let modified = dir.path().join("modified.py");
+        let deleted = dir.path().join("deleted.py");
+        std::fs::write(&unchanged, "def a(): pass\n").unwrap();
+        std::fs::write(&modified, "def b(): pass\n").unwrap();
+
+        let mut db = ArborDatabase::new(Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        });
+        db.symbol_index
+            .set_file_hash(unchanged.clone(), Indexer::hash_content("def a(): pass\n"));
+        db.symbol_index
+            .set_file_hash(modified.clone(), Indexer::hash_content("def b(): pass\n"));
+        db.symbol_index
+            .set_file_hash(deleted.clone(), Indexer::hash_content("def c(): pass\n"));
+        std::fs::write(&modified, "def b():\n    raise ValueError\n").unwrap();
+
+        let changed = ChangedFilesDetector::detect(&db);
+        let mut expected = vec![deleted.clone(), modified.clone()];
+        expected.sort();
+        assert_eq!(changed, expected);
+
+        db.add_function(FunctionAnalysis::new(
+            "stable.a".to_string(),
+            "def a()".to_string(),
+            CodeLocation::new(unchanged.clone(), 1),
+        ));
+        let mut caller = FunctionAnalysis::new(
+            "stable.caller".to_string(),
+            "def caller()".to_string(),
+            CodeLocation::new(unchanged, 5),
+        );
+        caller.raises.push(RaiseStatement::new(
+            "ValueError".to_string(),
+            "ValueError".to_string(),
+            CodeLocation::new(modified, 2),
+        ));
+        db.add_function(caller);
+
+        assert_eq!(
+            ChangedFilesDetector::affected_functions(&db, &changed),
+            vec!["stable.caller".to_string()]
+        );
+    }
+}
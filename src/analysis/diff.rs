@@ -0,0 +1,142 @@
+use crate::core::database::ArborDatabase;
+use crate::core::types::FunctionAnalysis;
+
+/// How one function's analysis differs between two databases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionDiff {
+    pub function_id: String,
+    /// False when the older database has no analysis for the function.
+    pub in_old: bool,
+    /// Exception types raised now but not before.
+    pub added_exceptions: Vec<String>,
+    /// Exception types raised before but not now.
+    pub removed_exceptions: Vec<String>,
+    pub none_sources_before: usize,
+    pub none_sources_after: usize,
+}
+
+impl FunctionDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.in_old
+            && self.added_exceptions.is_empty()
+            && self.removed_exceptions.is_empty()
+            && self.none_sources_before == self.none_sources_after
+    }
+
+    /// Markdown lines describing the change, empty when nothing changed.
+    pub fn lines(&self) -> Vec<String> {
+        if !self.in_old {
+            return vec![format!("- `{}`: not in comparison database", self.function_id)];
+        }
+        let mut lines = Vec::new();
+        for exc in &self.added_exceptions {
+            lines.push(format!("- `{}`: + `{}`", self.function_id, exc));
+        }
+        for exc in &self.removed_exceptions {
+            lines.push(format!("- `{}`: - `{}`", self.function_id, exc));
+        }
+        if self.none_sources_before != self.none_sources_after {
+            lines.push(format!(
+                "- `{}`: None sources {} → {}",
+                self.function_id, self.none_sources_before, self.none_sources_after
+            ));
+        }
+        lines
+    }
+}
+
+/// Exception types escaping `analysis`, sorted and deduplicated. Suppressed
+/// raises and bare re-raises are left out.
+fn escaping_exceptions(analysis: &FunctionAnalysis) -> Vec<&str> {
+    let mut types: Vec<&str> = analysis
+        .raises
+        .iter()
+        .filter(|r| r.suppressed_by.is_none() && r.exception_type != "(re-raise)")
+        .map(|r| r.exception_type.as_str())
+        .collect();
+    types.sort();
+    types.dedup();
+    types
+}
+
+/// Diffs each of `function_ids` in `new` against its counterpart in `old`.
+/// Functions not analyzed in `new` are skipped.
+pub fn diff_databases(old: &ArborDatabase, new: &ArborDatabase, function_ids: &[String]) -> Vec<FunctionDiff> {
+    function_ids
+        .iter()
+        .filter_map(|id| new.get_function(id))
+        .map(|after| {
+            let now = escaping_exceptions(after);
+            let Some(before) = old.get_function(&after.function_id) else {
+                return FunctionDiff {
+                    function_id: after.function_id.clone(),
+                    in_old: false,
+                    added_exceptions: vec![],
+                    removed_exceptions: vec![],
+                    none_sources_before: 0,
+                    none_sources_after: after.none_source_count(),
+                };
+            };
+            let then = escaping_exceptions(before);
+            FunctionDiff {
+                function_id: after.function_id.clone(),
+                in_old: true,
+                added_exceptions: now.iter().filter(|e| !then.contains(e)).map(|e| e.to_string()).collect(),
+                removed_exceptions: then.iter().filter(|e| !now.contains(e)).map(|e| e.to_string()).collect(),
+                none_sources_before: before.none_source_count(),
+                none_sources_after: after.none_source_count(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::Environment;
+    use crate::core::types::{CodeLocation, RaiseStatement};
+    use std::path::PathBuf;
+
+    fn db_with(functions: &[(&str, &[&str])]) -> ArborDatabase {
+        let mut db = ArborDatabase::new(Environment::default());
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        for (id, raises) in functions {
+            let mut analysis = FunctionAnalysis::new(id.to_string(), format!("def {}()", id), location.clone());
+            for exc in *raises {
+                analysis
+                    .raises
+                    .push(RaiseStatement::new(exc.to_string(), exc.to_string(), location.clone()));
+            }
+            db.add_function(analysis);
+        }
+        db
+    }
+
+    #[test]
+    fn test_diff_databases() {
+        let old = db_with(&[("app.fetch", &["KeyError", "OSError"]), ("app.save", &["OSError"])]);
+        let new = db_with(&[
+            ("app.fetch", &["OSError", "ValueError"]),
+            ("app.save", &["OSError"]),
+            ("app.load", &["KeyError"]),
+        ]);
+        let ids: Vec<String> = ["app.fetch", "app.save", "app.load", "app.missing"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let diffs = diff_databases(&old, &new, &ids);
+        assert_eq!(diffs.len(), 3);
+
+        assert_eq!(diffs[0].added_exceptions, vec!["ValueError"]);
+        assert_eq!(diffs[0].removed_exceptions, vec!["KeyError"]);
+        assert_eq!(
+            diffs[0].lines(),
+            vec!["- `app.fetch`: + `ValueError`", "- `app.fetch`: - `KeyError`"]
+        );
+        assert!(diffs[1].is_unchanged());
+        assert!(diffs[1].lines().is_empty());
+        assert!(!diffs[2].in_old);
+        assert!(diffs[2].added_exceptions.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::core::types::FunctionAnalysis;
+use std::collections::HashMap;
+
+/// Inclusive exception-count ranges, the last one open-ended.
+const BUCKETS: [(&str, usize, Option<usize>); 6] = [
+    ("0", 0, Some(0)),
+    ("1-2", 1, Some(2)),
+    ("3-5", 3, Some(5)),
+    ("6-10", 6, Some(10)),
+    ("11-20", 11, Some(20)),
+    ("21+", 21, None),
+];
+
+/// How many analyzed functions fall into each exception-count bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExceptionDistribution {
+    counts: [usize; BUCKETS.len()],
+}
+
+impl ExceptionDistribution {
+    /// `(label, function count)` per bucket, from fewest exceptions to most.
+    pub fn buckets(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        BUCKETS.iter().zip(self.counts.iter()).map(|((label, _, _), count)| (*label, *count))
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn to_map(&self) -> HashMap<String, usize> {
+        self.buckets().map(|(label, count)| (label.to_string(), count)).collect()
+    }
+}
+
+pub fn compute_distribution(functions: &HashMap<String, FunctionAnalysis>) -> ExceptionDistribution {
+    let mut counts = [0; BUCKETS.len()];
+    for analysis in functions.values() {
+        let exceptions = analysis.exception_count();
+        let bucket = BUCKETS
+            .iter()
+            .position(|(_, low, high)| exceptions >= *low && high.map_or(true, |h| exceptions <= h))
+            .unwrap_or(BUCKETS.len() - 1);
+        counts[bucket] += 1;
+    }
+    ExceptionDistribution { counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{CodeLocation, RaiseStatement};
+    use std::path::PathBuf;
+
+    fn analysis(id: &str, exceptions: usize) -> FunctionAnalysis {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut analysis = FunctionAnalysis::new(id.to_string(), String::new(), location.clone());
+        for _ in 0..exceptions {
+            analysis
+                .raises
+                .push(RaiseStatement::new("E".to_string(), "E".to_string(), location.clone()));
+        }
+        analysis
+    }
+
+    #[test]
+    fn test_bucket_boundaries() {
+        let functions: HashMap<String, FunctionAnalysis> = [0, 0, 1, 2, 3, 5, 6, 10, 11, 20, 21, 80]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (format!("f{}", i), analysis(&format!("f{}", i), *n)))
+            .collect();
+
+        let distribution = compute_distribution(&functions);
+        let buckets: Vec<(&str, usize)> = distribution.buckets().collect();
+        assert_eq!(
+            buckets,
+            vec![("0", 2), ("1-2", 2), ("3-5", 2), ("6-10", 2), ("11-20", 2), ("21+", 2)]
+        );
+        assert_eq!(distribution.max_count(), 2);
+        assert_eq!(distribution.to_map()["21+"], 2);
+    }
+
+    #[test]
+    fn test_empty_distribution() {
+        let distribution = compute_distribution(&HashMap::new());
+        assert_eq!(distribution.max_count(), 0);
+        assert!(distribution.buckets().all(|(_, count)| count == 0));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::core::types::FunctionAnalysis;
+
+/// Where a function's docstring `Raises` section and its detected raises
+/// disagree. Types are compared by short name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocstringMismatch {
+    pub function_id: String,
+    /// Raised (and not caught on the way out) but not documented.
+    pub missing_from_doc: Vec<String>,
+    /// Documented but never seen raised.
+    pub missing_from_code: Vec<String>,
+}
+
+impl DocstringMismatch {
+    /// One `⚠` line per undocumented or undetected exception.
+    pub fn warnings(&self) -> Vec<String> {
+        let undocumented = self.missing_from_doc.iter().map(|exc| {
+            format!("⚠ `{}`: raises `{}` but it's not in the docstring", self.function_id, exc)
+        });
+        let undetected = self.missing_from_code.iter().map(|exc| {
+            format!("⚠ `{}`: docstring claims `{}` but it was not detected", self.function_id, exc)
+        });
+        undocumented.chain(undetected).collect()
+    }
+}
+
+fn short_name(exc_type: &str) -> &str {
+    exc_type.rsplit('.').next().unwrap_or(exc_type)
+}
+
+/// Compares the exceptions that escape `analysis` with its docstring, or
+/// `None` when they agree. Suppressed raises and bare re-raises are ignored.
+pub fn check_docstring_consistency(analysis: &FunctionAnalysis) -> Option<DocstringMismatch> {
+    let mut raised: Vec<&str> = analysis
+        .raises
+        .iter()
+        .filter(|r| r.suppressed_by.is_none() && r.exception_type != "(re-raise)")
+        .map(|r| short_name(&r.exception_type))
+        .collect();
+    raised.sort();
+    raised.dedup();
+
+    let mut documented: Vec<&str> = analysis.docstring_raises.iter().map(|d| short_name(d)).collect();
+    documented.sort();
+    documented.dedup();
+
+    let missing_from_doc: Vec<String> = raised
+        .iter()
+        .filter(|exc| !documented.contains(exc))
+        .map(|exc| exc.to_string())
+        .collect();
+    let missing_from_code: Vec<String> = documented
+        .iter()
+        .filter(|exc| !raised.contains(exc))
+        .map(|exc| exc.to_string())
+        .collect();
+
+    if missing_from_doc.is_empty() && missing_from_code.is_empty() {
+        return None;
+    }
+    Some(DocstringMismatch {
+        function_id: analysis.function_id.clone(),
+        missing_from_doc,
+        missing_from_code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{CodeLocation, RaiseStatement};
+    use std::path::PathBuf;
+
+    fn analysis(raises: &[&str], documented: &[&str]) -> FunctionAnalysis {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut analysis =
+            FunctionAnalysis::new("myapp.process".to_string(), "def process()".to_string(), location.clone());
+        for exc in raises {
+            analysis
+                .raises
+                .push(RaiseStatement::new(exc.to_string(), exc.to_string(), location.clone()));
+        }
+        analysis.docstring_raises = documented.iter().map(|d| d.to_string()).collect();
+        analysis
+    }
+
+    #[test]
+    fn test_consistent_docstring() {
+        assert_eq!(check_docstring_consistency(&analysis(&["ValueError"], &["ValueError"])), None);
+        assert_eq!(
+            check_docstring_consistency(&analysis(&["errors.ApiError"], &["ApiError"])),
+            None
+        );
+        assert_eq!(check_docstring_consistency(&analysis(&["(re-raise)"], &[])), None);
+    }
+
+    #[test]
+    fn test_docstring_mismatch() {
+        let mismatch = check_docstring_consistency(&analysis(&["OSError", "KeyError"], &["KeyError", "ValueError"]))
+            .unwrap();
+        assert_eq!(mismatch.missing_from_doc, vec!["OSError"]);
+        assert_eq!(mismatch.missing_from_code, vec!["ValueError"]);
+        assert_eq!(
+            mismatch.warnings(),
+            vec![
+                "⚠ `myapp.process`: raises `OSError` but it's not in the docstring",
+                "⚠ `myapp.process`: docstring claims `ValueError` but it was not detected",
+            ]
+        );
+    }
+}
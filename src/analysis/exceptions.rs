@@ -1,6 +1,8 @@
+use crate::core::database::ArborDatabase;
 use crate::core::types::{CodeLocation, RaiseStatement};
 use crate::plugins::python::extractor;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -36,3 +38,91 @@ pub fn find_exception_definition(
 ) -> Option<CodeLocation> {
     extractor::find_exception_definition(_exc_type)
 }
+
+/// Where `function` catches `exc_type` in its own body, if it does: the
+/// first matching `except` clause, taken from the handlers recorded when the
+/// function was analyzed or traced as a callee. Re-raising clauses don't count.
+pub fn does_function_handle(db: &ArborDatabase, function: &str, exc_type: &str) -> Option<CodeLocation> {
+    let own = db.get_function(function).into_iter();
+    let others = db.functions.values().filter(|analysis| analysis.function_id != function);
+
+    own.chain(others)
+        .flat_map(|analysis| analysis.handlers.iter())
+        .filter(|handler| handler.location.containing_function.as_deref() == Some(function))
+        .filter(|handler| handler.catches(exc_type))
+        .map(|handler| &handler.location)
+        .min_by_key(|location| location.line)
+        .cloned()
+}
+
+/// Collapses raises reported more than once for the same exception type, file
+/// and line, as happens when a raise site is reached through several call
+/// paths. Order of first appearance is kept; of the duplicates, the one with
+/// the most detail (a resolved definition, a guarding condition) wins, and
+/// the earliest (shallowest) wins ties.
+pub fn deduplicate_raises(raises: Vec<RaiseStatement>) -> Vec<RaiseStatement> {
+    fn detail(raise: &RaiseStatement) -> usize {
+        usize::from(raise.definition_location.is_some()) + usize::from(raise.condition.is_some())
+    }
+
+    let mut positions: HashMap<(String, PathBuf, u32), usize> = HashMap::new();
+    let mut unique: Vec<RaiseStatement> = Vec::with_capacity(raises.len());
+
+    for raise in raises {
+        let key = (
+            raise.exception_type.clone(),
+            raise.raise_location.file.clone(),
+            raise.raise_location.line,
+        );
+        match positions.get(&key) {
+            Some(&i) => {
+                if detail(&raise) > detail(&unique[i]) {
+                    unique[i] = raise;
+                }
+            }
+            None => {
+                positions.insert(key, unique.len());
+                unique.push(raise);
+            }
+        }
+    }
+
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raise(exc: &str, file: &str, line: u32) -> RaiseStatement {
+        RaiseStatement::new(
+            exc.to_string(),
+            exc.to_string(),
+            CodeLocation::new(PathBuf::from(file), line),
+        )
+    }
+
+    #[test]
+    fn test_deduplicate_raises_keeps_most_complete() {
+        let bare = raise("ValueError", "d.py", 3);
+        let detailed = raise("ValueError", "d.py", 3)
+            .with_definition(CodeLocation::new(PathBuf::from("errors.py"), 1))
+            .with_condition("x < 0");
+        let raises = vec![
+            bare,
+            raise("KeyError", "d.py", 5),
+            detailed,
+            raise("ValueError", "d.py", 9),
+            raise("ValueError", "d.py", 3),
+        ];
+
+        let unique = deduplicate_raises(raises);
+        let summary: Vec<(&str, u32)> = unique
+            .iter()
+            .map(|r| (r.exception_type.as_str(), r.raise_location.line))
+            .collect();
+        assert_eq!(summary, vec![("ValueError", 3), ("KeyError", 5), ("ValueError", 9)]);
+        assert_eq!(unique[0].condition.as_deref(), Some("x < 0"));
+        assert!(unique[0].definition_location.is_some());
+    }
+}
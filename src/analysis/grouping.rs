@@ -1,7 +1,8 @@
+use crate::core::config::RecoveryConfig;
 use crate::core::database::GroupingSuggestion;
 use crate::core::types::RaiseStatement;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum GroupingSignal {
@@ -20,93 +21,135 @@ impl GroupingSignal {
             GroupingSignal::CommonParent => "common parent",
         }
     }
+
+    /// Higher values win when two suggestions overlap. Package groups are the
+    /// most actionable, recovery-strategy groups the least specific.
+    pub fn priority(&self) -> u8 {
+        match self {
+            GroupingSignal::SourcePackage => 3,
+            GroupingSignal::SemanticSimilarity => 2,
+            GroupingSignal::CommonParent => 1,
+            GroupingSignal::RecoveryStrategy => 0,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RecoveryStrategy {
     Retry,
     FixInput,
     ReAuthenticate,
     Abort,
     Ignore,
+    /// A strategy named in the `[recovery.strategies]` config table.
+    Custom(String),
 }
 
 impl RecoveryStrategy {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             RecoveryStrategy::Retry => "retry",
             RecoveryStrategy::FixInput => "fix input",
             RecoveryStrategy::ReAuthenticate => "re-authenticate",
             RecoveryStrategy::Abort => "abort",
             RecoveryStrategy::Ignore => "ignore",
+            RecoveryStrategy::Custom(name) => name,
         }
     }
 
-    pub fn from_exception_type(exc_type: &str) -> Self {
-        let lower = exc_type.to_lowercase();
-
-        if lower.contains("timeout")
-            || lower.contains("connection")
-            || lower.contains("network")
-            || lower.contains("temporary")
-            || lower.contains("retry")
-            || lower.contains("throttl")
-            || lower.contains("ratelimit")
-        {
-            return RecoveryStrategy::Retry;
+    /// Parses a strategy name from config. Built-in names (with either a space
+    /// or a dash, e.g. `fix-input`) map to their variants; anything else is a
+    /// `Custom` strategy.
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_lowercase().replace('-', " ").as_str() {
+            "retry" => RecoveryStrategy::Retry,
+            "fix input" => RecoveryStrategy::FixInput,
+            "re authenticate" | "reauthenticate" => RecoveryStrategy::ReAuthenticate,
+            "abort" => RecoveryStrategy::Abort,
+            "ignore" => RecoveryStrategy::Ignore,
+            _ => RecoveryStrategy::Custom(name.trim().to_string()),
         }
+    }
 
-        if lower.contains("auth")
-            || lower.contains("permission")
-            || lower.contains("forbidden")
-            || lower.contains("unauthorized")
-            || lower.contains("credential")
-            || lower.contains("token")
-        {
-            return RecoveryStrategy::ReAuthenticate;
+    /// Like `from_exception_type`, but a user-defined mapping for `exc_type`
+    /// takes precedence over the built-in name heuristics.
+    pub fn from_exception_type_with_config(exc_type: &str, config: &RecoveryConfig) -> Self {
+        match config.strategy_for(exc_type) {
+            Some(name) => Self::from_name(name),
+            None => Self::from_exception_type(exc_type),
         }
+    }
 
-        if lower.contains("validation")
-            || lower.contains("invalid")
-            || lower.contains("value")
-            || lower.contains("type")
-            || lower.contains("argument")
-            || lower.contains("format")
-            || lower.contains("parse")
-        {
-            return RecoveryStrategy::FixInput;
-        }
+    pub fn from_exception_type(exc_type: &str) -> Self {
+        Self::from_exception_type_scored(exc_type).0
+    }
 
-        if lower.contains("notfound")
-            || lower.contains("missing")
-            || lower.contains("doesnotexist")
-        {
-            return RecoveryStrategy::Ignore;
+    /// The keyword-matched strategy for `exc_type` with a confidence from 0.0
+    /// to 1.0: 0.9 when only one strategy's keywords match, 0.3 when several
+    /// strategies' keywords match (the first in `STRATEGY_KEYWORDS` wins), and
+    /// 0.5 for the `Abort` fallback when nothing matches.
+    pub fn from_exception_type_scored(exc_type: &str) -> (Self, f32) {
+        let lower = exc_type.to_lowercase();
+        let matched: Vec<&RecoveryStrategy> = STRATEGY_KEYWORDS
+            .iter()
+            .filter(|(_, keywords)| keywords.iter().any(|k| lower.contains(k)))
+            .map(|(strategy, _)| strategy)
+            .collect();
+
+        match matched.as_slice() {
+            [] => (RecoveryStrategy::Abort, 0.5),
+            [strategy] => ((*strategy).clone(), 0.9),
+            [strategy, ..] => ((*strategy).clone(), 0.3),
         }
-
-        RecoveryStrategy::Abort
     }
 }
 
+/// Name fragments that suggest each strategy, in precedence order.
+const STRATEGY_KEYWORDS: &[(RecoveryStrategy, &[&str])] = &[
+    (
+        RecoveryStrategy::Retry,
+        &["timeout", "connection", "network", "temporary", "retry", "throttl", "ratelimit"],
+    ),
+    (
+        RecoveryStrategy::ReAuthenticate,
+        &["auth", "permission", "forbidden", "unauthorized", "credential", "token"],
+    ),
+    (
+        RecoveryStrategy::FixInput,
+        &["validation", "invalid", "value", "type", "argument", "format", "parse"],
+    ),
+    (RecoveryStrategy::Ignore, &["notfound", "missing", "doesnotexist"]),
+];
+
+/// Exceptions whose strategy is a weaker guess than this are left out of
+/// recovery-strategy groups.
+const MIN_GROUPING_CONFIDENCE: f32 = 0.5;
+
 #[derive(Debug, Clone)]
 struct ExceptionInfo {
     exception_type: String,
     package: Option<String>,
     semantic_category: Option<String>,
     recovery_strategy: RecoveryStrategy,
+    /// 1.0 for user-configured strategies, otherwise the heuristic's score.
+    recovery_confidence: f32,
 }
 
 impl ExceptionInfo {
-    fn from_raise(raise: &RaiseStatement) -> Self {
+    fn from_raise(raise: &RaiseStatement, config: &RecoveryConfig) -> Self {
         let package = extract_package(&raise.qualified_type);
         let semantic_category = detect_semantic_category(&raise.exception_type);
-        let recovery_strategy = RecoveryStrategy::from_exception_type(&raise.exception_type);
+        let (recovery_strategy, recovery_confidence) = match config.strategy_for(&raise.exception_type) {
+            Some(name) => (RecoveryStrategy::from_name(name), 1.0),
+            None => RecoveryStrategy::from_exception_type_scored(&raise.exception_type),
+        };
 
         Self {
             exception_type: raise.exception_type.clone(),
             package,
             semantic_category,
             recovery_strategy,
+            recovery_confidence,
         }
     }
 }
@@ -151,54 +194,187 @@ fn detect_semantic_category(exc_type: &str) -> Option<String> {
 }
 
 pub fn suggest_groups(exceptions: &[RaiseStatement]) -> Vec<GroupingSuggestion> {
+    suggest_groups_with_config(exceptions, &RecoveryConfig::default())
+}
+
+/// Like `suggest_groups`, but recovery groups honour user-defined strategies.
+pub fn suggest_groups_with_config(
+    exceptions: &[RaiseStatement],
+    config: &RecoveryConfig,
+) -> Vec<GroupingSuggestion> {
     if exceptions.is_empty() {
         return Vec::new();
     }
 
-    let mut suggestions = Vec::new();
+    let infos: Vec<ExceptionInfo> = exceptions
+        .iter()
+        .map(|raise| ExceptionInfo::from_raise(raise, config))
+        .collect();
 
-    let infos: Vec<ExceptionInfo> = exceptions.iter().map(ExceptionInfo::from_raise).collect();
+    let mut candidates = Vec::new();
 
-    let package_groups = group_by_package(&infos);
-    for (package, exc_types) in package_groups {
-        if exc_types.len() >= 2 {
-            suggestions.push(GroupingSuggestion {
-                group_name: format!("{} exceptions", package),
-                exceptions: exc_types.clone(),
-                rationale: format!("All exceptions from the {} package", package),
-                handler_example: generate_handler_example(&exc_types, &package),
-            });
+    for (package, exc_types) in group_by_package(&infos) {
+        candidates.push(GroupCandidate {
+            key: GroupKey::Package(package),
+            exceptions: exc_types,
+        });
+    }
+
+    for (category, exc_types) in group_by_semantic(&infos) {
+        candidates.push(GroupCandidate {
+            key: GroupKey::Semantic(category),
+            exceptions: exc_types,
+        });
+    }
+
+    for (strategy, exc_types) in group_by_recovery(&infos) {
+        candidates.push(GroupCandidate {
+            key: GroupKey::Recovery(strategy),
+            exceptions: exc_types,
+        });
+    }
+
+    candidates.retain(|c| c.exceptions.len() >= 2);
+
+    deduplicate_suggestions(candidates)
+        .into_iter()
+        .map(|candidate| candidate.into_suggestion(&infos))
+        .collect()
+}
+
+/// Suggestions for `raises` that are not already recorded in `existing`,
+/// either under the same name or with the same set of exceptions.
+pub fn suggest_new_groups(
+    existing: &HashMap<String, GroupingSuggestion>,
+    raises: &[RaiseStatement],
+) -> Vec<GroupingSuggestion> {
+    let existing_sets: Vec<HashSet<&String>> = existing
+        .values()
+        .map(|s| s.exceptions.iter().collect())
+        .collect();
+
+    suggest_groups(raises)
+        .into_iter()
+        .filter(|suggestion| {
+            let set: HashSet<&String> = suggestion.exceptions.iter().collect();
+            !existing.contains_key(&suggestion.group_name) && !existing_sets.contains(&set)
+        })
+        .collect()
+}
+
+/// Combines two groups into one named `name`: the union of their exceptions,
+/// both rationales, the functions either applied to, and a recovery handler
+/// for the strategy of the first merged exception. The merged group is only
+/// as confident as the weaker one.
+pub fn merge_groups(first: &GroupingSuggestion, second: &GroupingSuggestion, name: &str) -> GroupingSuggestion {
+    let mut exceptions = first.exceptions.clone();
+    for exc in &second.exceptions {
+        if !exceptions.contains(exc) {
+            exceptions.push(exc.clone());
         }
     }
 
-    let semantic_groups = group_by_semantic(&infos);
-    for (category, exc_types) in semantic_groups {
-        if exc_types.len() >= 2 {
-            suggestions.push(GroupingSuggestion {
-                group_name: format!("{} errors", category),
-                exceptions: exc_types.clone(),
-                rationale: format!("Semantically related {} exceptions", category.to_lowercase()),
-                handler_example: generate_handler_example(&exc_types, &category),
-            });
+    let mut applicable_functions = first.applicable_functions.clone();
+    for function in &second.applicable_functions {
+        if !applicable_functions.contains(function) {
+            applicable_functions.push(function.clone());
         }
     }
 
-    let recovery_groups = group_by_recovery(&infos);
-    for (strategy, exc_types) in recovery_groups {
-        if exc_types.len() >= 2 {
-            let strategy_name = strategy.as_str();
-            suggestions.push(GroupingSuggestion {
-                group_name: format!("{} exceptions", capitalize(strategy_name)),
-                exceptions: exc_types.clone(),
-                rationale: format!("Exceptions that can be handled with {} strategy", strategy_name),
-                handler_example: generate_recovery_handler(&exc_types, strategy),
-            });
+    let strategy = exceptions
+        .first()
+        .map(|exc| RecoveryStrategy::from_exception_type(exc))
+        .unwrap_or(RecoveryStrategy::Abort);
+
+    GroupingSuggestion {
+        group_name: name.to_string(),
+        rationale: format!("{} and {}", first.rationale, second.rationale),
+        handler_example: generate_recovery_handler(&exceptions, &strategy),
+        exceptions,
+        confidence: first.confidence.min(second.confidence),
+        applicable_functions,
+    }
+}
+
+enum GroupKey {
+    Package(String),
+    Semantic(String),
+    Recovery(RecoveryStrategy),
+}
+
+impl GroupKey {
+    fn signal(&self) -> GroupingSignal {
+        match self {
+            GroupKey::Package(_) => GroupingSignal::SourcePackage,
+            GroupKey::Semantic(_) => GroupingSignal::SemanticSimilarity,
+            GroupKey::Recovery(_) => GroupingSignal::RecoveryStrategy,
         }
     }
+}
+
+struct GroupCandidate {
+    key: GroupKey,
+    exceptions: Vec<String>,
+}
 
-    deduplicate_suggestions(&mut suggestions);
+impl GroupCandidate {
+    /// Package groups of 3+ exceptions score 0.9 (0.7 for pairs), semantic
+    /// groups 0.6 for pairs and 0.7 beyond, and recovery groups 0.6 unless
+    /// their exceptions span several packages, which drops them to 0.5.
+    fn confidence(&self, infos: &[ExceptionInfo]) -> f32 {
+        match self.key {
+            GroupKey::Package(_) if self.exceptions.len() >= 3 => 0.9,
+            GroupKey::Package(_) => 0.7,
+            GroupKey::Semantic(_) if self.exceptions.len() >= 3 => 0.7,
+            GroupKey::Semantic(_) => 0.6,
+            GroupKey::Recovery(_) => {
+                let packages: HashSet<&String> = infos
+                    .iter()
+                    .filter(|info| self.exceptions.contains(&info.exception_type))
+                    .filter_map(|info| info.package.as_ref())
+                    .collect();
+                if packages.len() > 1 {
+                    0.5
+                } else {
+                    0.6
+                }
+            }
+        }
+    }
 
-    suggestions
+    fn into_suggestion(self, infos: &[ExceptionInfo]) -> GroupingSuggestion {
+        let confidence = self.confidence(infos);
+        let exc_types = self.exceptions;
+        match self.key {
+            GroupKey::Package(package) => GroupingSuggestion {
+                group_name: format!("{} exceptions", package),
+                rationale: format!("All exceptions from the {} package", package),
+                handler_example: generate_handler_example(&exc_types, &package),
+                exceptions: exc_types,
+                confidence,
+                applicable_functions: Vec::new(),
+            },
+            GroupKey::Semantic(category) => GroupingSuggestion {
+                group_name: format!("{} errors", category),
+                rationale: format!("Semantically related {} exceptions", category.to_lowercase()),
+                handler_example: generate_handler_example(&exc_types, &category),
+                exceptions: exc_types,
+                confidence,
+                applicable_functions: Vec::new(),
+            },
+            GroupKey::Recovery(strategy) => {
+                let strategy_name = strategy.as_str();
+                GroupingSuggestion {
+                    group_name: format!("{} exceptions", capitalize(strategy_name)),
+                    rationale: format!("Exceptions that can be handled with {} strategy", strategy_name),
+                    handler_example: generate_recovery_handler(&exc_types, &strategy),
+                    exceptions: exc_types,
+                    confidence,
+                    applicable_functions: Vec::new(),
+                }
+            }
+        }
+    }
 }
 
 fn group_by_package(infos: &[ExceptionInfo]) -> HashMap<String, Vec<String>> {
@@ -244,9 +420,9 @@ fn group_by_semantic(infos: &[ExceptionInfo]) -> HashMap<String, Vec<String>> {
 fn group_by_recovery(infos: &[ExceptionInfo]) -> HashMap<RecoveryStrategy, Vec<String>> {
     let mut groups: HashMap<RecoveryStrategy, Vec<String>> = HashMap::new();
 
-    for info in infos {
+    for info in infos.iter().filter(|i| i.recovery_confidence >= MIN_GROUPING_CONFIDENCE) {
         groups
-            .entry(info.recovery_strategy)
+            .entry(info.recovery_strategy.clone())
             .or_default()
             .push(info.exception_type.clone());
     }
@@ -272,7 +448,7 @@ except ({}) as e:
     )
 }
 
-fn generate_recovery_handler(exc_types: &[String], strategy: RecoveryStrategy) -> String {
+fn generate_recovery_handler(exc_types: &[String], strategy: &RecoveryStrategy) -> String {
     let types_str = exc_types.join(", ");
 
     match strategy {
@@ -323,6 +499,15 @@ except ({}) as e:
     raise"#,
             types_str
         ),
+        RecoveryStrategy::Custom(name) => format!(
+            r#"try:
+    result = call_function()
+except ({}) as e:
+    # Recovery strategy: {}
+    logger.warning(f"{} failed: {{e}}")
+    raise"#,
+            types_str, name, name
+        ),
     }
 }
 
@@ -334,33 +519,57 @@ fn capitalize(s: &str) -> String {
     }
 }
 
-fn deduplicate_suggestions(suggestions: &mut Vec<GroupingSuggestion>) {
-    let mut to_remove = Vec::new();
-
-    for i in 0..suggestions.len() {
-        for j in 0..suggestions.len() {
-            if i != j {
-                let set_i: std::collections::HashSet<_> = suggestions[i].exceptions.iter().collect();
-                let set_j: std::collections::HashSet<_> = suggestions[j].exceptions.iter().collect();
+/// Resolves overlapping groups by signal priority. Candidates are visited from
+/// highest to lowest priority; a candidate contained in an already kept group
+/// is dropped, while a lower-priority superset of a kept group loses the
+/// overlapping exceptions instead, so the more specific group survives.
+fn deduplicate_suggestions(mut candidates: Vec<GroupCandidate>) -> Vec<GroupCandidate> {
+    candidates.sort_by(|a, b| {
+        b.key
+            .signal()
+            .priority()
+            .cmp(&a.key.signal().priority())
+            .then_with(|| b.exceptions.len().cmp(&a.exceptions.len()))
+    });
+
+    let mut kept: Vec<(GroupCandidate, HashSet<String>)> = Vec::new();
+
+    for mut candidate in candidates {
+        let set: HashSet<String> = candidate.exceptions.iter().cloned().collect();
+
+        let mut claimed: HashSet<&String> = HashSet::new();
+        let mut contained = false;
+        for (_, kept_set) in &kept {
+            if set.is_subset(kept_set) {
+                contained = true;
+                break;
+            }
+            if kept_set.is_subset(&set) {
+                claimed.extend(kept_set.iter());
+            }
+        }
+        if contained {
+            continue;
+        }
 
-                if set_i.is_subset(&set_j) && set_i.len() < set_j.len() {
-                    to_remove.push(i);
-                    break;
-                }
+        if !claimed.is_empty() {
+            candidate.exceptions.retain(|e| !claimed.contains(e));
+            if candidate.exceptions.len() < 2 {
+                continue;
             }
         }
-    }
 
-    to_remove.sort();
-    to_remove.dedup();
-    for i in to_remove.into_iter().rev() {
-        suggestions.remove(i);
+        let set = candidate.exceptions.iter().cloned().collect();
+        kept.push((candidate, set));
     }
+
+    kept.into_iter().map(|(candidate, _)| candidate).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::ArborConfig;
     use crate::core::types::CodeLocation;
     use std::path::PathBuf;
 
@@ -396,6 +605,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_recovery_strategy_confidence() {
+        assert_eq!(
+            RecoveryStrategy::from_exception_type_scored("ConnectionTimeout"),
+            (RecoveryStrategy::Retry, 0.9)
+        );
+        assert_eq!(
+            RecoveryStrategy::from_exception_type_scored("MyValidationConnectionError"),
+            (RecoveryStrategy::Retry, 0.3)
+        );
+        assert_eq!(
+            RecoveryStrategy::from_exception_type_scored("UnknownError"),
+            (RecoveryStrategy::Abort, 0.5)
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_exceptions_left_out_of_recovery_groups() {
+        let raises = vec![
+            make_raise("ReadTimeout", "ReadTimeout"),
+            make_raise("NetworkDown", "NetworkDown"),
+            make_raise("TokenConnectionError", "TokenConnectionError"),
+        ];
+
+        let suggestions = suggest_groups(&raises);
+        let retry = suggestions
+            .iter()
+            .find(|s| s.group_name == "Retry exceptions")
+            .expect("retry group");
+        assert_eq!(retry.exceptions, vec!["NetworkDown", "ReadTimeout"]);
+    }
+
+    #[test]
+    fn test_suggestion_confidence() {
+        let raises = vec![
+            make_raise("HTTPError", "requests.HTTPError"),
+            make_raise("SSLError", "requests.SSLError"),
+            make_raise("ProxyError", "requests.ProxyError"),
+            make_raise("ReadTimeout", "urllib3.ReadTimeout"),
+            make_raise("ConnectTimeout", "httpx.ConnectTimeout"),
+        ];
+
+        let suggestions = suggest_groups(&raises);
+        let confidence = |name: &str| {
+            suggestions
+                .iter()
+                .find(|s| s.group_name == name)
+                .map(|s| s.confidence)
+                .unwrap_or_else(|| panic!("{} not suggested", name))
+        };
+        assert_eq!(confidence("requests exceptions"), 0.9);
+        assert_eq!(confidence("Timeout errors"), 0.6);
+    }
+
+    #[test]
+    fn test_custom_strategy_from_config() {
+        let config: ArborConfig = toml::from_str(
+            r#"
+[recovery.strategies]
+CircuitOpenError = "circuit-break"
+"*CacheMiss*" = "fall-back-to-cache"
+QuotaExceeded = "retry"
+"#,
+        )
+        .unwrap();
+        let recovery = &config.recovery;
+
+        let strategy = RecoveryStrategy::from_exception_type_with_config("CircuitOpenError", recovery);
+        assert_eq!(strategy, RecoveryStrategy::Custom("circuit-break".to_string()));
+        assert_ne!(strategy, RecoveryStrategy::Custom("fall-back-to-cache".to_string()));
+        assert_eq!(strategy.as_str(), "circuit-break");
+        assert_eq!(
+            RecoveryStrategy::from_exception_type_with_config("RedisCacheMissError", recovery),
+            RecoveryStrategy::Custom("fall-back-to-cache".to_string())
+        );
+        assert_eq!(
+            RecoveryStrategy::from_exception_type_with_config("QuotaExceeded", recovery),
+            RecoveryStrategy::Retry
+        );
+        assert_eq!(
+            RecoveryStrategy::from_exception_type_with_config("ValidationError", recovery),
+            RecoveryStrategy::FixInput
+        );
+
+        let handler = generate_recovery_handler(&["CircuitOpenError".to_string()], &strategy);
+        assert!(handler.contains("except (CircuitOpenError) as e:"));
+        assert!(handler.contains("# Recovery strategy: circuit-break"));
+    }
+
+    #[test]
+    fn test_suggest_groups_with_custom_strategy() {
+        let config: ArborConfig = toml::from_str(
+            r#"
+[recovery.strategies]
+"*Unavailable" = "circuit-break"
+"#,
+        )
+        .unwrap();
+        let raises = vec![
+            make_raise("BackendUnavailable", "BackendUnavailable"),
+            make_raise("CacheUnavailable", "CacheUnavailable"),
+        ];
+
+        let suggestions = suggest_groups_with_config(&raises, &config.recovery);
+        let group = suggestions
+            .iter()
+            .find(|s| s.group_name == "Circuit-break exceptions")
+            .expect("custom recovery group");
+        assert_eq!(group.exceptions, vec!["BackendUnavailable", "CacheUnavailable"]);
+        assert!(group.handler_example.contains("# Recovery strategy: circuit-break"));
+
+        // Without the mapping both fall through to the abort group
+        assert!(suggest_groups(&raises).iter().any(|s| s.group_name == "Abort exceptions"));
+    }
+
     #[test]
     fn test_semantic_category_detection() {
         assert_eq!(detect_semantic_category("ConnectionError"), Some("Connection".to_string()));
@@ -425,6 +749,82 @@ mod tests {
         assert_eq!(requests_group.unwrap().exceptions.len(), 2);
     }
 
+    #[test]
+    fn test_signal_priority_order() {
+        assert!(GroupingSignal::SourcePackage.priority() > GroupingSignal::SemanticSimilarity.priority());
+        assert!(GroupingSignal::SemanticSimilarity.priority() > GroupingSignal::CommonParent.priority());
+        assert!(GroupingSignal::CommonParent.priority() > GroupingSignal::RecoveryStrategy.priority());
+    }
+
+    #[test]
+    fn test_package_subset_trims_recovery_superset() {
+        let raises = vec![
+            make_raise("ConnectionTimeout", "requests.exceptions.ConnectionTimeout"),
+            make_raise("ReadTimeout", "requests.exceptions.ReadTimeout"),
+            make_raise("PoolTimeout", "httpx.PoolTimeout"),
+            make_raise("ServiceUnavailable", "ServiceUnavailable"),
+            make_raise("RateLimited", "RateLimited"),
+        ];
+
+        let suggestions = suggest_groups(&raises);
+
+        let requests_group = suggestions
+            .iter()
+            .find(|s| s.group_name == "requests exceptions")
+            .expect("package group should be kept");
+        assert_eq!(requests_group.exceptions, vec!["ConnectionTimeout", "ReadTimeout"]);
+
+        let retry_group = suggestions
+            .iter()
+            .find(|s| s.group_name == "Retry exceptions")
+            .expect("recovery group should be trimmed, not removed");
+        assert!(!retry_group.exceptions.contains(&"ConnectionTimeout".to_string()));
+        assert!(!retry_group.exceptions.contains(&"ReadTimeout".to_string()));
+        assert!(retry_group.exceptions.contains(&"RateLimited".to_string()));
+        assert!(!retry_group.handler_example.contains("ReadTimeout"));
+    }
+
+    #[test]
+    fn test_lower_priority_subset_removed() {
+        let raises = vec![
+            make_raise("ConnectionTimeout", "requests.exceptions.ConnectionTimeout"),
+            make_raise("ReadTimeout", "requests.exceptions.ReadTimeout"),
+            make_raise("InvalidURL", "requests.exceptions.InvalidURL"),
+        ];
+
+        let suggestions = suggest_groups(&raises);
+
+        assert!(suggestions.iter().any(|s| s.group_name == "requests exceptions"));
+        assert!(!suggestions.iter().any(|s| s.group_name == "Timeout errors"));
+        assert!(!suggestions.iter().any(|s| s.group_name == "Retry exceptions"));
+    }
+
+    #[test]
+    fn test_suggest_new_groups_skips_existing() {
+        let raises = vec![
+            make_raise("ConnectionError", "requests.exceptions.ConnectionError"),
+            make_raise("Timeout", "requests.exceptions.Timeout"),
+            make_raise("ValueError", "ValueError"),
+            make_raise("TypeError", "TypeError"),
+        ];
+        let all = suggest_groups(&raises);
+        assert!(all.len() >= 2);
+
+        let mut existing = HashMap::new();
+        let mut renamed = all[0].clone();
+        renamed.group_name = "Network".to_string();
+        existing.insert(renamed.group_name.clone(), renamed);
+
+        let new = suggest_new_groups(&existing, &raises);
+        assert_eq!(new.len(), all.len() - 1);
+        assert!(!new.iter().any(|s| s.exceptions == all[0].exceptions));
+
+        for suggestion in &all {
+            existing.insert(suggestion.group_name.clone(), suggestion.clone());
+        }
+        assert!(suggest_new_groups(&existing, &raises).is_empty());
+    }
+
     #[test]
     fn test_empty_exceptions() {
         let suggestions = suggest_groups(&[]);
@@ -437,4 +837,41 @@ mod tests {
         let suggestions = suggest_groups(&raises);
         assert!(suggestions.is_empty());
     }
+
+    #[test]
+    fn test_merge_groups() {
+        let group = |name: &str, exceptions: &[&str], rationale: &str, confidence: f32| GroupingSuggestion {
+            group_name: name.to_string(),
+            exceptions: exceptions.iter().map(|e| e.to_string()).collect(),
+            rationale: rationale.to_string(),
+            handler_example: String::new(),
+            confidence,
+            applicable_functions: Vec::new(),
+        };
+        let mut requests = group(
+            "requests exceptions",
+            &["ConnectionError", "Timeout"],
+            "All exceptions from the requests package",
+            0.9,
+        );
+        let mut urllib3 = group(
+            "urllib3 exceptions",
+            &["Timeout", "ProtocolError"],
+            "All exceptions from the urllib3 package",
+            0.7,
+        );
+        requests.applicable_functions = vec!["app.fetch".to_string(), "app.sync".to_string()];
+        urllib3.applicable_functions = vec!["app.sync".to_string(), "app.stream".to_string()];
+
+        let merged = merge_groups(&requests, &urllib3, "HTTP client errors");
+        assert_eq!(merged.group_name, "HTTP client errors");
+        assert_eq!(merged.exceptions, vec!["ConnectionError", "Timeout", "ProtocolError"]);
+        assert_eq!(
+            merged.rationale,
+            "All exceptions from the requests package and All exceptions from the urllib3 package"
+        );
+        assert!(merged.handler_example.contains("except (ConnectionError, Timeout, ProtocolError)"));
+        assert_eq!(merged.confidence, 0.7);
+        assert_eq!(merged.applicable_functions, vec!["app.fetch", "app.sync", "app.stream"]);
+    }
 }
@@ -1,7 +1,12 @@
+use crate::analysis::progress::ProgressReporter;
 use crate::core::database::{SymbolIndex, SymbolLocation};
+use crate::core::types::{is_stub_file, PropertyKind};
+use crate::plugins::python::{archive, extractor};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
-use std::path::{Path, PathBuf};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 use thiserror::Error;
 use walkdir::WalkDir;
 
@@ -15,10 +20,21 @@ pub enum IndexerError {
 
     #[error("Walkdir error: {0}")]
     WalkDir(#[from] walkdir::Error),
+
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
 }
 
 pub struct Indexer {
     parser: tree_sitter::Parser,
+    progress: Option<Box<dyn ProgressReporter>>,
+    /// How many directory levels below each root to walk; `None` is unbounded.
+    max_depth: Option<usize>,
+    /// Files larger than this many KB are skipped; `None` is unlimited.
+    max_file_size_kb: Option<usize>,
+    /// Source files seen so far by `index_directories_incremental`; `None`
+    /// outside it, when every file is parsed.
+    visited: Option<HashSet<PathBuf>>,
 }
 
 impl Indexer {
@@ -27,35 +43,123 @@ impl Indexer {
         parser
             .set_language(&tree_sitter_python::LANGUAGE.into())
             .map_err(|e| IndexerError::Parser(e.to_string()))?;
-        Ok(Self { parser })
+        Ok(Self {
+            parser,
+            progress: None,
+            max_depth: None,
+            max_file_size_kb: None,
+            visited: None,
+        })
+    }
+
+    pub fn with_progress(mut self, reporter: Box<dyn ProgressReporter>) -> Self {
+        self.progress = Some(reporter);
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_max_file_size_kb(mut self, max_file_size_kb: Option<usize>) -> Self {
+        self.max_file_size_kb = max_file_size_kb;
+        self
     }
 
     pub fn index_directories(&mut self, directories: &[PathBuf]) -> Result<SymbolIndex, IndexerError> {
         let mut index = SymbolIndex::new();
 
         for dir in directories {
-            self.index_directory(dir, &mut index)?;
+            self.index_directory(dir, &mut index, self.max_depth)?;
         }
 
         index.mark_indexed();
+
+        if let Some(ref progress) = self.progress {
+            progress.on_complete(index.len());
+        }
+
         Ok(index)
     }
 
-    fn index_directory(&mut self, dir: &Path, index: &mut SymbolIndex) -> Result<(), IndexerError> {
+    /// Like `index_directories`, but starting from `existing`: files whose
+    /// hash is unchanged keep their symbols without being parsed, changed and
+    /// new files are re-indexed, and files no longer found lose their symbols.
+    pub fn index_directories_incremental(
+        &mut self,
+        directories: &[PathBuf],
+        existing: &SymbolIndex,
+    ) -> Result<SymbolIndex, IndexerError> {
+        let mut index = existing.clone();
+        index.skipped_large_files.clear();
+
+        self.visited = Some(HashSet::new());
+        let walked = directories
+            .iter()
+            .try_for_each(|dir| self.index_directory(dir, &mut index, self.max_depth));
+        let visited = self.visited.take().unwrap_or_default();
+        walked?;
+
+        let removed: Vec<PathBuf> = index
+            .file_hashes
+            .keys()
+            .filter(|path| !visited.contains(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            forget_source(&mut index, &path);
+        }
+
+        index.mark_indexed();
+
+        if let Some(ref progress) = self.progress {
+            progress.on_complete(index.len());
+        }
+
+        Ok(index)
+    }
+
+    fn index_directory(
+        &mut self,
+        dir: &Path,
+        index: &mut SymbolIndex,
+        max_depth: Option<usize>,
+    ) -> Result<(), IndexerError> {
         if !dir.exists() {
             return Ok(());
         }
 
-        for entry in WalkDir::new(dir)
-            .follow_links(true)
+        let mut walker = WalkDir::new(dir).follow_links(true);
+        if let Some(max_depth) = max_depth {
+            walker = walker.max_depth(max_depth);
+        }
+
+        for entry in walker
             .into_iter()
-            .filter_entry(|e| !Self::is_venv_dir(e.path()))
+            .filter_entry(|e| !Self::should_skip_during_walk(e))
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
 
-            if path.extension().map_or(false, |ext| ext == "py") {
-                if let Err(e) = self.index_file(path, dir, index) {
+            // A stub is only indexed where it stands in for missing source
+            let is_source = path.extension().is_some_and(|ext| ext == "py")
+                || (is_stub_file(path) && !path.with_extension("py").exists());
+            if is_source {
+                match self.index_file(path, dir, index) {
+                    Ok(()) => {
+                        if let Some(ref progress) = self.progress {
+                            progress.on_file_indexed(path);
+                        }
+                    }
+                    Err(e) => eprintln!("Warning: Failed to index {}: {}", path.display(), e),
+                }
+            } else if archive::is_archive(path) && path.is_file() {
+                let base_module = path
+                    .parent()
+                    .map(|parent| Self::path_to_module(parent, dir))
+                    .unwrap_or_default();
+                if let Err(e) = self.index_zip(path, &base_module, index) {
                     eprintln!("Warning: Failed to index {}: {}", path.display(), e);
                 }
             }
@@ -64,54 +168,121 @@ impl Indexer {
         Ok(())
     }
 
-    fn is_venv_dir(path: &Path) -> bool {
-        if !path.is_dir() {
-            return false;
+    /// Indexes the `.py` files in a `.whl` or `.egg` archive, with module
+    /// paths taken from the entry names and prefixed with `base_module`.
+    /// Symbols point into the archive as `archive::read_source` expects. One
+    /// hash is recorded for the whole archive; during an incremental run an
+    /// unchanged archive is skipped and a changed one re-indexed from scratch.
+    pub fn index_zip(
+        &mut self,
+        path: &Path,
+        base_module: &str,
+        index: &mut SymbolIndex,
+    ) -> Result<(), IndexerError> {
+        let bytes = std::fs::read(path)?;
+        let hash = Self::hash_bytes(&bytes);
+        if let Some(ref mut visited) = self.visited {
+            visited.insert(path.to_path_buf());
+            if !index.file_changed(path, &hash) {
+                return Ok(());
+            }
+            index.remove_archive(path);
         }
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
 
-        let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i)?;
+            let name = entry.name().to_string();
+            if !name.ends_with(".py") || !archive::is_package_entry(&name) {
+                continue;
+            }
 
-        let skip_dirs = ["__pycache__", ".git", "node_modules", ".tox", ".nox",
-                         ".mypy_cache", ".pytest_cache", ".ruff_cache", ".eggs"];
+            let file_path = path.join(&name);
+            let mut content = String::new();
+            if let Err(e) = entry.read_to_string(&mut content) {
+                eprintln!("Warning: Failed to index {}: {}", file_path.display(), e);
+                continue;
+            }
+            let Some(tree) = self.parser.parse(&content, None) else {
+                eprintln!("Warning: Failed to index {}: could not parse", file_path.display());
+                continue;
+            };
+
+            let module_path = match Self::path_to_module(Path::new(&name), Path::new("")) {
+                module if base_module.is_empty() => module,
+                module if module.is_empty() => base_module.to_string(),
+                module => format!("{}.{}", base_module, module),
+            };
+            Self::extract_symbols(&tree, &content, &file_path, &module_path, index);
+
+            if let Some(ref progress) = self.progress {
+                progress.on_file_indexed(&file_path);
+            }
+        }
 
-        if skip_dirs.contains(&dir_name) || dir_name.ends_with(".egg-info") {
-            return true;
+        index.set_file_hash(path.to_path_buf(), hash);
+        Ok(())
+    }
+
+    /// Whether `path` is a directory holding a Python installation.
+    fn is_venv_root(path: &Path) -> bool {
+        path.is_dir() && is_python_runtime_dir(path)
+    }
+
+    /// Whether a directory walk should prune `entry`. The walk root is always
+    /// indexed. Below it, tooling directories are pruned, and so are Python
+    /// installations unless the entry is inside site-packages: a package
+    /// there may ship a `bin/python` of its own.
+    fn should_skip_during_walk(entry: &walkdir::DirEntry) -> bool {
+        if entry.depth() == 0 || !entry.file_type().is_dir() {
+            return false;
         }
 
-        // Detect venv ROOT directories (not site-packages inside them)
-        // A venv root has pyvenv.cfg or bin/python - site-packages doesn't have these
-        let has_pyvenv_cfg = path.join("pyvenv.cfg").exists();
-        let has_bin_python = path.join("bin/python").exists();
-        let has_scripts_python = path.join("Scripts/python.exe").exists();
+        let dir_name = entry.file_name().to_str().unwrap_or("");
+        if is_tooling_dir(dir_name) {
+            return true;
+        }
 
-        has_pyvenv_cfg || has_bin_python || has_scripts_python
+        let in_site_packages = entry
+            .path()
+            .parent()
+            .is_some_and(|parent| parent.ancestors().any(is_site_packages_dir));
+        !in_site_packages && Self::is_venv_root(entry.path())
     }
 
-    fn index_file(
+    /// Adds the symbols of one source file to `index`, with module paths
+    /// relative to `base_dir`.
+    pub fn index_file(
         &mut self,
         path: &Path,
         base_dir: &Path,
         index: &mut SymbolIndex,
     ) -> Result<(), IndexerError> {
+        if let Some(ref mut visited) = self.visited {
+            visited.insert(path.to_path_buf());
+        }
+        if let Some(max_kb) = self.max_file_size_kb {
+            let size_kb = (std::fs::metadata(path)?.len() / 1024) as usize;
+            if size_kb > max_kb {
+                eprintln!("Skipping large file: {} ({} KB)", path.display(), size_kb);
+                index.skipped_large_files.push(path.to_path_buf());
+                // A file that grew past the limit since the last index
+                index.remove_file(path);
+                index.file_hashes.remove(path);
+                return Ok(());
+            }
+        }
         let content = std::fs::read_to_string(path)?;
-        let hash = Self::hash_content(&content);
-
-        let tree = self
-            .parser
-            .parse(&content, None)
-            .ok_or_else(|| IndexerError::Parser(format!("Failed to parse {}", path.display())))?;
-
-        let module_path = Self::path_to_module(path, base_dir);
-
-        self.extract_symbols(&tree, &content, path, &module_path, index);
-
-        index.set_file_hash(path.to_path_buf(), hash);
-
-        Ok(())
+        if self.visited.is_some() {
+            if !index.file_changed(path, &Self::hash_content(&content)) {
+                return Ok(());
+            }
+            index.remove_file(path);
+        }
+        index_file_with(&mut self.parser, path, base_dir, &content, index)
     }
 
     fn extract_symbols(
-        &self,
         tree: &tree_sitter::Tree,
         content: &str,
         file_path: &Path,
@@ -119,11 +290,10 @@ impl Indexer {
         index: &mut SymbolIndex,
     ) {
         let root = tree.root_node();
-        self.extract_from_node(root, content, file_path, module_path, None, index);
+        Self::extract_from_node(root, content, file_path, module_path, None, index);
     }
 
     fn extract_from_node(
-        &self,
         node: tree_sitter::Node,
         content: &str,
         file_path: &Path,
@@ -133,23 +303,7 @@ impl Indexer {
     ) {
         match node.kind() {
             "function_definition" => {
-                if let Some(name_node) = node.child_by_field_name("name") {
-                    let name = &content[name_node.byte_range()];
-                    let qualified_name = match current_class {
-                        Some(class) => format!("{}.{}.{}", module_path, class, name),
-                        None => format!("{}.{}", module_path, name),
-                    };
-
-                    let location = SymbolLocation {
-                        file_path: file_path.to_path_buf(),
-                        line_start: node.start_position().row as u32 + 1,
-                        line_end: node.end_position().row as u32 + 1,
-                        is_method: current_class.is_some(),
-                        parent_class: current_class.map(|s| s.to_string()),
-                    };
-
-                    index.add(qualified_name, location);
-                }
+                Self::index_function(node, content, file_path, module_path, current_class, None, index);
             }
             "class_definition" => {
                 if let Some(name_node) = node.child_by_field_name("name") {
@@ -162,6 +316,11 @@ impl Indexer {
                         line_end: node.end_position().row as u32 + 1,
                         is_method: false,
                         parent_class: None,
+                        is_property: false,
+                        property_kind: None,
+                        column_start: Some(name_node.start_position().column as u32),
+                        column_end: Some(name_node.end_position().column as u32),
+                        is_stub: is_stub_file(file_path),
                     };
 
                     index.add(qualified_name, location);
@@ -169,7 +328,7 @@ impl Indexer {
                     if let Some(body) = node.child_by_field_name("body") {
                         for i in 0..body.child_count() {
                             if let Some(child) = body.child(i) {
-                                self.extract_from_node(
+                                Self::extract_from_node(
                                     child,
                                     content,
                                     file_path,
@@ -183,8 +342,15 @@ impl Indexer {
                 }
             }
             "decorated_definition" => {
+                let property = current_class.and_then(|_| extractor::property_kind(node, content));
+                if let (Some(definition), Some(kind)) = (node.child_by_field_name("definition"), property) {
+                    if definition.kind() == "function_definition" {
+                        Self::index_function(definition, content, file_path, module_path, current_class, Some(kind), index);
+                        return;
+                    }
+                }
                 if let Some(definition) = node.child_by_field_name("definition") {
-                    self.extract_from_node(
+                    Self::extract_from_node(
                         definition,
                         content,
                         file_path,
@@ -197,7 +363,7 @@ impl Indexer {
             "module" => {
                 for i in 0..node.child_count() {
                     if let Some(child) = node.child(i) {
-                        self.extract_from_node(
+                        Self::extract_from_node(
                             child,
                             content,
                             file_path,
@@ -212,19 +378,77 @@ impl Indexer {
         }
     }
 
-    fn path_to_module(path: &Path, base_dir: &Path) -> String {
-        let relative = path
-            .strip_prefix(base_dir)
-            .unwrap_or(path);
+    /// Property setters and deleters are indexed as `Class.name.setter` and
+    /// `Class.name.deleter`, leaving `Class.name` for the getter.
+    fn index_function(
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &Path,
+        module_path: &str,
+        current_class: Option<&str>,
+        property: Option<PropertyKind>,
+        index: &mut SymbolIndex,
+    ) {
+        let Some(name_node) = node.child_by_field_name("name") else {
+            return;
+        };
+        let name = &content[name_node.byte_range()];
+        let qualified_name = match current_class {
+            Some(class) => format!("{}.{}.{}", module_path, class, name),
+            None => format!("{}.{}", module_path, name),
+        };
+        let qualified_name = match property {
+            Some(kind) => kind.symbol_name(&qualified_name),
+            None => qualified_name,
+        };
+
+        let location = SymbolLocation {
+            file_path: file_path.to_path_buf(),
+            line_start: node.start_position().row as u32 + 1,
+            line_end: node.end_position().row as u32 + 1,
+            is_method: current_class.is_some(),
+            parent_class: current_class.map(|s| s.to_string()),
+            is_property: property.is_some(),
+            property_kind: property,
+            column_start: Some(name_node.start_position().column as u32),
+            column_end: Some(name_node.end_position().column as u32),
+            is_stub: is_stub_file(file_path),
+        };
+
+        index.add(qualified_name, location);
+    }
 
-        let mut module_parts: Vec<&str> = relative
+    fn path_to_module(path: &Path, base_dir: &Path) -> String {
+        // Normalize separators by hand: a Windows path is a single component on
+        // Unix, and `Component::as_os_str` would leak backslashes into the name.
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        let base_str = base_dir.to_string_lossy().replace('\\', "/");
+        let base_str = base_str.trim_end_matches('/');
+
+        let relative = match path_str.strip_prefix(base_str) {
+            Some(rest) if !base_str.is_empty() && (rest.is_empty() || rest.starts_with('/')) => rest,
+            _ => path_str.as_str(),
+        };
+
+        let mut module_parts: Vec<&str> = Path::new(relative)
             .components()
-            .filter_map(|c| c.as_os_str().to_str())
+            .filter_map(|c| match c {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            })
+            .filter(|part| !part.ends_with(':'))
             .collect();
 
         if let Some(last) = module_parts.last_mut() {
-            if last.ends_with(".py") {
-                *last = &last[..last.len() - 3];
+            if let Some(stem) = last.strip_suffix(".py").or_else(|| last.strip_suffix(".pyi")) {
+                *last = stem;
+            }
+        }
+
+        // PEP 561 stub-only packages: `requests-stubs/api.pyi` is `requests.api`
+        if let Some(first) = module_parts.first_mut() {
+            if let Some(package) = first.strip_suffix("-stubs") {
+                *first = package;
             }
         }
 
@@ -235,11 +459,82 @@ impl Indexer {
         module_parts.join(".")
     }
 
-    fn hash_content(content: &str) -> String {
+    pub fn hash_content(content: &str) -> String {
         let mut hasher = DefaultHasher::new();
         content.hash(&mut hasher);
         format!("{:x}", hasher.finish())
     }
+
+    fn hash_bytes(bytes: &[u8]) -> String {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        format!("{:x}", hasher.finish())
+    }
+
+    /// The hash indexing records for `path`: of the whole file for an
+    /// archive, of its text for a source file.
+    pub fn hash_file(path: &Path) -> std::io::Result<String> {
+        if archive::is_archive(path) {
+            Ok(Self::hash_bytes(&std::fs::read(path)?))
+        } else {
+            Ok(Self::hash_content(&std::fs::read_to_string(path)?))
+        }
+    }
+}
+
+/// Drops the symbols and hash of a source file or archive.
+fn forget_source(index: &mut SymbolIndex, path: &Path) {
+    if archive::is_archive(path) {
+        index.remove_archive(path);
+    } else {
+        index.remove_file(path);
+    }
+    index.file_hashes.remove(path);
+}
+
+fn index_file_with(
+    parser: &mut tree_sitter::Parser,
+    path: &Path,
+    base_dir: &Path,
+    content: &str,
+    index: &mut SymbolIndex,
+) -> Result<(), IndexerError> {
+    let hash = Indexer::hash_content(content);
+
+    let tree = parser
+        .parse(content, None)
+        .ok_or_else(|| IndexerError::Parser(format!("Failed to parse {}", path.display())))?;
+
+    let module_path = Indexer::path_to_module(path, base_dir);
+
+    Indexer::extract_symbols(&tree, content, path, &module_path, index);
+
+    index.set_file_hash(path.to_path_buf(), hash);
+
+    Ok(())
+}
+
+impl SymbolIndex {
+    /// Re-indexes one file in place: drops the symbols it used to define,
+    /// parses it again and refreshes its hash. A deleted file just loses its
+    /// symbols. Returns the net change in symbol count.
+    pub fn reindex_file(
+        &mut self,
+        path: &Path,
+        base_dir: &Path,
+        parser: &mut tree_sitter::Parser,
+    ) -> Result<isize, IndexerError> {
+        let removed = self.remove_file(path);
+        if !path.exists() {
+            self.file_hashes.remove(path);
+            return Ok(-(removed as isize));
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let before = self.len();
+        index_file_with(parser, path, base_dir, &content, self)?;
+        Ok((self.len() - before) as isize - removed as isize)
+    }
 }
 
 impl Default for Indexer {
@@ -248,6 +543,33 @@ impl Default for Indexer {
     }
 }
 
+/// A `site-packages` or Debian `dist-packages` directory of installed packages.
+fn is_site_packages_dir(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "site-packages" || name == "dist-packages")
+}
+
+/// Caches and build artifacts that never contain project source.
+fn is_tooling_dir(dir_name: &str) -> bool {
+    let skip_dirs = ["__pycache__", ".git", "node_modules", ".tox", ".nox",
+                     ".mypy_cache", ".pytest_cache", ".ruff_cache", ".eggs"];
+
+    skip_dirs.contains(&dir_name) || dir_name.ends_with(".egg-info")
+}
+
+/// Detect the ROOT of a Python installation (venv, conda env or pyenv
+/// version), not the site-packages inside it. Only files an installation
+/// itself creates count: a `.python-version` merely pins an interpreter and
+/// is often committed alongside ordinary project code.
+pub fn is_python_runtime_dir(path: &Path) -> bool {
+    let has_venv_cfg = path.join("pyvenv.cfg").is_file() || path.join("pyenv.cfg").is_file();
+    let has_bin_python = path.join("bin/python").exists();
+    let has_scripts_python = path.join("Scripts/python.exe").exists();
+    let has_conda_meta = path.join("conda-meta").is_dir();
+
+    has_venv_cfg || has_bin_python || has_scripts_python || has_conda_meta
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,6 +594,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_path_to_module_windows_separators() {
+        let base = PathBuf::from(r"C:\project\src");
+
+        assert_eq!(
+            Indexer::path_to_module(&PathBuf::from(r"C:\project\src\mypackage\api.py"), &base),
+            "mypackage.api"
+        );
+
+        assert_eq!(
+            Indexer::path_to_module(&PathBuf::from(r"C:\project\src\mypackage\__init__.py"), &base),
+            "mypackage"
+        );
+    }
+
+    #[test]
+    fn test_path_to_module_sibling_prefix() {
+        let base = PathBuf::from("/project/src");
+
+        assert_eq!(
+            Indexer::path_to_module(&PathBuf::from("/project/srcs/mypackage/api.py"), &base),
+            "project.srcs.mypackage.api"
+        );
+    }
+
     #[test]
     fn test_hash_content() {
         let hash1 = Indexer::hash_content("hello world");
@@ -281,4 +628,82 @@ mod tests {
         assert_eq!(hash1, hash2);
         assert_ne!(hash1, hash3);
     }
+
+    #[test]
+    fn test_is_python_runtime_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let make = |name: &str| {
+            let dir = root.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            dir
+        };
+
+        let venv = make("venv");
+        std::fs::write(venv.join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+        assert!(is_python_runtime_dir(&venv));
+
+        let pyenv = make("pyenv");
+        std::fs::write(pyenv.join("pyenv.cfg"), "").unwrap();
+        assert!(is_python_runtime_dir(&pyenv));
+
+        let conda = make("conda");
+        std::fs::create_dir(conda.join("conda-meta")).unwrap();
+        assert!(is_python_runtime_dir(&conda));
+
+        let pinned = make("pinned");
+        std::fs::write(pinned.join(".python-version"), "3.12.1\n").unwrap();
+        assert!(!is_python_runtime_dir(&pinned));
+
+        let unix_venv = make("unix");
+        std::fs::create_dir(unix_venv.join("bin")).unwrap();
+        std::fs::write(unix_venv.join("bin/python"), "").unwrap();
+        assert!(is_python_runtime_dir(&unix_venv));
+
+        let windows_venv = make("windows");
+        std::fs::create_dir(windows_venv.join("Scripts")).unwrap();
+        std::fs::write(windows_venv.join("Scripts/python.exe"), "").unwrap();
+        assert!(is_python_runtime_dir(&windows_venv));
+
+        let package = make("mypackage");
+        std::fs::write(package.join("__init__.py"), "").unwrap();
+        assert!(!is_python_runtime_dir(&package));
+        assert!(!Indexer::is_venv_root(&package));
+        assert!(Indexer::is_venv_root(&venv));
+
+        assert!(!is_python_runtime_dir(&make("__pycache__")));
+    }
+
+    #[test]
+    fn test_should_skip_during_walk() {
+        let root = tempfile::tempdir().unwrap();
+        let site_packages = root.path().join("venv/lib/python3.11/site-packages");
+        for dir in ["venv/bin", "venv/lib/python3.11/site-packages/tool/bin", "project/__pycache__"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        std::fs::write(root.path().join("venv/bin/python"), "").unwrap();
+        std::fs::write(site_packages.join("tool/bin/python"), "").unwrap();
+
+        let skipped = |walk_root: &Path| -> Vec<PathBuf> {
+            WalkDir::new(walk_root)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(Indexer::should_skip_during_walk)
+                .map(|e| e.path().strip_prefix(root.path()).unwrap().to_path_buf())
+                .collect()
+        };
+
+        // From above the venv, the venv and tooling dirs are pruned
+        let mut from_top = skipped(root.path());
+        from_top.sort();
+        assert_eq!(from_top, vec![PathBuf::from("project/__pycache__"), PathBuf::from("venv")]);
+
+        // A project subpackage pinning its interpreter is still indexed
+        std::fs::create_dir_all(root.path().join("project/service")).unwrap();
+        std::fs::write(root.path().join("project/service/.python-version"), "3.12.1\n").unwrap();
+        assert!(!skipped(root.path()).contains(&PathBuf::from("project/service")));
+
+        // Walking site-packages itself, or the venv root, keeps the package that ships bin/python
+        assert!(skipped(&site_packages).is_empty());
+        assert!(skipped(&root.path().join("venv")).is_empty());
+    }
 }
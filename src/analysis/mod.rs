@@ -1,5 +1,11 @@
+pub mod changes;
+pub mod diff;
+pub mod distribution;
+pub mod docstrings;
 pub mod exceptions;
 pub mod grouping;
 pub mod indexer;
 pub mod none_sources;
+pub mod progress;
+pub mod suppressors;
 pub mod traversal;
@@ -1,6 +1,10 @@
-use crate::core::types::NoneSource;
+use crate::core::database::ArborDatabase;
+use crate::core::types::{CodeLocation, NoneFlowPath, NoneSource, NoneSourceKind, ReturnAnnotation};
 use crate::plugins::python::extractor;
-use std::path::Path;
+use crate::plugins::python::parser::PythonParser;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -29,3 +33,155 @@ pub fn extract_all_none_sources(
 ) -> Result<Vec<NoneSource>, NoneSourceError> {
     Ok(extractor::extract_none_sources(tree, content, file_path)?)
 }
+
+/// `trace_none_flow` for each source, reading files from disk. `None` where
+/// the source file can no longer be read or parsed.
+pub fn trace_none_flows(sources: &[NoneSource]) -> Vec<Option<NoneFlowPath>> {
+    let Ok(mut parser) = PythonParser::new() else {
+        return vec![None; sources.len()];
+    };
+    let mut files: HashMap<PathBuf, Option<(String, tree_sitter::Tree)>> = HashMap::new();
+
+    sources
+        .iter()
+        .map(|source| {
+            let file = &source.location.file;
+            let parsed = files.entry(file.clone()).or_insert_with(|| {
+                let content = std::fs::read_to_string(file).ok()?;
+                let tree = parser.parse_str(&content, file).ok()?;
+                Some((content, tree))
+            });
+            parsed
+                .as_ref()
+                .map(|(content, tree)| extractor::trace_none_flow(tree, content, source))
+        })
+        .collect()
+}
+
+/// Whether the function behind a `FunctionCall` None source declares its
+/// return type, and if so whether it admits None. `Unknown` when the callee
+/// can't be found or read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoneAnnotationStatus {
+    Annotated,
+    Unannotated,
+    AnnotatedOptional,
+    Unknown,
+}
+
+impl NoneAnnotationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoneAnnotationStatus::Annotated => "annotated",
+            NoneAnnotationStatus::Unannotated => "unannotated",
+            NoneAnnotationStatus::AnnotatedOptional => "annotated-optional",
+            NoneAnnotationStatus::Unknown => "unknown",
+        }
+    }
+}
+
+/// Classifies the callee of a `FunctionCall` None source by its return
+/// annotation. The callee is `source_definition` when recorded, otherwise the
+/// indexed function named at the call site. Other kinds of source, and calls
+/// whose callee can't be found or read, are `Unknown`.
+pub fn classify_none_source(source: &NoneSource, db: &ArborDatabase) -> NoneAnnotationStatus {
+    if source.kind != NoneSourceKind::FunctionCall {
+        return NoneAnnotationStatus::Unknown;
+    }
+    let Some(annotation) = callee_definition(source, db).and_then(|definition| return_annotation(&definition)) else {
+        return NoneAnnotationStatus::Unknown;
+    };
+
+    match annotation {
+        None => NoneAnnotationStatus::Unannotated,
+        Some(annotation) if admits_none(&annotation) => NoneAnnotationStatus::AnnotatedOptional,
+        Some(_) => NoneAnnotationStatus::Annotated,
+    }
+}
+
+/// Where the function called at `source` is defined. A name defined in the
+/// source's own file wins; otherwise the name must be unique in the index.
+fn callee_definition(source: &NoneSource, db: &ArborDatabase) -> Option<CodeLocation> {
+    if let Some(ref definition) = source.source_definition {
+        return Some(definition.clone());
+    }
+
+    let content = std::fs::read_to_string(&source.location.file).ok()?;
+    let line = content.lines().nth(source.location.line.checked_sub(1)? as usize)?;
+    let call = line.get(source.location.column.unwrap_or(0) as usize..)?;
+    let callee = call.split('(').next()?.trim();
+    let name = callee.rsplit('.').next()?;
+
+    let candidates: Vec<_> = db
+        .symbol_index
+        .iter()
+        .filter(|(qualified, _)| qualified.rsplit('.').next() == Some(name))
+        .map(|(_, location)| location)
+        .collect();
+    let location = match candidates.iter().find(|l| l.file_path == source.location.file) {
+        Some(local) => local,
+        None if candidates.len() == 1 => candidates[0],
+        None => return None,
+    };
+    Some(location.to_code_location())
+}
+
+/// The return annotation of the function defined at `definition`, or
+/// `None` when its file can't be read or parsed.
+fn return_annotation(definition: &CodeLocation) -> Option<Option<ReturnAnnotation>> {
+    let content = std::fs::read_to_string(&definition.file).ok()?;
+    let tree = PythonParser::new().ok()?.parse_str(&content, &definition.file).ok()?;
+    Some(extractor::extract_type_annotations(&tree, &content, definition.line))
+}
+
+/// `Optional[X]`, `X | None`, `Union[..., None]` or plain `None`.
+fn admits_none(annotation: &ReturnAnnotation) -> bool {
+    annotation.as_str().trim() == "None" || annotation.is_optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::Environment;
+
+    #[test]
+    fn test_admits_none() {
+        let admits = |annotation: &str| admits_none(&ReturnAnnotation::parse(annotation));
+        assert!(admits("Optional[User]"));
+        assert!(admits("User | None"));
+        assert!(admits("typing.Union[User, None]"));
+        assert!(admits("None"));
+        assert!(!admits("dict[str, NoneType]"));
+        assert!(!admits("User"));
+    }
+
+    #[test]
+    fn test_classify_none_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("app.py");
+        std::fs::write(
+            &file,
+            "def lookup(k) -> int | None:\n    ...\n\ndef plain(k):\n    ...\n\ndef typed(k) -> int:\n    ...\n\n@cache\ndef find(\n    key: str, default=\"->\",\n) -> Optional[User]:\n    ...\n",
+        )
+        .unwrap();
+        let db = ArborDatabase::new(Environment::default());
+        let call = |line| {
+            NoneSource::new(NoneSourceKind::FunctionCall, CodeLocation::new(file.clone(), 20))
+                .with_source_definition(CodeLocation::new(file.clone(), line))
+        };
+
+        assert_eq!(classify_none_source(&call(1), &db), NoneAnnotationStatus::AnnotatedOptional);
+        assert_eq!(classify_none_source(&call(4), &db), NoneAnnotationStatus::Unannotated);
+        assert_eq!(classify_none_source(&call(7), &db), NoneAnnotationStatus::Annotated);
+        assert_eq!(classify_none_source(&call(11), &db), NoneAnnotationStatus::AnnotatedOptional);
+
+        let implicit = NoneSource::new(NoneSourceKind::ImplicitReturn, CodeLocation::new(file.clone(), 1));
+        assert_eq!(classify_none_source(&implicit, &db), NoneAnnotationStatus::Unknown);
+
+        // Callee named at the call site but not in the index
+        std::fs::write(dir.path().join("caller.py"), "x = missing(1)\n").unwrap();
+        let unresolved = NoneSource::new(NoneSourceKind::FunctionCall, CodeLocation::new(dir.path().join("caller.py"), 1));
+        assert_eq!(classify_none_source(&unresolved, &db), NoneAnnotationStatus::Unknown);
+    }
+}
@@ -0,0 +1,167 @@
+use std::cell::{Cell, RefCell};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+/// Receives progress events from long-running indexing and traversal work so
+/// callers can render progress however they like.
+pub trait ProgressReporter {
+    fn on_function_start(&self, function_id: &str, depth: usize);
+    fn on_function_done(&self, function_id: &str, raise_count: usize);
+    fn on_file_indexed(&self, path: &Path);
+    fn on_complete(&self, total: usize);
+    /// Sent after each `on_function_start` with the number of functions still
+    /// queued and the number visited so far for the current root.
+    fn on_queue_size_changed(&self, queue_size: usize, visited_size: usize);
+}
+
+/// Prints a status line to stdout, or to stderr when stdout carries
+/// machine-readable output such as `--format json`.
+fn print_status(to_stderr: bool, line: &str) {
+    if to_stderr {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Default CLI reporter: prints the same status lines arbor has always printed.
+pub struct ConsoleReporter {
+    /// Print status lines to stderr instead of stdout.
+    pub to_stderr: bool,
+}
+
+impl ProgressReporter for ConsoleReporter {
+    fn on_function_start(&self, function_id: &str, depth: usize) {
+        if depth == 0 {
+            print_status(self.to_stderr, &format!("\nAnalyzing {}...", function_id));
+        }
+    }
+
+    fn on_function_done(&self, _function_id: &str, _raise_count: usize) {}
+
+    fn on_file_indexed(&self, _path: &Path) {}
+
+    fn on_complete(&self, _total: usize) {}
+
+    fn on_queue_size_changed(&self, _queue_size: usize, _visited_size: usize) {}
+}
+
+/// CLI reporter with a live `Analyzing [42/~180] function...` counter on
+/// stderr, redrawn in place on a terminal and one line per function otherwise.
+pub struct PrintingReporter {
+    live: bool,
+    to_stderr: bool,
+    current: RefCell<String>,
+    line_open: Cell<bool>,
+}
+
+impl PrintingReporter {
+    pub fn new() -> Self {
+        Self {
+            live: std::io::stderr().is_terminal(),
+            to_stderr: false,
+            current: RefCell::new(String::new()),
+            line_open: Cell::new(false),
+        }
+    }
+
+    /// Print the per-function `Analyzing ...` lines to stderr as well.
+    pub fn with_status_to_stderr(mut self, to_stderr: bool) -> Self {
+        self.to_stderr = to_stderr;
+        self
+    }
+
+    fn finish_line(&self) {
+        if self.line_open.replace(false) {
+            eprintln!();
+        }
+    }
+}
+
+impl Default for PrintingReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `Analyzing [visited/~estimate] function...`, estimating the total as
+/// everything visited plus everything still queued.
+pub fn progress_line(function_id: &str, queue_size: usize, visited_size: usize) -> String {
+    format!(
+        "Analyzing [{}/~{}] {}...",
+        visited_size,
+        visited_size + queue_size,
+        function_id
+    )
+}
+
+impl ProgressReporter for PrintingReporter {
+    fn on_function_start(&self, function_id: &str, depth: usize) {
+        if depth == 0 {
+            self.finish_line();
+            print_status(self.to_stderr, &format!("\nAnalyzing {}...", function_id));
+        }
+        *self.current.borrow_mut() = function_id.to_string();
+    }
+
+    fn on_function_done(&self, _function_id: &str, _raise_count: usize) {}
+
+    fn on_file_indexed(&self, _path: &Path) {}
+
+    fn on_complete(&self, _total: usize) {
+        self.finish_line();
+    }
+
+    fn on_queue_size_changed(&self, queue_size: usize, visited_size: usize) {
+        let line = progress_line(&self.current.borrow(), queue_size, visited_size);
+        if self.live {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[K{}", line);
+            let _ = stderr.flush();
+            self.line_open.set(true);
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+/// Reporter that discards all events, used for `--quiet`.
+pub struct SilentReporter;
+
+impl ProgressReporter for SilentReporter {
+    fn on_function_start(&self, _function_id: &str, _depth: usize) {}
+
+    fn on_function_done(&self, _function_id: &str, _raise_count: usize) {}
+
+    fn on_file_indexed(&self, _path: &Path) {}
+
+    fn on_complete(&self, _total: usize) {}
+
+    fn on_queue_size_changed(&self, _queue_size: usize, _visited_size: usize) {}
+}
+
+/// `SilentReporter` when quiet, otherwise `PrintingReporter` with a live
+/// counter or the plain `ConsoleReporter` without one. With `to_stderr`,
+/// status lines stay off stdout.
+pub fn cli_reporter(quiet: bool, live_progress: bool, to_stderr: bool) -> Box<dyn ProgressReporter> {
+    if quiet {
+        Box::new(SilentReporter)
+    } else if live_progress {
+        Box::new(PrintingReporter::new().with_status_to_stderr(to_stderr))
+    } else {
+        Box::new(ConsoleReporter { to_stderr })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_line_estimate() {
+        assert_eq!(
+            progress_line("pkg.mod.func", 138, 42),
+            "Analyzing [42/~180] pkg.mod.func..."
+        );
+    }
+}
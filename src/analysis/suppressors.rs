@@ -0,0 +1,181 @@
+use crate::core::types::{CodeLocation, ExceptionSuppressor};
+use crate::plugins::python::extractor;
+use std::path::Path;
+
+/// Catch-all `except` clauses in `try` statements starting between
+/// `line_start` and `line_end` that swallow what they catch: no `raise`
+/// anywhere in the handler and no call to a logger. Handlers naming only
+/// specific types are left alone, as are tuples without a catch-all in them.
+pub fn extract_exception_suppressors(
+    tree: &tree_sitter::Tree,
+    content: &str,
+    path: &Path,
+    line_start: u32,
+    line_end: u32,
+) -> Vec<ExceptionSuppressor> {
+    let mut suppressors = Vec::new();
+    collect_suppressors(tree.root_node(), content, path, (line_start, line_end), &mut suppressors);
+    suppressors
+}
+
+fn collect_suppressors(
+    node: tree_sitter::Node,
+    content: &str,
+    path: &Path,
+    line_range: (u32, u32),
+    suppressors: &mut Vec<ExceptionSuppressor>,
+) {
+    let line = node.start_position().row as u32 + 1;
+    if node.kind() == "try_statement" && line >= line_range.0 && line <= line_range.1 {
+        for i in 0..node.named_child_count() {
+            let Some(clause) = node.named_child(i) else { continue };
+            if clause.kind() != "except_clause" {
+                continue;
+            }
+            if let Some(suppressor) = suppressor_for_clause(clause, content, path) {
+                suppressors.push(suppressor);
+            }
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            collect_suppressors(child, content, path, line_range, suppressors);
+        }
+    }
+}
+
+fn suppressor_for_clause(clause: tree_sitter::Node, content: &str, path: &Path) -> Option<ExceptionSuppressor> {
+    let caught_types = extractor::parse_caught_types(clause, content);
+    let caught_type = if caught_types.is_empty() {
+        "*".to_string()
+    } else {
+        caught_types.into_iter().find(|t| is_catch_all(t))?
+    };
+
+    let block = (0..clause.named_child_count())
+        .filter_map(|i| clause.named_child(i))
+        .find(|n| n.kind() == "block")?;
+    if contains_raise_or_log(block, content) {
+        return None;
+    }
+
+    let first_statement = block
+        .named_child(0)
+        .map(|statement| content[statement.byte_range()].to_string())
+        .unwrap_or_default();
+    let location = CodeLocation::new(path.to_path_buf(), clause.start_position().row as u32 + 1)
+        .with_column(clause.start_position().column as u32);
+
+    Some(ExceptionSuppressor {
+        location,
+        caught_type,
+        first_statement,
+    })
+}
+
+fn is_catch_all(caught_type: &str) -> bool {
+    matches!(
+        caught_type.rsplit('.').next().unwrap_or(caught_type),
+        "Exception" | "BaseException"
+    )
+}
+
+/// A `raise` statement, or a logging call: a logger method called on
+/// `logging` or on a receiver whose last segment is `log` or `logger`, as in
+/// `logging.warning(...)`, `logger.exception(...)`, `self.log.error(...)`.
+fn contains_raise_or_log(node: tree_sitter::Node, content: &str) -> bool {
+    match node.kind() {
+        "raise_statement" => return true,
+        "call" if node.child_by_field_name("function").is_some_and(|f| is_logging_call(f, content)) => {
+            return true
+        }
+        _ => {}
+    }
+
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|child| contains_raise_or_log(child, content))
+}
+
+fn is_logging_call(function: tree_sitter::Node, content: &str) -> bool {
+    if function.kind() != "attribute" {
+        return false;
+    }
+    let (Some(receiver), Some(method)) = (
+        function.child_by_field_name("object"),
+        function.child_by_field_name("attribute"),
+    ) else {
+        return false;
+    };
+    if !matches!(
+        &content[method.byte_range()],
+        "debug" | "info" | "warning" | "warn" | "error" | "exception" | "critical" | "log"
+    ) {
+        return false;
+    }
+
+    let receiver = &content[receiver.byte_range()];
+    let last = receiver.rsplit('.').next().unwrap_or(receiver).trim_start_matches('_');
+    receiver == "logging" || last.eq_ignore_ascii_case("log") || last.eq_ignore_ascii_case("logger")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::python::parser::PythonParser;
+
+    fn suppressors(source: &str) -> Vec<ExceptionSuppressor> {
+        let mut parser = PythonParser::new().unwrap();
+        let path = Path::new("app.py");
+        let tree = parser.parse_str(source, path).unwrap();
+        extract_exception_suppressors(&tree, source, path, 1, u32::MAX)
+    }
+
+    #[test]
+    fn test_bare_except() {
+        let found = suppressors("def f():\n    try:\n        g()\n    except:\n        pass\n");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].caught_type, "*");
+        assert_eq!(found[0].first_statement, "pass");
+        assert_eq!(found[0].location.line, 4);
+        assert_eq!(found[0].location.column, Some(4));
+    }
+
+    #[test]
+    fn test_typed_except() {
+        let found = suppressors(
+            "def f():\n    try:\n        g()\n    except ValueError:\n        pass\n    except Exception as e:\n        return None\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].caught_type, "Exception");
+        assert_eq!(found[0].first_statement, "return None");
+        assert_eq!(found[0].location.line, 6);
+    }
+
+    #[test]
+    fn test_multi_type_except() {
+        let found = suppressors(
+            "def f():\n    try:\n        g()\n    except (KeyError, builtins.BaseException):\n        x = 1\n    except (KeyError, ValueError):\n        pass\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].caught_type, "builtins.BaseException");
+        assert_eq!(found[0].first_statement, "x = 1");
+    }
+
+    #[test]
+    fn test_reraise_and_logging_are_not_suppressors() {
+        let source = "def f():\n    try:\n        g()\n    except Exception:\n        cleanup()\n        raise\n\ndef h():\n    try:\n        g()\n    except:\n        if verbose:\n            logger.exception(\"failed\")\n\ndef k():\n    try:\n        g()\n    except Exception as e:\n        raise RuntimeError() from e\n";
+        assert!(suppressors(source).is_empty());
+
+        let logged = "def f():\n    try:\n        g()\n    except Exception:\n        logging.warning(\"failed\")\n\ndef h():\n    try:\n        g()\n    except Exception:\n        self._log.error(\"failed\")\n";
+        assert!(suppressors(logged).is_empty());
+    }
+
+    #[test]
+    fn test_log_like_names_are_not_logging() {
+        let source = "def f():\n    try:\n        g()\n    except Exception:\n        dialog.close()\n\ndef h():\n    try:\n        g()\n    except Exception:\n        catalog.get(key)\n\ndef k():\n    try:\n        g()\n    except:\n        login()\n\ndef m():\n    try:\n        g()\n    except Exception:\n        y = math.log(x)\n";
+        let found: Vec<String> = suppressors(source).into_iter().map(|s| s.first_statement).collect();
+        assert_eq!(found, vec!["dialog.close()", "catalog.get(key)", "login()", "y = math.log(x)"]);
+    }
+}
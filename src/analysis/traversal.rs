@@ -1,12 +1,20 @@
+use crate::analysis::exceptions::deduplicate_raises;
+use crate::analysis::progress::ProgressReporter;
+use crate::analysis::suppressors;
+use crate::core::config::matches_name_pattern;
 use crate::core::database::SymbolIndex;
 use crate::core::types::{
-    CodeLocation, FunctionAnalysis, NoneSource, RaiseStatement, SingleFunctionAnalysis,
+    is_stub_file, CallChainEntry, CallGraphStats, CatchClause, ClassCallResolution, CodeLocation, ExceptionHandler,
+    ExceptionSuppressor, FunctionAnalysis, NoneSource, NoneSourceKind, PropertyKind, RaiseStatement, ReturnAnnotation,
+    SingleFunctionAnalysis,
 };
+use crate::plugins::python::archive;
 use crate::plugins::python::extractor::{self, CallContext};
 use crate::plugins::python::parser::PythonParser;
 use crate::plugins::python::resolver::PythonResolver;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,6 +25,9 @@ pub enum TraversalError {
     #[error("Max depth exceeded: {0}")]
     MaxDepthExceeded(usize),
 
+    #[error("Analysis timed out after tracing {0} functions")]
+    Timeout(usize),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 
@@ -34,14 +45,53 @@ pub struct Traverser {
     pub resolver: PythonResolver,
     pub symbol_index: Option<SymbolIndex>,
     pub max_depth: usize,
+    pub prefer_local: bool,
+    pub timeout: Option<Duration>,
+    /// Keep the lines around each raise in `RaiseStatement::source_context`.
+    pub include_source_context: bool,
+    /// Plain directories such as `src` that hold top-level packages; they
+    /// never become part of a module path.
+    pub src_layout_roots: Vec<String>,
+    /// Extra exception names or `*` globs treated like Python builtins, from
+    /// `[analysis] builtin_exceptions`.
+    pub builtin_exceptions: Vec<String>,
+    progress: Option<Box<dyn ProgressReporter>>,
     parser: PythonParser,
 }
 
+/// Result of `Traverser::plan_function`.
+#[derive(Debug, Clone, Default)]
+pub struct TraversalPlan {
+    /// Resolved functions in traversal order, with their source file.
+    pub functions: Vec<(String, PathBuf)>,
+    /// Callees that would not be traced because they are past `max_depth`.
+    pub skipped_functions: Vec<String>,
+    /// Deepest call depth reached.
+    pub max_depth: usize,
+}
+
+/// What `Traverser::walk` gathered besides the functions it visited.
+#[derive(Debug, Default)]
+struct Walk {
+    functions_traced: usize,
+    /// Deepest call depth reached.
+    max_depth: usize,
+    /// Callees past `max_depth`, in the order first seen.
+    skipped_functions: Vec<String>,
+    call_edges: Vec<(String, String)>,
+    awaited_edges: Vec<(String, String)>,
+    graph_stats: CallGraphStats,
+    /// The timeout stopped the walk early.
+    partial: bool,
+}
+
 #[derive(Debug, Clone)]
 struct QueueItem {
     function_id: String,
     depth: usize,
     call_chain: Vec<String>,
+    /// Some hop on the way here awaited a coroutine.
+    via_await: bool,
 }
 
 impl Traverser {
@@ -51,6 +101,12 @@ impl Traverser {
             resolver,
             symbol_index: None,
             max_depth,
+            prefer_local: false,
+            timeout: None,
+            include_source_context: true,
+            src_layout_roots: vec!["src".to_string(), "lib".to_string()],
+            builtin_exceptions: Vec::new(),
+            progress: None,
             parser,
         })
     }
@@ -60,42 +116,105 @@ impl Traverser {
         self
     }
 
-    pub fn analyze_function(&mut self, function_id: &str) -> Result<FunctionAnalysis, TraversalError> {
-        let mut visited: HashSet<String> = HashSet::new();
-        let mut all_raises: Vec<RaiseStatement> = Vec::new();
-        let mut all_none_sources: Vec<NoneSource> = Vec::new();
-        let mut call_chains: HashMap<String, Vec<String>> = HashMap::new();
-        let mut functions_traced = 0;
-        let mut max_call_depth = 0;
+    pub fn with_prefer_local(mut self, prefer_local: bool) -> Self {
+        self.prefer_local = prefer_local;
+        self
+    }
 
-        let mut queue: VecDeque<QueueItem> = VecDeque::new();
-        queue.push_back(QueueItem {
-            function_id: function_id.to_string(),
-            depth: 0,
-            call_chain: vec![function_id.to_string()],
-        });
+    pub fn with_progress(mut self, reporter: Box<dyn ProgressReporter>) -> Self {
+        self.progress = Some(reporter);
+        self
+    }
 
-        let mut root_location: Option<CodeLocation> = None;
-        let mut root_signature = String::new();
+    /// Limit the wall-clock time spent on each root function.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        while let Some(item) = queue.pop_front() {
-            if visited.contains(&item.function_id) {
-                continue;
-            }
+    pub fn with_source_context(mut self, include: bool) -> Self {
+        self.include_source_context = include;
+        self
+    }
 
-            if item.depth > self.max_depth {
-                continue;
+    pub fn with_src_layout_roots(mut self, roots: Vec<String>) -> Self {
+        self.src_layout_roots = roots;
+        self
+    }
+
+    pub fn with_builtin_exceptions(mut self, patterns: Vec<String>) -> Self {
+        self.builtin_exceptions = patterns;
+        self
+    }
+
+    fn is_configured_builtin(&self, name: &str) -> bool {
+        self.builtin_exceptions.iter().any(|pattern| matches_name_pattern(pattern, name))
+    }
+
+    /// Whether `exc_type` matches `builtin_exceptions` as written, as
+    /// imported, or qualified with the module raising it.
+    fn is_configured_builtin_exception(
+        &self,
+        exc_type: &str,
+        imports: &HashMap<String, String>,
+        current_file: &Path,
+    ) -> bool {
+        if self.builtin_exceptions.is_empty() {
+            return false;
+        }
+        self.is_configured_builtin(exc_type)
+            || imports.get(exc_type).is_some_and(|qualified| self.is_configured_builtin(qualified))
+            || self.is_configured_builtin(&format!("{}.{}", self.module_path(current_file), exc_type))
+    }
+
+    fn module_path(&self, path: &Path) -> String {
+        get_full_module_path(path, &self.resolver.python_path, &self.src_layout_roots)
+    }
+
+    pub fn analyze_function(&mut self, function_id: &str) -> Result<FunctionAnalysis, TraversalError> {
+        let analysis = self.analyze_function_partial(function_id)?;
+        if analysis.partial {
+            return Err(TraversalError::Timeout(analysis.functions_traced));
+        }
+        Ok(analysis)
+    }
+
+    /// The functions `analyze_function` would trace from `function_id`, found
+    /// by resolving callees only; no raises or None sources are extracted and
+    /// the timeout does not apply.
+    pub fn plan_function(&mut self, function_id: &str) -> TraversalPlan {
+        let mut functions = Vec::new();
+        let walk = self.walk(function_id, false, |this, item, _, resolved| {
+            functions.push((item.function_id.clone(), resolved.file_path.clone()));
+            if resolved.is_stub {
+                return None;
             }
+            this.function_calls(resolved, &item.function_id).ok()
+        });
 
-            visited.insert(item.function_id.clone());
-            functions_traced += 1;
-            max_call_depth = max_call_depth.max(item.depth);
+        TraversalPlan {
+            functions,
+            skipped_functions: walk.skipped_functions,
+            max_depth: walk.max_depth,
+        }
+    }
 
-            let resolved = match self.resolve_function(&item.function_id) {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
+    /// Like `analyze_function`, but on timeout returns what was collected so far
+    /// with `partial` set instead of an error.
+    pub fn analyze_function_partial(&mut self, function_id: &str) -> Result<FunctionAnalysis, TraversalError> {
+        let mut all_raises: Vec<RaiseStatement> = Vec::new();
+        let mut all_none_sources: Vec<NoneSource> = Vec::new();
+        let mut call_chains: Vec<CallChainEntry> = Vec::new();
+        let mut handlers_by_function: HashMap<String, Vec<ExceptionHandler>> = HashMap::new();
+        let mut all_handlers: Vec<ExceptionHandler> = Vec::new();
+        let mut all_suppressors: Vec<ExceptionSuppressor> = Vec::new();
+        let mut all_catches: HashMap<String, Vec<CatchClause>> = HashMap::new();
 
+        let mut root_location: Option<CodeLocation> = None;
+        let mut root_signature = String::new();
+        let mut root_docstring_raises = Vec::new();
+
+        let walk = self.walk(function_id, true, |this, item, call_chain, resolved| {
             if item.depth == 0 {
                 root_location = Some(CodeLocation::new(
                     resolved.file_path.clone(),
@@ -107,43 +226,55 @@ impl Traverser {
                 );
             }
 
-            let analysis = match self.analyze_single_function(&resolved, &item.function_id) {
-                Ok(a) => a,
-                Err(_) => continue,
-            };
+            let mut analysis = this.analyze_single_function(resolved, &item.function_id).ok()?;
+            if item.depth == 0 {
+                root_docstring_raises = std::mem::take(&mut analysis.docstring_raises);
+            }
 
-            for raise in analysis.raises {
-                let chain_key = format!(
-                    "{}@{}:{}",
-                    raise.exception_type,
-                    raise.raise_location.file.display(),
-                    raise.raise_location.line
-                );
-                call_chains.insert(chain_key, item.call_chain.clone());
+            if let Some(ref progress) = this.progress {
+                progress.on_function_done(&item.function_id, analysis.raises.len());
+            }
+
+            all_suppressors.extend(analysis.suppressors);
+            for catch in analysis.catches {
+                all_catches.entry(catch.caught_type.clone()).or_default().push(catch);
+            }
+            if !analysis.handlers.is_empty() {
+                all_handlers.extend(analysis.handlers.iter().cloned());
+                if let Some(current) = call_chain.last() {
+                    handlers_by_function.insert(current.clone(), analysis.handlers);
+                }
+            }
+
+            for mut raise in analysis.raises {
+                raise.source_depth = item.depth;
+                raise.via_await = item.via_await;
+                record_chain(&mut call_chains, CallChainEntry {
+                    exception_type: raise.exception_type.clone(),
+                    location: raise.raise_location.clone(),
+                    chain: call_chain.to_vec(),
+                });
                 all_raises.push(raise);
             }
 
             for none_source in analysis.none_sources {
-                let chain_key = format!(
-                    "{}@{}:{}",
-                    none_source.kind.as_str(),
-                    none_source.location.file.display(),
-                    none_source.location.line
-                );
-                call_chains.insert(chain_key, item.call_chain.clone());
+                record_chain(&mut call_chains, CallChainEntry {
+                    exception_type: none_source.kind.as_str().to_string(),
+                    location: none_source.location.clone(),
+                    chain: call_chain.to_vec(),
+                });
                 all_none_sources.push(none_source);
             }
 
-            for call in analysis.calls {
-                if !visited.contains(&call) {
-                    let mut new_chain = item.call_chain.clone();
-                    new_chain.push(call.clone());
-                    queue.push_back(QueueItem {
-                        function_id: call,
-                        depth: item.depth + 1,
-                        call_chain: new_chain,
-                    });
-                }
+            Some((analysis.calls, analysis.awaited_calls))
+        });
+
+        for raise in &mut all_raises {
+            let entry = call_chains.iter().find(|entry| {
+                entry.exception_type == raise.exception_type && entry.location == raise.raise_location
+            });
+            if let Some(entry) = entry {
+                raise.suppressed_by = find_suppressing_handler(raise, &entry.chain, &handlers_by_function);
             }
         }
 
@@ -156,33 +287,177 @@ impl Traverser {
             root_signature,
             location,
         );
-        analysis.raises = all_raises;
+        analysis.raises = deduplicate_raises(all_raises);
         analysis.none_sources = all_none_sources;
-        analysis.functions_traced = functions_traced;
-        analysis.call_depth = max_call_depth;
+        analysis.handlers = all_handlers;
+        analysis.suppressors = all_suppressors;
+        analysis.catches = all_catches;
+        analysis.call_edges = walk.call_edges;
+        analysis.awaited_edges = walk.awaited_edges;
+        analysis.call_graph_stats = walk.graph_stats;
+        analysis.docstring_raises = root_docstring_raises;
+        let symbol = self.symbol_index.as_ref().and_then(|index| index.get(function_id));
+        analysis.property_kind = symbol.and_then(|loc| loc.property_kind);
+        if let Some(symbol) = symbol.filter(|loc| loc.line_start == analysis.location.line) {
+            analysis.location.column = symbol.column_start;
+        }
+        analysis.functions_traced = walk.functions_traced;
+        analysis.call_depth = walk.max_depth;
         analysis.call_chains = call_chains;
+        analysis.partial = walk.partial;
+        analysis.depth_limit_hits = walk.skipped_functions.len();
+        analysis.skipped_functions = walk.skipped_functions;
 
         Ok(analysis)
     }
 
+    /// Breadth-first walk of the calls reachable from `function_id`, shared by
+    /// `analyze_function_partial` and `plan_function` so a dry run visits what
+    /// a real run would.
+    ///
+    /// `visit` is given each resolved function with the chain that reached it
+    /// (a class call shows as `Class(...)` followed by its `__init__`) and
+    /// returns its calls and the awaited subset, or None to trace nothing
+    /// further from it. When `analyzing`, the timeout applies and progress is
+    /// reported.
+    fn walk(
+        &mut self,
+        function_id: &str,
+        analyzing: bool,
+        mut visit: impl FnMut(&mut Self, &QueueItem, &[String], &ResolvedLocation) -> Option<(Vec<String>, Vec<String>)>,
+    ) -> Walk {
+        let started = Instant::now();
+        let mut walk = Walk::default();
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut skipped: HashSet<String> = HashSet::new();
+
+        let mut queue: VecDeque<QueueItem> = VecDeque::new();
+        queue.push_back(QueueItem {
+            function_id: function_id.to_string(),
+            depth: 0,
+            call_chain: vec![function_id.to_string()],
+            via_await: false,
+        });
+
+        while let Some(item) = queue.pop_front() {
+            if let Some(timeout) = self.timeout.filter(|_| analyzing) {
+                if walk.functions_traced > 0 && started.elapsed() > timeout {
+                    walk.partial = true;
+                    break;
+                }
+            }
+
+            if visited.contains(&item.function_id) {
+                continue;
+            }
+
+            if item.depth > self.max_depth {
+                if skipped.insert(item.function_id.clone()) {
+                    walk.skipped_functions.push(item.function_id);
+                }
+                continue;
+            }
+
+            visited.insert(item.function_id.clone());
+            walk.functions_traced += 1;
+            walk.max_depth = walk.max_depth.max(item.depth);
+
+            if let Some(progress) = self.progress.as_ref().filter(|_| analyzing) {
+                progress.on_function_start(&item.function_id, item.depth);
+                progress.on_queue_size_changed(queue.len(), visited.len());
+            }
+
+            let resolved = match self.resolve_function(&item.function_id) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            let call_chain = match self.class_constructor(&item.function_id) {
+                Some(class_call) => {
+                    visited.insert(class_call.init_id.clone());
+                    let mut chain = item.call_chain.clone();
+                    if let Some(last) = chain.last_mut() {
+                        *last = format!("{}(...)", class_call.class_id);
+                    }
+                    chain.push(class_call.init_id);
+                    chain
+                }
+                None => item.call_chain.clone(),
+            };
+
+            let Some((mut calls, awaited_calls)) = visit(self, &item, &call_chain, &resolved) else {
+                continue;
+            };
+            if resolved.is_stub {
+                calls.clear();
+            }
+
+            let callees: HashSet<&String> = calls.iter().collect();
+            let indexed_callees = match self.symbol_index {
+                Some(ref index) => callees.iter().filter(|call| index.contains(call)).count(),
+                None => callees.len(),
+            };
+            walk.graph_stats.record(item.depth, callees.len(), indexed_callees);
+            walk.graph_stats.cycle_count += callees.iter().filter(|call| call_chain.contains(call)).count();
+
+            for call in calls {
+                let awaited = awaited_calls.contains(&call);
+                if let Some(caller) = call_chain.last() {
+                    walk.call_edges.push((caller.clone(), call.clone()));
+                    if awaited {
+                        walk.awaited_edges.push((caller.clone(), call.clone()));
+                    }
+                }
+                // Constructing a configured builtin exception has nothing to
+                // trace, and resolving it would only fail
+                if !visited.contains(&call) && !self.is_configured_builtin(&call) {
+                    let mut new_chain = call_chain.clone();
+                    new_chain.push(call.clone());
+                    queue.push_back(QueueItem {
+                        function_id: call,
+                        depth: item.depth + 1,
+                        call_chain: new_chain,
+                        via_await: item.via_await || awaited,
+                    });
+                }
+            }
+        }
+
+        if let Some(progress) = self.progress.as_ref().filter(|_| analyzing) {
+            progress.on_complete(walk.functions_traced);
+        }
+
+        walk
+    }
+
     fn resolve_function(&mut self, function_id: &str) -> Result<ResolvedLocation, TraversalError> {
         if let Some(ref index) = self.symbol_index {
             if let Some(loc) = index.get(function_id) {
                 return Ok(ResolvedLocation {
                     file_path: loc.file_path.clone(),
-                    function_name: function_id.split('.').last().unwrap_or(function_id).to_string(),
+                    function_name: function_id.split('.').next_back().unwrap_or(function_id).to_string(),
                     line_start: loc.line_start,
                     line_end: loc.line_end,
+                    parent_class: loc.parent_class.clone(),
+                    is_stub: loc.is_stub,
                 });
             }
         }
 
-        match self.resolver.resolve(function_id) {
+        let result = if self.prefer_local {
+            self.resolver.resolve_with_fallback(function_id)
+        } else {
+            self.resolver.resolve(function_id)
+        };
+
+        match result {
             Ok(resolved) => Ok(ResolvedLocation {
+                is_stub: is_stub_file(&resolved.file_path),
                 file_path: resolved.file_path,
                 function_name: resolved.function_name,
                 line_start: resolved.line_start,
                 line_end: resolved.line_end,
+                parent_class: resolved.parent_class,
             }),
             Err(e) => Err(TraversalError::ResolutionError(e.to_string())),
         }
@@ -193,7 +468,17 @@ impl Traverser {
         resolved: &ResolvedLocation,
         function_id: &str,
     ) -> Result<SingleFunctionAnalysis, TraversalError> {
-        let content = std::fs::read_to_string(&resolved.file_path)?;
+        let class_call = self.class_constructor(function_id);
+        let constructor;
+        let resolved = match class_call {
+            Some(ref class_call) => {
+                constructor = self.resolve_function(&class_call.init_id)?;
+                &constructor
+            }
+            None => resolved,
+        };
+
+        let content = archive::read_source(&resolved.file_path)?;
         let tree = self
             .parser
             .parse_str(&content, &resolved.file_path)
@@ -209,6 +494,9 @@ impl Traverser {
 
         let imports = extractor::extract_imports(&tree, &content);
         for raise in &mut raises {
+            if !self.include_source_context {
+                raise.source_context = None;
+            }
             if let Some(def_location) = self.resolve_exception_definition(
                 &raise.exception_type,
                 &imports,
@@ -223,32 +511,182 @@ impl Traverser {
             }
         }
 
-        let none_sources = extractor::extract_none_sources_in_range(
+        let mut none_sources = extractor::extract_none_sources_in_range(
             &tree,
             &content,
             &resolved.file_path,
             resolved.line_start,
             resolved.line_end,
         )?;
+        if none_sources.is_empty() {
+            if let Some(ReturnAnnotation::Optional(annotation)) =
+                extractor::extract_type_annotations(&tree, &content, resolved.line_start)
+            {
+                let location = CodeLocation::new(resolved.file_path.clone(), resolved.line_start);
+                none_sources.push(
+                    NoneSource::new(NoneSourceKind::AnnotatedOptional, location)
+                        .with_note(format!("annotated `-> {}`: the function's contract allows None", annotation)),
+                );
+            }
+        }
 
-        let call_context = CallContext {
-            current_module: get_full_module_path(&resolved.file_path),
-            current_class: extract_class_from_function_id(function_id),
+        let call_context = call_context(
+            resolved,
+            function_id,
+            self.module_path(&resolved.file_path),
             imports,
-        };
+        );
+        let (calls, awaited_calls) = self.traced_calls(&tree, &content, resolved, &call_context)?;
 
-        let calls = extractor::extract_calls_in_range_with_context(
+        let containing_function = class_call
+            .as_ref()
+            .map_or(function_id, |class_call| class_call.init_id.as_str());
+        let mut handlers = extractor::extract_handlers_in_range(
             &tree,
             &content,
+            &resolved.file_path,
             resolved.line_start,
             resolved.line_end,
-            &call_context,
         )?;
+        for handler in &mut handlers {
+            handler.location.containing_function = Some(containing_function.to_string());
+            handler.covered_calls = extractor::extract_calls_in_range_with_context(
+                &tree,
+                &content,
+                handler.try_line_start,
+                handler.try_line_end,
+                &call_context,
+            )?;
+        }
+
+        let mut suppressors = suppressors::extract_exception_suppressors(
+            &tree,
+            &content,
+            &resolved.file_path,
+            resolved.line_start,
+            resolved.line_end,
+        );
+        for suppressor in &mut suppressors {
+            suppressor.location.containing_function = Some(containing_function.to_string());
+        }
+
+        let mut catches = extractor::extract_catches(&tree, &content, &resolved.file_path);
+        catches.retain(|catch| catch.location.line >= resolved.line_start && catch.location.line <= resolved.line_end);
+        for catch in &mut catches {
+            catch.location.containing_function = Some(containing_function.to_string());
+        }
+
+        let docstring_raises = extractor::extract_docstring_raises(&tree, &content, resolved.line_start);
 
         Ok(SingleFunctionAnalysis {
             raises,
             none_sources,
             calls,
+            awaited_calls,
+            handlers,
+            suppressors,
+            catches,
+            class_call,
+            docstring_raises,
+        })
+    }
+
+    /// Functions called from `resolved`, including property accessors, which
+    /// run on attribute access and so are traced like calls, and the subset
+    /// of them that are awaited.
+    fn traced_calls(
+        &self,
+        tree: &tree_sitter::Tree,
+        content: &str,
+        resolved: &ResolvedLocation,
+        call_context: &CallContext,
+    ) -> Result<(Vec<String>, Vec<String>), TraversalError> {
+        let (mut calls, awaited) = extractor::extract_calls_and_awaits_in_range_with_context(
+            tree,
+            content,
+            resolved.line_start,
+            resolved.line_end,
+            call_context,
+        )?;
+
+        for accessor in self.property_accessors(
+            extractor::extract_attribute_accesses_in_range_with_context(
+                tree,
+                content,
+                resolved.line_start,
+                resolved.line_end,
+                call_context,
+            ),
+        ) {
+            if !calls.contains(&accessor) {
+                calls.push(accessor);
+            }
+        }
+
+        Ok((calls, awaited))
+    }
+
+    /// The calls made by `function_id` (for a class, by its `__init__`) and
+    /// the subset of them that are awaited.
+    fn function_calls(
+        &mut self,
+        resolved: &ResolvedLocation,
+        function_id: &str,
+    ) -> Result<(Vec<String>, Vec<String>), TraversalError> {
+        let class_call = self.class_constructor(function_id);
+        let constructor;
+        let resolved = match class_call {
+            Some(ref class_call) => {
+                constructor = self.resolve_function(&class_call.init_id)?;
+                &constructor
+            }
+            None => resolved,
+        };
+
+        let content = archive::read_source(&resolved.file_path)?;
+        let tree = self
+            .parser
+            .parse_str(&content, &resolved.file_path)
+            .map_err(|e| TraversalError::ParseError(e.to_string()))?;
+        let imports = extractor::extract_imports(&tree, &content);
+        let call_context = call_context(
+            resolved,
+            function_id,
+            self.module_path(&resolved.file_path),
+            imports,
+        );
+
+        self.traced_calls(&tree, &content, resolved, &call_context)
+    }
+
+    /// Symbol names of the property accessors invoked by `accesses`; plain
+    /// attributes are dropped.
+    fn property_accessors(&self, accesses: Vec<(String, PropertyKind)>) -> Vec<String> {
+        let Some(ref index) = self.symbol_index else {
+            return Vec::new();
+        };
+
+        accesses
+            .into_iter()
+            .map(|(name, kind)| kind.symbol_name(&name))
+            .filter(|symbol| index.get(symbol).is_some_and(|loc| loc.is_property))
+            .collect()
+    }
+
+    /// A class reached as a callee stands for its constructor, so analyze its
+    /// `__init__` instead of the whole class body when the index has one.
+    fn class_constructor(&self, function_id: &str) -> Option<ClassCallResolution> {
+        let index = self.symbol_index.as_ref()?;
+        if index.get(function_id)?.is_method {
+            return None;
+        }
+
+        let init_id = format!("{}.__init__", function_id);
+        index.get(&init_id)?;
+
+        Some(ClassCallResolution {
+            class_id: function_id.to_string(),
+            init_id,
         })
     }
 
@@ -256,9 +694,11 @@ impl Traverser {
         &self,
         exc_type: &str,
         imports: &HashMap<String, String>,
-        current_file: &PathBuf,
+        current_file: &Path,
     ) -> Option<CodeLocation> {
-        if is_builtin_exception(exc_type) {
+        if is_builtin_exception(exc_type)
+            || self.is_configured_builtin_exception(exc_type, imports, current_file)
+        {
             return None;
         }
 
@@ -315,7 +755,7 @@ fn is_builtin_exception(exc_type: &str) -> bool {
     builtins.contains(&exc_type)
 }
 
-fn get_module_from_path(path: &PathBuf) -> Option<String> {
+fn get_module_from_path(path: &Path) -> Option<String> {
     let file_stem = path.file_stem()?.to_str()?;
     if file_stem == "__init__" {
         path.parent()?.file_name()?.to_str().map(|s| s.to_string())
@@ -324,9 +764,14 @@ fn get_module_from_path(path: &PathBuf) -> Option<String> {
     }
 }
 
-fn get_full_module_path(path: &PathBuf) -> String {
+/// Dotted module name of `path`, walking up through package directories.
+/// The walk stops at a `python_path` entry, or at a top-level directory named
+/// in `src_layout_roots` even when it has a stray `__init__.py`, so
+/// `src/mypackage/api.py` is `mypackage.api` while `numpy/lib/function_base.py`
+/// keeps its `lib`, which sits inside a package.
+fn get_full_module_path(path: &Path, python_path: &[PathBuf], src_layout_roots: &[String]) -> String {
     let mut components = Vec::new();
-    let mut current = path.clone();
+    let mut current = path.to_path_buf();
 
     if let Some(stem) = current.file_stem() {
         let stem_str = stem.to_string_lossy();
@@ -337,7 +782,13 @@ fn get_full_module_path(path: &PathBuf) -> String {
 
     current = current.parent().map(|p| p.to_path_buf()).unwrap_or_default();
 
-    while current.join("__init__.py").exists() || current.file_name().map_or(false, |n| n == "src") {
+    let is_root = |dir: &Path| {
+        python_path.iter().any(|root| root == dir)
+            || (dir.file_name().is_some_and(|n| src_layout_roots.iter().any(|root| n == root.as_str()))
+                && !dir.parent().is_some_and(|parent| parent.join("__init__.py").exists()))
+    };
+
+    while current.join("__init__.py").exists() && !is_root(&current) {
         if let Some(name) = current.file_name() {
             components.push(name.to_string_lossy().to_string());
         }
@@ -351,23 +802,95 @@ fn get_full_module_path(path: &PathBuf) -> String {
     components.join(".")
 }
 
+/// Adds `entry`, replacing the chain already recorded for the same site.
+fn record_chain(call_chains: &mut Vec<CallChainEntry>, entry: CallChainEntry) {
+    let existing = call_chains
+        .iter_mut()
+        .find(|e| e.exception_type == entry.exception_type && e.location == entry.location);
+    match existing {
+        Some(existing) => *existing = entry,
+        None => call_chains.push(entry),
+    }
+}
+
+/// Walk the call chain from the raise site back to the root and return the
+/// first handler that stops the exception.
+fn find_suppressing_handler(
+    raise: &RaiseStatement,
+    chain: &[String],
+    handlers_by_function: &HashMap<String, Vec<ExceptionHandler>>,
+) -> Option<ExceptionHandler> {
+    if raise.exception_type == "(re-raise)" {
+        return None;
+    }
+
+    let raising_function = chain.last()?;
+    if let Some(handlers) = handlers_by_function.get(raising_function) {
+        if let Some(handler) = handlers
+            .iter()
+            .find(|h| h.guards_line(raise.raise_location.line) && h.catches(&raise.exception_type))
+        {
+            return Some(handler.clone());
+        }
+    }
+
+    for pair in chain.windows(2).rev() {
+        let (caller, callee) = (&pair[0], &pair[1]);
+        // `Class(...)` entries stand for the constructor that follows them
+        if caller.ends_with("(...)") {
+            continue;
+        }
+        let callee = callee.strip_suffix("(...)").unwrap_or(callee);
+
+        let Some(handlers) = handlers_by_function.get(caller) else {
+            continue;
+        };
+        if let Some(handler) = handlers.iter().find(|h| {
+            h.covered_calls.iter().any(|c| c == callee) && h.catches(&raise.exception_type)
+        }) {
+            return Some(handler.clone());
+        }
+    }
+
+    None
+}
+
 fn extract_class_from_function_id(function_id: &str) -> Option<String> {
     let parts: Vec<&str> = function_id.split('.').collect();
     if parts.len() >= 2 {
         let potential_class = parts[parts.len() - 2];
-        if potential_class.chars().next().map_or(false, |c| c.is_uppercase()) {
+        if potential_class.chars().next().is_some_and(|c| c.is_uppercase()) {
             return Some(potential_class.to_string());
         }
     }
     None
 }
 
+fn call_context(
+    resolved: &ResolvedLocation,
+    function_id: &str,
+    current_module: String,
+    imports: HashMap<String, String>,
+) -> CallContext {
+    CallContext {
+        current_module,
+        current_class: resolved
+            .parent_class
+            .clone()
+            .or_else(|| extract_class_from_function_id(function_id)),
+        imports,
+    }
+}
+
 #[derive(Debug)]
 struct ResolvedLocation {
     file_path: PathBuf,
     function_name: String,
     line_start: u32,
     line_end: u32,
+    parent_class: Option<String>,
+    /// Found in a `.pyi` stub: there is no body, so no callees to follow.
+    is_stub: bool,
 }
 
 #[cfg(test)]
@@ -380,4 +903,56 @@ mod tests {
         let traverser = Traverser::new(resolver, 10);
         assert!(traverser.is_ok());
     }
+
+    fn package(root: &Path, dirs: &[&str]) -> PathBuf {
+        let mut dir = root.to_path_buf();
+        for name in dirs {
+            dir = dir.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("__init__.py"), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_module_path_src_layout() {
+        let root = tempfile::tempdir().unwrap();
+        let roots = vec!["src".to_string(), "lib".to_string()];
+        let pkg = package(&root.path().join("src"), &["mypackage", "sub"]);
+
+        assert_eq!(get_full_module_path(&pkg.join("api.py"), &[], &roots), "mypackage.sub.api");
+        assert_eq!(get_full_module_path(&pkg.join("__init__.py"), &[], &roots), "mypackage.sub");
+    }
+
+    #[test]
+    fn test_module_path_src_layout_root_with_init() {
+        let root = tempfile::tempdir().unwrap();
+        let roots = vec!["src".to_string(), "lib".to_string()];
+        let pkg = package(root.path(), &["src", "mypackage"]);
+
+        assert_eq!(get_full_module_path(&pkg.join("api.py"), &[], &roots), "mypackage.api");
+        assert_eq!(get_full_module_path(&pkg.join("api.py"), &[], &[]), "src.mypackage.api");
+    }
+
+    #[test]
+    fn test_module_path_keeps_lib_subpackage() {
+        let root = tempfile::tempdir().unwrap();
+        let roots = vec!["src".to_string(), "lib".to_string()];
+        let lib = package(root.path(), &["pkg", "lib"]);
+
+        assert_eq!(get_full_module_path(&lib.join("mod.py"), &[], &roots), "pkg.lib.mod");
+
+        // A `lib` on the python path is still a root
+        assert_eq!(get_full_module_path(&lib.join("mod.py"), std::slice::from_ref(&lib), &roots), "mod");
+    }
+
+    #[test]
+    fn test_module_path_stops_at_python_path() {
+        let root = tempfile::tempdir().unwrap();
+        let pkg = package(root.path(), &["code", "mypackage"]);
+        let python_path = vec![root.path().join("code")];
+
+        assert_eq!(get_full_module_path(&pkg.join("api.py"), &python_path, &[]), "mypackage.api");
+        assert_eq!(get_full_module_path(&pkg.join("api.py"), &[], &[]), "code.mypackage.api");
+    }
 }
@@ -1,10 +1,21 @@
-use crate::analysis::grouping::suggest_groups;
+use crate::analysis::diff::diff_databases;
+use crate::analysis::docstrings::check_docstring_consistency;
+use crate::analysis::grouping::suggest_groups_with_config;
+use crate::analysis::indexer::Indexer;
+use crate::analysis::progress::cli_reporter;
 use crate::analysis::traversal::Traverser;
-use crate::core::config::ArborConfig;
-use crate::core::database::ArborDatabase;
+use crate::core::config::{matches_name_pattern, ArborConfig};
+use crate::core::database::{ArborDatabase, Environment, SymbolIndex};
 use crate::core::types::FunctionAnalysis;
-use crate::plugins::python::resolver::PythonResolver;
-use std::path::PathBuf;
+use crate::output::JsonArrayStreamer;
+use crate::plugins::python::extractor::{extract_dunder_all, extract_imports};
+use crate::plugins::python::parser::PythonParser;
+use crate::plugins::python::resolver::{detect_venv_type, PythonResolver, VenvType};
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -21,11 +32,23 @@ pub enum AnalyzeError {
     #[error("Database error: {0}")]
     Database(#[from] crate::core::database::DatabaseError),
 
+    #[error("Indexer error: {0}")]
+    Indexer(#[from] crate::analysis::indexer::IndexerError),
+
     #[error("Traversal error: {0}")]
     Traversal(#[from] crate::analysis::traversal::TraversalError),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("{0} function(s) have docstrings that disagree with their raises")]
+    DocstringMismatches(usize),
+
+    #[error("{0} function(s) raise exceptions not in the comparison database")]
+    NewExceptions(usize),
+
+    #[error("Interrupted; analyzed functions so far were saved")]
+    Interrupted,
 }
 
 pub struct AnalyzeArgs {
@@ -33,6 +56,102 @@ pub struct AnalyzeArgs {
     pub depth: usize,
     pub output_format: OutputFormat,
     pub venv_path: Option<PathBuf>,
+    pub prefer_local: bool,
+    pub quiet: bool,
+    pub timeout_seconds: Option<u64>,
+    pub warn_depth_limit: bool,
+    /// One-off package exclusions from `--exclude`; unlike `[ignore]` in the
+    /// config these are not persisted.
+    pub exclude_packages: Vec<String>,
+    pub exclude_functions: Vec<String>,
+    pub max_none_sources: Option<usize>,
+    /// Keep `_`-prefixed names when expanding `module.*` patterns.
+    pub include_private: bool,
+    /// Don't store source lines around each raise.
+    pub no_context: bool,
+    /// Warn where docstring `Raises` sections disagree with detected raises.
+    pub check_docstrings: bool,
+    /// With `check_docstrings`, fail the run if any function disagrees.
+    pub fail_on_mismatch: bool,
+    /// Print one line per root instead of the live progress counter.
+    pub no_progress: bool,
+    /// Preview what would be traced instead of analyzing; nothing is saved.
+    pub dry_run: bool,
+    /// With `dry_run`, list every function that would be analyzed.
+    pub verbose_dry_run: bool,
+    /// Print results without saving; works without an initialized database.
+    pub output_only: bool,
+    /// Overrides `analysis.checkpoint_every`.
+    pub checkpoint_every: Option<usize>,
+    /// Database to diff the analyzed functions against once the run finishes.
+    pub compare_with: Option<PathBuf>,
+    /// Index only the target functions' files and their direct imports
+    /// instead of loading the database; nothing is saved.
+    pub no_index: bool,
+}
+
+/// The CLI defaults: markdown output, depth 50, nothing excluded.
+impl Default for AnalyzeArgs {
+    fn default() -> Self {
+        Self {
+            functions: Vec::new(),
+            depth: 50,
+            output_format: OutputFormat::Markdown,
+            venv_path: None,
+            prefer_local: false,
+            quiet: false,
+            timeout_seconds: None,
+            warn_depth_limit: false,
+            exclude_packages: Vec::new(),
+            exclude_functions: Vec::new(),
+            max_none_sources: None,
+            include_private: false,
+            no_context: false,
+            check_docstrings: false,
+            fail_on_mismatch: false,
+            no_progress: false,
+            dry_run: false,
+            verbose_dry_run: false,
+            output_only: false,
+            checkpoint_every: None,
+            compare_with: None,
+            no_index: false,
+        }
+    }
+}
+
+impl AnalyzeArgs {
+    pub fn should_exclude_package(&self, package: &str) -> bool {
+        self.exclude_packages.iter().any(|p| matches_name_pattern(p, package))
+    }
+
+    pub fn should_exclude_function(&self, function: &str) -> bool {
+        self.exclude_functions.iter().any(|f| matches_name_pattern(f, function))
+    }
+
+    fn status_to_stderr(&self) -> bool {
+        matches!(self.output_format, OutputFormat::Json)
+    }
+
+    /// Prints a status line, to stderr for JSON output so stdout stays parseable.
+    fn print_status(&self, line: &str) {
+        if self.status_to_stderr() {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// What `arbor analyze` would do, from `dry_run_analyze`.
+#[derive(Debug, Clone, Default)]
+pub struct DryRunAnalysis {
+    /// Every function that would be traced, roots and callees, in order.
+    pub would_analyze: Vec<String>,
+    /// Roots skipped by exclusions or config, and callees past the depth limit.
+    pub would_skip: Vec<String>,
+    pub estimated_file_count: usize,
+    pub estimated_depth: usize,
 }
 
 #[derive(Clone, Copy)]
@@ -41,18 +160,145 @@ pub enum OutputFormat {
     Json,
 }
 
-pub fn run_analyze(args: AnalyzeArgs) -> Result<(), AnalyzeError> {
-    let config = ArborConfig::load_or_default();
+/// Read function names one per line, skipping blank lines and `#` comments.
+/// Lines that are not valid UTF-8 are skipped with a warning.
+pub fn read_functions_from_reader(reader: impl BufRead) -> Vec<String> {
+    let mut functions = Vec::new();
 
-    let db_path = std::env::current_dir()?.join(&config.database.path);
+    for (index, line) in reader.split(b'\n').enumerate() {
+        let bytes = match line {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("Warning: failed to read function list: {}", e);
+                break;
+            }
+        };
 
-    if !db_path.exists() {
-        return Err(AnalyzeError::DatabaseNotFound);
+        let line = match String::from_utf8(bytes) {
+            Ok(line) => line,
+            Err(_) => {
+                eprintln!("Warning: skipping line {} (not valid UTF-8)", index + 1);
+                continue;
+            }
+        };
+
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            functions.push(trimmed.to_string());
+        }
     }
 
-    println!("Loading database...");
-    let mut db = ArborDatabase::load(&db_path)?;
+    functions
+}
+
+/// Expands a `module.*` pattern into the public functions and methods indexed
+/// under `module`. Names not ending in `.*` are returned unchanged.
+pub fn expand_wildcard_function(pattern: &str, index: &SymbolIndex) -> Vec<String> {
+    expand_wildcard_function_with(pattern, index, false)
+}
+
+/// Like `expand_wildcard_function`, optionally keeping private names.
+///
+/// A name is private when any part after the module starts with `_`. For
+/// public-only expansion, a module that defines `__all__` is further limited
+/// to the names it lists.
+pub fn expand_wildcard_function_with(
+    pattern: &str,
+    index: &SymbolIndex,
+    include_private: bool,
+) -> Vec<String> {
+    let Some(module) = pattern.strip_suffix(".*") else {
+        return vec![pattern.to_string()];
+    };
+    let prefix = format!("{}.", module);
+
+    let exported = if include_private {
+        None
+    } else {
+        module_file(index, &prefix)
+            .and_then(|file| parse_dunder_all(&std::fs::read_to_string(file).ok()?, file))
+    };
+
+    index
+        .with_prefix(&prefix)
+        .map(|(name, _)| name)
+        .filter(|name| {
+            let rest = &name[prefix.len()..];
+            if !include_private && rest.split('.').any(|part| part.starts_with('_')) {
+                return false;
+            }
+            match exported {
+                Some(ref names) => {
+                    let top = rest.split('.').next().unwrap_or(rest);
+                    names.iter().any(|n| n == top)
+                }
+                None => true,
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// The source file defining `prefix`'s module, found through a function or
+/// method indexed directly in it rather than in a submodule.
+fn module_file<'a>(index: &'a SymbolIndex, prefix: &'a str) -> Option<&'a Path> {
+    index
+        .with_prefix(prefix)
+        .find(|(name, location)| {
+            let rest = &name[prefix.len()..];
+            match rest.split_once('.') {
+                None => !location.is_method,
+                Some((class, method)) => {
+                    !method.contains('.') && location.parent_class.as_deref() == Some(class)
+                }
+            }
+        })
+        .map(|(_, location)| location.file_path.as_path())
+}
+
+/// The names `file` lists in `__all__`; see `extract_dunder_all`.
+fn parse_dunder_all(source: &str, file: &Path) -> Option<Vec<String>> {
+    let mut parser = PythonParser::new().ok()?;
+    let tree = parser.parse_str(source, file).ok()?;
+    extract_dunder_all(&tree, source)
+}
+
+/// Why `function_id` is left out of this run, if it is.
+fn skip_reason(args: &AnalyzeArgs, config: &ArborConfig, function_id: &str) -> Option<String> {
+    let package = function_id.split('.').next().unwrap_or(function_id);
+    if args.should_exclude_function(function_id) {
+        Some("excluded".to_string())
+    } else if args.should_exclude_package(package) {
+        Some(format!("package {} excluded", package))
+    } else if config.should_ignore_function(function_id) {
+        Some("ignored by config".to_string())
+    } else if config.should_ignore_package(package) {
+        Some(format!("package {} ignored by config", package))
+    } else {
+        None
+    }
+}
+
+/// `args.functions` with `module.*` patterns expanded against `index`.
+fn expand_functions(args: &AnalyzeArgs, index: &SymbolIndex) -> Vec<String> {
+    let mut functions = Vec::new();
+    for function_id in &args.functions {
+        if function_id.ends_with(".*") {
+            let expanded = expand_wildcard_function_with(function_id, index, args.include_private);
+            if expanded.is_empty() {
+                eprintln!("Warning: no functions found for {}", function_id);
+            } else if !args.quiet {
+                args.print_status(&format!("Expanded {} to {} functions", function_id, expanded.len()));
+            }
+            functions.extend(expanded);
+        } else {
+            functions.push(function_id.clone());
+        }
+    }
+    functions
+}
 
+fn build_resolver(args: &AnalyzeArgs, config: &ArborConfig, db: &ArborDatabase) -> PythonResolver {
     let python_path: Vec<PathBuf> = if config.environment.python_path.is_empty() {
         db.environment
             .python_path
@@ -81,57 +327,447 @@ pub fn run_analyze(args: AnalyzeArgs) -> Result<(), AnalyzeError> {
             .collect()
     };
 
-    let resolver = PythonResolver::new(python_path, site_packages);
+    PythonResolver::new(python_path, site_packages)
+}
+
+/// Set by the SIGINT handler; `run_analyze` stops before the next function.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// On the first Ctrl-C, asks `run_analyze` to stop after the function in
+/// progress so the session can save; a second Ctrl-C exits immediately.
+fn install_interrupt_handler() {
+    // Fails only if a handler is already installed, which is the one we want
+    let _ = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        eprintln!("\nInterrupted; finishing the current function and saving (Ctrl-C again to quit now)");
+    });
+}
+
+/// The database for one `arbor analyze` run. Analyses are kept in memory and
+/// written every `checkpoint_every` functions and at the end; dropping an
+/// unsaved session (e.g. while unwinding a panic) writes what it has.
+struct AnalyzeSession {
+    db: ArborDatabase,
+    db_path: PathBuf,
+    /// False for `--output-only`, which never writes.
+    persist: bool,
+    checkpoint_every: usize,
+    pending: usize,
+    saved: bool,
+}
+
+impl AnalyzeSession {
+    fn new(db: ArborDatabase, db_path: PathBuf, persist: bool, checkpoint_every: usize) -> Self {
+        Self {
+            db,
+            db_path,
+            persist,
+            checkpoint_every,
+            pending: 0,
+            saved: true,
+        }
+    }
+
+    fn add_function(&mut self, analysis: FunctionAnalysis) -> Result<(), AnalyzeError> {
+        for (caller, callee) in &analysis.call_edges {
+            self.db.dependency_graph.add_call(caller, callee);
+        }
+        self.db.add_function(analysis);
+        self.pending += 1;
+        self.saved = false;
+
+        if self.checkpoint_every > 0 && self.pending >= self.checkpoint_every {
+            if self.persist {
+                self.db.checkpoint(&self.db_path)?;
+            }
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Final save for the run; records a stats snapshot.
+    fn save(&mut self) -> Result<(), AnalyzeError> {
+        if self.persist {
+            self.db.save(&self.db_path)?;
+        }
+        self.pending = 0;
+        self.saved = true;
+        Ok(())
+    }
+}
+
+impl Drop for AnalyzeSession {
+    fn drop(&mut self) {
+        if self.persist && !self.saved {
+            if let Err(e) = self.db.save(&self.db_path) {
+                eprintln!("Error: could not save analysis results: {}", e);
+            }
+        }
+    }
+}
+
+/// Previews an analysis: resolves the functions `run_analyze` would trace
+/// (callees included) without extracting raises or None sources.
+pub fn dry_run_analyze(args: &AnalyzeArgs, db: &ArborDatabase) -> Result<DryRunAnalysis, AnalyzeError> {
+    let config = ArborConfig::load_or_default();
+    let mut traverser = Traverser::new(build_resolver(args, &config, db), args.depth)?
+        .with_symbol_index(db.symbol_index.clone())
+        .with_prefer_local(args.prefer_local)
+        .with_src_layout_roots(config.analysis.src_layout_roots.clone())
+        .with_builtin_exceptions(config.analysis.builtin_exceptions.clone());
+
+    let mut preview = DryRunAnalysis::default();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut files: HashSet<PathBuf> = HashSet::new();
+
+    for function_id in expand_functions(args, &db.symbol_index) {
+        if skip_reason(args, &config, &function_id).is_some() {
+            preview.would_skip.push(function_id);
+            continue;
+        }
+
+        let plan = traverser.plan_function(&function_id);
+        preview.estimated_depth = preview.estimated_depth.max(plan.max_depth);
+        for (function, file) in plan.functions {
+            files.insert(file);
+            if seen.insert(function.clone()) {
+                preview.would_analyze.push(function);
+            }
+        }
+        for skipped in plan.skipped_functions {
+            if !preview.would_skip.contains(&skipped) {
+                preview.would_skip.push(skipped);
+            }
+        }
+    }
+
+    preview.would_skip.retain(|f| !seen.contains(f));
+    preview.estimated_file_count = files.len();
+    Ok(preview)
+}
+
+fn print_dry_run(preview: &DryRunAnalysis, verbose: bool) {
+    println!(
+        "Would analyze {} functions across {} files, estimated depth up to {}",
+        preview.would_analyze.len(),
+        preview.estimated_file_count,
+        preview.estimated_depth
+    );
+    if !preview.would_skip.is_empty() {
+        println!("Would skip {} functions", preview.would_skip.len());
+    }
+
+    if verbose {
+        println!("\n## Would Analyze\n");
+        for function in &preview.would_analyze {
+            println!("- {}", function);
+        }
+        if !preview.would_skip.is_empty() {
+            println!("\n## Would Skip\n");
+            for function in &preview.would_skip {
+                println!("- {}", function);
+            }
+        }
+    }
+
+    println!("\nDry run: database not modified");
+}
+
+pub fn run_analyze(args: AnalyzeArgs) -> Result<(), AnalyzeError> {
+    let config = ArborConfig::load_or_default();
+
+    let db_path = std::env::current_dir()?.join(&config.database.path);
+
+    let db = if args.no_index {
+        eprintln!("Warning: Running without full index; call graph may be incomplete");
+        open_local_database(&args, &config)?
+    } else {
+        if db_path.exists() && !args.quiet {
+            args.print_status("Loading database...");
+        }
+        open_database(&db_path, args.output_only)?
+    };
+    let persist = !args.output_only && !args.no_index;
+
+    if args.dry_run {
+        print_dry_run(&dry_run_analyze(&args, &db)?, args.verbose_dry_run);
+        return Ok(());
+    }
+
+    // Loaded up front so a bad path fails before the analysis, not after
+    let compare_db = match args.compare_with {
+        Some(ref path) => Some(ArborDatabase::load(path)?),
+        None => None,
+    };
+
+    let resolver = build_resolver(&args, &config, &db);
 
     let max_depth = args.depth;
     let mut traverser = Traverser::new(resolver, max_depth)?
-        .with_symbol_index(db.symbol_index.clone());
+        .with_symbol_index(db.symbol_index.clone())
+        .with_prefer_local(args.prefer_local)
+        .with_source_context(!args.no_context && config.analysis.include_source_context)
+        .with_src_layout_roots(config.analysis.src_layout_roots.clone())
+        .with_builtin_exceptions(config.analysis.builtin_exceptions.clone())
+        .with_progress(cli_reporter(
+            args.quiet,
+            !args.no_progress,
+            args.status_to_stderr(),
+        ));
 
-    for function_id in &args.functions {
-        if config.should_ignore_function(function_id) {
-            println!("\nSkipping {} (ignored by config)", function_id);
+    let timeout_seconds = args.timeout_seconds.unwrap_or(config.analysis.timeout_seconds);
+    if timeout_seconds > 0 {
+        traverser = traverser.with_timeout(Duration::from_secs(timeout_seconds));
+    }
+
+    let max_none_sources = args.max_none_sources.or(config.analysis.max_none_sources);
+
+    let functions = expand_functions(&args, &db.symbol_index);
+
+    let checkpoint_every = args.checkpoint_every.unwrap_or(config.analysis.checkpoint_every);
+    let mut session = AnalyzeSession::new(db, db_path.clone(), persist, checkpoint_every);
+    install_interrupt_handler();
+
+    if !args.exclude_packages.is_empty() {
+        args.print_status(&format!("Excluding packages: {}", args.exclude_packages.join(", ")));
+    }
+    if !args.exclude_functions.is_empty() {
+        args.print_status(&format!("Excluding functions: {}", args.exclude_functions.join(", ")));
+    }
+
+    let mut docstring_mismatches = Vec::new();
+    let mut analyzed = Vec::new();
+
+    // Several JSON results are streamed as one array, each item as it
+    // finishes; an error returning early still closes it on drop
+    let mut json_array = match args.output_format {
+        OutputFormat::Json if functions.len() > 1 => Some(JsonArrayStreamer::begin(std::io::stdout())?),
+        _ => None,
+    };
+
+    for function_id in &functions {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Some(reason) = skip_reason(&args, &config, function_id) {
+            args.print_status(&format!("\nSkipping {} ({})", function_id, reason));
             continue;
         }
 
-        if let Some(package) = function_id.split('.').next() {
-            if config.should_ignore_package(package) {
-                println!("\nSkipping {} (package {} ignored by config)", function_id, package);
-                continue;
-            }
+        let mut analysis = traverser.analyze_function_partial(function_id)?;
+
+        let risk_before = analysis.risk_level();
+        analysis.strip_ignored_raises(|exc_type| config.should_ignore_exception(exc_type));
+        let risk_after = analysis.risk_level();
+        if risk_after < risk_before {
+            let mut ignored: Vec<&str> =
+                analysis.ignored_raises.iter().map(|r| r.exception_type.as_str()).collect();
+            ignored.sort();
+            ignored.dedup();
+            eprintln!(
+                "⚠ `{}` risk was {}, reduced to {} after ignoring {}",
+                function_id,
+                risk_before.as_str(),
+                risk_after.as_str(),
+                ignored.join(", ")
+            );
         }
 
-        println!("\nAnalyzing {}...", function_id);
+        if analysis.partial {
+            eprintln!(
+                "Warning: analysis of {} timed out after {}s ({} functions traced); saving partial result",
+                function_id, timeout_seconds, analysis.functions_traced
+            );
+        }
 
-        let analysis = traverser.analyze_function(function_id)?;
+        if let Some(max) = max_none_sources {
+            if analysis.none_source_count() > max {
+                eprintln!(
+                    "Warning: {} has {} None sources (max {})",
+                    function_id,
+                    analysis.none_source_count(),
+                    max
+                );
+            }
+        }
+
+        if args.warn_depth_limit {
+            for skipped in &analysis.skipped_functions {
+                eprintln!(
+                    "Warning: {} not traced from {} (depth limit {})",
+                    skipped, function_id, max_depth
+                );
+            }
+        }
 
         if !analysis.raises.is_empty() {
-            let suggestions = suggest_groups(&analysis.raises);
-            for suggestion in suggestions {
-                db.grouping_suggestions.insert(suggestion.group_name.clone(), suggestion);
+            let suggestions = suggest_groups_with_config(&analysis.raises, &config.recovery);
+            for mut suggestion in suggestions {
+                suggestion.applicable_functions.push(analysis.function_id.clone());
+                session.db.add_grouping_suggestion(suggestion);
             }
         }
 
-        print_analysis_summary(&analysis, args.output_format);
+        match json_array {
+            Some(ref mut array) => array.write_item(&analysis)?,
+            None => print_analysis_summary(&analysis, args.output_format),
+        }
+
+        if args.check_docstrings {
+            docstring_mismatches.extend(check_docstring_consistency(&analysis));
+        }
+
+        analyzed.push(analysis.function_id.clone());
+        session.add_function(analysis)?;
+    }
 
-        db.functions.insert(function_id.clone(), analysis);
+    if let Some(array) = json_array {
+        array.finish()?;
     }
 
-    if !db.grouping_suggestions.is_empty() {
-        println!("\n## Grouping Suggestions\n");
-        for suggestion in db.grouping_suggestions.values() {
-            println!("### {}\n", suggestion.group_name);
-            println!("**Exceptions:** {}\n", suggestion.exceptions.join(", "));
-            println!("**Rationale:** {}\n", suggestion.rationale);
-            println!("```python\n{}\n```\n", suggestion.handler_example);
+    if !session.db.grouping_suggestions.is_empty() {
+        args.print_status("\n## Grouping Suggestions\n");
+        for suggestion in session.db.grouping_suggestions.values() {
+            args.print_status(&format!("### {}\n", suggestion.group_name));
+            args.print_status(&format!("**Exceptions:** {}\n", suggestion.exceptions.join(", ")));
+            args.print_status(&format!("**Rationale:** {}\n", suggestion.rationale));
+            args.print_status(&format!("```python\n{}\n```\n", suggestion.handler_example));
         }
     }
 
-    db.save(&db_path)?;
-    println!("\nResults saved to {}", db_path.display());
+    session.save()?;
+    if persist {
+        args.print_status(&format!("\nResults saved to {}", db_path.display()));
+    }
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        return Err(AnalyzeError::Interrupted);
+    }
+
+    if let (Some(ref compare_db), Some(ref path)) = (compare_db, &args.compare_with) {
+        let diffs = diff_databases(compare_db, &session.db, &analyzed);
+        let report = |text: &str| args.print_status(text);
+        report(&format!("\n## Changes Since {}\n", path.display()));
+        let lines: Vec<String> = diffs.iter().flat_map(|d| d.lines()).collect();
+        if lines.is_empty() {
+            report("No changes");
+        }
+        for line in &lines {
+            report(line);
+        }
+        let gained = diffs.iter().filter(|d| !d.added_exceptions.is_empty()).count();
+        if gained > 0 {
+            return Err(AnalyzeError::NewExceptions(gained));
+        }
+    }
+
+    if args.check_docstrings {
+        if docstring_mismatches.is_empty() {
+            args.print_status("\nDocstrings match detected raises");
+        } else {
+            eprintln!();
+            for mismatch in &docstring_mismatches {
+                for warning in mismatch.warnings() {
+                    eprintln!("{}", warning);
+                }
+            }
+            if args.fail_on_mismatch {
+                return Err(AnalyzeError::DocstringMismatches(docstring_mismatches.len()));
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// The database at `db_path`. With `output_only` a missing database is not an
+/// error: analysis then runs against an empty one rooted at the current
+/// directory, with no symbol index, and nothing is written.
+fn open_database(db_path: &Path, output_only: bool) -> Result<ArborDatabase, AnalyzeError> {
+    if db_path.exists() {
+        return Ok(ArborDatabase::load(db_path)?);
+    }
+    if !output_only {
+        return Err(AnalyzeError::DatabaseNotFound);
+    }
+    Ok(ArborDatabase::new(Environment {
+        python_path: vec![std::env::current_dir()?.to_string_lossy().to_string()],
+        ..Environment::default()
+    }))
+}
+
+/// A throwaway database for `--no-index`, rooted at the current directory
+/// like `open_database` with `output_only`, whose symbol index covers only
+/// the files defining `args.functions` and the modules they import directly.
+fn open_local_database(args: &AnalyzeArgs, config: &ArborConfig) -> Result<ArborDatabase, AnalyzeError> {
+    let mut db = ArborDatabase::new(Environment {
+        python_path: vec![std::env::current_dir()?.to_string_lossy().to_string()],
+        ..Environment::default()
+    });
+    let mut resolver = build_resolver(args, config, &db);
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for function in &args.functions {
+        let found = match function.strip_suffix(".*") {
+            Some(module) => module_source(&resolver, module),
+            None => resolver.resolve(function).ok().map(|resolved| resolved.file_path),
+        };
+        match found {
+            Some(file) if !files.contains(&file) => files.push(file),
+            Some(_) => {}
+            None => eprintln!("Warning: Could not resolve {}", function),
+        }
+    }
+
+    let mut parser = PythonParser::new().map_err(|e| AnalyzeError::AnalysisFailed(e.to_string()))?;
+    for file in files.clone() {
+        let Ok(content) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        let Ok(tree) = parser.parse_str(&content, &file) else {
+            continue;
+        };
+        for imported in extract_imports(&tree, &content).into_values() {
+            // `from m import f` names a function in m; `import m` names m itself
+            let found = resolver
+                .resolve(&imported)
+                .ok()
+                .map(|resolved| resolved.file_path)
+                .or_else(|| module_source(&resolver, &imported));
+            if let Some(found) = found.filter(|f| !files.contains(f)) {
+                files.push(found);
+            }
+        }
+    }
+
+    let mut indexer = Indexer::new()?;
+    let search_paths: Vec<PathBuf> = resolver.search_paths().into_iter().cloned().collect();
+    for file in &files {
+        let base_dir = search_paths
+            .iter()
+            .filter(|base| file.starts_with(base))
+            .max_by_key(|base| base.components().count())
+            .cloned()
+            .or_else(|| file.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        if let Err(e) = indexer.index_file(file, &base_dir, &mut db.symbol_index) {
+            eprintln!("Warning: Failed to index {}: {}", file.display(), e);
+        }
+    }
+    db.symbol_index.mark_indexed();
+
+    Ok(db)
+}
+
+/// The source file of a module: its `.py` file, or a package's `__init__.py`.
+fn module_source(resolver: &PythonResolver, module: &str) -> Option<PathBuf> {
+    let parts: Vec<&str> = module.split('.').collect();
+    let path = resolver.resolve_module_path(&parts)?;
+    let path = if path.is_dir() { path.join("__init__.py") } else { path };
+    path.is_file().then_some(path)
+}
+
 fn print_analysis_summary(analysis: &FunctionAnalysis, format: OutputFormat) {
     match format {
         OutputFormat::Markdown => print_markdown(analysis),
@@ -148,6 +784,12 @@ fn print_markdown(analysis: &FunctionAnalysis) {
     println!("**Location:** {}", analysis.location.to_string_short());
     println!("**Functions traced:** {}", analysis.functions_traced);
     println!("**Max call depth:** {}", analysis.call_depth);
+    if analysis.partial {
+        println!("**Partial:** yes (analysis timed out)");
+    }
+    if analysis.depth_limit_hits > 0 {
+        println!("**Skipped (depth limit):** {}", analysis.depth_limit_hits);
+    }
     println!();
 
     if !analysis.raises.is_empty() {
@@ -200,7 +842,13 @@ fn print_json(analysis: &FunctionAnalysis) {
     }
 }
 
-fn find_venv_site_packages(venv_path: &PathBuf) -> Vec<PathBuf> {
+fn find_venv_site_packages(venv_path: &Path) -> Vec<PathBuf> {
+    if detect_venv_type(venv_path) == VenvType::Conda {
+        if let Some(site_packages) = PythonResolver::find_conda_site_packages(venv_path) {
+            return vec![site_packages];
+        }
+    }
+
     let mut results = Vec::new();
 
     let candidates = [
@@ -220,22 +868,193 @@ fn find_venv_site_packages(venv_path: &PathBuf) -> Vec<PathBuf> {
     }
 
     if results.is_empty() {
-        if let Ok(entries) = std::fs::read_dir(venv_path.join("lib")) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    let name = path.file_name().unwrap_or_default().to_string_lossy();
-                    if name.starts_with("python") {
-                        let site_packages = path.join("site-packages");
-                        if site_packages.exists() {
-                            results.push(site_packages);
-                            break;
-                        }
-                    }
-                }
-            }
-        }
+        results.extend(PythonResolver::search_site_packages(venv_path));
     }
 
     results
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args_with_exclusions(packages: &[&str], functions: &[&str]) -> AnalyzeArgs {
+        AnalyzeArgs {
+            quiet: true,
+            exclude_packages: packages.iter().map(|s| s.to_string()).collect(),
+            exclude_functions: functions.iter().map(|s| s.to_string()).collect(),
+            ..AnalyzeArgs::default()
+        }
+    }
+
+    #[test]
+    fn test_session_checkpoints_and_saves_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db.json");
+        let analysis = |id: &str| {
+            FunctionAnalysis::new(
+                id.to_string(),
+                format!("def {}()", id),
+                crate::core::types::CodeLocation::new(dir.path().join("app.py"), 1),
+            )
+        };
+        let saved_count = || ArborDatabase::load(&db_path).unwrap().functions.len();
+
+        let mut session = AnalyzeSession::new(ArborDatabase::new(Environment::default()), db_path.clone(), true, 2);
+        session.add_function(analysis("app.a")).unwrap();
+        assert!(!db_path.exists());
+        session.add_function(analysis("app.b")).unwrap();
+        assert_eq!(saved_count(), 2);
+        assert!(ArborDatabase::load(&db_path).unwrap().stats_history.is_empty());
+
+        session.add_function(analysis("app.c")).unwrap();
+        drop(session);
+        assert_eq!(saved_count(), 3);
+
+        let mut session = AnalyzeSession::new(ArborDatabase::load(&db_path).unwrap(), db_path.clone(), false, 1);
+        session.add_function(analysis("app.d")).unwrap();
+        drop(session);
+        assert_eq!(saved_count(), 3);
+    }
+
+    #[test]
+    fn test_open_database_output_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join(".arbor").join("db.json");
+
+        assert!(matches!(open_database(&db_path, false), Err(AnalyzeError::DatabaseNotFound)));
+
+        let db = open_database(&db_path, true).unwrap();
+        assert!(db.symbol_index.is_empty());
+        assert_eq!(db.environment.python_path.len(), 1);
+        assert!(!db_path.exists());
+    }
+
+    #[test]
+    fn test_open_local_database_indexes_target_and_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg = dir.path().join("pkg");
+        std::fs::create_dir(&pkg).unwrap();
+        std::fs::write(pkg.join("__init__.py"), "").unwrap();
+        std::fs::write(pkg.join("app.py"), "from pkg.util import helper\n\ndef main():\n    helper()\n").unwrap();
+        std::fs::write(pkg.join("util.py"), "def helper():\n    raise ValueError()\n").unwrap();
+        std::fs::write(pkg.join("other.py"), "def unrelated():\n    pass\n").unwrap();
+
+        let mut config = ArborConfig::default();
+        config.environment.python_path = vec![dir.path().to_path_buf()];
+        let mut args = args_with_exclusions(&[], &[]);
+        args.functions = vec!["pkg.app.main".to_string()];
+        args.no_index = true;
+
+        let db = open_local_database(&args, &config).unwrap();
+        assert!(db.symbol_index.contains("pkg.app.main"));
+        assert!(db.symbol_index.contains("pkg.util.helper"));
+        assert!(!db.symbol_index.contains("pkg.other.unrelated"));
+    }
+
+    #[test]
+    fn test_read_functions_from_reader() {
+        let input: &[u8] = b"# functions to check\napi.fetch\n\n  api.save  \r\n\xff\xfe\nutils.\xe5\xa4\x84\xe7\x90\x86";
+        assert_eq!(
+            read_functions_from_reader(input),
+            vec!["api.fetch", "api.save", "utils.处理"]
+        );
+    }
+
+    #[test]
+    fn test_cli_exclusions() {
+        let args = args_with_exclusions(&["django", "celery*"], &["*._private_*"]);
+
+        assert!(args.should_exclude_package("django"));
+        assert!(args.should_exclude_package("celery_beat"));
+        assert!(!args.should_exclude_package("requests"));
+
+        assert!(args.should_exclude_function("api.client._private_fetch"));
+        assert!(!args.should_exclude_function("api.client.fetch"));
+    }
+
+    fn index_with(names: &[(&str, &Path, Option<&str>)]) -> SymbolIndex {
+        let mut index = SymbolIndex::new();
+        for (name, file, parent_class) in names {
+            index.add(
+                name.to_string(),
+                crate::core::database::SymbolLocation {
+                    file_path: file.to_path_buf(),
+                    line_start: 1,
+                    line_end: 2,
+                    is_method: parent_class.is_some(),
+                    parent_class: parent_class.map(str::to_string),
+                    is_property: false,
+                    property_kind: None,
+                    column_start: None,
+                    column_end: None,
+                    is_stub: false,
+                },
+            );
+        }
+        index
+    }
+
+    #[test]
+    fn test_expand_wildcard_public_by_convention() {
+        let file = Path::new("myapp/api.py");
+        let index = index_with(&[
+            ("myapp.api.get_user", file, None),
+            ("myapp.api._load", file, None),
+            ("myapp.api.Client.fetch", file, Some("Client")),
+            ("myapp.api.Client._retry", file, Some("Client")),
+            ("myapp.api.Client.__init__", file, Some("Client")),
+            ("myapp.apis.other", Path::new("myapp/apis.py"), None),
+        ]);
+
+        assert_eq!(
+            expand_wildcard_function("myapp.api.*", &index),
+            vec!["myapp.api.Client.fetch", "myapp.api.get_user"]
+        );
+        assert_eq!(
+            expand_wildcard_function_with("myapp.api.*", &index, true),
+            vec![
+                "myapp.api.Client.__init__",
+                "myapp.api.Client._retry",
+                "myapp.api.Client.fetch",
+                "myapp.api._load",
+                "myapp.api.get_user",
+            ]
+        );
+        assert_eq!(expand_wildcard_function("myapp.api.get_user", &index), vec!["myapp.api.get_user"]);
+        assert!(expand_wildcard_function("missing.*", &index).is_empty());
+    }
+
+    #[test]
+    fn test_expand_wildcard_respects_dunder_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("api.py");
+        std::fs::write(
+            &file,
+            "__all__ = [\"get_user\", 'Client']\n__all__ += (\"_compat\",)\n\ndef get_user(): ...\n",
+        )
+        .unwrap();
+        let index = index_with(&[
+            ("api.get_user", &file, None),
+            ("api.delete_user", &file, None),
+            ("api._compat", &file, None),
+            ("api.Client.fetch", &file, Some("Client")),
+        ]);
+
+        assert_eq!(
+            expand_wildcard_function("api.*", &index),
+            vec!["api.Client.fetch", "api.get_user"]
+        );
+        // --include-private ignores __all__ and keeps everything under the module
+        assert_eq!(expand_wildcard_function_with("api.*", &index, true).len(), 4);
+    }
+
+    #[test]
+    fn test_parse_dunder_all() {
+        assert_eq!(
+            parse_dunder_all("import os\n__all__ = ['a', \"b\",\n    'c']\n", Path::new("api.py")),
+            Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+        assert_eq!(parse_dunder_all("def f():\n    return 1\n", Path::new("api.py")), None);
+    }
+}
@@ -1,8 +1,19 @@
+use crate::analysis::changes::ChangedFilesDetector;
 use crate::analysis::indexer::Indexer;
+use crate::analysis::progress::cli_reporter;
+use crate::cli::analyze::{run_analyze, AnalyzeArgs};
+use crate::core::config::{
+    add_framework_builtins, framework_builtin_exceptions, ArborConfig, ConfigError, PyprojectReader,
+    KNOWN_BUILTIN_FRAMEWORKS,
+};
 use crate::core::database::{ArborDatabase, Environment};
 use crate::core::paths;
+use crate::core::types::RiskLevel;
+use crate::output::{json_serialize, write_jsonl, HtmlOutput, SarifOutput};
 use crate::plugins::python::resolver::PythonResolver;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use thiserror::Error;
 
@@ -23,6 +34,15 @@ pub enum DbCommandError {
     #[error("Database error: {0}")]
     Database(#[from] crate::core::database::DatabaseError),
 
+    #[error("Analysis error: {0}")]
+    Analyze(#[from] crate::cli::analyze::AnalyzeError),
+
+    #[error("Config error: {0}")]
+    Config(#[from] crate::core::config::ConfigError),
+
+    #[error("No bundled builtin exceptions for '{0}' (known: {1})")]
+    UnknownFramework(String, String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -30,6 +50,23 @@ pub enum DbCommandError {
 pub struct InitOptions {
     pub force: bool,
     pub index_site_packages: bool,
+    pub quiet: bool,
+    /// Locate these packages in system site-packages, for setups without a venv.
+    pub requirements: Option<PathBuf>,
+    /// Seed the environment and `.arbor/config.toml` from `pyproject.toml`.
+    pub scan_pyproject: bool,
+    /// Directory levels to index below each path, overriding
+    /// `analysis.index_depth`.
+    pub index_depth: Option<usize>,
+    /// Skip Python files larger than this many KB, overriding
+    /// `analysis.max_file_size_kb`.
+    pub max_file_size_kb: Option<usize>,
+    /// Frameworks whose bundled exception patterns are appended to
+    /// `analysis.builtin_exceptions`.
+    pub add_builtins: Vec<String>,
+    /// Interpreter (path or name on `PATH`) to detect the Python version and
+    /// site-packages with, instead of `python3`/`python`.
+    pub python_interpreter: Option<String>,
 }
 
 impl Default for InitOptions {
@@ -37,13 +74,48 @@ impl Default for InitOptions {
         Self {
             force: false,
             index_site_packages: true,
+            quiet: false,
+            requirements: None,
+            scan_pyproject: false,
+            index_depth: None,
+            max_file_size_kb: None,
+            add_builtins: Vec::new(),
+            python_interpreter: None,
+        }
+    }
+}
+
+/// Output path that sends an export to stdout instead of a file.
+pub const STDOUT_PATH: &str = "-";
+
+/// How many source lines to embed around each raise site in an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceContextLevel {
+    pub lines_before: usize,
+    pub lines_after: usize,
+}
+
+impl SourceContextLevel {
+    pub fn symmetric(lines: usize) -> Self {
+        Self {
+            lines_before: lines,
+            lines_after: lines,
         }
     }
 }
 
 pub struct ExportOptions {
     pub output_path: Option<PathBuf>,
+    /// Split the export into one file per package; `output_path` is ignored.
+    pub output_dir: Option<PathBuf>,
     pub format: String,
+    pub source_context: Option<SourceContextLevel>,
+    /// Only export functions at or above this risk level.
+    pub filter_risk: Option<RiskLevel>,
+    /// Indent JSON exports; `--no-pretty` writes them on one line.
+    pub pretty_print: bool,
+    /// Open the written file with the system's default application.
+    pub open: bool,
 }
 
 pub fn run_init(options: InitOptions) -> Result<PathBuf, DbCommandError> {
@@ -53,21 +125,80 @@ pub fn run_init(options: InitOptions) -> Result<PathBuf, DbCommandError> {
         return Err(DbCommandError::AlreadyExists(db_path.display().to_string()));
     }
 
+    if let Some(unknown) = options.add_builtins.iter().find(|f| framework_builtin_exceptions(f).is_none()) {
+        return Err(DbCommandError::UnknownFramework(unknown.clone(), KNOWN_BUILTIN_FRAMEWORKS.join(", ")));
+    }
+
     paths::ensure_arbor_dir()?;
 
-    println!("Detecting Python environment...");
-    let environment = detect_environment()?;
+    let pyproject_config = if options.scan_pyproject {
+        read_pyproject_config(options.quiet)?
+    } else {
+        None
+    };
 
-    println!("Python version: {}", environment.python_version);
-    if let Some(ref venv) = environment.venv_path {
-        println!("Virtual env: {}", venv);
+    if !options.quiet {
+        println!("Detecting Python environment...");
+    }
+    let mut environment = detect_environment(options.python_interpreter.as_deref())?;
+    if let Some(ref config) = pyproject_config {
+        apply_config_environment(&mut environment, config);
+    }
+
+    if let Some(ref requirements) = options.requirements {
+        let packages = parse_requirements(requirements);
+        let search_dirs = system_site_packages_dirs();
+        let mut missing = Vec::new();
+
+        for package in &packages {
+            match locate_package(package, &search_dirs) {
+                Some(dir) => {
+                    let dir = dir.display().to_string();
+                    if !environment.site_packages.contains(&dir) {
+                        environment.site_packages.push(dir);
+                    }
+                }
+                None => missing.push(package.as_str()),
+            }
+        }
+
+        if !options.quiet {
+            println!(
+                "Located {} of {} requirements",
+                packages.len() - missing.len(),
+                packages.len()
+            );
+        }
+        if !missing.is_empty() {
+            eprintln!("Warning: could not locate: {}", missing.join(", "));
+        }
+    }
+
+    if !options.quiet {
+        println!("Python version: {}", environment.python_version);
+        if let Some(ref venv) = environment.venv_path {
+            println!("Virtual env: {}", venv);
+        }
+        println!("Site-packages: {:?}", environment.site_packages);
     }
-    println!("Site-packages: {:?}", environment.site_packages);
 
     let mut db = ArborDatabase::new(environment.clone());
 
-    println!("Indexing Python files...");
-    let mut indexer = Indexer::new()?;
+    if !options.quiet {
+        println!("Indexing Python files...");
+    }
+    let index_depth = options.index_depth.or_else(|| match pyproject_config {
+        Some(ref config) => config.analysis.index_depth,
+        None => ArborConfig::load_or_default().analysis.index_depth,
+    });
+    let max_file_size_kb = options.max_file_size_kb.or_else(|| match pyproject_config {
+        Some(ref config) => config.analysis.max_file_size_kb,
+        None => ArborConfig::load_or_default().analysis.max_file_size_kb,
+    });
+    let mut indexer = Indexer::new()?
+        .with_progress(cli_reporter(options.quiet, false, false))
+        .with_max_depth(index_depth)
+        .with_max_file_size_kb(max_file_size_kb);
 
     let mut dirs_to_index: Vec<PathBuf> = environment
         .python_path
@@ -94,11 +225,24 @@ pub fn run_init(options: InitOptions) -> Result<PathBuf, DbCommandError> {
 
     let config_path = paths::config_path();
     if !config_path.exists() {
-        let config_content = crate::core::config::ArborConfig::default_toml();
-        std::fs::write(&config_path, config_content)?;
+        match pyproject_config {
+            Some(ref config) => config.save(&config_path)?,
+            None => std::fs::write(&config_path, ArborConfig::default_toml())?,
+        }
         println!("Created {}", config_path.display());
     }
 
+    if !options.add_builtins.is_empty() {
+        let mut document: toml_edit::DocumentMut = std::fs::read_to_string(&config_path)?
+            .parse()
+            .map_err(ConfigError::from)?;
+        for framework in &options.add_builtins {
+            let added = add_framework_builtins(&mut document, framework).unwrap_or(0);
+            println!("Added {} builtin exception pattern(s) for {}", added, framework);
+        }
+        std::fs::write(&config_path, document.to_string())?;
+    }
+
     let command_path = paths::commands_dir().join("arbor.md");
     if !command_path.exists() {
         std::fs::write(&command_path, default_command_content())?;
@@ -122,7 +266,7 @@ pub fn run_refresh(functions: Option<Vec<String>>) -> Result<usize, DbCommandErr
         Some(fn_list) => {
             let mut count = 0;
             for function_id in &fn_list {
-                if db.functions.remove(function_id).is_some() {
+                if db.remove_function(function_id).is_some() {
                     println!("Marked for refresh: {}", function_id);
                     count += 1;
                 } else {
@@ -133,28 +277,7 @@ pub fn run_refresh(functions: Option<Vec<String>>) -> Result<usize, DbCommandErr
             Ok(count)
         }
         None => {
-            println!("Re-indexing Python files...");
-            let mut indexer = Indexer::new()?;
-
-            let mut dirs_to_index: Vec<PathBuf> = db
-                .environment
-                .python_path
-                .iter()
-                .map(PathBuf::from)
-                .collect();
-
-            dirs_to_index.extend(
-                db.environment
-                    .site_packages
-                    .iter()
-                    .map(PathBuf::from),
-            );
-
-            let index = indexer.index_directories(&dirs_to_index)?;
-            let count = index.len();
-            println!("Indexed {} symbols", count);
-
-            db.symbol_index = index;
+            let count = reindex(&mut db)?;
             db.save(&db_path)?;
             println!("Updated {}", db_path.display());
 
@@ -163,59 +286,277 @@ pub fn run_refresh(functions: Option<Vec<String>>) -> Result<usize, DbCommandErr
     }
 }
 
-pub fn run_remove(functions: Option<Vec<String>>) -> Result<(), DbCommandError> {
+/// Re-analyze only the functions whose source files changed since they were
+/// indexed. Returns the number of functions re-analyzed.
+pub fn run_refresh_changed() -> Result<usize, DbCommandError> {
     let db_path = paths::database_path();
 
     if !db_path.exists() {
         return Err(DbCommandError::NotFound(db_path.display().to_string()));
     }
 
-    match functions {
-        Some(fn_list) => {
+    println!("Loading database...");
+    let mut db = ArborDatabase::load(&db_path)?;
+
+    let changed = ChangedFilesDetector::detect(&db);
+    let affected = ChangedFilesDetector::affected_functions(&db, &changed);
+    println!(
+        "{} functions need re-analysis due to {} changed files",
+        affected.len(),
+        changed.len()
+    );
+
+    if changed.is_empty() {
+        return Ok(0);
+    }
+
+    // Stale analyses stay until their replacements are saved, so a failed
+    // or interrupted re-analysis loses nothing
+    reindex(&mut db)?;
+    db.save(&db_path)?;
+
+    if affected.is_empty() {
+        return Ok(0);
+    }
+
+    let config = crate::core::config::ArborConfig::load_or_default();
+    let count = affected.len();
+    run_analyze(AnalyzeArgs {
+        functions: affected,
+        depth: config.analysis.max_depth,
+        ..AnalyzeArgs::default()
+    })?;
+
+    Ok(count)
+}
+
+/// Bring the symbol index up to date with the stored environment paths.
+fn reindex(db: &mut ArborDatabase) -> Result<usize, DbCommandError> {
+    println!("Re-indexing Python files...");
+    let config = ArborConfig::load_or_default();
+    let mut indexer = Indexer::new()?
+        .with_max_depth(config.analysis.index_depth)
+        .with_max_file_size_kb(config.analysis.max_file_size_kb);
+
+    let mut dirs_to_index: Vec<PathBuf> = db
+        .environment
+        .python_path
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+    dirs_to_index.extend(
+        db.environment
+            .site_packages
+            .iter()
+            .map(PathBuf::from),
+    );
+
+    // Unchanged files keep their symbols; only new and edited ones are parsed
+    let index = indexer.index_directories_incremental(&dirs_to_index, &db.symbol_index)?;
+    let count = index.len();
+    println!("Indexed {} symbols", count);
+
+    db.symbol_index = index;
+    Ok(count)
+}
+
+/// Merges the database at `other_path` into this project's database, for
+/// monorepos initialized separately per root. Returns how many analyzed
+/// functions the project database gained.
+pub fn run_merge(other_path: &Path) -> Result<usize, DbCommandError> {
+    let db_path = paths::database_path();
+
+    if !db_path.exists() {
+        return Err(DbCommandError::NotFound(db_path.display().to_string()));
+    }
+    if !other_path.exists() {
+        return Err(DbCommandError::NotFound(other_path.display().to_string()));
+    }
+
+    println!("Loading databases...");
+    let db = ArborDatabase::load(&db_path)?;
+    let other = ArborDatabase::load(other_path)?;
+    let before = db.function_count();
+
+    let mut merged = db.merge(other);
+    merged.save(&db_path)?;
+    println!("Updated {}", db_path.display());
+
+    Ok(merged.function_count() - before)
+}
+
+/// What `arbor remove` deletes.
+pub enum RemoveTarget {
+    /// The whole `.arbor/` directory.
+    Database,
+    /// These analyzed functions.
+    Functions(Vec<String>),
+    /// Every analyzed function in this package.
+    Package(String),
+}
+
+/// Returns how many analyzed functions were removed; 0 for `Database`.
+pub fn run_remove(target: RemoveTarget) -> Result<usize, DbCommandError> {
+    let db_path = paths::database_path();
+
+    if !db_path.exists() {
+        return Err(DbCommandError::NotFound(db_path.display().to_string()));
+    }
+
+    match target {
+        RemoveTarget::Functions(fn_list) => {
             let mut db = ArborDatabase::load(&db_path)?;
+            let mut count = 0;
             for function_id in &fn_list {
-                if db.functions.remove(function_id).is_some() {
+                if db.remove_function(function_id).is_some() {
                     println!("Removed: {}", function_id);
+                    count += 1;
                 } else {
                     eprintln!("Warning: {} not found in database", function_id);
                 }
             }
             db.save(&db_path)?;
-            Ok(())
+            Ok(count)
         }
-        None => {
+        RemoveTarget::Package(package) => {
+            let mut db = ArborDatabase::load(&db_path)?;
+            let removed = db.remove_package(&package);
+            for function_id in &removed {
+                println!("Removed: {}", function_id);
+            }
+            if removed.is_empty() {
+                eprintln!("Warning: no analyzed functions in package {}", package);
+            } else {
+                db.save(&db_path)?;
+            }
+            Ok(removed.len())
+        }
+        RemoveTarget::Database => {
             let arbor_dir = paths::arbor_dir();
             if arbor_dir.exists() {
                 std::fs::remove_dir_all(&arbor_dir)?;
                 println!("Removed {}", arbor_dir.display());
             }
-            Ok(())
+            Ok(0)
         }
     }
 }
 
 pub fn run_export(options: ExportOptions) -> Result<PathBuf, DbCommandError> {
-    use crate::output::markdown::{MarkdownOutput, DatabaseStats};
-
     let db_path = paths::database_path();
 
     if !db_path.exists() {
         return Err(DbCommandError::NotFound(db_path.display().to_string()));
     }
 
-    let db = ArborDatabase::load(&db_path)?;
+    let mut db = ArborDatabase::load(&db_path)?;
+
+    if let Some(level) = options.filter_risk {
+        filter_by_risk(&mut db, level);
+    }
+
+    if let Some(level) = options.source_context {
+        attach_source_context(&mut db, level);
+    }
+
+    if let Some(output_dir) = options.output_dir {
+        export_packages(&db, &options.format, &output_dir, options.filter_risk, options.pretty_print)?;
+        return Ok(output_dir);
+    }
 
     let output_path = options.output_path.unwrap_or_else(|| {
-        let ext = if options.format == "json" { "json" } else { "md" };
-        PathBuf::from(format!("arbor-export.{}", ext))
+        match options.format.as_str() {
+            "json" => PathBuf::from("arbor-export.json"),
+            "sarif" => PathBuf::from("arbor-export.sarif"),
+            "html" => PathBuf::from("arbor-export.html"),
+            // JSON Lines is meant for piping, so it streams to stdout by default
+            "jsonl" => PathBuf::from(STDOUT_PATH),
+            _ => PathBuf::from("arbor-export.md"),
+        }
     });
 
-    let content = match options.format.as_str() {
+    if options.format == "jsonl" {
+        if output_path.as_os_str() == STDOUT_PATH {
+            let stdout = std::io::stdout();
+            write_jsonl(&db, &mut std::io::BufWriter::new(stdout.lock()))?;
+        } else {
+            let file = std::fs::File::create(&output_path)?;
+            write_jsonl(&db, &mut std::io::BufWriter::new(file))?;
+        }
+        return Ok(output_path);
+    }
+
+    let content = render_export(&db, &options.format, options.filter_risk, options.pretty_print)?;
+
+    if output_path.as_os_str() == STDOUT_PATH {
+        println!("{}", content);
+    } else {
+        std::fs::write(&output_path, content)?;
+        if options.open {
+            open_with_default_app(&output_path);
+        }
+    }
+
+    Ok(output_path)
+}
+
+/// `open` on macOS, `xdg-open` elsewhere. Failing to open only warns: the
+/// export itself has already been written.
+fn open_with_default_app(path: &Path) {
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    match Command::new(opener).arg(path).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("Warning: {} {} exited with {}", opener, path.display(), status),
+        Err(e) => eprintln!("Warning: Could not run {}: {}", opener, e),
+    }
+}
+
+/// Drops the functions below `level` from `db` before it is exported.
+fn filter_by_risk(db: &mut ArborDatabase, level: RiskLevel) {
+    db.functions.retain(|_, analysis| analysis.risk_level() >= level);
+    db.rebuild_exception_index();
+}
+
+/// A risk-filtered JSON export: the database plus what was filtered.
+#[derive(Serialize)]
+struct FilteredExport<'a> {
+    filter_applied: String,
+    included_functions: usize,
+    #[serde(flatten)]
+    database: &'a ArborDatabase,
+}
+
+/// Renders `db`, which has already been filtered to `filter_risk` if set.
+fn render_export(
+    db: &ArborDatabase,
+    format: &str,
+    filter_risk: Option<RiskLevel>,
+    pretty: bool,
+) -> Result<String, DbCommandError> {
+    use crate::output::markdown::{MarkdownOutput, DatabaseStats};
+
+    let content = match format {
         "json" => {
-            serde_json::to_string_pretty(&db).map_err(|e| {
+            let json = match filter_risk {
+                Some(level) => json_serialize(
+                    &FilteredExport {
+                        filter_applied: level.as_str().to_lowercase(),
+                        included_functions: db.functions.len(),
+                        database: db,
+                    },
+                    pretty,
+                ),
+                None => json_serialize(db, pretty),
+            };
+            json.map_err(|e| {
                 DbCommandError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
             })?
         }
+        "html" => db.to_html(),
+        "sarif" => json_serialize(&db.to_sarif(), pretty).map_err(|e| {
+            DbCommandError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+        })?,
         _ => {
             let mut output = String::new();
 
@@ -256,6 +597,9 @@ pub fn run_export(options: ExportOptions) -> Result<PathBuf, DbCommandError> {
                 high_risk,
                 medium_risk,
                 low_risk,
+                filter_note: filter_risk.map(|level| {
+                    format!("Showing {} functions at {} risk or above", db.functions.len(), level.as_str())
+                }),
             };
 
             output.push_str(&stats.to_markdown());
@@ -279,15 +623,206 @@ pub fn run_export(options: ExportOptions) -> Result<PathBuf, DbCommandError> {
         }
     };
 
-    std::fs::write(&output_path, content)?;
+    Ok(content)
+}
+
+#[derive(Debug, Serialize)]
+struct PackageSummary {
+    package: String,
+    function_count: usize,
+    file: String,
+}
 
-    Ok(output_path)
+/// Write one export file per top-level package into `output_dir`, plus an
+/// index (`package_summary.md`, or `summary.json` for JSON formats).
+fn export_packages(
+    db: &ArborDatabase,
+    format: &str,
+    output_dir: &Path,
+    filter_risk: Option<RiskLevel>,
+    pretty: bool,
+) -> Result<(), DbCommandError> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let extension = match format {
+        "json" => "json",
+        "jsonl" => "jsonl",
+        "sarif" => "sarif",
+        "html" => "html",
+        _ => "md",
+    };
+
+    let mut summaries = Vec::new();
+    for package in export_package_names(db) {
+        let package_db = package_database(db, &package);
+        let file_name = format!("{}.{}", package, extension);
+        let path = output_dir.join(&file_name);
+
+        if format == "jsonl" {
+            let file = std::fs::File::create(&path)?;
+            write_jsonl(&package_db, &mut std::io::BufWriter::new(file))?;
+        } else {
+            std::fs::write(&path, render_export(&package_db, format, filter_risk, pretty)?)?;
+        }
+
+        summaries.push(PackageSummary {
+            package,
+            function_count: package_db.functions.len(),
+            file: file_name,
+        });
+    }
+
+    if extension == "md" {
+        let mut output = String::from("# Package Summary\n\n");
+        output.push_str("| Package | Functions | File |\n");
+        output.push_str("|---------|-----------|------|\n");
+        for summary in &summaries {
+            output.push_str(&format!(
+                "| `{}` | {} | [{}]({}) |\n",
+                summary.package, summary.function_count, summary.file, summary.file
+            ));
+        }
+        std::fs::write(output_dir.join("package_summary.md"), output)?;
+    } else {
+        let content = json_serialize(&summaries, pretty).map_err(|e| {
+            DbCommandError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+        })?;
+        std::fs::write(output_dir.join("summary.json"), content)?;
+    }
+
+    Ok(())
+}
+
+/// Top-level packages of the analyzed functions, sorted.
+fn export_package_names(db: &ArborDatabase) -> Vec<String> {
+    let packages: std::collections::BTreeSet<&str> = db
+        .functions
+        .keys()
+        .filter_map(|id| id.split('.').next())
+        .collect();
+    packages.into_iter().map(str::to_string).collect()
+}
+
+/// A copy of `db` restricted to one top-level package: its functions, its
+/// indexed symbols, and the grouping suggestions covering its exceptions.
+fn package_database(db: &ArborDatabase, package: &str) -> ArborDatabase {
+    let in_package = |id: &str| id.split('.').next() == Some(package);
+
+    let mut package_db = ArborDatabase::new(db.environment.clone());
+    package_db.version = db.version.clone();
+    package_db.created_at = db.created_at;
+    package_db.symbol_index.indexed_at = db.symbol_index.indexed_at;
+
+    for (id, location) in &db.symbol_index.symbols {
+        if in_package(id) {
+            package_db.symbol_index.add(id.clone(), location.clone());
+        }
+    }
+    for analysis in db.functions.values().filter(|a| in_package(&a.function_id)) {
+        package_db.add_function(analysis.clone());
+    }
+    package_db.updated_at = db.updated_at;
+
+    for (name, suggestion) in &db.grouping_suggestions {
+        if suggestion
+            .exceptions
+            .iter()
+            .any(|exc| package_db.functions_raising(exc).next().is_some())
+        {
+            package_db.grouping_suggestions.insert(name.clone(), suggestion.clone());
+        }
+    }
+
+    package_db
 }
 
-fn detect_environment() -> Result<Environment, DbCommandError> {
-    let python_version = detect_python_version()?;
+/// Fill in `source_context` on every raise. Unreadable files leave it unset.
+fn attach_source_context(db: &mut ArborDatabase, level: SourceContextLevel) {
+    let mut files: HashMap<PathBuf, Option<Vec<String>>> = HashMap::new();
+
+    for analysis in db.functions.values_mut() {
+        for raise in &mut analysis.raises {
+            let lines = files
+                .entry(raise.raise_location.file.clone())
+                .or_insert_with_key(|path| {
+                    std::fs::read_to_string(path)
+                        .ok()
+                        .map(|content| content.lines().map(str::to_string).collect())
+                });
+
+            raise.source_context = lines
+                .as_ref()
+                .and_then(|lines| source_context(lines, raise.raise_location.line, level));
+        }
+    }
+}
+
+fn source_context(lines: &[String], line: u32, level: SourceContextLevel) -> Option<Vec<String>> {
+    let index = (line as usize).checked_sub(1).filter(|&i| i < lines.len())?;
+    let start = index.saturating_sub(level.lines_before);
+    let end = (index + level.lines_after + 1).min(lines.len());
+    Some(lines[start..end].to_vec())
+}
+
+/// The `[tool.arbor]` settings of `./pyproject.toml`, or `None` without one.
+fn read_pyproject_config(quiet: bool) -> Result<Option<ArborConfig>, DbCommandError> {
+    let path = paths::pyproject_path();
+    if !path.exists() {
+        if !quiet {
+            println!("No {} found, using default config", paths::PYPROJECT_FILE);
+        }
+        return Ok(None);
+    }
+
+    let reader = PyprojectReader::load(&path)?;
+    if !quiet {
+        if reader.has_arbor_table() {
+            println!("Reading [tool.arbor] from {}", path.display());
+        } else {
+            println!("No [tool.arbor] table in {}, using defaults", path.display());
+        }
+    }
+    Ok(Some(reader.into_config()?))
+}
+
+/// Fill in what environment detection missed from the config: the venv (and
+/// its site-packages), and any explicit search paths.
+fn apply_config_environment(environment: &mut Environment, config: &ArborConfig) {
+    if environment.venv_path.is_none() {
+        if let Some(ref venv) = config.environment.venv_path {
+            environment.venv_path = Some(venv.display().to_string());
+            if let Ok(site_packages) = PythonResolver::find_site_packages(venv) {
+                let site_packages = site_packages.display().to_string();
+                if !environment.site_packages.contains(&site_packages) {
+                    environment.site_packages.push(site_packages);
+                }
+            }
+        }
+    }
+
+    for dir in &config.environment.site_packages {
+        let dir = dir.display().to_string();
+        if !environment.site_packages.contains(&dir) {
+            environment.site_packages.push(dir);
+        }
+    }
+
+    if !config.environment.python_path.is_empty() {
+        environment.python_path = config
+            .environment
+            .python_path
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+    }
+}
+
+/// With `interpreter`, the version and site-packages come from running it
+/// rather than from `python3`/`python` and the detected venv.
+fn detect_environment(interpreter: Option<&str>) -> Result<Environment, DbCommandError> {
+    let python_version = detect_python_version(interpreter)?;
     let venv_path = detect_venv();
-    let site_packages = detect_site_packages(&venv_path)?;
+    let site_packages = detect_site_packages(&venv_path, interpreter)?;
     let python_path = detect_python_path();
 
     Ok(Environment {
@@ -295,15 +830,19 @@ fn detect_environment() -> Result<Environment, DbCommandError> {
         venv_path: venv_path.map(|p| p.display().to_string()),
         site_packages: site_packages.iter().map(|p| p.display().to_string()).collect(),
         python_path: python_path.iter().map(|p| p.display().to_string()).collect(),
+        python_interpreter: interpreter.map(str::to_string),
     })
 }
 
-fn detect_python_version() -> Result<String, DbCommandError> {
-    let output = Command::new("python3")
-        .args(["--version"])
-        .output()
-        .or_else(|_| Command::new("python").args(["--version"]).output())
-        .map_err(|e| DbCommandError::EnvironmentDetection(e.to_string()))?;
+fn detect_python_version(interpreter: Option<&str>) -> Result<String, DbCommandError> {
+    let output = match interpreter {
+        Some(interpreter) => Command::new(interpreter).args(["--version"]).output(),
+        None => Command::new("python3")
+            .args(["--version"])
+            .output()
+            .or_else(|_| Command::new("python").args(["--version"]).output()),
+    }
+    .map_err(|e| DbCommandError::EnvironmentDetection(e.to_string()))?;
 
     let version = String::from_utf8_lossy(&output.stdout);
     let version = version.trim().replace("Python ", "");
@@ -338,10 +877,12 @@ fn detect_venv() -> Option<PathBuf> {
     None
 }
 
-fn detect_site_packages(venv: &Option<PathBuf>) -> Result<Vec<PathBuf>, DbCommandError> {
+fn detect_site_packages(venv: &Option<PathBuf>, interpreter: Option<&str>) -> Result<Vec<PathBuf>, DbCommandError> {
     let mut packages = Vec::new();
 
-    if let Some(venv_path) = venv {
+    if let Some(sp) = interpreter.and_then(interpreter_site_packages) {
+        packages.push(sp);
+    } else if let Some(venv_path) = venv {
         if let Ok(sp) = PythonResolver::find_site_packages(venv_path) {
             packages.push(sp);
         }
@@ -350,6 +891,123 @@ fn detect_site_packages(venv: &Option<PathBuf>) -> Result<Vec<PathBuf>, DbComman
     Ok(packages)
 }
 
+/// The first site-packages directory `interpreter` reports, if it runs and
+/// the directory exists.
+fn interpreter_site_packages(interpreter: &str) -> Option<PathBuf> {
+    let output = Command::new(interpreter)
+        .args(["-c", "import site; print(site.getsitepackages()[0])"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    path.is_dir().then_some(path)
+}
+
+/// Package names from a requirements file. Version specifiers, extras,
+/// environment markers, comments and pip options (`-r`, `-e`, ...) are dropped.
+pub fn parse_requirements(path: &Path) -> Vec<String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Warning: could not read {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut packages = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() || line.starts_with('-') {
+            continue;
+        }
+
+        let name = line
+            .split(|c: char| "=<>!~;[ @".contains(c))
+            .next()
+            .unwrap_or("")
+            .trim();
+        if !name.is_empty() && !packages.iter().any(|p| p == name) {
+            packages.push(name.to_string());
+        }
+    }
+
+    packages
+}
+
+/// System-wide package directories used when there is no venv.
+fn system_site_packages_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("/usr/lib/python3/dist-packages")];
+
+    for lib in ["/usr/local/lib", "/usr/lib"] {
+        let Ok(entries) = std::fs::read_dir(lib) else {
+            continue;
+        };
+        let mut versions: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("python3"))
+            })
+            .collect();
+        versions.sort();
+
+        for version in versions {
+            dirs.push(version.join("dist-packages"));
+            dirs.push(version.join("site-packages"));
+        }
+    }
+
+    dirs.retain(|d| d.is_dir());
+    dirs.dedup();
+    dirs
+}
+
+/// Find the site-packages directory holding `package`, falling back to
+/// `pip show` when it is in none of `search_dirs`.
+fn locate_package(package: &str, search_dirs: &[PathBuf]) -> Option<PathBuf> {
+    if let Some(dir) = search_dirs.iter().find(|dir| package_in_dir(package, dir)) {
+        return Some(dir.clone());
+    }
+
+    let output = Command::new("pip").args(["show", package]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("Location:"))
+        .map(|location| PathBuf::from(location.trim()))
+}
+
+fn package_in_dir(package: &str, dir: &Path) -> bool {
+    let module = package.to_lowercase().replace(['-', '.'], "_");
+
+    if dir.join(&module).is_dir() || dir.join(format!("{}.py", module)).is_file() {
+        return true;
+    }
+
+    // Distribution names often differ from the import name (PyYAML -> yaml),
+    // but the dist-info directory always uses the normalized distribution name
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries.filter_map(|e| e.ok()).any(|entry| {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(stem) = file_name
+            .strip_suffix(".dist-info")
+            .or_else(|| file_name.strip_suffix(".egg-info"))
+        else {
+            return false;
+        };
+        let distribution = stem.split('-').next().unwrap_or(stem);
+        distribution.to_lowercase().replace('.', "_") == module
+    })
+}
+
 fn detect_python_path() -> Vec<PathBuf> {
     let mut paths = Vec::new();
 
@@ -377,5 +1035,236 @@ fn detect_python_path() -> Vec<PathBuf> {
 }
 
 fn default_command_content() -> &'static str {
-    include_str!("../assets/arbor_command.md")
+    include_str!("../assets/arbor.md")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{CodeLocation, FunctionAnalysis, RaiseStatement};
+
+    fn lines(n: usize) -> Vec<String> {
+        (1..=n).map(|i| format!("line {}", i)).collect()
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("requirements.txt");
+        std::fs::write(
+            &path,
+            "# runtime deps\nrequests==2.31.0\nDjango>=4.2,<5  # web\n\n-r dev.txt\n\
+             celery[redis]~=5.3\npyyaml\nrequests>=2\nuvicorn ; python_version >= '3.8'\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            parse_requirements(&path),
+            vec!["requests", "Django", "celery", "pyyaml", "uvicorn"]
+        );
+        assert!(parse_requirements(&dir.path().join("missing.txt")).is_empty());
+    }
+
+    #[test]
+    fn test_package_in_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("requests")).unwrap();
+        std::fs::create_dir(dir.path().join("yaml")).unwrap();
+        std::fs::create_dir(dir.path().join("PyYAML-6.0.1.dist-info")).unwrap();
+        std::fs::write(dir.path().join("six.py"), "").unwrap();
+        std::fs::create_dir(dir.path().join("requests_oauthlib-1.3.1.dist-info")).unwrap();
+
+        assert!(package_in_dir("requests", dir.path()));
+        assert!(package_in_dir("PyYAML", dir.path()));
+        assert!(package_in_dir("six", dir.path()));
+        assert!(!package_in_dir("django", dir.path()));
+        assert!(package_in_dir("requests-oauthlib", dir.path()));
+        assert!(!package_in_dir("oauthlib", dir.path()));
+    }
+
+    #[test]
+    fn test_source_context_window() {
+        let level = SourceContextLevel::symmetric(2);
+
+        assert_eq!(
+            source_context(&lines(10), 5, level),
+            Some(vec!["line 3", "line 4", "line 5", "line 6", "line 7"].into_iter().map(String::from).collect())
+        );
+        assert_eq!(source_context(&lines(10), 1, level).unwrap().len(), 3);
+        assert_eq!(source_context(&lines(10), 10, level).unwrap().len(), 3);
+        assert_eq!(source_context(&lines(10), 11, level), None);
+        assert_eq!(source_context(&lines(10), 0, level), None);
+    }
+
+    #[test]
+    fn test_attach_source_context_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let present = dir.path().join("present.py");
+        std::fs::write(&present, "def f():\n    raise ValueError()\n").unwrap();
+
+        let mut db = ArborDatabase::new(Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        });
+        for (id, file) in [("app.present", present), ("app.missing", dir.path().join("missing.py"))] {
+            let location = CodeLocation::new(file, 2);
+            let mut analysis = FunctionAnalysis::new(id.to_string(), String::new(), location.clone());
+            analysis
+                .raises
+                .push(RaiseStatement::new("ValueError".into(), "ValueError".into(), location));
+            db.add_function(analysis);
+        }
+
+        attach_source_context(&mut db, SourceContextLevel::symmetric(3));
+
+        assert_eq!(
+            db.functions["app.present"].raises[0].source_context,
+            Some(vec!["def f():".to_string(), "    raise ValueError()".to_string()])
+        );
+        assert_eq!(db.functions["app.missing"].raises[0].source_context, None);
+    }
+
+    #[test]
+    fn test_export_packages_splits_by_package() {
+        let mut db = ArborDatabase::new(Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        });
+        for id in ["myapp.views.index", "myapp.models.save", "requests.api.get"] {
+            let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+            let mut analysis = FunctionAnalysis::new(id.to_string(), format!("def {}()", id), location.clone());
+            analysis
+                .raises
+                .push(RaiseStatement::new("ValueError".to_string(), "ValueError".to_string(), location));
+            db.add_function(analysis);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("export");
+        export_packages(&db, "markdown", &output_dir, None, true).unwrap();
+
+        let myapp = std::fs::read_to_string(output_dir.join("myapp.md")).unwrap();
+        assert!(myapp.contains("myapp.views.index"));
+        assert!(!myapp.contains("requests.api.get"));
+        assert!(output_dir.join("requests.md").exists());
+        let summary = std::fs::read_to_string(output_dir.join("package_summary.md")).unwrap();
+        assert!(summary.contains("| `myapp` | 2 | [myapp.md](myapp.md) |"));
+        assert!(summary.contains("| `requests` | 1 | [requests.md](requests.md) |"));
+
+        export_packages(&db, "json", &output_dir, None, false).unwrap();
+        let requests_json = std::fs::read_to_string(output_dir.join("requests.json")).unwrap();
+        assert_eq!(requests_json.lines().count(), 1);
+        let requests: ArborDatabase = serde_json::from_str(&requests_json).unwrap();
+        assert_eq!(requests.functions.len(), 1);
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(output_dir.join("summary.json")).unwrap()).unwrap();
+        assert_eq!(summary[0]["package"], "myapp");
+        assert_eq!(summary[0]["function_count"], 2);
+        assert_eq!(summary[1]["file"], "requests.json");
+    }
+
+    #[test]
+    fn test_export_filter_risk() {
+        let mut db = ArborDatabase::new(Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        });
+        for (id, exceptions) in [("app.low", 1), ("app.medium", 5), ("app.high", 12)] {
+            let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+            let mut analysis = FunctionAnalysis::new(id.to_string(), format!("def {}()", id), location.clone());
+            for i in 0..exceptions {
+                let exc = format!("{}Error{}", id, i);
+                analysis.raises.push(RaiseStatement::new(exc.clone(), exc, location.clone()));
+            }
+            db.add_function(analysis);
+        }
+
+        filter_by_risk(&mut db, RiskLevel::Medium);
+        let mut ids: Vec<&str> = db.functions.keys().map(String::as_str).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["app.high", "app.medium"]);
+        assert!(!db.exception_index.contains_key("app.lowError0"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_export(&db, "json", Some(RiskLevel::Medium), true).unwrap()).unwrap();
+        assert_eq!(json["filter_applied"], "medium");
+        assert_eq!(json["included_functions"], 2);
+        assert!(json["functions"]["app.high"].is_object());
+
+        let unfiltered: serde_json::Value = serde_json::from_str(&render_export(&db, "json", None, true).unwrap()).unwrap();
+        assert!(unfiltered.get("filter_applied").is_none());
+
+        let markdown = render_export(&db, "markdown", Some(RiskLevel::Medium), true).unwrap();
+        assert!(markdown.contains("**Showing 2 functions at Medium risk or above**"));
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&render_export(&db, "sarif", Some(RiskLevel::Medium), false).unwrap()).unwrap();
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 17);
+        let level = |rule: &str| results.iter().find(|r| r["ruleId"] == rule).unwrap()["level"].clone();
+        assert_eq!(level("raise.app.highError0"), "error");
+        assert_eq!(level("raise.app.mediumError0"), "warning");
+    }
+
+    #[test]
+    fn test_apply_config_environment_fills_gaps() {
+        let mut environment = Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec!["/sp".to_string()],
+            python_path: vec![".".to_string()],
+            python_interpreter: None,
+        };
+        let mut config = ArborConfig::default();
+        config.environment.venv_path = Some(PathBuf::from("/nonexistent/.venv"));
+        config.environment.site_packages = vec![PathBuf::from("/sp"), PathBuf::from("/vendor")];
+        config.environment.python_path = vec![PathBuf::from("src")];
+
+        apply_config_environment(&mut environment, &config);
+
+        assert_eq!(environment.venv_path.as_deref(), Some("/nonexistent/.venv"));
+        assert_eq!(environment.site_packages, vec!["/sp", "/vendor"]);
+        assert_eq!(environment.python_path, vec!["src"]);
+
+        // A detected venv is kept
+        environment.venv_path = Some("/detected".to_string());
+        apply_config_environment(&mut environment, &config);
+        assert_eq!(environment.venv_path.as_deref(), Some("/detected"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detect_with_interpreter() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let site_packages = dir.path().join("site-packages");
+        std::fs::create_dir(&site_packages).unwrap();
+        let interpreter = dir.path().join("python3.10");
+        std::fs::write(
+            &interpreter,
+            format!(
+                "#!/bin/sh\nif [ \"$1\" = --version ]; then echo 'Python 3.10.4'; else echo '{}'; fi\n",
+                site_packages.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&interpreter, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let interpreter = interpreter.to_str().unwrap();
+
+        assert_eq!(detect_python_version(Some(interpreter)).unwrap(), "3.10.4");
+        assert_eq!(detect_site_packages(&None, Some(interpreter)).unwrap(), vec![site_packages]);
+
+        assert!(detect_python_version(Some("/nonexistent/python")).is_err());
+        assert!(detect_site_packages(&None, Some("/nonexistent/python")).unwrap().is_empty());
+    }
 }
@@ -1,7 +1,19 @@
-use crate::analysis::grouping::RecoveryStrategy;
-use crate::core::database::ArborDatabase;
+use crate::analysis::distribution::{compute_distribution, ExceptionDistribution};
+use crate::analysis::exceptions::does_function_handle;
+use crate::analysis::grouping::{merge_groups, suggest_new_groups, RecoveryStrategy};
+use crate::analysis::none_sources::{classify_none_source, trace_none_flows, NoneAnnotationStatus};
+use crate::core::config::{matches_name_pattern, ArborConfig, NoneSourceWeights};
+use crate::core::database::{ArborDatabase, GroupingSuggestion, StatsSnapshot};
+use crate::core::types::{
+    CatchClause, CodeLocation, FunctionAnalysis, HandlerStrategy, NoneFlowPath, NoneSource, NoneSourceKind,
+    RaiseStatement, RiskLevel,
+};
 use crate::core::paths;
-use std::path::PathBuf;
+use crate::output::{
+    call_chain_to_mermaid, json_serialize, JsonFormatOptions, MermaidDiagramType, SchemaOutput,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,6 +30,9 @@ pub enum QueryError {
     #[error("None source index out of bounds: {0}")]
     NoneSourceIndexOutOfBounds(usize),
 
+    #[error("Group not found: {0}")]
+    GroupNotFound(String),
+
     #[error("Invalid query: {0}")]
     InvalidQuery(String),
 
@@ -28,12 +43,20 @@ pub enum QueryError {
     Io(#[from] std::io::Error),
 }
 
+/// JSON for `--format json` output, minified under `--no-pretty`.
+fn to_json_string<T: Serialize + ?Sized>(
+    value: &T,
+    options: JsonFormatOptions,
+) -> Result<String, serde_json::Error> {
+    json_serialize(value, options.pretty)
+}
+
 fn load_database() -> Result<ArborDatabase, QueryError> {
     let db_path = paths::database_path();
     if !db_path.exists() {
         return Err(QueryError::DatabaseNotInitialized);
     }
-    Ok(ArborDatabase::load(&db_path)?)
+    Ok(ArborDatabase::load_cached(&db_path)?)
 }
 
 // ============================================================================
@@ -46,18 +69,35 @@ pub fn query_risk(function: &str) -> Result<String, QueryError> {
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
-    let risk = analysis.risk_level();
-    let exc_count = analysis.exception_count();
-    let none_count = analysis.none_source_count();
+    Ok(format_risk_line(analysis))
+}
 
-    Ok(format!(
-        "{} {} | {} exceptions, {} None sources | depth: {}",
+/// `🔴 High | 3 exceptions, 1 None sources | depth: 4`
+fn format_risk_line(analysis: &FunctionAnalysis) -> String {
+    let risk = analysis.risk_level();
+    format!(
+        "{} {} | {} exceptions, {} None sources | depth: {}{}",
         risk.emoji(),
         risk.as_str(),
-        exc_count,
-        none_count,
-        analysis.call_depth
-    ))
+        analysis.exception_count(),
+        analysis.none_source_count(),
+        analysis.call_depth,
+        if analysis.depth_limit_hits > 0 { " (incomplete)" } else { "" }
+    )
+}
+
+/// `query_risk` for every analyzed function, highest risk score first.
+pub fn query_risk_all(filter_risk: Option<RiskLevel>) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let weights = ArborConfig::load_or_default().risk.none_source_weights;
+    let lines: Vec<String> = list_functions(&db, SortField::Risk, filter_risk, &weights)
+        .into_iter()
+        .map(|(id, analysis)| format!("`{}`: {}", id, format_risk_line(analysis)))
+        .collect();
+    if lines.is_empty() {
+        return Ok("No functions match.".to_string());
+    }
+    Ok(lines.join("\n"))
 }
 
 pub fn query_has(function: &str, exception: &str) -> Result<String, QueryError> {
@@ -69,7 +109,7 @@ pub fn query_has(function: &str, exception: &str) -> Result<String, QueryError>
     let found = analysis
         .raises
         .iter()
-        .find(|r| r.exception_type == exception || r.qualified_type == exception);
+        .find(|r| r.matches_type(exception));
 
     match found {
         Some(raise) => {
@@ -86,9 +126,9 @@ pub fn query_has(function: &str, exception: &str) -> Result<String, QueryError>
                 .map(|l| l.to_string_short())
                 .unwrap_or_else(|| "(builtin)".to_string());
 
-            let fn_name = function.split('.').last().unwrap_or(function);
+            let fn_name = function.split('.').next_back().unwrap_or(function);
 
-            let mut result = format!("## Yes\n\n");
+            let mut result = "## Yes\n\n".to_string();
             result.push_str(&format!(
                 "`{}` can raise `{}`.\n\n",
                 function, exception
@@ -109,7 +149,7 @@ pub fn query_has(function: &str, exception: &str) -> Result<String, QueryError>
             Ok(result)
         }
         None => {
-            let mut result = format!("## No\n\n");
+            let mut result = "## No\n\n".to_string();
             result.push_str(&format!(
                 "`{}` cannot raise `{}`.\n\n",
                 function, exception
@@ -130,7 +170,7 @@ pub fn query_handle(function: &str) -> Result<String, QueryError> {
         return Ok(format!(
             "# {} raises no exceptions - no handler needed\nresult = {}()",
             function,
-            function.split('.').last().unwrap_or(function)
+            function.split('.').next_back().unwrap_or(function)
         ));
     }
 
@@ -158,7 +198,7 @@ pub fn query_handle(function: &str) -> Result<String, QueryError> {
     other_exceptions.sort();
     other_exceptions.dedup();
 
-    let fn_name = function.split('.').last().unwrap_or(function);
+    let fn_name = function.split('.').next_back().unwrap_or(function);
     let mut handler = String::from("try:\n    result = ");
     handler.push_str(fn_name);
     handler.push_str("()\n");
@@ -207,6 +247,88 @@ pub fn query_signature(function: &str) -> Result<String, QueryError> {
     ))
 }
 
+#[derive(Debug, Serialize)]
+struct SignatureEntry {
+    function_id: String,
+    signature: Option<String>,
+    is_analyzed: bool,
+    location: String,
+}
+
+/// Collects every function under `module`, preferring analyzed signatures and
+/// falling back to the symbol index for functions that were only indexed.
+fn module_signatures(db: &ArborDatabase, module: &str) -> Vec<SignatureEntry> {
+    let prefix = format!("{}.", module);
+    let mut entries: std::collections::BTreeMap<&str, SignatureEntry> = std::collections::BTreeMap::new();
+
+    for (id, analysis) in db.functions.iter().filter(|(id, _)| id.starts_with(&prefix)) {
+        entries.insert(
+            id,
+            SignatureEntry {
+                function_id: id.clone(),
+                signature: Some(analysis.signature.clone()),
+                is_analyzed: true,
+                location: analysis.location.to_string_short(),
+            },
+        );
+    }
+
+    for (id, loc) in db.symbol_index.with_prefix(&prefix) {
+        entries.entry(id).or_insert_with(|| SignatureEntry {
+            function_id: id.clone(),
+            signature: None,
+            is_analyzed: false,
+            location: loc.to_code_location().to_string_short(),
+        });
+    }
+
+    entries.into_values().collect()
+}
+
+pub fn query_signatures(module: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    Ok(format_signatures(&db, module))
+}
+
+fn format_signatures(db: &ArborDatabase, module: &str) -> String {
+    let entries = module_signatures(db, module);
+    if entries.is_empty() {
+        return format!("No functions found in module `{}`.", module);
+    }
+
+    let analyzed = entries.iter().filter(|e| e.is_analyzed).count();
+    let mut result = format!("# Signatures in `{}`\n\n", module);
+    result.push_str(&format!("**Functions:** {} ({} analyzed)\n", entries.len(), analyzed));
+
+    let mut scopes: std::collections::BTreeMap<&str, Vec<&SignatureEntry>> = std::collections::BTreeMap::new();
+    for entry in &entries {
+        let scope = entry
+            .function_id
+            .rsplit_once('.')
+            .map(|(scope, _)| scope)
+            .unwrap_or(module);
+        scopes.entry(scope).or_default().push(entry);
+    }
+
+    for (scope, entries) in scopes {
+        result.push_str(&format!("\n## `{}`\n\n", scope));
+        for entry in entries {
+            match entry.signature {
+                Some(ref signature) => result.push_str(&format!(
+                    "- `{}` — `{}`\n",
+                    signature, entry.location
+                )),
+                None => result.push_str(&format!(
+                    "- `{}` — `{}` (not analyzed)\n",
+                    entry.function_id, entry.location
+                )),
+            }
+        }
+    }
+
+    result
+}
+
 pub fn query_one_exception(function: &str, exc_type: &str) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
@@ -216,7 +338,7 @@ pub fn query_one_exception(function: &str, exc_type: &str) -> Result<String, Que
     let raise = analysis
         .raises
         .iter()
-        .find(|r| r.exception_type == exc_type || r.qualified_type == exc_type)
+        .find(|r| r.matches_type(exc_type))
         .ok_or_else(|| QueryError::ExceptionNotFound(exc_type.to_string(), function.to_string()))?;
 
     let mut result = format!("Exception: {}\n", raise.exception_type);
@@ -240,8 +362,8 @@ pub fn query_one_exception(function: &str, exc_type: &str) -> Result<String, Que
         result.push_str(&format!("Message: {}\n", msg));
     }
 
-    let strategy = RecoveryStrategy::from_exception_type(&raise.exception_type);
-    result.push_str(&format!("Recovery: {}", strategy.as_str()));
+    let (strategy, confidence) = RecoveryStrategy::from_exception_type_scored(&raise.exception_type);
+    result.push_str(&format!("Recovery: {} (confidence {:.1})", strategy.as_str(), confidence));
 
     Ok(result)
 }
@@ -271,13 +393,26 @@ pub fn query_one_none(function: &str, index: usize) -> Result<String, QueryError
     Ok(result)
 }
 
-pub fn query_callers(function: &str) -> Result<String, QueryError> {
+/// Above this many transitive callers, the markdown output is summarized by package.
+const TRANSITIVE_CALLER_LIMIT: usize = 100;
+
+pub fn query_callers(function: &str, transitive: bool) -> Result<String, QueryError> {
     let db = load_database()?;
+    format_callers(&db, function, transitive)
+}
 
+fn format_callers(db: &ArborDatabase, function: &str, transitive: bool) -> Result<String, QueryError> {
     if !db.functions.contains_key(function) && !db.symbol_index.contains(function) {
         return Err(QueryError::FunctionNotFound(function.to_string()));
     }
 
+    if transitive {
+        return Ok(format_transitive_callers(
+            function,
+            &db.dependency_graph.transitive_callers(function),
+        ));
+    }
+
     match db.dependency_graph.get_callers(function) {
         Some(callers) if !callers.is_empty() => {
             let mut result = format!("Functions calling {}:\n", function);
@@ -290,6 +425,50 @@ pub fn query_callers(function: &str) -> Result<String, QueryError> {
     }
 }
 
+fn format_transitive_callers(function: &str, callers: &[(String, usize)]) -> String {
+    if callers.is_empty() {
+        return format!("No callers found for {}", function);
+    }
+
+    let mut result = format!("# Transitive Callers of `{}`\n\n", function);
+    result.push_str(&format!("**Total Callers:** {}\n\n", callers.len()));
+
+    if callers.len() > TRANSITIVE_CALLER_LIMIT {
+        let mut packages: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+        for (caller, _) in callers {
+            let package = caller.split('.').next().unwrap_or("unknown");
+            *packages.entry(package).or_default() += 1;
+        }
+
+        result.push_str("## By Package\n\n");
+        result.push_str("| Package | Callers |\n");
+        result.push_str("|---------|---------|\n");
+        for (package, count) in packages {
+            result.push_str(&format!("| `{}` | {} |\n", package, count));
+        }
+        return result;
+    }
+
+    let mut by_depth: std::collections::BTreeMap<usize, Vec<&str>> = std::collections::BTreeMap::new();
+    for (caller, depth) in callers {
+        by_depth.entry(*depth).or_default().push(caller);
+    }
+
+    for (depth, group) in by_depth {
+        if depth == 1 {
+            result.push_str(&format!("## Depth 1 (direct) ({})\n\n", group.len()));
+        } else {
+            result.push_str(&format!("## Depth {} ({})\n\n", depth, group.len()));
+        }
+        for caller in group {
+            result.push_str(&format!("- `{}`\n", caller));
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
 pub fn query_callees(function: &str) -> Result<String, QueryError> {
     let db = load_database()?;
 
@@ -325,7 +504,7 @@ pub fn query_diff(function: &str) -> Result<String, QueryError> {
 // FULL ANALYSIS Queries
 // ============================================================================
 
-pub fn query_exceptions(function: &str) -> Result<String, QueryError> {
+pub fn query_exceptions(function: &str, show_ignored: bool) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
@@ -338,6 +517,9 @@ pub fn query_exceptions(function: &str) -> Result<String, QueryError> {
 
     if analysis.raises.is_empty() {
         result.push_str("This function does not raise any exceptions.\n");
+        if show_ignored {
+            result.push_str(&format_ignored_raises(analysis));
+        }
         return Ok(result);
     }
 
@@ -370,55 +552,264 @@ pub fn query_exceptions(function: &str) -> Result<String, QueryError> {
             if retryable { "retryable" } else { "not retryable" }
         ));
 
-        if let Some(ref containing_fn) = raise.raise_location.containing_function {
-            if let Some(chain) = analysis.call_chains.get(containing_fn) {
-                if !chain.is_empty() {
-                    let chain_str = std::iter::once(function.to_string())
-                        .chain(chain.iter().cloned())
-                        .collect::<Vec<_>>()
-                        .join(" → ");
-                    result.push_str(&format!("- **Call Chain:** `{}`\n", chain_str));
-                }
+        if let Some(entry) = analysis.chain_to(&raise.exception_type, &raise.raise_location) {
+            if entry.chain.len() > 1 {
+                result.push_str(&format!("- **Call Chain:** `{}`\n", entry.chain.join(" → ")));
             }
         }
 
         result.push('\n');
     }
 
+    if show_ignored {
+        result.push_str(&format_ignored_raises(analysis));
+    }
+
     if !db.grouping_suggestions.is_empty() {
         result.push_str("---\n\n");
         result.push_str("## Suggested Groupings\n\n");
+        result.push_str("For grouping details, see: `arbor query groups`\n");
+    }
+
+    Ok(result)
+}
+
+/// Raises dropped by `ignore.exception_types`, for `--show-ignored`.
+fn format_ignored_raises(analysis: &FunctionAnalysis) -> String {
+    if analysis.ignored_raises.is_empty() {
+        return "\nNo exceptions were ignored by config.\n".to_string();
+    }
+
+    let mut result = format!("\n## Ignored Exceptions ({})\n\n", analysis.ignored_raises.len());
+    for raise in &analysis.ignored_raises {
         result.push_str(&format!(
-            "For grouping details, see: `arbor query groups`\n"
+            "- `{}` at `{}`\n",
+            raise.exception_type,
+            raise.raise_location.to_string_short()
         ));
     }
+    result
+}
 
-    Ok(result)
+pub fn query_handled(function: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    Ok(format_handled(function, analysis))
+}
+
+fn format_handled(function: &str, analysis: &FunctionAnalysis) -> String {
+    let handled: Vec<_> = analysis
+        .raises
+        .iter()
+        .filter_map(|r| r.suppressed_by.as_ref().map(|h| (r, h)))
+        .collect();
+    let unhandled = analysis.raises.len() - handled.len();
+
+    let mut result = format!("# Handled Exceptions for `{}`\n\n", function);
+    result.push_str(&format!("**Handled:** {}\n", handled.len()));
+    result.push_str(&format!("**Unhandled:** {}\n\n", unhandled));
+
+    if handled.is_empty() {
+        result.push_str("No exceptions are caught within the call chain.\n");
+        return result;
+    }
+
+    result.push_str("| Exception | Caught in | Location | Strategy |\n");
+    result.push_str("|-----------|-----------|----------|----------|\n");
+
+    for (raise, handler) in handled {
+        result.push_str(&format!(
+            "| `{}` | `{}` | `{}` | {} |\n",
+            raise.exception_type,
+            handler.location.containing_function.as_deref().unwrap_or("unknown"),
+            handler.location.to_string_short(),
+            handler.strategy.describe()
+        ));
+    }
+
+    if unhandled > 0 {
+        result.push_str(&format!(
+            "\nFor the {} exception(s) that reach callers, see: `arbor query exceptions {}`\n",
+            unhandled, function
+        ));
+    }
+
+    result
+}
+
+pub fn query_suppressors(function: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    Ok(format_suppressors(function, analysis))
+}
+
+fn format_suppressors(function: &str, analysis: &FunctionAnalysis) -> String {
+    let mut result = format!("# Exception Suppressors for `{}`\n\n", function);
+    if analysis.suppressors.is_empty() {
+        result.push_str("No catch-all handlers swallow exceptions within the call chain.\n");
+        return result;
+    }
+
+    result.push_str(&format!(
+        "**{} catch-all handler(s)** neither re-raise nor log what they catch.\n\n",
+        analysis.suppressors.len()
+    ));
+    result.push_str("| Catches | In | Location | First statement |\n");
+    result.push_str("|---------|----|----------|-----------------|\n");
+
+    for suppressor in &analysis.suppressors {
+        let caught = if suppressor.caught_type == "*" {
+            "bare `except:`".to_string()
+        } else {
+            format!("`{}`", suppressor.caught_type)
+        };
+        let first_line = suppressor.first_statement.lines().next().unwrap_or_default();
+        result.push_str(&format!(
+            "| {} | `{}` | `{}` | `{}` |\n",
+            caught,
+            suppressor.location.containing_function.as_deref().unwrap_or("unknown"),
+            suppressor.location.to_string_short(),
+            first_line.replace('|', "\\|")
+        ));
+    }
+
+    result
 }
 
-pub fn query_none(function: &str) -> Result<String, QueryError> {
+pub fn query_none(
+    function: &str,
+    kind_filter: Option<NoneSourceKind>,
+    trace: bool,
+    annotate_source: bool,
+) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
+    let weights = ArborConfig::load_or_default().risk.none_source_weights;
+    let flows = trace.then(|| trace_none_flows(&analysis.none_sources));
+    let annotations = annotate_source.then(|| annotate_none_sources(analysis, &db));
+    Ok(format_none(
+        function,
+        analysis,
+        kind_filter,
+        flows.as_deref(),
+        annotations.as_deref(),
+        &weights,
+    ))
+}
+
+/// `classify_none_source` for each function-call source in `analysis`, in
+/// the order of `analysis.none_sources`; `None` for other kinds.
+fn annotate_none_sources(analysis: &FunctionAnalysis, db: &ArborDatabase) -> Vec<Option<NoneAnnotationStatus>> {
+    analysis
+        .none_sources
+        .iter()
+        .map(|source| {
+            (source.kind == NoneSourceKind::FunctionCall).then(|| classify_none_source(source, db))
+        })
+        .collect()
+}
+
+/// `function call` → `x` (line 10) → `y` (line 12) → return (line 15)
+fn format_none_flow(flow: &NoneFlowPath) -> String {
+    let mut steps = vec![format!("`{}`", flow.source.kind.as_str())];
+    for (variable, location) in &flow.assignments {
+        steps.push(format!("`{}` (line {})", variable, location.line));
+    }
+    match flow.return_location {
+        Some(ref location) => steps.push(format!("return (line {})", location.line)),
+        None => steps.push("not returned".to_string()),
+    }
+    steps.join(" → ")
+}
+
+/// None sources matching `kind_filter`, paired with their index in the full
+/// list so they can still be addressed with `query one-none`.
+fn filter_none_sources(
+    analysis: &FunctionAnalysis,
+    kind_filter: Option<NoneSourceKind>,
+) -> Vec<(usize, &NoneSource)> {
+    analysis
+        .none_sources
+        .iter()
+        .enumerate()
+        .filter(|(_, source)| kind_filter.map_or(true, |kind| source.kind == kind))
+        .collect()
+}
+
+/// With `flows` (one per entry of `analysis.none_sources`, from `--trace`)
+/// each source also shows how its value reaches the return. `annotations`
+/// (from `--annotate-source`) label function-call sources by the callee's
+/// return annotation.
+fn format_none(
+    function: &str,
+    analysis: &FunctionAnalysis,
+    kind_filter: Option<NoneSourceKind>,
+    flows: Option<&[Option<NoneFlowPath>]>,
+    annotations: Option<&[Option<NoneAnnotationStatus>]>,
+    weights: &NoneSourceWeights,
+) -> String {
+    let sources = filter_none_sources(analysis, kind_filter);
+    let weighted_total: f64 = sources
+        .iter()
+        .map(|(_, source)| weights.weight(&source.kind))
+        .sum();
+
     let mut result = format!("# None Sources for `{}`\n\n", function);
     result.push_str(&format!("**Signature:** `{}`\n", analysis.signature));
     result.push_str(&format!("**Location:** `{}`\n", analysis.location.to_string_short()));
-    result.push_str(&format!("**Total None Sources:** {}\n\n", analysis.none_sources.len()));
-
-    if analysis.none_sources.is_empty() {
-        result.push_str("This function does not have any None sources.\n");
-        return Ok(result);
+    match kind_filter {
+        Some(kind) => {
+            result.push_str(&format!("**Kind Filter:** {}\n", kind.as_str()));
+            result.push_str(&format!(
+                "**Total None Sources:** {} of {}\n",
+                sources.len(),
+                analysis.none_sources.len()
+            ));
+        }
+        None => result.push_str(&format!("**Total None Sources:** {}\n", analysis.none_sources.len())),
+    }
+    result.push_str(&format!("**Weighted Risk:** {:.1}\n\n", weighted_total));
+
+    if sources.is_empty() {
+        match kind_filter {
+            Some(kind) => result.push_str(&format!(
+                "This function does not have any {} None sources.\n",
+                kind.as_str()
+            )),
+            None => result.push_str("This function does not have any None sources.\n"),
+        }
+        return result;
     }
 
     result.push_str("## None Sources\n\n");
 
-    for (i, source) in analysis.none_sources.iter().enumerate() {
-        result.push_str(&format!("### {}. {}\n\n", i + 1, source.kind.as_str()));
+    for (i, source) in sources {
+        match annotations.and_then(|a| a.get(i).copied().flatten()) {
+            Some(status) => result.push_str(&format!(
+                "### {}. {} [{}]\n\n",
+                i + 1,
+                source.kind.as_str(),
+                status.as_str()
+            )),
+            None => result.push_str(&format!("### {}. {}\n\n", i + 1, source.kind.as_str())),
+        }
         result.push_str(&format!("- **Kind:** `{}`\n", source.kind.as_str()));
+        result.push_str(&format!("- **Risk Weight:** {:.1}\n", weights.weight(&source.kind)));
         result.push_str(&format!("- **Location:** `{}`\n", source.location.to_string_short()));
 
+        if let Some(ref variable) = source.variable_name {
+            result.push_str(&format!("- **Variable:** `{}`\n", variable));
+        }
+
         if let Some(ref def_loc) = source.source_definition {
             result.push_str(&format!("- **Source:** `{}`\n", def_loc.to_string_short()));
         }
@@ -427,15 +818,20 @@ pub fn query_none(function: &str) -> Result<String, QueryError> {
             result.push_str(&format!("- **Condition:** {}\n", cond));
         }
 
-        if let Some(ref containing_fn) = source.location.containing_function {
-            if let Some(chain) = analysis.call_chains.get(containing_fn) {
-                if !chain.is_empty() {
-                    let chain_str = std::iter::once(function.to_string())
-                        .chain(chain.iter().cloned())
-                        .collect::<Vec<_>>()
-                        .join(" → ");
-                    result.push_str(&format!("- **Call Chain:** `{}`\n", chain_str));
-                }
+        if let Some(ref note) = source.note {
+            result.push_str(&format!("- **Note:** {}\n", note));
+        }
+
+        if let Some(entry) = analysis.chain_to(source.kind.as_str(), &source.location) {
+            if entry.chain.len() > 1 {
+                result.push_str(&format!("- **Call Chain:** `{}`\n", entry.chain.join(" → ")));
+            }
+        }
+
+        if let Some(flows) = flows {
+            match flows.get(i).and_then(Option::as_ref) {
+                Some(flow) => result.push_str(&format!("- **Flow:** {}\n", format_none_flow(flow))),
+                None => result.push_str("- **Flow:** (source file unavailable)\n"),
             }
         }
 
@@ -448,15 +844,45 @@ pub fn query_none(function: &str) -> Result<String, QueryError> {
     result.push_str("- Check for None before accessing attributes\n");
     result.push_str("- Use type hints: `-> T | None` if None is intentional\n");
 
-    Ok(result)
+    result
+}
+
+/// Number of None sources of each kind present, in declaration order.
+fn none_source_breakdown(analysis: &FunctionAnalysis) -> Vec<(NoneSourceKind, usize)> {
+    NoneSourceKind::ALL
+        .iter()
+        .map(|kind| {
+            let count = analysis.none_sources.iter().filter(|s| s.kind == *kind).count();
+            (*kind, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect()
 }
 
-pub fn query_function(function: &str) -> Result<String, QueryError> {
+pub fn query_function(function: &str, depth_profile: bool, show_chains: bool) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
+    let mut groups: Vec<&GroupingSuggestion> = db
+        .grouping_suggestions
+        .values()
+        .filter(|group| group.applies_to(&analysis.function_id))
+        .collect();
+    groups.sort_by(|a, b| a.group_name.cmp(&b.group_name));
+
+    Ok(format_function(analysis, &groups, depth_profile, show_chains))
+}
+
+/// `groups` are the saved grouping suggestions that apply to `analysis`.
+fn format_function(
+    analysis: &FunctionAnalysis,
+    groups: &[&GroupingSuggestion],
+    depth_profile: bool,
+    show_chains: bool,
+) -> String {
+    let function = analysis.function_id.as_str();
     let risk = analysis.risk_level();
     let mut result = format!("# Function Analysis: `{}`\n\n", analysis.function_id);
 
@@ -465,6 +891,9 @@ pub fn query_function(function: &str) -> Result<String, QueryError> {
     result.push_str("|----------|-------|\n");
     result.push_str(&format!("| **Qualified Name** | `{}` |\n", analysis.function_id));
     result.push_str(&format!("| **Signature** | `{}` |\n", analysis.signature));
+    if let Some(kind) = analysis.property_kind {
+        result.push_str(&format!("| **Property** | {} |\n", kind.as_str()));
+    }
     result.push_str(&format!(
         "| **File** | `{}` |\n",
         analysis.location.file.display()
@@ -478,10 +907,27 @@ pub fn query_function(function: &str) -> Result<String, QueryError> {
     result.push_str("|--------|-------|\n");
     result.push_str(&format!("| Exceptions | {} |\n", analysis.raises.len()));
     result.push_str(&format!("| None sources | {} |\n", analysis.none_sources.len()));
+    for (kind, count) in none_source_breakdown(analysis) {
+        result.push_str(&format!("| ↳ {} | {} |\n", kind.as_str(), count));
+    }
     result.push_str(&format!("| Functions traced | {} |\n", analysis.functions_traced));
     result.push_str(&format!("| Call depth | {} |\n", analysis.call_depth));
+    if !analysis.call_graph_stats.width_at_depth.is_empty() {
+        result.push_str(&format!("| Call graph | {} |\n", analysis.call_graph_stats.summary()));
+    }
     result.push('\n');
 
+    if analysis.depth_limit_hits > 0 {
+        result.push_str(&format!(
+            "⚠ {} functions skipped (depth limit)\n\n",
+            analysis.depth_limit_hits
+        ));
+        for name in &analysis.skipped_functions {
+            result.push_str(&format!("- `{}`\n", name));
+        }
+        result.push('\n');
+    }
+
     if !analysis.raises.is_empty() {
         result.push_str("## Exception Groups (by Recovery Strategy)\n\n");
         result.push_str("| Group | Exceptions | Retryable |\n");
@@ -526,6 +972,14 @@ pub fn query_function(function: &str) -> Result<String, QueryError> {
         result.push('\n');
     }
 
+    if show_chains && !analysis.raises.is_empty() {
+        result.push_str(&format_function_chains(analysis));
+    }
+
+    if depth_profile && !analysis.raises.is_empty() {
+        result.push_str(&format_depth_profile(analysis));
+    }
+
     if !analysis.none_sources.is_empty() {
         result.push_str("## None Sources\n\n");
         for source in &analysis.none_sources {
@@ -539,6 +993,14 @@ pub fn query_function(function: &str) -> Result<String, QueryError> {
         result.push('\n');
     }
 
+    if !groups.is_empty() {
+        result.push_str("## Grouping Suggestions\n\n");
+        for group in groups {
+            result.push_str(&format!("- **{}**: {}\n", group.group_name, group.exceptions.join(", ")));
+        }
+        result.push('\n');
+    }
+
     result.push_str("---\n\n");
     result.push_str("## Quick Commands\n\n");
     result.push_str("```bash\n");
@@ -547,7 +1009,124 @@ pub fn query_function(function: &str) -> Result<String, QueryError> {
     result.push_str(&format!("arbor query handle {}        # Generate handler\n", function));
     result.push_str("```\n");
 
-    Ok(result)
+    result
+}
+
+/// How many chains `--show-chains` renders before pointing at `query chain`.
+const MAX_INLINE_CHAINS: usize = 5;
+
+/// `## Call Chains`: the shortest chain to each exception type, drawn once
+/// per chain with every type it leads to, longest chains first.
+fn format_function_chains(analysis: &FunctionAnalysis) -> String {
+    let mut groups: Vec<(&[String], Vec<&RaiseStatement>)> = Vec::new();
+    for (chain, raise) in analysis.shortest_chains().into_values() {
+        match groups.iter_mut().find(|(c, _)| *c == chain) {
+            Some((_, raises)) => raises.push(raise),
+            None => groups.push((chain, vec![raise])),
+        }
+    }
+    groups.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(b.0)));
+
+    let mut result = String::from("## Call Chains\n\n");
+    if groups.is_empty() {
+        result.push_str("No call chains recorded. Re-run `arbor analyze` to record them.\n\n");
+        return result;
+    }
+
+    result.push_str("```\n");
+    for (i, (chain, raises)) in groups.iter().take(MAX_INLINE_CHAINS).enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        for (depth, fn_name) in chain.iter().enumerate() {
+            if depth == 0 {
+                result.push_str(&format!("{}\n", fn_name));
+            } else {
+                result.push_str(&format!("{}└── {}\n", "    ".repeat(depth - 1), fn_name));
+            }
+        }
+        let sites: Vec<String> = raises
+            .iter()
+            .map(|raise| {
+                format!(
+                    "{} ({}:{})",
+                    raise.exception_type,
+                    raise.raise_location.file.file_name().unwrap_or_default().to_string_lossy(),
+                    raise.raise_location.line
+                )
+            })
+            .collect();
+        result.push_str(&format!(
+            "{}🔴 {}\n",
+            "    ".repeat(chain.len().saturating_sub(1)),
+            sites.join(", ")
+        ));
+    }
+    result.push_str("```\n\n");
+
+    if groups.len() > MAX_INLINE_CHAINS {
+        result.push_str(&format!(
+            "*Showing {} of {} call chains. Run 'arbor query chain {} <exc> --all' for all.*\n\n",
+            MAX_INLINE_CHAINS,
+            analysis.unique_chain_count(),
+            analysis.function_id
+        ));
+    }
+    result
+}
+
+const BLOCK_BAR_WIDTH: usize = 20;
+
+fn format_depth_profile(analysis: &FunctionAnalysis) -> String {
+    let mut min_depths: std::collections::BTreeMap<&str, usize> =
+        std::collections::BTreeMap::new();
+    let mut counts: std::collections::BTreeMap<usize, usize> = std::collections::BTreeMap::new();
+    for raise in &analysis.raises {
+        let depth = min_depths.entry(raise.exception_type.as_str()).or_insert(raise.source_depth);
+        *depth = (*depth).min(raise.source_depth);
+        *counts.entry(raise.source_depth).or_insert(0) += 1;
+    }
+
+    let mut by_depth: Vec<(&str, usize)> = min_depths.into_iter().collect();
+    by_depth.sort_by_key(|(_, depth)| *depth);
+
+    let mut result = "## Exceptions by Depth\n\n".to_string();
+    result.push_str("| Exception | Min Depth |\n");
+    result.push_str("|-----------|-----------|\n");
+    for (exception, depth) in &by_depth {
+        result.push_str(&format!("| `{}` | {} |\n", exception, depth));
+    }
+    result.push('\n');
+
+    let max = counts.values().copied().max().unwrap_or(0);
+    let label_width = counts.keys().map(|d| d.to_string().len()).max().unwrap_or(1);
+    result.push_str("```\n");
+    for (depth, count) in &counts {
+        result.push_str(&format!(
+            "depth {:>width$} │ {} {}\n",
+            depth,
+            block_bar(*count, max),
+            count,
+            width = label_width
+        ));
+    }
+    result.push_str("```\n\n");
+    result
+}
+
+/// Renders `count` relative to `max` as a bar of eighth-block characters.
+fn block_bar(count: usize, max: usize) -> String {
+    const PARTIAL: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    if max == 0 || count == 0 {
+        return String::new();
+    }
+    let eighths = ((count * BLOCK_BAR_WIDTH * 8) as f64 / max as f64).round() as usize;
+    let eighths = eighths.max(1);
+    let mut bar = "█".repeat(eighths / 8);
+    if eighths % 8 > 0 {
+        bar.push(PARTIAL[eighths % 8 - 1]);
+    }
+    bar
 }
 
 fn capitalize(s: &str) -> String {
@@ -558,68 +1137,47 @@ fn capitalize(s: &str) -> String {
     }
 }
 
+/// The first raise of `exception` in `analysis` and the call chain reaching
+/// it, or just the analyzed function when no chain was recorded.
+fn first_chain<'a>(
+    analysis: &'a FunctionAnalysis,
+    exception: &str,
+) -> Result<(&'a RaiseStatement, Vec<String>), QueryError> {
+    let raise = analysis
+        .raises
+        .iter()
+        .find(|r| r.matches_type(exception))
+        .ok_or_else(|| QueryError::ExceptionNotFound(exception.to_string(), analysis.function_id.clone()))?;
+
+    let chain = match analysis.chain_to(&raise.exception_type, &raise.raise_location) {
+        Some(entry) if !entry.chain.is_empty() => entry.chain.clone(),
+        _ => vec![analysis.function_id.clone()],
+    };
+    Ok((raise, chain))
+}
+
 pub fn query_chain(function: &str, exception: &str) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
-    let raise = analysis
-        .raises
-        .iter()
-        .find(|r| r.exception_type == exception || r.qualified_type == exception)
-        .ok_or_else(|| QueryError::ExceptionNotFound(exception.to_string(), function.to_string()))?;
-
-    let containing_fn = raise
-        .raise_location
-        .containing_function
-        .as_deref()
-        .unwrap_or("unknown");
-
-    let chain = analysis.call_chains.get(containing_fn);
+    let (raise, chain_vec) = first_chain(analysis, exception)?;
 
     let strategy = RecoveryStrategy::from_exception_type(&raise.exception_type);
     let retryable = matches!(strategy, RecoveryStrategy::Retry);
 
     let mut result = format!("# Call Chain: `{}` in `{}`\n\n", exception, function);
 
-    let chain_vec: Vec<String> = match chain {
-        Some(c) if !c.is_empty() => {
-            std::iter::once(function.to_string())
-                .chain(c.iter().cloned())
-                .collect()
-        }
-        _ => vec![function.to_string()],
-    };
-
     result.push_str("## Path\n\n");
-    result.push_str("```\n");
+    result.push_str(&format_chain_tree(&chain_vec, raise, exception, &analysis.awaited_edges));
+    result.push('\n');
 
     let raise_file = raise.raise_location.file.file_name()
         .unwrap_or_default()
         .to_string_lossy();
     let raise_line = raise.raise_location.line;
 
-    for (i, fn_name) in chain_vec.iter().enumerate() {
-        let is_last = i == chain_vec.len() - 1;
-        let indent = "    ".repeat(i);
-
-        if i == 0 {
-            result.push_str(&format!("{} ({}:{})\n", fn_name, raise_file, raise_line));
-        } else {
-            result.push_str(&format!("{}│\n", indent));
-            result.push_str(&format!("{}└── {}\n", indent, fn_name));
-        }
-
-        if is_last {
-            let final_indent = "    ".repeat(i + 1);
-            result.push_str(&format!("{}│\n", final_indent));
-            result.push_str(&format!("{}└── 🔴 raise {}(\"...\")\n", final_indent, exception));
-        }
-    }
-
-    result.push_str("```\n\n");
-
     result.push_str("## Details\n\n");
     result.push_str("| Depth | Function | File | Line |\n");
     result.push_str("|-------|----------|------|------|\n");
@@ -656,7 +1214,13 @@ pub fn query_chain(function: &str, exception: &str) -> Result<String, QueryError
         result.push_str(&format!("- **Message:** \"{}\"\n", msg));
     }
 
-    result.push('\n');
+    if let Some(ref cause) = raise.chained_from {
+        result.push_str(&format!("- **Chained from:** `{}` (available as `__cause__`)\n", cause));
+    } else if raise.suppress_chain {
+        result.push_str("- **Chained from:** nothing; `from None` hides the exception being handled\n");
+    }
+
+    result.push('\n');
 
     result.push_str("## Handling Recommendation\n\n");
     result.push_str(&format!(
@@ -665,7 +1229,7 @@ pub fn query_chain(function: &str, exception: &str) -> Result<String, QueryError
         function
     ));
 
-    let fn_name = function.split('.').last().unwrap_or(function);
+    let fn_name = function.split('.').next_back().unwrap_or(function);
     result.push_str("```python\n");
     result.push_str(&format!("try:\n    result = {}()\n", fn_name));
     result.push_str(&format!(
@@ -679,15 +1243,284 @@ pub fn query_chain(function: &str, exception: &str) -> Result<String, QueryError
     Ok(result)
 }
 
+/// ` from <cause>` for a chained raise, ` from None` when chaining is
+/// suppressed, otherwise empty.
+fn format_raise_cause(raise: &RaiseStatement) -> String {
+    match raise.chained_from {
+        Some(ref cause) => format!(" from {}", cause),
+        None if raise.suppress_chain => " from None".to_string(),
+        None => String::new(),
+    }
+}
+
+/// The fenced path diagram from the first function in `chain` down to the
+/// raise, as an indented tree. When the raise is reached through awaited
+/// coroutines, hops the caller awaits (per `awaited_edges`) are marked
+/// `(async)`.
+fn format_chain_tree(
+    chain: &[String],
+    raise: &RaiseStatement,
+    exception: &str,
+    awaited_edges: &[(String, String)],
+) -> String {
+    let mut result = String::from("```\n");
+
+    let raise_file = raise.raise_location.file.file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let raise_line = raise.raise_location.line;
+
+    for (i, fn_name) in chain.iter().enumerate() {
+        let is_last = i == chain.len() - 1;
+        let indent = "    ".repeat(i);
+
+        if i == 0 {
+            result.push_str(&format!("{} ({}:{})\n", fn_name, raise_file, raise_line));
+        } else {
+            result.push_str(&format!("{}│\n", indent));
+            let awaited = raise.via_await
+                && awaited_edges
+                    .iter()
+                    .any(|(caller, callee)| caller == &chain[i - 1] && callee == fn_name);
+            let suffix = if awaited { " (async)" } else { "" };
+            result.push_str(&format!("{}└── {}{}\n", indent, fn_name, suffix));
+        }
+
+        if is_last {
+            let final_indent = "    ".repeat(i + 1);
+            result.push_str(&format!("{}│\n", final_indent));
+            result.push_str(&format!(
+                "{}└── 🔴 raise {}(\"...\"){}\n",
+                final_indent,
+                exception,
+                format_raise_cause(raise)
+            ));
+        }
+    }
+
+    result.push_str("```\n");
+    result
+}
+
+/// Every distinct path from the analyzed function to a raise of
+/// `exception`, each with the first raise it leads to.
+fn chain_paths<'a>(analysis: &'a FunctionAnalysis, exception: &str) -> Vec<(&'a RaiseStatement, &'a [String])> {
+    let mut paths: Vec<(&RaiseStatement, &[String])> = Vec::new();
+    for raise in analysis
+        .raises
+        .iter()
+        .filter(|r| r.matches_type(exception))
+    {
+        let Some(entry) = analysis.chain_to(&raise.exception_type, &raise.raise_location) else {
+            continue;
+        };
+        if !paths.iter().any(|(_, chain)| *chain == entry.chain.as_slice()) {
+            paths.push((raise, &entry.chain));
+        }
+    }
+    paths
+}
+
+/// `query_chain` as a Mermaid sequence diagram.
+pub fn query_chain_mermaid(function: &str, exception: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    let (_, chain) = first_chain(analysis, exception)?;
+    Ok(call_chain_to_mermaid(&[&chain], exception, MermaidDiagramType::SequenceDiagram))
+}
+
+/// Every distinct path from `query_chain_all` merged into one Mermaid flowchart.
+pub fn query_chain_all_mermaid(function: &str, exception: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    let chains: Vec<&[String]> = chain_paths(analysis, exception).into_iter().map(|(_, chain)| chain).collect();
+    if chains.is_empty() {
+        return Err(QueryError::ExceptionNotFound(exception.to_string(), function.to_string()));
+    }
+    Ok(call_chain_to_mermaid(&chains, exception, MermaidDiagramType::Flowchart))
+}
+
+pub fn query_chain_all(function: &str, exception: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+    format_chain_all(analysis, exception)
+}
+
+fn format_chain_all(analysis: &FunctionAnalysis, exception: &str) -> Result<String, QueryError> {
+    let paths = chain_paths(analysis, exception);
+    if paths.is_empty() {
+        return Err(QueryError::ExceptionNotFound(exception.to_string(), analysis.function_id.clone()));
+    }
+
+    let mut result = format!("# Call Chains: `{}` in `{}`\n\n", exception, analysis.function_id);
+    result.push_str(&format!("**Distinct Paths:** {}\n\n", paths.len()));
+
+    for (i, (raise, chain)) in paths.iter().enumerate() {
+        result.push_str(&format!("## Path {} of {}:\n\n", i + 1, paths.len()));
+        result.push_str(&format_chain_tree(chain, raise, exception, &analysis.awaited_edges));
+        result.push_str(&format!("\nRaised at `{}`\n\n", raise.raise_location.to_string_short()));
+    }
+
+    Ok(result)
+}
+
+pub fn query_chain_all_json(
+    function: &str,
+    exception: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    let paths: Vec<&[String]> = chain_paths(analysis, exception).into_iter().map(|(_, chain)| chain).collect();
+    if paths.is_empty() {
+        return Err(QueryError::ExceptionNotFound(exception.to_string(), analysis.function_id.clone()));
+    }
+    to_json_string(&paths, options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
 // ============================================================================
 // CROSS-FUNCTION Queries
 // ============================================================================
 
-pub fn query_groups(package: Option<&str>) -> Result<String, QueryError> {
+/// With `function`, only the groups recorded for that analyzed function.
+pub fn query_groups(
+    package: Option<&str>,
+    function: Option<&str>,
+    suggest_new: bool,
+    min_confidence: Option<f32>,
+) -> Result<String, QueryError> {
     let db = load_database()?;
+    Ok(format_groups(&db, package, function, suggest_new, min_confidence))
+}
+
+/// Replaces the saved groups `first` and `second` with one merged group named
+/// `name` (`"<first> and <second>"` by default). With `dry_run` the database
+/// is left untouched.
+pub fn query_groups_merge(
+    first: &str,
+    second: &str,
+    name: Option<&str>,
+    dry_run: bool,
+) -> Result<String, QueryError> {
+    let merged = merge_saved_groups(first, second, name, dry_run)?;
+    let mut result = if dry_run {
+        "# Merged Group (dry run, not saved)\n\n".to_string()
+    } else {
+        format!("# Merged `{}` and `{}`\n\n", first, second)
+    };
+    result.push_str(&format_group_definition(&merged));
+    Ok(result)
+}
+
+pub fn query_groups_merge_json(
+    first: &str,
+    second: &str,
+    name: Option<&str>,
+    dry_run: bool,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let merged = merge_saved_groups(first, second, name, dry_run)?;
+    to_json_string(&merged, options).map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+fn merge_saved_groups(
+    first: &str,
+    second: &str,
+    name: Option<&str>,
+    dry_run: bool,
+) -> Result<GroupingSuggestion, QueryError> {
+    let mut db = load_database()?;
+    let default_name = format!("{} and {}", first, second);
+    let merged = merge_group_entries(&mut db, first, second, name.unwrap_or(&default_name))?;
+    if !dry_run {
+        db.save(&paths::database_path())?;
+    }
+    Ok(merged)
+}
+
+/// Swaps `first` and `second` in `db.grouping_suggestions` for their merge.
+fn merge_group_entries(
+    db: &mut ArborDatabase,
+    first: &str,
+    second: &str,
+    name: &str,
+) -> Result<GroupingSuggestion, QueryError> {
+    if first == second {
+        return Err(QueryError::InvalidQuery(format!("cannot merge group '{}' with itself", first)));
+    }
+    let lookup = |group: &str| {
+        db.grouping_suggestions
+            .get(group)
+            .ok_or_else(|| QueryError::GroupNotFound(group.to_string()))
+    };
+    let merged = merge_groups(lookup(first)?, lookup(second)?, name);
+
+    db.grouping_suggestions.remove(first);
+    db.grouping_suggestions.remove(second);
+    db.grouping_suggestions.insert(merged.group_name.clone(), merged.clone());
+    Ok(merged)
+}
+
+/// A single group as markdown: name, rationale, confidence, exceptions and
+/// handler example.
+fn format_group_definition(suggestion: &GroupingSuggestion) -> String {
+    let mut result = format!("## {}\n\n", suggestion.group_name);
+    result.push_str(&format!("**Reason:** {}\n", suggestion.rationale));
+    result.push_str(&format!("**Confidence:** {}\n\n", format_confidence(suggestion.confidence)));
+    result.push_str("**Exceptions:**\n");
+    for exc in &suggestion.exceptions {
+        result.push_str(&format!("- `{}`\n", exc));
+    }
+    result.push_str("\n**Recommended Handler:**\n");
+    result.push_str(&format!("```python\n{}\n```\n", suggestion.handler_example));
+    result
+}
+
+/// `🔴 low`, `🟡 medium` or `🟢 high` followed by the score.
+fn format_confidence(confidence: f32) -> String {
+    let label = if confidence < 0.5 {
+        "🔴 low"
+    } else if confidence <= 0.8 {
+        "🟡 medium"
+    } else {
+        "🟢 high"
+    };
+    format!("{} ({:.2})", label, confidence)
+}
+
+fn meets_confidence(suggestion: &GroupingSuggestion, min_confidence: Option<f32>) -> bool {
+    min_confidence.map_or(true, |min| suggestion.confidence >= min)
+}
+
+fn applies_to_function(suggestion: &GroupingSuggestion, function: Option<&str>) -> bool {
+    function.map_or(true, |f| suggestion.applies_to(f))
+}
+
+fn applies_to_package(suggestion: &GroupingSuggestion, package: Option<&str>) -> bool {
+    package.map_or(true, |pkg| suggestion.applies_to_package(pkg))
+}
 
-    if db.grouping_suggestions.is_empty() {
-        return Ok("No grouping suggestions. Run 'arbor analyze' first.".to_string());
+fn format_groups(
+    db: &ArborDatabase,
+    package: Option<&str>,
+    function: Option<&str>,
+    suggest_new: bool,
+    min_confidence: Option<f32>,
+) -> String {
+    if db.grouping_suggestions.is_empty() && !suggest_new {
+        return "No grouping suggestions. Run 'arbor analyze' first.".to_string();
     }
 
     let pkg_name = package.unwrap_or("all packages");
@@ -699,10 +1532,11 @@ pub fn query_groups(package: Option<&str>) -> Result<String, QueryError> {
     let mut found_any = false;
 
     for suggestion in db.grouping_suggestions.values() {
-        if let Some(pkg) = package {
-            if !suggestion.group_name.to_lowercase().contains(&pkg.to_lowercase()) {
-                continue;
-            }
+        if !applies_to_package(suggestion, package)
+            || !meets_confidence(suggestion, min_confidence)
+            || !applies_to_function(suggestion, function)
+        {
+            continue;
         }
 
         found_any = true;
@@ -714,14 +1548,21 @@ pub fn query_groups(package: Option<&str>) -> Result<String, QueryError> {
         result.push_str(&format!("## {}\n\n", suggestion.group_name));
         result.push_str(&format!("**Retryable:** {}\n", if retryable { "Yes" } else { "No" }));
         result.push_str(&format!("**Reason:** {}\n", suggestion.rationale));
-        result.push_str(&format!("**Recovery:** {}\n\n", strategy.as_str()));
+        result.push_str(&format!("**Confidence:** {}\n", format_confidence(suggestion.confidence)));
+        result.push_str(&format!("**Recovery:** {}\n", strategy.as_str()));
+        result.push_str(&format!("**Relevant to:** {} functions\n\n", suggestion.applicable_functions.len()));
 
-        result.push_str("| Exception | Recovery Strategy |\n");
-        result.push_str("|-----------|------------------|\n");
+        result.push_str("| Exception | Recovery Strategy | Raised By |\n");
+        result.push_str("|-----------|-------------------|-----------|\n");
 
         for exc in &suggestion.exceptions {
             let exc_strategy = RecoveryStrategy::from_exception_type(exc);
-            result.push_str(&format!("| `{}` | {} |\n", exc, exc_strategy.as_str()));
+            result.push_str(&format!(
+                "| `{}` | {} | {} functions |\n",
+                exc,
+                exc_strategy.as_str(),
+                db.functions_raising(exc).count()
+            ));
         }
 
         result.push_str("\n**Recommended Handler:**\n");
@@ -733,33 +1574,95 @@ pub fn query_groups(package: Option<&str>) -> Result<String, QueryError> {
         result.push_str(&format!("No grouping suggestions found for '{}'.\n", pkg_name));
     }
 
-    Ok(result)
+    if suggest_new {
+        result.push_str(&format_new_groups(db, package, min_confidence));
+    }
+
+    result
 }
 
-pub fn query_exception(exc_type: &str) -> Result<String, QueryError> {
-    let db = load_database()?;
+/// Groups `suggest_groups` would produce for the analyzed functions that are
+/// not saved yet, each with the functions whose re-analysis would record it.
+fn new_group_suggestions<'a>(
+    db: &'a ArborDatabase,
+    package: Option<&str>,
+    min_confidence: Option<f32>,
+) -> Vec<(GroupingSuggestion, Vec<&'a str>)> {
+    let mut ids: Vec<&String> = db
+        .functions
+        .keys()
+        .filter(|id| package.map_or(true, |pkg| *id == pkg || id.starts_with(&format!("{}.", pkg))))
+        .collect();
+    ids.sort();
 
-    struct Occurrence {
-        function: String,
-        file: PathBuf,
-        line: u32,
-        condition: Option<String>,
+    let mut suggestions: Vec<(GroupingSuggestion, Vec<&str>)> = Vec::new();
+    for id in ids {
+        let analysis = &db.functions[id];
+        for suggestion in suggest_new_groups(&db.grouping_suggestions, &analysis.raises) {
+            if !meets_confidence(&suggestion, min_confidence) {
+                continue;
+            }
+            match suggestions.iter_mut().find(|(s, _)| s.group_name == suggestion.group_name) {
+                Some((_, functions)) => functions.push(id),
+                None => suggestions.push((suggestion, vec![id])),
+            }
+        }
     }
 
-    let mut occurrences: Vec<Occurrence> = Vec::new();
-    let mut definition_loc: Option<String> = None;
-    let mut qualified_name: Option<String> = None;
+    suggestions
+}
 
-    for (fn_id, analysis) in &db.functions {
-        for raise in &analysis.raises {
-            if raise.exception_type == exc_type || raise.qualified_type == exc_type {
-                if definition_loc.is_none() {
-                    definition_loc = raise.definition_location.as_ref().map(|l| l.to_string_short());
-                    qualified_name = Some(raise.qualified_type.clone());
-                }
+fn format_new_groups(db: &ArborDatabase, package: Option<&str>, min_confidence: Option<f32>) -> String {
+    let suggestions = new_group_suggestions(db, package, min_confidence);
+
+    let mut result = String::from("## Suggested New Groups\n\n");
+    if suggestions.is_empty() {
+        result.push_str("Every exception is already covered by a saved group.\n");
+        return result;
+    }
+
+    for (suggestion, functions) in suggestions {
+        result.push_str(&format!("### {}\n\n", suggestion.group_name));
+        result.push_str(&format!("**Reason:** {}\n", suggestion.rationale));
+        result.push_str(&format!("**Confidence:** {}\n\n", format_confidence(suggestion.confidence)));
+        for exc in &suggestion.exceptions {
+            result.push_str(&format!("- `{}`\n", exc));
+        }
+        result.push_str("\n**Save it by re-analyzing:**\n");
+        result.push_str(&format!("```bash\narbor analyze {}\n```\n\n", functions.join(" ")));
+    }
+
+    result
+}
+
+#[derive(Debug, Serialize)]
+struct ExceptionOccurrence {
+    function: String,
+    file: PathBuf,
+    line: u32,
+    condition: Option<String>,
+}
+
+/// An `except` clause in the analyzed code that catches a given exception.
+#[derive(Debug, Serialize)]
+pub struct HandlerLocation {
+    pub function_id: String,
+    pub file: PathBuf,
+    pub line: u32,
+    pub handler_body: String,
+}
+
+fn exception_occurrences(db: &ArborDatabase, exc_type: &str) -> Vec<ExceptionOccurrence> {
+    let mut occurrences = Vec::new();
 
-                occurrences.push(Occurrence {
-                    function: fn_id.clone(),
+    for fn_id in db.functions_raising(exc_type) {
+        let Some(analysis) = db.get_function(fn_id) else {
+            continue;
+        };
+        for raise in &analysis.raises {
+            if raise.matches_type(exc_type) {
+                occurrences.push(ExceptionOccurrence {
+                    function: fn_id.to_string(),
                     file: raise.raise_location.file.clone(),
                     line: raise.raise_location.line,
                     condition: raise.condition.clone(),
@@ -768,10 +1671,334 @@ pub fn query_exception(exc_type: &str) -> Result<String, QueryError> {
         }
     }
 
+    occurrences
+}
+
+/// Handlers recorded in any analysis that would catch `exc_type`, one per
+/// `except` clause even when several analyses traced the same code.
+fn find_handlers(db: &ArborDatabase, exc_type: &str) -> Vec<HandlerLocation> {
+    let mut seen: std::collections::HashSet<(&PathBuf, u32)> = std::collections::HashSet::new();
+    let mut handlers = Vec::new();
+
+    for analysis in db.functions.values() {
+        for handler in &analysis.handlers {
+            if !handler.catches(exc_type) || !seen.insert((&handler.location.file, handler.location.line)) {
+                continue;
+            }
+            handlers.push(HandlerLocation {
+                function_id: handler
+                    .location
+                    .containing_function
+                    .clone()
+                    .unwrap_or_else(|| analysis.function_id.clone()),
+                file: handler.location.file.clone(),
+                line: handler.location.line,
+                handler_body: handler.body.clone(),
+            });
+        }
+    }
+
+    handlers.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    handlers
+}
+
+/// What an exception type given on the command line refers to.
+#[derive(Debug, PartialEq)]
+enum ExceptionMatch {
+    /// The name to look up: the query itself, or the one qualified type
+    /// containing it.
+    One(String),
+    /// Every qualified type containing the query, sorted.
+    Many(Vec<String>),
+}
+
+/// Resolve `query` to an exception type. An exact match on the short or
+/// qualified name always wins; otherwise, unless `exact`, any type whose
+/// short or qualified name contains `query` (ignoring case) matches.
+fn match_exception_type(db: &ArborDatabase, query: &str, exact: bool) -> ExceptionMatch {
+    if exact || db.functions_raising(query).next().is_some() {
+        return ExceptionMatch::One(query.to_string());
+    }
+
+    let needle = query.to_lowercase();
+    let mut matches: Vec<String> = db
+        .functions
+        .values()
+        .flat_map(|analysis| analysis.raises.iter())
+        .filter(|r| {
+            r.exception_type.to_lowercase().contains(&needle) || r.qualified_type.to_lowercase().contains(&needle)
+        })
+        .map(|r| r.qualified_type.clone())
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    match matches.len() {
+        0 => ExceptionMatch::One(query.to_string()),
+        1 => ExceptionMatch::One(matches.remove(0)),
+        _ => ExceptionMatch::Many(matches),
+    }
+}
+
+fn format_ambiguous_exception(query: &str, matches: &[String]) -> String {
+    let mut result = String::from("## Ambiguous Query — Multiple Matches\n\n");
+    result.push_str(&format!("`{}` matches {} exception types:\n\n", query, matches.len()));
+    for name in matches {
+        result.push_str(&format!("- `{}`\n", name));
+    }
+    result.push_str("\nUse the full qualified name to pick one, or `--exact` to match the name as typed.\n");
+    result
+}
+
+pub fn query_exception(
+    exc_type: &str,
+    exact: bool,
+    include_handlers: bool,
+    cross_ref: Option<&str>,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let exc_type = match match_exception_type(&db, exc_type, exact) {
+        ExceptionMatch::One(name) => name,
+        ExceptionMatch::Many(matches) => return Ok(format_ambiguous_exception(exc_type, &matches)),
+    };
+    let mut result = format_exception(&db, &exc_type, include_handlers);
+    if let Some(function) = cross_ref {
+        result.push_str("\n## Cross-Reference\n\n");
+        result.push_str(&format_cross_ref(&db, function, &exc_type));
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// One raise site in `--locations-only` output.
+#[derive(Debug, Serialize)]
+struct Location {
+    file: String,
+    line: u32,
+    function: String,
+    exception: String,
+}
+
+/// Raise sites as `file.py:42: ValueError (in myapp.api.process)` lines, or
+/// a flat JSON array, for shell pipelines and editors.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+struct LocationsOnlyOutput {
+    locations: Vec<Location>,
+}
+
+impl LocationsOnlyOutput {
+    /// One location per distinct raise site, sorted by file and line. Paths
+    /// are shown relative to `base` when under it. `raises` pairs each raise
+    /// with the analyzed function it was found from.
+    fn new<'a>(raises: impl Iterator<Item = (&'a str, &'a RaiseStatement)>, base: &Path) -> Self {
+        let mut locations: Vec<Location> = Vec::new();
+        let mut seen: std::collections::HashSet<(&Path, u32, &str)> = std::collections::HashSet::new();
+        for (analyzed, raise) in raises {
+            let line = raise.raise_location.line;
+            if !seen.insert((&raise.raise_location.file, line, &raise.exception_type)) {
+                continue;
+            }
+            let file = crate::core::types::relative_path(&raise.raise_location.file, base)
+                .display()
+                .to_string();
+            locations.push(Location {
+                file,
+                line,
+                function: raise
+                    .raise_location
+                    .containing_function
+                    .clone()
+                    .unwrap_or_else(|| analyzed.to_string()),
+                exception: raise.exception_type.clone(),
+            });
+        }
+        locations.sort_by(|a, b| (&a.file, a.line, &a.exception).cmp(&(&b.file, b.line, &b.exception)));
+        Self { locations }
+    }
+
+    fn to_text(&self) -> String {
+        self.locations
+            .iter()
+            .map(|l| format!("{}:{}: {} (in {})\n", l.file, l.line, l.exception, l.function))
+            .collect()
+    }
+
+    fn to_json(&self, options: JsonFormatOptions) -> Result<String, QueryError> {
+        to_json_string(self, options).map_err(|e| QueryError::InvalidQuery(e.to_string()))
+    }
+}
+
+fn current_dir() -> PathBuf {
+    std::env::current_dir().unwrap_or_default()
+}
+
+/// Every raise site of the types `query` matches across the database; an
+/// ambiguous query lists the sites of all of them.
+fn exception_locations(db: &ArborDatabase, query: &str, exact: bool) -> LocationsOnlyOutput {
+    let types = match match_exception_type(db, query, exact) {
+        ExceptionMatch::One(name) => vec![name],
+        ExceptionMatch::Many(matches) => matches,
+    };
+    let raises = types.iter().flat_map(|exc_type| {
+        db.functions_raising(exc_type)
+            .filter_map(|id| db.get_function(id))
+            .flat_map(move |analysis| {
+                analysis
+                    .raises
+                    .iter()
+                    .filter(move |r| r.matches_type(exc_type))
+                    .map(move |r| (analysis.function_id.as_str(), r))
+            })
+    });
+    LocationsOnlyOutput::new(raises, &current_dir())
+}
+
+pub fn query_exception_locations(exc_type: &str, exact: bool) -> Result<String, QueryError> {
+    let db = load_database()?;
+    Ok(exception_locations(&db, exc_type, exact).to_text())
+}
+
+pub fn query_exception_locations_json(
+    exc_type: &str,
+    exact: bool,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    exception_locations(&db, exc_type, exact).to_json(options)
+}
+
+pub fn query_catchers(exc_type: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    Ok(format_catchers(exc_type, &catchers(&db, exc_type)))
+}
+
+pub fn query_catchers_json(
+    exc_type: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    to_json_string(&catchers(&db, exc_type), options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+/// `except` clauses naming `exc_type` across all analyzed functions, matched
+/// by short name, so `ConnectionError` finds `except requests.ConnectionError`.
+/// A clause traced from several analyses is listed once.
+fn catchers<'a>(db: &'a ArborDatabase, exc_type: &str) -> Vec<&'a CatchClause> {
+    let short = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+    let wanted = short(exc_type);
+
+    let mut clauses: Vec<&CatchClause> = db
+        .functions
+        .values()
+        .flat_map(|analysis| &analysis.catches)
+        .filter(|(caught, _)| short(caught) == wanted)
+        .flat_map(|(_, clauses)| clauses)
+        .collect();
+    clauses.sort_by(|a, b| {
+        a.location
+            .containing_function
+            .cmp(&b.location.containing_function)
+            .then(a.location.file.cmp(&b.location.file))
+            .then(a.location.line.cmp(&b.location.line))
+            .then(a.caught_type.cmp(&b.caught_type))
+    });
+    clauses.dedup_by(|a, b| a.location == b.location && a.caught_type == b.caught_type);
+    clauses
+}
+
+fn format_catchers(exc_type: &str, clauses: &[&CatchClause]) -> String {
+    let mut result = format!("# Catchers: `{}`\n\n", exc_type);
+    result.push_str(&format!("**Handlers:** {}\n\n", clauses.len()));
+
+    if clauses.is_empty() {
+        result.push_str(&format!(
+            "No analyzed function catches `{}`. For where it is raised: `arbor query exception {}`\n",
+            exc_type, exc_type
+        ));
+        return result;
+    }
+
+    result.push_str("| Function | Location | Catches | Re-raises |\n");
+    result.push_str("|----------|----------|---------|-----------|\n");
+    for clause in clauses {
+        let reraises = match clause.strategy {
+            HandlerStrategy::Reraise => "yes".to_string(),
+            HandlerStrategy::Raises(ref replacement) => format!("as `{}`", replacement),
+            _ => "no".to_string(),
+        };
+        result.push_str(&format!(
+            "| `{}` | `{}` | `{}` | {} |\n",
+            clause.location.containing_function.as_deref().unwrap_or("unknown"),
+            clause.location.to_string_short(),
+            clause.caught_type,
+            reraises
+        ));
+    }
+
+    result
+}
+
+/// Every raise site reachable from `function`.
+fn function_raise_locations(analysis: &FunctionAnalysis) -> LocationsOnlyOutput {
+    let raises = analysis.raises.iter().map(|r| (analysis.function_id.as_str(), r));
+    LocationsOnlyOutput::new(raises, &current_dir())
+}
+
+pub fn query_exceptions_locations(function: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+    Ok(function_raise_locations(analysis).to_text())
+}
+
+pub fn query_exceptions_locations_json(
+    function: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+    function_raise_locations(analysis).to_json(options)
+}
+
+/// `✓ ... catches ... at file:line` or `✗ ... does not handle ...`.
+fn format_cross_ref(db: &ArborDatabase, function: &str, exc_type: &str) -> String {
+    match does_function_handle(db, function, exc_type) {
+        Some(location) => format!(
+            "✓ `{}` catches `{}` at {}:{}",
+            function,
+            exc_type,
+            location.file.file_name().unwrap_or_default().to_string_lossy(),
+            location.line
+        ),
+        None => format!("✗ `{}` does not handle `{}`", function, exc_type),
+    }
+}
+
+fn format_exception(db: &ArborDatabase, exc_type: &str, include_handlers: bool) -> String {
+    let occurrences = exception_occurrences(db, exc_type);
+
     if occurrences.is_empty() {
-        return Ok(format!("Exception `{}` not found in analyzed functions.", exc_type));
+        return format!("Exception `{}` not found in analyzed functions.", exc_type);
     }
 
+    let (definition_loc, qualified_name) = db
+        .functions_raising(exc_type)
+        .filter_map(|id| db.get_function(id))
+        .flat_map(|analysis| analysis.raises.iter())
+        .find(|r| r.matches_type(exc_type))
+        .map(|r| {
+            (
+                r.definition_location.as_ref().map(|l| l.to_string_short()),
+                Some(r.qualified_type.clone()),
+            )
+        })
+        .unwrap_or((None, None));
+
     let strategy = RecoveryStrategy::from_exception_type(exc_type);
     let retryable = matches!(strategy, RecoveryStrategy::Retry);
 
@@ -859,12 +2086,83 @@ pub fn query_exception(exc_type: &str) -> Result<String, QueryError> {
         ));
     }
 
-    Ok(result)
+    if include_handlers {
+        result.push_str("\n## Where It's Caught\n\n");
+        let handlers = find_handlers(db, exc_type);
+        if handlers.is_empty() {
+            result.push_str("No handler in the analyzed code catches this exception.\n");
+        }
+        for handler in &handlers {
+            result.push_str(&format!(
+                "### `{}` at `{}:{}`\n\n",
+                handler.function_id,
+                handler.file.display(),
+                handler.line
+            ));
+            result.push_str(&format!("```python\n{}\n```\n\n", handler.handler_body));
+        }
+    }
+
+    result
 }
 
-pub fn query_package(name: &str) -> Result<String, QueryError> {
-    let db = load_database()?;
+/// One analyzed function in `arbor query package`.
+#[derive(Debug, Serialize)]
+struct PackageFunction {
+    function_id: String,
+    exception_count: usize,
+    none_count: usize,
+    call_depth: usize,
+    risk_level: RiskLevel,
+    risk_score: f64,
+}
+
+fn in_package(function_id: &str, name: &str) -> bool {
+    function_id == name
+        || function_id.starts_with(&format!("{}.", name))
+        || function_id.contains(&format!(".{}.", name))
+}
+
+/// The functions in package `name`, sorted by `sort_by`.
+fn package_functions(
+    db: &ArborDatabase,
+    name: &str,
+    sort_by: SortField,
+    weights: &NoneSourceWeights,
+) -> Vec<PackageFunction> {
+    let mut functions: Vec<_> = db.functions.iter().filter(|(id, _)| in_package(id, name)).collect();
+    sort_by.sort(&mut functions, weights);
+    functions
+        .into_iter()
+        .map(|(id, analysis)| PackageFunction {
+            function_id: id.clone(),
+            exception_count: analysis.exception_count(),
+            none_count: analysis.none_source_count(),
+            call_depth: analysis.call_depth,
+            risk_level: analysis.risk_level(),
+            risk_score: analysis.risk_score_with(weights),
+        })
+        .collect()
+}
+
+/// `--top-risk N` without `--sort-by` ranks by risk; otherwise by name.
+pub fn package_sort_field(sort_by: Option<SortField>, top: Option<usize>) -> SortField {
+    sort_by.unwrap_or(if top.is_some() { SortField::Risk } else { SortField::Name })
+}
+
+pub fn query_package(name: &str, sort_by: SortField, top: Option<usize>) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let config = ArborConfig::load_or_default();
+    Ok(format_package(&db, name, sort_by, top, &config.risk.none_source_weights))
+}
 
+fn format_package(
+    db: &ArborDatabase,
+    name: &str,
+    sort_by: SortField,
+    top: Option<usize>,
+    weights: &NoneSourceWeights,
+) -> String {
     struct ExceptionInfo {
         exception_type: String,
         qualified_type: String,
@@ -874,16 +2172,10 @@ pub fn query_package(name: &str) -> Result<String, QueryError> {
 
     let mut exception_map: std::collections::HashMap<String, ExceptionInfo> =
         std::collections::HashMap::new();
-    let mut functions: Vec<(String, usize, usize)> = Vec::new(); // (name, exceptions, none_sources)
+    let functions = package_functions(db, name, sort_by, weights);
 
     for (fn_id, analysis) in &db.functions {
-        if fn_id.starts_with(name) || fn_id.contains(&format!(".{}.", name)) {
-            functions.push((
-                fn_id.clone(),
-                analysis.exception_count(),
-                analysis.none_source_count(),
-            ));
-
+        if in_package(fn_id, name) {
             for raise in &analysis.raises {
                 let entry = exception_map
                     .entry(raise.exception_type.clone())
@@ -905,16 +2197,16 @@ pub fn query_package(name: &str) -> Result<String, QueryError> {
     }
 
     if functions.is_empty() {
-        return Ok(format!(
+        return format!(
             "Package `{}` not found in analyzed functions.\n\nTry `arbor query search {}` to find related functions.",
             name, name
-        ));
+        );
     }
 
     let mut result = format!("# Package Analysis: `{}`\n\n", name);
 
-    let total_exceptions: usize = functions.iter().map(|(_, e, _)| e).sum();
-    let total_none: usize = functions.iter().map(|(_, _, n)| n).sum();
+    let total_exceptions: usize = functions.iter().map(|f| f.exception_count).sum();
+    let total_none: usize = functions.iter().map(|f| f.none_count).sum();
 
     result.push_str("## Summary\n\n");
     result.push_str("| Metric | Count |\n");
@@ -931,7 +2223,7 @@ pub fn query_package(name: &str) -> Result<String, QueryError> {
         result.push_str("|-----------|----------------|------------|-------------|----------|\n");
 
         let mut exceptions: Vec<_> = exception_map.values().collect();
-        exceptions.sort_by(|a, b| b.occurrences.cmp(&a.occurrences));
+        exceptions.sort_by_key(|e| std::cmp::Reverse(e.occurrences));
 
         for exc in exceptions {
             let strategy = RecoveryStrategy::from_exception_type(&exc.exception_type);
@@ -948,23 +2240,26 @@ pub fn query_package(name: &str) -> Result<String, QueryError> {
     }
 
     result.push_str("## Functions\n\n");
+    let shown = top.unwrap_or(functions.len()).min(functions.len());
+    if shown < functions.len() {
+        result.push_str(&format!(
+            "Showing top {} of {} functions by {}\n\n",
+            shown,
+            functions.len(),
+            sort_by.as_str()
+        ));
+    }
     result.push_str("| Function | Exceptions | None Sources | Risk |\n");
     result.push_str("|----------|------------|--------------|------|\n");
 
-    functions.sort_by(|a, b| a.0.cmp(&b.0));
-
-    for (fn_id, exc_count, none_count) in &functions {
-        let analysis = db.get_function(fn_id);
-        let risk = analysis
-            .map(|a| a.risk_level())
-            .unwrap_or(crate::core::types::RiskLevel::Low);
+    for function in &functions[..shown] {
         result.push_str(&format!(
             "| `{}` | {} | {} | {} {} |\n",
-            fn_id,
-            exc_count,
-            none_count,
-            risk.emoji(),
-            risk.as_str()
+            function.function_id,
+            function.exception_count,
+            function.none_count,
+            function.risk_level.emoji(),
+            function.risk_level.as_str()
         ));
     }
     result.push('\n');
@@ -993,60 +2288,384 @@ pub fn query_package(name: &str) -> Result<String, QueryError> {
         result.push_str("No grouping suggestions available for this package.\n");
     }
 
-    Ok(result)
+    result
+}
+
+#[derive(Serialize)]
+struct PackageJson<'a> {
+    package: &'a str,
+    sort_by: &'static str,
+    shown: usize,
+    total: usize,
+    functions: Vec<PackageFunction>,
+}
+
+pub fn query_package_json(
+    name: &str,
+    sort_by: SortField,
+    top: Option<usize>,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let config = ArborConfig::load_or_default();
+    let mut functions = package_functions(&db, name, sort_by, &config.risk.none_source_weights);
+    let total = functions.len();
+    if let Some(top) = top {
+        functions.truncate(top);
+    }
+
+    to_json_string(&PackageJson {
+        package: name,
+        sort_by: sort_by.as_str(),
+        shown: functions.len(),
+        total,
+        functions,
+    }, options)
+    .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+/// The window for `--since`: relative (`24h`, `7d`, `1w`) or an ISO 8601
+/// datetime, ending now.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Period {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// `last 24h` or `since 2024-05-01 09:00:00`.
+    pub label: String,
+}
+
+impl Period {
+    pub fn parse(since: &str) -> Result<Self, QueryError> {
+        Self::parse_at(since, Utc::now())
+    }
+
+    fn parse_at(since: &str, now: DateTime<Utc>) -> Result<Self, QueryError> {
+        let since = since.trim();
+        if let Some(duration) = parse_relative_duration(since) {
+            return Ok(Self {
+                start: now - duration,
+                end: now,
+                label: format!("last {}", since),
+            });
+        }
+
+        let start = DateTime::parse_from_rfc3339(since)
+            .map(|dt| dt.with_timezone(&Utc))
+            .or_else(|_| NaiveDateTime::parse_from_str(since, "%Y-%m-%dT%H:%M:%S").map(|dt| dt.and_utc()))
+            .or_else(|_| {
+                NaiveDate::parse_from_str(since, "%Y-%m-%d")
+                    .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+            })
+            .map_err(|_| {
+                QueryError::InvalidQuery(format!(
+                    "invalid --since '{}' (expected 24h, 7d, 1w or YYYY-MM-DDTHH:MM:SS)",
+                    since
+                ))
+            })?;
+        Ok(Self {
+            start,
+            end: now,
+            label: format!("since {}", start.format("%Y-%m-%d %H:%M:%S")),
+        })
+    }
+}
+
+/// `24h`, `7d` or `1w`.
+fn parse_relative_duration(since: &str) -> Option<chrono::Duration> {
+    let split = since.len().checked_sub(1)?;
+    let (count, unit) = (since.get(..split)?, since.get(split..)?);
+    let count: i64 = count.parse().ok().filter(|n| *n >= 0)?;
+    match unit {
+        "h" => chrono::Duration::try_hours(count),
+        "d" => chrono::Duration::try_days(count),
+        "w" => chrono::Duration::try_weeks(count),
+        _ => None,
+    }
+}
+
+/// Ordering for `arbor query list` and `arbor query package`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortField {
+    Risk,
+    Exceptions,
+    None,
+    Depth,
+    #[default]
+    Name,
+}
+
+impl SortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::Risk => "risk",
+            SortField::Exceptions => "exceptions",
+            SortField::None => "none",
+            SortField::Depth => "depth",
+            SortField::Name => "name",
+        }
+    }
+
+    fn sort(&self, functions: &mut [(&String, &FunctionAnalysis)], weights: &NoneSourceWeights) {
+        match self {
+            SortField::Risk => functions.sort_by(|a, b| {
+                b.1.risk_score_with(weights)
+                    .total_cmp(&a.1.risk_score_with(weights))
+                    .then_with(|| a.0.cmp(b.0))
+            }),
+            SortField::Exceptions => functions.sort_by(|a, b| {
+                b.1.exception_count().cmp(&a.1.exception_count()).then_with(|| a.0.cmp(b.0))
+            }),
+            SortField::None => functions.sort_by(|a, b| {
+                b.1.none_source_count().cmp(&a.1.none_source_count()).then_with(|| a.0.cmp(b.0))
+            }),
+            SortField::Depth => functions.sort_by(|a, b| {
+                b.1.call_depth.cmp(&a.1.call_depth).then_with(|| a.0.cmp(b.0))
+            }),
+            SortField::Name => functions.sort_by(|a, b| a.0.cmp(b.0)),
+        }
+    }
+}
+
+impl std::str::FromStr for SortField {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "risk" => Ok(SortField::Risk),
+            "exceptions" => Ok(SortField::Exceptions),
+            "none" => Ok(SortField::None),
+            "depth" => Ok(SortField::Depth),
+            "name" => Ok(SortField::Name),
+            other => Err(QueryError::InvalidQuery(format!(
+                "unknown sort field '{}' (expected risk, exceptions, none, depth or name)",
+                other
+            ))),
+        }
+    }
+}
+
+fn list_functions<'a>(
+    db: &'a ArborDatabase,
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    weights: &NoneSourceWeights,
+) -> Vec<(&'a String, &'a FunctionAnalysis)> {
+    let mut functions: Vec<_> = db
+        .functions
+        .iter()
+        .filter(|(_, analysis)| filter_risk.map_or(true, |level| analysis.risk_level() == level))
+        .collect();
+    sort_by.sort(&mut functions, weights);
+    functions
+}
+
+pub fn query_uncaught(package: &str) -> Result<String, QueryError> {
+    let db = load_database()?;
+    Ok(format_uncaught(package, &uncaught_exceptions(&db, package)))
 }
 
-pub fn query_list() -> Result<String, QueryError> {
+pub fn query_uncaught_json(
+    package: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
     let db = load_database()?;
+    to_json_string(&uncaught_exceptions(&db, package), options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct UncaughtException {
+    pub exception_type: String,
+    /// Raise sites of the type that escape some public function, one per
+    /// site however many functions reach it.
+    pub occurrence_count: usize,
+    /// The public functions it escapes, sorted.
+    pub functions: Vec<String>,
+}
+
+/// Whether `function_id` is part of `package`'s public surface: no module,
+/// class or function on the way down is `_private`. Dunder methods such as
+/// `__init__` are public.
+fn is_public_api(function_id: &str, package: &str) -> bool {
+    let inner = function_id
+        .strip_prefix(package)
+        .or_else(|| function_id.split_once(&format!(".{}", package)).map(|(_, rest)| rest))
+        .unwrap_or(function_id);
+    inner
+        .split('.')
+        .all(|part| !part.starts_with('_') || (part.starts_with("__") && part.ends_with("__")))
+}
+
+/// Exception types raised out of `package`'s public functions that nothing in
+/// the traced call tree catches, most frequent first.
+fn uncaught_exceptions(db: &ArborDatabase, package: &str) -> Vec<UncaughtException> {
+    let mut by_type: std::collections::BTreeMap<&str, UncaughtException> = std::collections::BTreeMap::new();
+    let mut sites: std::collections::HashSet<(&str, &Path, u32)> = std::collections::HashSet::new();
+    for (id, analysis) in &db.functions {
+        if !in_package(id, package) || !is_public_api(id, package) {
+            continue;
+        }
+        for raise in &analysis.raises {
+            if raise.suppressed_by.is_some() || raise.exception_type == "(re-raise)" {
+                continue;
+            }
+            let entry = by_type
+                .entry(&raise.exception_type)
+                .or_insert_with(|| UncaughtException {
+                    exception_type: raise.exception_type.clone(),
+                    occurrence_count: 0,
+                    functions: Vec::new(),
+                });
+            let site = (raise.exception_type.as_str(), raise.raise_location.file.as_path(), raise.raise_location.line);
+            if sites.insert(site) {
+                entry.occurrence_count += 1;
+            }
+            if !entry.functions.contains(id) {
+                entry.functions.push(id.clone());
+            }
+        }
+    }
+
+    let mut uncaught: Vec<UncaughtException> = by_type.into_values().collect();
+    for entry in &mut uncaught {
+        entry.functions.sort();
+    }
+    uncaught.sort_by_key(|u| std::cmp::Reverse(u.occurrence_count));
+    uncaught
+}
+
+fn format_uncaught(package: &str, uncaught: &[UncaughtException]) -> String {
+    let mut result = format!("# Uncaught Exceptions: `{}`\n\n", package);
+    result.push_str(&format!("**Exception Types:** {}\n\n", uncaught.len()));
+
+    if uncaught.is_empty() {
+        result.push_str("No exceptions escape the package's public functions uncaught.\n");
+        return result;
+    }
+
+    result.push_str("| Exception | Occurrences | Public Functions | Example |\n");
+    result.push_str("|-----------|-------------|------------------|---------|\n");
+    for entry in uncaught {
+        result.push_str(&format!(
+            "| `{}` | {} | {} | `{}` |\n",
+            entry.exception_type,
+            entry.occurrence_count,
+            entry.functions.len(),
+            entry.functions[0]
+        ));
+    }
+
+    result.push_str("\nTo see how one reaches callers: `arbor query chain <function> <exception>`\n");
+    result
+}
+
+/// `db` with only the functions analyzed within `period`, if one is given.
+fn load_database_for(period: Option<&Period>) -> Result<ArborDatabase, QueryError> {
+    let mut db = load_database()?;
+    if let Some(period) = period {
+        db.retain_analyzed_since(period.start);
+    }
+    Ok(db)
+}
 
+pub fn query_list(
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    period: Option<&Period>,
+) -> Result<String, QueryError> {
+    let db = load_database_for(period)?;
+    let config = ArborConfig::load_or_default();
+    Ok(format_list(&db, sort_by, filter_risk, period, &config.risk.none_source_weights))
+}
+
+fn format_list(
+    db: &ArborDatabase,
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    period: Option<&Period>,
+    weights: &NoneSourceWeights,
+) -> String {
     if db.functions.is_empty() {
-        return Ok("No functions analyzed. Run 'arbor analyze <function>' first.".to_string());
+        return match period {
+            Some(period) => format!("No functions analyzed in the period ({}).", period.label),
+            None => "No functions analyzed. Run 'arbor analyze <function>' first.".to_string(),
+        };
     }
 
-    let mut result = format!("# Analyzed Functions\n\n");
+    let functions = list_functions(db, sort_by, filter_risk, weights);
+
+    let mut result = "# Analyzed Functions\n\n".to_string();
     result.push_str(&format!("**Database:** `{}/{}`\n", paths::ARBOR_DIR, paths::DATABASE_FILE));
-    result.push_str(&format!("**Total Functions:** {}\n", db.functions.len()));
+    if filter_risk.is_some() {
+        result.push_str(&format!(
+            "**Total Functions:** {} of {}\n",
+            functions.len(),
+            db.functions.len()
+        ));
+    } else {
+        result.push_str(&format!("**Total Functions:** {}\n", db.functions.len()));
+    }
+    result.push_str(&format!("**Sorted By:** {}\n", sort_by.as_str()));
+    if let Some(period) = period {
+        result.push_str(&format!("**Period:** {}\n", period.label));
+    }
+    if let Some(level) = filter_risk {
+        result.push_str(&format!("**Risk Filter:** {} {}\n", level.emoji(), level.as_str()));
+    }
     result.push_str(&format!(
         "**Last Updated:** {}\n\n",
         db.updated_at.format("%Y-%m-%d %H:%M:%S")
     ));
 
-    let mut packages: std::collections::HashMap<String, Vec<(&String, &crate::core::types::FunctionAnalysis)>> =
-        std::collections::HashMap::new();
-
-    for (fn_id, analysis) in &db.functions {
-        let package = fn_id
-            .split('.')
-            .next()
-            .unwrap_or("unknown")
-            .to_string();
-        packages.entry(package).or_default().push((fn_id, analysis));
+    if functions.is_empty() {
+        result.push_str("No functions match the risk filter.\n");
+        return result;
     }
 
-    let mut package_names: Vec<_> = packages.keys().collect();
-    package_names.sort();
+    if sort_by == SortField::Name {
+        let mut packages: std::collections::BTreeMap<&str, Vec<(&String, &FunctionAnalysis)>> =
+            std::collections::BTreeMap::new();
+
+        for (fn_id, analysis) in functions {
+            let package = fn_id.split('.').next().unwrap_or("unknown");
+            packages.entry(package).or_default().push((fn_id, analysis));
+        }
 
-    result.push_str("## By Package\n\n");
+        result.push_str("## By Package\n\n");
 
-    for package in package_names {
-        let functions = packages.get(package).unwrap();
-        result.push_str(&format!("### {} ({} functions)\n\n", package, functions.len()));
-        result.push_str("| Function | Exceptions | None Sources | Risk |\n");
-        result.push_str("|----------|------------|--------------|------|\n");
+        for (package, functions) in packages {
+            result.push_str(&format!("### {} ({} functions)\n\n", package, functions.len()));
+            result.push_str("| Function | Exceptions | None Sources | Risk |\n");
+            result.push_str("|----------|------------|--------------|------|\n");
 
-        let mut sorted_functions = functions.clone();
-        sorted_functions.sort_by_key(|(id, _)| id.as_str());
+            for (fn_id, analysis) in functions {
+                let risk = analysis.risk_level();
+                let short_name = fn_id
+                    .strip_prefix(&format!("{}.", package))
+                    .unwrap_or(fn_id);
+                result.push_str(&format!(
+                    "| `{}` | {} | {} | {} {} |\n",
+                    short_name,
+                    analysis.exception_count(),
+                    analysis.none_source_count(),
+                    risk.emoji(),
+                    risk.as_str()
+                ));
+            }
+            result.push('\n');
+        }
+    } else {
+        result.push_str("| Function | Exceptions | None Sources | Depth | Risk |\n");
+        result.push_str("|----------|------------|--------------|-------|------|\n");
 
-        for (fn_id, analysis) in sorted_functions {
+        for (fn_id, analysis) in functions {
             let risk = analysis.risk_level();
-            let short_name = fn_id
-                .strip_prefix(&format!("{}.", package))
-                .unwrap_or(fn_id);
             result.push_str(&format!(
-                "| `{}` | {} | {} | {} {} |\n",
-                short_name,
+                "| `{}` | {} | {} | {} | {} {} |\n",
+                fn_id,
                 analysis.exception_count(),
                 analysis.none_source_count(),
+                analysis.call_depth,
                 risk.emoji(),
                 risk.as_str()
             ));
@@ -1063,11 +2682,221 @@ pub fn query_list() -> Result<String, QueryError> {
     result.push_str("arbor query function <function>\n");
     result.push_str("```\n");
 
-    Ok(result)
+    result
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExceptionStats {
+    pub exception_type: String,
+    pub occurrence_count: usize,
+    pub function_count: usize,
+    pub most_common_file: String,
+    pub recovery_strategy: String,
+    pub group: Option<String>,
+    /// Share of occurrences found in the single function with the most of them
+    /// (1.0 = all in one function).
+    pub concentration: f64,
+}
+
+fn exception_stats(db: &ArborDatabase, min_occurrences: usize) -> Vec<ExceptionStats> {
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Default)]
+    struct Tally<'a> {
+        occurrences: usize,
+        per_function: HashMap<&'a str, usize>,
+        per_file: BTreeMap<PathBuf, usize>,
+    }
+
+    let mut tallies: HashMap<&str, Tally> = HashMap::new();
+    for (fn_id, analysis) in &db.functions {
+        for raise in &analysis.raises {
+            if raise.exception_type == "(re-raise)" {
+                continue;
+            }
+            let tally = tallies.entry(&raise.exception_type).or_default();
+            tally.occurrences += 1;
+            *tally.per_function.entry(fn_id).or_default() += 1;
+            *tally.per_file.entry(raise.raise_location.file.clone()).or_default() += 1;
+        }
+    }
+
+    let mut group_names: Vec<&String> = db.grouping_suggestions.keys().collect();
+    group_names.sort();
+
+    let mut stats: Vec<ExceptionStats> = tallies
+        .into_iter()
+        .filter(|(_, tally)| tally.occurrences >= min_occurrences)
+        .map(|(exc_type, tally)| {
+            // BTreeMap order makes ties resolve to the first path alphabetically
+            let most_common_file = tally
+                .per_file
+                .iter()
+                .rev()
+                .max_by_key(|(_, count)| **count)
+                .map(|(file, _)| file.display().to_string())
+                .unwrap_or_default();
+            let max_in_function = tally.per_function.values().copied().max().unwrap_or(0);
+
+            ExceptionStats {
+                exception_type: exc_type.to_string(),
+                occurrence_count: tally.occurrences,
+                function_count: tally.per_function.len(),
+                most_common_file,
+                recovery_strategy: RecoveryStrategy::from_exception_type(exc_type).as_str().to_string(),
+                group: group_names
+                    .iter()
+                    .find(|name| db.grouping_suggestions[**name].exceptions.iter().any(|e| e == exc_type))
+                    .map(|name| name.to_string()),
+                concentration: max_in_function as f64 / tally.occurrences as f64,
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| {
+        b.occurrence_count
+            .cmp(&a.occurrence_count)
+            .then_with(|| a.exception_type.cmp(&b.exception_type))
+    });
+    stats
+}
+
+fn concentration_bar(concentration: f64) -> String {
+    let filled = ((concentration * 5.0).round() as usize).clamp(1, 5);
+    format!("{}{}", "█".repeat(filled), "░".repeat(5 - filled))
+}
+
+pub fn query_exception_stats(min_occurrences: usize) -> Result<String, QueryError> {
+    let db = load_database()?;
+    Ok(format_exception_stats(&exception_stats(&db, min_occurrences), min_occurrences))
+}
+
+fn format_exception_stats(stats: &[ExceptionStats], min_occurrences: usize) -> String {
+    let mut result = "# Exception Statistics\n\n".to_string();
+    result.push_str(&format!("**Exception Types:** {}\n", stats.len()));
+    if min_occurrences > 1 {
+        result.push_str(&format!("**Min Occurrences:** {}\n", min_occurrences));
+    }
+    result.push('\n');
+
+    if stats.is_empty() {
+        result.push_str("No exceptions recorded.\n");
+        return result;
+    }
+
+    result.push_str("| Exception | Occurrences | Functions | Most Common File | Recovery | Group | Concentration |\n");
+    result.push_str("|-----------|-------------|-----------|------------------|----------|-------|---------------|\n");
+
+    for stat in stats {
+        result.push_str(&format!(
+            "| `{}` | {} | {} | `{}` | {} | {} | {} |\n",
+            stat.exception_type,
+            stat.occurrence_count,
+            stat.function_count,
+            stat.most_common_file,
+            stat.recovery_strategy,
+            stat.group.as_deref().unwrap_or("-"),
+            concentration_bar(stat.concentration)
+        ));
+    }
+
+    result.push_str("\nConcentration: █████ = all occurrences in one function, █░░░░ = spread across many.\n");
+    result
 }
 
-pub fn query_search(query: &str) -> Result<String, QueryError> {
+/// With `exception`, searches analyzed functions by what they raise instead,
+/// narrowed by `query` on the function name and by `risk` when given.
+pub fn query_search(
+    query: Option<&str>,
+    exception: Option<&str>,
+    risk: Option<RiskLevel>,
+    limit: usize,
+) -> Result<String, QueryError> {
     let db = load_database()?;
+    Ok(match exception {
+        Some(pattern) => format_exception_search(&db, pattern, query, risk, limit),
+        None => format_search(&db, query.unwrap_or_default(), limit),
+    })
+}
+
+/// Analyzed functions with at least one raise whose short or qualified type
+/// matches `pattern` (an exact name or `*` glob), paired with how many of
+/// their raises match, most matches first.
+fn functions_raising_matching<'a>(
+    db: &'a ArborDatabase,
+    pattern: &str,
+    name_filter: Option<&str>,
+    risk: Option<RiskLevel>,
+) -> Vec<(&'a FunctionAnalysis, usize)> {
+    let name_filter = name_filter.map(str::to_lowercase);
+    let mut found: Vec<(&FunctionAnalysis, usize)> = db
+        .functions
+        .values()
+        .filter(|analysis| {
+            name_filter
+                .as_deref()
+                .map_or(true, |name| analysis.function_id.to_lowercase().contains(name))
+        })
+        .filter(|analysis| risk.map_or(true, |risk| analysis.risk_level() == risk))
+        .map(|analysis| {
+            let count = analysis
+                .raises
+                .iter()
+                .filter(|r| {
+                    matches_name_pattern(pattern, &r.exception_type)
+                        || matches_name_pattern(pattern, &r.qualified_type)
+                })
+                .count();
+            (analysis, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    found.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.function_id.cmp(&b.0.function_id)));
+    found
+}
+
+fn format_exception_search(
+    db: &ArborDatabase,
+    pattern: &str,
+    name_filter: Option<&str>,
+    risk: Option<RiskLevel>,
+    limit: usize,
+) -> String {
+    let found = functions_raising_matching(db, pattern, name_filter, risk);
+    if found.is_empty() {
+        return format!("No analyzed functions raise an exception matching '{}'\n", pattern);
+    }
+
+    let mut result = "# Search Results\n\n".to_string();
+    result.push_str(&format!("**Exception:** `{}`\n", pattern));
+    if let Some(name) = name_filter {
+        result.push_str(&format!("**Query:** `{}`\n", name));
+    }
+    if let Some(risk) = risk {
+        result.push_str(&format!("**Risk:** {}\n", risk.as_str()));
+    }
+    result.push_str(&format!("**Results:** {} functions\n\n", found.len()));
+
+    result.push_str("| Function | Matching Raises | Exceptions | Risk |\n");
+    result.push_str("|----------|-----------------|------------|------|\n");
+    for (analysis, count) in found.iter().take(limit) {
+        let risk = analysis.risk_level();
+        result.push_str(&format!(
+            "| `{}` | {} | {} | {} {} |\n",
+            analysis.function_id,
+            count,
+            analysis.exception_count(),
+            risk.emoji(),
+            risk.as_str()
+        ));
+    }
+    if found.len() > limit {
+        result.push_str(&format!("\n*... and {} more functions*\n", found.len() - limit));
+    }
+    result
+}
+
+fn format_search(db: &ArborDatabase, query: &str, limit: usize) -> String {
     let query_lower = query.to_lowercase();
 
     struct SearchMatch {
@@ -1094,39 +2923,43 @@ pub fn query_search(query: &str) -> Result<String, QueryError> {
         }
     }
 
-    for (symbol, loc) in &db.symbol_index.symbols {
-        if symbol.to_lowercase().contains(&query_lower) {
-            if !matches.iter().any(|m| m.name == *symbol) {
-                matches.push(SearchMatch {
-                    name: symbol.clone(),
-                    is_analyzed: false,
-                    exceptions: 0,
-                    none_sources: 0,
-                    risk: None,
-                    location: Some(format!("{}:{}", loc.file_path.display(), loc.line_start)),
-                });
-            }
+    // Prefix matches first, then the rest, as in `SymbolIndex::search`
+    matches.sort_by(|a, b| {
+        let a_prefix = a.name.to_lowercase().starts_with(&query_lower);
+        let b_prefix = b.name.to_lowercase().starts_with(&query_lower);
+        b_prefix.cmp(&a_prefix).then_with(|| a.name.cmp(&b.name))
+    });
+
+    for (symbol, loc) in db.symbol_index.search(query) {
+        if !db.functions.contains_key(symbol) {
+            matches.push(SearchMatch {
+                name: symbol.to_string(),
+                is_analyzed: false,
+                exceptions: 0,
+                none_sources: 0,
+                risk: None,
+                location: Some(format!("{}:{}", loc.file_path.display(), loc.line_start)),
+            });
         }
     }
 
     let mut exception_matches: Vec<String> = Vec::new();
     for analysis in db.functions.values() {
         for raise in &analysis.raises {
-            if raise.exception_type.to_lowercase().contains(&query_lower)
-                || raise.qualified_type.to_lowercase().contains(&query_lower)
+            if (raise.exception_type.to_lowercase().contains(&query_lower)
+                || raise.qualified_type.to_lowercase().contains(&query_lower))
+                && !exception_matches.contains(&raise.exception_type)
             {
-                if !exception_matches.contains(&raise.exception_type) {
-                    exception_matches.push(raise.exception_type.clone());
-                }
+                exception_matches.push(raise.exception_type.clone());
             }
         }
     }
 
     if matches.is_empty() && exception_matches.is_empty() {
-        return Ok(format!("No matches for '{}'\n\nTry a different search term.", query));
+        return format!("No matches for '{}'\n\nTry a different search term.", query);
     }
 
-    let mut result = format!("# Search Results\n\n");
+    let mut result = "# Search Results\n\n".to_string();
     result.push_str(&format!("**Query:** `{}`\n", query));
     result.push_str(&format!(
         "**Results:** {} functions, {} exceptions\n\n",
@@ -1145,7 +2978,7 @@ pub fn query_search(query: &str) -> Result<String, QueryError> {
             result.push_str("| Function | Exceptions | None | Risk |\n");
             result.push_str("|----------|------------|------|------|\n");
 
-            for m in analyzed.iter().take(25) {
+            for m in analyzed.iter().take(limit) {
                 let risk = m.risk.as_ref().unwrap();
                 result.push_str(&format!(
                     "| `{}` | {} | {} | {} {} |\n",
@@ -1157,8 +2990,8 @@ pub fn query_search(query: &str) -> Result<String, QueryError> {
                 ));
             }
 
-            if analyzed.len() > 25 {
-                result.push_str(&format!("\n*... and {} more analyzed functions*\n", analyzed.len() - 25));
+            if analyzed.len() > limit {
+                result.push_str(&format!("\n*... and {} more analyzed functions*\n", analyzed.len() - limit));
             }
             result.push('\n');
         }
@@ -1166,7 +2999,7 @@ pub fn query_search(query: &str) -> Result<String, QueryError> {
         if !unanalyzed.is_empty() {
             result.push_str("### Not Analyzed\n\n");
 
-            for m in unanalyzed.iter().take(25) {
+            for m in unanalyzed.iter().take(limit) {
                 result.push_str(&format!(
                     "- `{}` - {}\n",
                     m.name,
@@ -1174,8 +3007,8 @@ pub fn query_search(query: &str) -> Result<String, QueryError> {
                 ));
             }
 
-            if unanalyzed.len() > 25 {
-                result.push_str(&format!("\n*... and {} more unanalyzed functions*\n", unanalyzed.len() - 25));
+            if unanalyzed.len() > limit {
+                result.push_str(&format!("\n*... and {} more unanalyzed functions*\n", unanalyzed.len() - limit));
             }
             result.push('\n');
         }
@@ -1199,11 +3032,30 @@ pub fn query_search(query: &str) -> Result<String, QueryError> {
     result.push_str("- Use `arbor query function <name>` for full analysis\n");
     result.push_str("- Use `arbor analyze <name>` to analyze unanalyzed functions\n");
 
-    Ok(result)
+    result
 }
 
-pub fn query_stats() -> Result<String, QueryError> {
-    let db = load_database()?;
+fn format_exception_distribution(distribution: &ExceptionDistribution) -> String {
+    let max = distribution.max_count();
+    let mut result = "## Exception Count Distribution\n\n```\n".to_string();
+    for (label, count) in distribution.buckets() {
+        let label = if label == "0" { "0 exceptions" } else { label };
+        result.push_str(&format!(
+            "{:<12} │ {} {} function{}\n",
+            label,
+            block_bar(count, max),
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+    result.push_str("```\n\n");
+    result
+}
+
+pub fn query_stats(trend: bool, period: Option<&Period>) -> Result<String, QueryError> {
+    use std::io::IsTerminal;
+
+    let db = load_database_for(period)?;
 
     let total_none: usize = db.functions.values().map(|a| a.none_source_count()).sum();
 
@@ -1223,12 +3075,7 @@ pub fn query_stats() -> Result<String, QueryError> {
         .filter(|a| a.risk_level() == crate::core::types::RiskLevel::Low)
         .count();
 
-    let mut unique_exceptions: std::collections::HashSet<&str> = std::collections::HashSet::new();
-    for analysis in db.functions.values() {
-        for raise in &analysis.raises {
-            unique_exceptions.insert(&raise.exception_type);
-        }
-    }
+    let unique_exceptions = &db.exception_index;
 
     let mut packages: std::collections::HashSet<&str> = std::collections::HashSet::new();
     for fn_id in db.functions.keys() {
@@ -1238,10 +3085,13 @@ pub fn query_stats() -> Result<String, QueryError> {
     }
 
     let mut exception_counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
-    for analysis in db.functions.values() {
-        for raise in &analysis.raises {
-            *exception_counts.entry(&raise.exception_type).or_insert(0) += 1;
-        }
+    for exc in db.exception_index.keys() {
+        let count = db
+            .functions_raising(exc)
+            .filter_map(|id| db.get_function(id))
+            .map(|a| a.raises.iter().filter(|r| r.matches_type(exc)).count())
+            .sum();
+        exception_counts.insert(exc, count);
     }
 
     let mut result = String::from("# Arbor Database Statistics\n\n");
@@ -1252,15 +3102,25 @@ pub fn query_stats() -> Result<String, QueryError> {
         db.created_at.format("%Y-%m-%d %H:%M:%S")
     ));
     result.push_str(&format!(
-        "**Updated:** {}\n\n",
+        "**Updated:** {}\n",
         db.updated_at.format("%Y-%m-%d %H:%M:%S")
     ));
+    if let Some(period) = period {
+        result.push_str(&format!("**Period:** {}\n", period.label));
+    }
+    result.push('\n');
 
     result.push_str("## Summary\n\n");
     result.push_str("| Metric | Count |\n");
     result.push_str("|--------|-------|\n");
     result.push_str(&format!("| Functions analyzed | {} |\n", db.function_count()));
     result.push_str(&format!("| Symbols indexed | {} |\n", db.symbol_count()));
+    if !db.symbol_index.skipped_large_files.is_empty() {
+        result.push_str(&format!(
+            "| Large files skipped | {} |\n",
+            db.symbol_index.skipped_large_files.len()
+        ));
+    }
     result.push_str(&format!("| Unique exceptions | {} |\n", unique_exceptions.len()));
     result.push_str(&format!("| Unique None sources | {} |\n", total_none));
     result.push_str(&format!("| Packages covered | {} |\n", packages.len()));
@@ -1268,6 +3128,7 @@ pub fn query_stats() -> Result<String, QueryError> {
         "| Grouping suggestions | {} |\n",
         db.grouping_suggestions.len()
     ));
+    result.push_str(&format!("| Exception suppressors | {} |\n", count_suppressors(&db)));
     result.push('\n');
 
     let total_functions = db.function_count();
@@ -1279,23 +3140,27 @@ pub fn query_stats() -> Result<String, QueryError> {
         result.push_str(&format!(
             "| 🔴 High | {} | {}% |\n",
             high_risk,
-            (high_risk * 100) / total_functions
+            (high_risk * 100).checked_div(total_functions).unwrap_or(0)
         ));
         result.push_str(&format!(
             "| 🟡 Medium | {} | {}% |\n",
             medium_risk,
-            (medium_risk * 100) / total_functions
+            (medium_risk * 100).checked_div(total_functions).unwrap_or(0)
         ));
         result.push_str(&format!(
             "| 🟢 Low | {} | {}% |\n",
             low_risk,
-            (low_risk * 100) / total_functions
+            (low_risk * 100).checked_div(total_functions).unwrap_or(0)
         ));
     } else {
         result.push_str("| - | 0 | 0% |\n");
     }
     result.push('\n');
 
+    if total_functions > 0 {
+        result.push_str(&format_exception_distribution(&compute_distribution(&db.functions)));
+    }
+
     if !exception_counts.is_empty() {
         result.push_str("## Top Exceptions\n\n");
         result.push_str("| Exception | Occurrences | Recovery |\n");
@@ -1338,6 +3203,10 @@ pub fn query_stats() -> Result<String, QueryError> {
         result.push('\n');
     }
 
+    if trend {
+        result.push_str(&format_trend(stats_trend(&db).as_ref(), std::io::stdout().is_terminal()));
+    }
+
     result.push_str("---\n\n");
     result.push_str("## Commands\n\n");
     result.push_str("```bash\n");
@@ -1352,40 +3221,118 @@ pub fn query_stats() -> Result<String, QueryError> {
     Ok(result)
 }
 
+/// One line of the quick reference. `query_quickref` and
+/// `query_quickref_json` both render `QUICKREF`, so they cannot drift apart.
+struct QuickRefSpec {
+    section: &'static str,
+    command: &'static str,
+    args: &'static [&'static str],
+    description: &'static str,
+    json: bool,
+}
+
+const fn spec(
+    section: &'static str,
+    command: &'static str,
+    args: &'static [&'static str],
+    description: &'static str,
+    json: bool,
+) -> QuickRefSpec {
+    QuickRefSpec { section, command, args, description, json }
+}
+
+const LOCAL: &str = "LOCAL (Entity-Level) Queries";
+const FULL: &str = "FULL ANALYSIS Queries";
+const CROSS: &str = "CROSS-FUNCTION Queries";
+
+const QUICKREF: &[QuickRefSpec] = &[
+    spec(LOCAL, "risk", &["<function>"], "One-line risk summary", true),
+    spec(LOCAL, "has", &["<function>", "<exc>"], "Check if function raises exception", false),
+    spec(LOCAL, "handle", &["<function>"], "Generate try/except block", false),
+    spec(LOCAL, "signature", &["<function>"], "Function signature + location", false),
+    spec(LOCAL, "signatures", &["<module>"], "All function signatures in a module", true),
+    spec(LOCAL, "one-exception", &["<fn>", "<exc>"], "Single exception details", false),
+    spec(LOCAL, "one-none", &["<fn>", "<idx>"], "Single None source details", false),
+    spec(LOCAL, "callers", &["<function>"], "What calls this function (--transitive for all)", true),
+    spec(LOCAL, "callees", &["<function>"], "What this function calls", false),
+    spec(LOCAL, "diff", &["<function>"], "Compare current vs previous", false),
+    spec(FULL, "exceptions", &["<function>"], "All exceptions with locations (--locations-only, --show-ignored)", true),
+    spec(FULL, "handled", &["<function>"], "Exceptions caught in the call chain", true),
+    spec(FULL, "suppressors", &["<function>"], "Catch-all handlers that swallow exceptions", true),
+    spec(FULL, "none", &["<function>"], "All None sources (--kind to filter, --trace for flow)", true),
+    spec(FULL, "function", &["<function>"], "Complete function summary (--depth-profile, --show-chains)", true),
+    spec(FULL, "chain", &["<function>", "<exc>"], "Call chain visualization (--all for every path)", false),
+    spec(CROSS, "groups", &["[package]"], "Grouping suggestions (--suggest-new, --merge <g1> <g2>)", true),
+    spec(CROSS, "exception", &["<type>"], "Exception type details, substring match unless --exact (--find-handlers, --cross-ref <fn>, --locations-only)", true),
+    spec(CROSS, "catchers", &["<exc_type>"], "Functions that catch an exception type, and whether they re-raise", true),
+    spec(CROSS, "package", &["<name>"], "Package exception analysis (--top-risk N, --sort-by <field>)", true),
+    spec(CROSS, "uncaught", &["<package>"], "Exception types escaping the package's public API uncaught", true),
+    spec(CROSS, "list", &[], "All analyzed functions (--since 24h|7d|1w|<datetime>)", true),
+    spec(
+        CROSS,
+        "search",
+        &["<query>"],
+        "Search by name, prefix matches first (--limit N; --exception <glob> [--risk] for functions raising it)",
+        false,
+    ),
+    spec(CROSS, "stats", &[], "Database statistics (--trend for changes since the last save, --since <when>)", true),
+    spec(CROSS, "stats", &["--by-exception"], "Statistics per exception type", true),
+    spec(CROSS, "quickref", &[], "This reference (-f json for a machine-readable version)", true),
+];
+
 pub fn query_quickref() -> String {
-    r#"
-Arbor Query Commands - Quick Reference
-
-LOCAL (Entity-Level) Queries:
-  arbor query risk <function>           One-line risk summary
-  arbor query has <function> <exc>      Check if function raises exception
-  arbor query handle <function>         Generate try/except block
-  arbor query signature <function>      Function signature + location
-  arbor query one-exception <fn> <exc>  Single exception details
-  arbor query one-none <fn> <idx>       Single None source details
-  arbor query callers <function>        What calls this function
-  arbor query callees <function>        What this function calls
-  arbor query diff <function>           Compare current vs previous
-
-FULL ANALYSIS Queries:
-  arbor query exceptions <function>     All exceptions with locations
-  arbor query none <function>           All None sources
-  arbor query function <function>       Complete function summary
-  arbor query chain <function> <exc>    Call chain visualization
-
-CROSS-FUNCTION Queries:
-  arbor query groups [package]          Grouping suggestions
-  arbor query exception <type>          Exception type details
-  arbor query package <name>            Package exception analysis
-  arbor query list                      All analyzed functions
-  arbor query search <query>            Search with filters
-  arbor query stats                     Database statistics
-
-OUTPUT FORMAT:
-  arbor query -f json <subcommand>      Output as JSON
-  arbor query -f markdown <subcommand>  Output as Markdown (default)
-"#
-    .to_string()
+    let mut result = String::from("\nArbor Query Commands - Quick Reference\n");
+    let mut section = "";
+
+    for spec in QUICKREF {
+        if spec.section != section {
+            section = spec.section;
+            result.push_str(&format!("\n{}:\n", section));
+        }
+        let mut usage = format!("arbor query {}", spec.command);
+        for arg in spec.args {
+            usage.push(' ');
+            usage.push_str(arg);
+        }
+        result.push_str(&format!("  {:<38}{}\n", usage, spec.description));
+    }
+
+    result.push_str("\nOUTPUT FORMAT:\n");
+    result.push_str("  arbor query -f json <subcommand>      Output as JSON\n");
+    result.push_str("  arbor query -f markdown <subcommand>  Output as Markdown (default)\n");
+    result
+}
+
+/// A quick reference entry as emitted by `arbor query quickref -f json`.
+#[derive(Debug, Serialize)]
+pub struct QuickRefEntry {
+    pub command: String,
+    pub args: Vec<String>,
+    pub description: String,
+    pub format_variants: Vec<String>,
+    pub section: String,
+}
+
+impl From<&QuickRefSpec> for QuickRefEntry {
+    fn from(spec: &QuickRefSpec) -> Self {
+        let mut format_variants = vec!["markdown".to_string()];
+        if spec.json {
+            format_variants.push("json".to_string());
+        }
+        Self {
+            command: spec.command.to_string(),
+            args: spec.args.iter().map(|a| a.to_string()).collect(),
+            description: spec.description.to_string(),
+            format_variants,
+            section: spec.section.to_string(),
+        }
+    }
+}
+
+/// The quick reference as a JSON array, for editor plugins and other tools.
+pub fn query_quickref_json(options: JsonFormatOptions) -> String {
+    let entries: Vec<QuickRefEntry> = QUICKREF.iter().map(QuickRefEntry::from).collect();
+    to_json_string(&entries, options).unwrap_or_default()
 }
 
 // ============================================================================
@@ -1399,74 +3346,387 @@ struct RiskJson {
     function: String,
     risk_level: String,
     risk_emoji: String,
+    risk_score: f64,
     exception_count: usize,
     none_source_count: usize,
     call_depth: usize,
 }
 
-pub fn query_risk_json(function: &str) -> Result<String, QueryError> {
-    let db = load_database()?;
-    let analysis = db
-        .get_function(function)
-        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
-
-    let risk = analysis.risk_level();
-    let output = RiskJson {
-        function: function.to_string(),
-        risk_level: risk.as_str().to_string(),
-        risk_emoji: risk.emoji().to_string(),
-        exception_count: analysis.exception_count(),
-        none_source_count: analysis.none_source_count(),
-        call_depth: analysis.call_depth,
-    };
-
-    serde_json::to_string_pretty(&output)
-        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+impl RiskJson {
+    fn new(analysis: &FunctionAnalysis, weights: &NoneSourceWeights) -> Self {
+        let risk = analysis.risk_level();
+        Self {
+            function: analysis.function_id.clone(),
+            risk_level: risk.as_str().to_string(),
+            risk_emoji: risk.emoji().to_string(),
+            risk_score: analysis.risk_score_with(weights),
+            exception_count: analysis.exception_count(),
+            none_source_count: analysis.none_source_count(),
+            call_depth: analysis.call_depth,
+        }
+    }
 }
 
-pub fn query_exceptions_json(function: &str) -> Result<String, QueryError> {
+pub fn query_risk_json(function: &str, options: JsonFormatOptions) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
-    serde_json::to_string_pretty(&analysis.raises)
+    let weights = ArborConfig::load_or_default().risk.none_source_weights;
+    to_json_string(&RiskJson::new(analysis, &weights), options)
         .map_err(|e| QueryError::InvalidQuery(e.to_string()))
 }
 
-pub fn query_none_json(function: &str) -> Result<String, QueryError> {
+/// A `RiskJson` array of every analyzed function, highest risk score first.
+pub fn query_risk_all_json(
+    filter_risk: Option<RiskLevel>,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let weights = ArborConfig::load_or_default().risk.none_source_weights;
+    format_risk_all_json(&db, filter_risk, &weights, options)
+}
+
+fn format_risk_all_json(
+    db: &ArborDatabase,
+    filter_risk: Option<RiskLevel>,
+    weights: &NoneSourceWeights,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let output: Vec<RiskJson> = list_functions(db, SortField::Risk, filter_risk, weights)
+        .into_iter()
+        .map(|(_, analysis)| RiskJson::new(analysis, weights))
+        .collect();
+
+    to_json_string(&output, options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+#[derive(Serialize)]
+struct CallerJson {
+    function: String,
+    depth: usize,
+}
+
+pub fn query_callers_json(
+    function: &str,
+    transitive: bool,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+
+    if !db.functions.contains_key(function) && !db.symbol_index.contains(function) {
+        return Err(QueryError::FunctionNotFound(function.to_string()));
+    }
+
+    let callers: Vec<CallerJson> = if transitive {
+        db.dependency_graph
+            .transitive_callers(function)
+            .into_iter()
+            .map(|(function, depth)| CallerJson { function, depth })
+            .collect()
+    } else {
+        let mut direct: Vec<String> = db
+            .dependency_graph
+            .get_callers(function)
+            .cloned()
+            .unwrap_or_default();
+        direct.sort();
+        direct.dedup();
+        direct
+            .into_iter()
+            .map(|function| CallerJson { function, depth: 1 })
+            .collect()
+    };
+
+    to_json_string(&callers, options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+#[derive(Serialize)]
+struct ExceptionsWithIgnoredJson<'a> {
+    raises: &'a [RaiseStatement],
+    ignored: &'a [RaiseStatement],
+}
+
+pub fn query_exceptions_json(
+    function: &str,
+    show_ignored: bool,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    let result = if show_ignored {
+        to_json_string(&ExceptionsWithIgnoredJson {
+            raises: &analysis.raises,
+            ignored: &analysis.ignored_raises,
+        }, options)
+    } else {
+        to_json_string(&analysis.raises, options)
+    };
+    result.map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+#[derive(Serialize)]
+struct HandledJson<'a> {
+    handled: Vec<HandledExceptionJson<'a>>,
+    unhandled: Vec<&'a RaiseStatement>,
+}
+
+#[derive(Serialize)]
+struct HandledExceptionJson<'a> {
+    exception_type: &'a str,
+    raised_at: String,
+    caught_in: Option<&'a str>,
+    caught_at: String,
+    strategy: String,
+}
+
+pub fn query_handled_json(
+    function: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
-    serde_json::to_string_pretty(&analysis.none_sources)
+    format_handled_json(analysis, options)
+}
+
+fn format_handled_json(
+    analysis: &FunctionAnalysis,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let mut output = HandledJson {
+        handled: Vec::new(),
+        unhandled: Vec::new(),
+    };
+
+    for raise in &analysis.raises {
+        match raise.suppressed_by {
+            Some(ref handler) => output.handled.push(HandledExceptionJson {
+                exception_type: &raise.exception_type,
+                raised_at: raise.raise_location.to_string_short(),
+                caught_in: handler.location.containing_function.as_deref(),
+                caught_at: handler.location.to_string_short(),
+                strategy: handler.strategy.describe(),
+            }),
+            None => output.unhandled.push(raise),
+        }
+    }
+
+    to_json_string(&output, options)
         .map_err(|e| QueryError::InvalidQuery(e.to_string()))
 }
 
-pub fn query_function_json(function: &str) -> Result<String, QueryError> {
+pub fn query_suppressors_json(
+    function: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
     let db = load_database()?;
     let analysis = db
         .get_function(function)
         .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
 
-    serde_json::to_string_pretty(analysis)
+    to_json_string(&analysis.suppressors, options)
         .map_err(|e| QueryError::InvalidQuery(e.to_string()))
 }
 
-pub fn query_groups_json(package: Option<&str>) -> Result<String, QueryError> {
+pub fn query_signatures_json(
+    module: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
     let db = load_database()?;
+    to_json_string(&module_signatures(&db, module), options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
 
-    let groups: Vec<_> = if let Some(pkg) = package {
-        db.grouping_suggestions
-            .values()
-            .filter(|s| s.group_name.starts_with(pkg) || s.exceptions.iter().any(|e| e.starts_with(pkg)))
-            .collect()
+#[derive(Serialize)]
+struct ExceptionJson<'a> {
+    exception_type: &'a str,
+    occurrences: Vec<ExceptionOccurrence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    handlers: Option<Vec<HandlerLocation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cross_ref: Option<CrossRefJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct CrossRefJson<'a> {
+    function: &'a str,
+    handled: bool,
+    location: Option<CodeLocation>,
+}
+
+#[derive(Serialize)]
+struct AmbiguousExceptionJson<'a> {
+    query: &'a str,
+    matches: Vec<String>,
+}
+
+/// An ambiguous `exc_type` gives `{"query", "matches"}` instead of the
+/// exception details.
+pub fn query_exception_json(
+    exc_type: &str,
+    exact: bool,
+    include_handlers: bool,
+    cross_ref: Option<&str>,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let exc_type = match match_exception_type(&db, exc_type, exact) {
+        ExceptionMatch::One(name) => name,
+        ExceptionMatch::Many(matches) => {
+            return to_json_string(&AmbiguousExceptionJson { query: exc_type, matches }, options)
+                .map_err(|e| QueryError::InvalidQuery(e.to_string()));
+        }
+    };
+    let exc_type = exc_type.as_str();
+    let output = ExceptionJson {
+        exception_type: exc_type,
+        occurrences: exception_occurrences(&db, exc_type),
+        handlers: include_handlers.then(|| find_handlers(&db, exc_type)),
+        cross_ref: cross_ref.map(|function| {
+            let location = does_function_handle(&db, function, exc_type);
+            CrossRefJson {
+                function,
+                handled: location.is_some(),
+                location,
+            }
+        }),
+    };
+
+    to_json_string(&output, options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+/// A None source with its `--annotate-source` label, if requested.
+#[derive(Serialize)]
+struct AnnotatedNoneSourceJson<'a> {
+    #[serde(flatten)]
+    source: &'a NoneSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    annotation: Option<NoneAnnotationStatus>,
+}
+
+/// `NoneFlowPath` with its source labelled as in `AnnotatedNoneSourceJson`.
+#[derive(Serialize)]
+struct AnnotatedNoneFlowJson<'a> {
+    source: AnnotatedNoneSourceJson<'a>,
+    assignments: &'a [(String, CodeLocation)],
+    return_location: &'a Option<CodeLocation>,
+}
+
+/// With `trace`, each entry is a `NoneFlowPath` wrapping its source, or
+/// null where the source file could not be read. With `annotate_source`,
+/// function-call sources also carry their `annotation`.
+pub fn query_none_json(
+    function: &str,
+    kind_filter: Option<NoneSourceKind>,
+    trace: bool,
+    annotate_source: bool,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    let filtered = filter_none_sources(analysis, kind_filter);
+    let annotations = annotate_source.then(|| annotate_none_sources(analysis, &db));
+    let annotated = |i: usize, source| AnnotatedNoneSourceJson {
+        source,
+        annotation: annotations.as_ref().and_then(|a| a[i]),
+    };
+
+    let result = if trace {
+        let flows = trace_none_flows(&analysis.none_sources);
+        let flows: Vec<Option<AnnotatedNoneFlowJson>> = filtered
+            .iter()
+            .map(|(i, _)| {
+                flows[*i].as_ref().map(|flow| AnnotatedNoneFlowJson {
+                    source: annotated(*i, &flow.source),
+                    assignments: &flow.assignments,
+                    return_location: &flow.return_location,
+                })
+            })
+            .collect();
+        to_json_string(&flows, options)
     } else {
-        db.grouping_suggestions.values().collect()
+        let sources: Vec<AnnotatedNoneSourceJson> =
+            filtered.into_iter().map(|(i, source)| annotated(i, source)).collect();
+        to_json_string(&sources, options)
     };
+    result.map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+pub fn query_function_json(
+    function: &str,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+    let analysis = db
+        .get_function(function)
+        .ok_or_else(|| QueryError::FunctionNotFound(function.to_string()))?;
+
+    to_json_string(analysis, options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+/// JSON Schema for `query_function_json` output. Needs no database.
+pub fn query_json_schema(options: JsonFormatOptions) -> Result<String, QueryError> {
+    to_json_string(&SchemaOutput::function_analysis(), options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+#[derive(Serialize)]
+struct GroupsWithNewJson<'a> {
+    groups: Vec<&'a GroupingSuggestion>,
+    suggested_new: Vec<NewGroupJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct NewGroupJson<'a> {
+    #[serde(flatten)]
+    suggestion: GroupingSuggestion,
+    functions: Vec<&'a str>,
+}
+
+pub fn query_groups_json(
+    package: Option<&str>,
+    function: Option<&str>,
+    suggest_new: bool,
+    min_confidence: Option<f32>,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database()?;
+
+    let groups: Vec<_> = db
+        .grouping_suggestions
+        .values()
+        .filter(|s| {
+            applies_to_package(s, package) && meets_confidence(s, min_confidence) && applies_to_function(s, function)
+        })
+        .collect();
+
+    if suggest_new {
+        let output = GroupsWithNewJson {
+            groups,
+            suggested_new: new_group_suggestions(&db, package, min_confidence)
+                .into_iter()
+                .map(|(suggestion, functions)| NewGroupJson { suggestion, functions })
+                .collect(),
+        };
+        return to_json_string(&output, options)
+            .map_err(|e| QueryError::InvalidQuery(e.to_string()));
+    }
 
-    serde_json::to_string_pretty(&groups)
+    to_json_string(&groups, options)
         .map_err(|e| QueryError::InvalidQuery(e.to_string()))
 }
 
@@ -1479,12 +3739,34 @@ struct FunctionSummary {
     location: String,
 }
 
-pub fn query_list_json() -> Result<String, QueryError> {
-    let db = load_database()?;
+#[derive(Serialize)]
+struct FunctionListJson {
+    sort_by: String,
+    filter_risk: Option<String>,
+    total_functions: usize,
+    functions: Vec<FunctionSummary>,
+}
 
-    let functions: Vec<FunctionSummary> = db
-        .functions
-        .iter()
+pub fn query_list_json(
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    period: Option<&Period>,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database_for(period)?;
+    let config = ArborConfig::load_or_default();
+    format_list_json(&db, sort_by, filter_risk, &config.risk.none_source_weights, options)
+}
+
+fn format_list_json(
+    db: &ArborDatabase,
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    weights: &NoneSourceWeights,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let functions: Vec<FunctionSummary> = list_functions(db, sort_by, filter_risk, weights)
+        .into_iter()
         .map(|(id, analysis)| FunctionSummary {
             function_id: id.clone(),
             exception_count: analysis.exception_count(),
@@ -1494,10 +3776,66 @@ pub fn query_list_json() -> Result<String, QueryError> {
         })
         .collect();
 
-    serde_json::to_string_pretty(&functions)
+    let output = FunctionListJson {
+        sort_by: sort_by.as_str().to_string(),
+        filter_risk: filter_risk.map(|level| level.as_str().to_string()),
+        total_functions: db.functions.len(),
+        functions,
+    };
+
+    to_json_string(&output, options)
         .map_err(|e| QueryError::InvalidQuery(e.to_string()))
 }
 
+pub fn query_list_csv(
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    period: Option<&Period>,
+) -> Result<String, QueryError> {
+    let db = load_database_for(period)?;
+    let config = ArborConfig::load_or_default();
+    Ok(format_list_csv(&db, sort_by, filter_risk, &config.risk.none_source_weights))
+}
+
+/// RFC 4180 CSV with a header row and one row per function, CRLF-terminated.
+fn format_list_csv(
+    db: &ArborDatabase,
+    sort_by: SortField,
+    filter_risk: Option<RiskLevel>,
+    weights: &NoneSourceWeights,
+) -> String {
+    let mut result = String::from(
+        "function_id,file,line,exception_count,none_source_count,risk_level,risk_score,call_depth,analyzed_at\r\n",
+    );
+    for (id, analysis) in list_functions(db, sort_by, filter_risk, weights) {
+        let fields = [
+            id.clone(),
+            analysis.location.file.display().to_string(),
+            analysis.location.line.to_string(),
+            analysis.exception_count().to_string(),
+            analysis.none_source_count().to_string(),
+            analysis.risk_level().as_str().to_string(),
+            format!("{:.1}", analysis.risk_score_with(weights)),
+            analysis.call_depth.to_string(),
+            analysis.analyzed_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        result.push_str(&row.join(","));
+        result.push_str("\r\n");
+    }
+    result
+}
+
+/// Quotes `value` when it holds a comma, quote or line break, doubling any
+/// quotes inside.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[derive(Serialize)]
 struct StatsJson {
     version: String,
@@ -1509,7 +3847,172 @@ struct StatsJson {
     unique_none_sources: usize,
     package_count: usize,
     group_count: usize,
+    suppressor_count: usize,
     risk_distribution: RiskDistribution,
+    /// Functions per exception-count bucket ("0", "1-2", ..., "21+").
+    distribution: std::collections::HashMap<String, usize>,
+    /// Unset until some function has been analyzed with call graph stats.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_graph: Option<CallGraphPercentiles>,
+    /// Set with `--since`, when the other fields cover only that window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    period_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    period_end: Option<String>,
+}
+
+/// `CallGraphStats` across the analyzed functions that recorded them.
+#[derive(Debug, Serialize)]
+struct CallGraphPercentiles {
+    functions: usize,
+    branching_factor: Percentiles,
+    leaf_count: Percentiles,
+    cycle_count: Percentiles,
+    /// Widest level of each function's call graph.
+    max_width: Percentiles,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Percentiles {
+    p50: f32,
+    p90: f32,
+    p99: f32,
+    max: f32,
+}
+
+impl Percentiles {
+    /// Nearest-rank percentiles; `values` must not be empty.
+    fn of(mut values: Vec<f32>) -> Self {
+        values.sort_by(|a, b| a.total_cmp(b));
+        let rank = |p: f32| {
+            let index = ((p / 100.0) * values.len() as f32).ceil() as usize;
+            values[index.clamp(1, values.len()) - 1]
+        };
+        Self {
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p99: rank(99.0),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// Distinct catch-all handlers that swallow exceptions. One in a shared
+/// helper shows up in every caller's analysis but is counted once.
+fn count_suppressors(db: &ArborDatabase) -> usize {
+    db.functions
+        .values()
+        .flat_map(|a| &a.suppressors)
+        .map(|s| (&s.location.file, s.location.line, s.location.column))
+        .collect::<std::collections::HashSet<_>>()
+        .len()
+}
+
+fn call_graph_percentiles(db: &ArborDatabase) -> Option<CallGraphPercentiles> {
+    let stats: Vec<&crate::core::types::CallGraphStats> = db
+        .functions
+        .values()
+        .map(|analysis| &analysis.call_graph_stats)
+        .filter(|stats| !stats.width_at_depth.is_empty())
+        .collect();
+    if stats.is_empty() {
+        return None;
+    }
+
+    let collect = |value: fn(&crate::core::types::CallGraphStats) -> f32| {
+        Percentiles::of(stats.iter().map(|s| value(s)).collect())
+    };
+    Some(CallGraphPercentiles {
+        functions: stats.len(),
+        branching_factor: collect(|s| s.branching_factor),
+        leaf_count: collect(|s| s.leaf_count as f32),
+        cycle_count: collect(|s| s.cycle_count as f32),
+        max_width: collect(|s| s.width_at_depth.iter().copied().max().unwrap_or(0) as f32),
+    })
+}
+
+#[derive(Serialize)]
+struct StatsWithTrendJson {
+    current: StatsJson,
+    trend: Option<StatsTrend>,
+}
+
+/// How each metric moved between a saved snapshot and now.
+#[derive(Debug, Serialize)]
+struct StatsTrend {
+    since: String,
+    functions: i64,
+    symbols: i64,
+    unique_exceptions: i64,
+    none_sources: i64,
+    high_risk: i64,
+    medium_risk: i64,
+    low_risk: i64,
+}
+
+impl StatsTrend {
+    fn between(previous: &StatsSnapshot, current: &StatsSnapshot) -> Self {
+        let delta = |before: usize, after: usize| after as i64 - before as i64;
+        Self {
+            since: previous.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            functions: delta(previous.function_count, current.function_count),
+            symbols: delta(previous.symbol_count, current.symbol_count),
+            unique_exceptions: delta(previous.unique_exceptions, current.unique_exceptions),
+            none_sources: delta(previous.none_sources, current.none_sources),
+            high_risk: delta(previous.high_risk, current.high_risk),
+            medium_risk: delta(previous.medium_risk, current.medium_risk),
+            low_risk: delta(previous.low_risk, current.low_risk),
+        }
+    }
+
+    /// Label, delta, and whether an increase is bad news (`None` if neutral).
+    fn rows(&self) -> [(&'static str, i64, Option<bool>); 7] {
+        [
+            ("functions", self.functions, None),
+            ("symbols", self.symbols, None),
+            ("unique exceptions", self.unique_exceptions, Some(true)),
+            ("None sources", self.none_sources, Some(true)),
+            ("high risk", self.high_risk, Some(true)),
+            ("medium risk", self.medium_risk, None),
+            ("low risk", self.low_risk, None),
+        ]
+    }
+}
+
+/// Change since the last save that changed the metrics, if there was one.
+fn stats_trend(db: &ArborDatabase) -> Option<StatsTrend> {
+    let current = db.stats_snapshot();
+    db.previous_stats_snapshot(&current)
+        .map(|previous| StatsTrend::between(previous, &current))
+}
+
+/// `## Trend` section: `↑ Δ functions: +3` per changed metric, red when an
+/// increase is bad and green when a decrease is good, if `color` is set.
+fn format_trend(trend: Option<&StatsTrend>, color: bool) -> String {
+    let mut result = String::from("## Trend\n\n");
+    let Some(trend) = trend else {
+        result.push_str("No earlier snapshot to compare with. Snapshots are taken when the database is saved.\n\n");
+        return result;
+    };
+
+    result.push_str(&format!("**Since:** {}\n\n", trend.since));
+    for (label, delta, increase_is_bad) in trend.rows() {
+        if delta == 0 {
+            result.push_str(&format!("-   Δ {}: 0\n", label));
+            continue;
+        }
+        let arrow = if delta > 0 { "↑" } else { "↓" };
+        let line = format!("{} Δ {}: {:+}", arrow, label, delta);
+        match increase_is_bad {
+            Some(bad) if color => {
+                let code = if (delta > 0) == bad { "31" } else { "32" };
+                result.push_str(&format!("- \x1b[{}m{}\x1b[0m\n", code, line));
+            }
+            _ => result.push_str(&format!("- {}\n", line)),
+        }
+    }
+    result.push('\n');
+    result
 }
 
 #[derive(Serialize)]
@@ -1519,8 +4022,21 @@ struct RiskDistribution {
     low: usize,
 }
 
-pub fn query_stats_json() -> Result<String, QueryError> {
+pub fn query_exception_stats_json(
+    min_occurrences: usize,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
     let db = load_database()?;
+    to_json_string(&exception_stats(&db, min_occurrences), options)
+        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+pub fn query_stats_json(
+    trend: bool,
+    period: Option<&Period>,
+    options: JsonFormatOptions,
+) -> Result<String, QueryError> {
+    let db = load_database_for(period)?;
 
     let total_none: usize = db.functions.values().map(|a| a.none_source_count()).sum();
 
@@ -1534,13 +4050,6 @@ pub fn query_stats_json() -> Result<String, QueryError> {
         .filter(|a| a.risk_level() == crate::core::types::RiskLevel::Low)
         .count();
 
-    let mut unique_exceptions: std::collections::HashSet<&str> = std::collections::HashSet::new();
-    for analysis in db.functions.values() {
-        for raise in &analysis.raises {
-            unique_exceptions.insert(&raise.exception_type);
-        }
-    }
-
     let mut packages: std::collections::HashSet<&str> = std::collections::HashSet::new();
     for fn_id in db.functions.keys() {
         if let Some(pkg) = fn_id.split('.').next() {
@@ -1554,17 +4063,1105 @@ pub fn query_stats_json() -> Result<String, QueryError> {
         updated_at: db.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
         function_count: db.function_count(),
         symbol_count: db.symbol_count(),
-        unique_exceptions: unique_exceptions.len(),
+        unique_exceptions: db.exception_index.len(),
         unique_none_sources: total_none,
         package_count: packages.len(),
         group_count: db.grouping_suggestions.len(),
+        suppressor_count: count_suppressors(&db),
         risk_distribution: RiskDistribution {
             high: high_risk,
             medium: medium_risk,
             low: low_risk,
         },
+        distribution: compute_distribution(&db.functions).to_map(),
+        call_graph: call_graph_percentiles(&db),
+        period_start: period.map(|p| p.start.format("%Y-%m-%d %H:%M:%S").to_string()),
+        period_end: period.map(|p| p.end.format("%Y-%m-%d %H:%M:%S").to_string()),
     };
 
-    serde_json::to_string_pretty(&stats)
-        .map_err(|e| QueryError::InvalidQuery(e.to_string()))
+    let json = if trend {
+        to_json_string(&StatsWithTrendJson {
+            current: stats,
+            trend: stats_trend(&db),
+        }, options)
+    } else {
+        to_json_string(&stats, options)
+    };
+    json.map_err(|e| QueryError::InvalidQuery(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::Environment;
+    use crate::core::types::{
+        CallChainEntry, CodeLocation, ExceptionHandler, ExceptionSuppressor, HandlerStrategy, NoneSource, NoneSourceKind,
+        RaiseStatement,
+    };
+
+    fn analysis(id: &str, exceptions: usize, none_sources: usize) -> FunctionAnalysis {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut analysis = FunctionAnalysis::new(id.to_string(), format!("def {}()", id), location.clone());
+        for i in 0..exceptions {
+            let exc = format!("Error{}", i);
+            analysis.raises.push(RaiseStatement::new(exc.clone(), exc, location.clone()));
+        }
+        for _ in 0..none_sources {
+            analysis
+                .none_sources
+                .push(NoneSource::new(NoneSourceKind::ExplicitReturn, location.clone()));
+        }
+        analysis
+    }
+
+    #[test]
+    fn test_format_chain_all_lists_each_path() {
+        let mut analysis = FunctionAnalysis::new(
+            "app.run".to_string(),
+            "def run()".to_string(),
+            CodeLocation::new(PathBuf::from("app.py"), 1),
+        );
+        for (callee, line) in [("app.validate_input", 10), ("app.parse_config", 20), ("app.validate_input", 12)] {
+            let location = CodeLocation::new(PathBuf::from("app.py"), line);
+            analysis
+                .raises
+                .push(RaiseStatement::new("ValueError".to_string(), "ValueError".to_string(), location.clone()));
+            analysis.call_chains.push(CallChainEntry {
+                exception_type: "ValueError".to_string(),
+                location,
+                chain: vec!["app.run".to_string(), callee.to_string()],
+            });
+        }
+
+        let paths: Vec<&[String]> = chain_paths(&analysis, "ValueError").into_iter().map(|(_, c)| c).collect();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[1], ["app.run".to_string(), "app.parse_config".to_string()]);
+
+        let output = format_chain_all(&analysis, "ValueError").unwrap();
+        assert!(output.contains("## Path 1 of 2:"));
+        assert!(output.contains("## Path 2 of 2:"));
+        assert!(output.contains("└── app.parse_config"));
+        assert!(!output.contains("Path 3"));
+        assert!(format_chain_all(&analysis, "KeyError").is_err());
+    }
+
+    #[test]
+    fn test_format_suppressors() {
+        let mut analysis = analysis("app.run", 0, 0);
+        assert!(format_suppressors("app.run", &analysis).contains("No catch-all handlers"));
+
+        for (caught, line) in [("*", 4), ("Exception", 9)] {
+            let mut location = CodeLocation::new(PathBuf::from("app.py"), line);
+            location.containing_function = Some("app.load".to_string());
+            analysis.suppressors.push(ExceptionSuppressor {
+                location,
+                caught_type: caught.to_string(),
+                first_statement: "pass".to_string(),
+            });
+        }
+
+        let output = format_suppressors("app.run", &analysis);
+        assert!(output.contains("**2 catch-all handler(s)**"));
+        assert!(output.contains("| bare `except:` | `app.load` | `app.py:4` | `pass` |"));
+        assert!(output.contains("| `Exception` | `app.load` | `app.py:9` | `pass` |"));
+    }
+
+    #[test]
+    fn test_chain_tree_shows_raise_cause() {
+        let chain = vec!["app.run".to_string(), "app.load".to_string()];
+        let mut raise = RaiseStatement::new(
+            "ValueError".to_string(),
+            "ValueError".to_string(),
+            CodeLocation::new(PathBuf::from("app.py"), 9),
+        );
+        assert!(format_chain_tree(&chain, &raise, "ValueError", &[]).contains("raise ValueError(\"...\")\n"));
+
+        raise.chained_from = Some("original_err".to_string());
+        assert!(format_chain_tree(&chain, &raise, "ValueError", &[]).contains("raise ValueError(\"...\") from original_err\n"));
+
+        raise.chained_from = None;
+        raise.suppress_chain = true;
+        assert!(format_chain_tree(&chain, &raise, "ValueError", &[]).contains("raise ValueError(\"...\") from None\n"));
+    }
+
+    #[test]
+    fn test_format_none_shows_annotation_note() {
+        let mut analysis = analysis("app.find", 0, 0);
+        analysis.none_sources.push(
+            NoneSource::new(NoneSourceKind::AnnotatedOptional, CodeLocation::new(PathBuf::from("app.py"), 1))
+                .with_note("annotated `-> Optional[int]`: the function's contract allows None"),
+        );
+
+        let output = format_none("app.find", &analysis, None, None, None, &NoneSourceWeights::default());
+        assert!(output.contains("### 1. annotated optional\n"));
+        assert!(output.contains("- **Note:** annotated `-> Optional[int]`: the function's contract allows None\n"));
+
+        let filtered = format_none(
+            "app.find",
+            &analysis,
+            Some("annotated".parse().unwrap()),
+            None,
+            None,
+            &NoneSourceWeights::default(),
+        );
+        assert!(filtered.contains("**Total None Sources:** 1 of 1"));
+    }
+
+    #[test]
+    fn test_chain_tree_marks_awaited_hops() {
+        let chain = vec!["app.run".to_string(), "app.load".to_string(), "app.parse".to_string()];
+        let awaited = vec![("app.run".to_string(), "app.load".to_string())];
+        let mut raise = RaiseStatement::new(
+            "ValueError".to_string(),
+            "ValueError".to_string(),
+            CodeLocation::new(PathBuf::from("app.py"), 9),
+        );
+        assert!(!format_chain_tree(&chain, &raise, "ValueError", &awaited).contains("(async)"));
+
+        raise.via_await = true;
+        let tree = format_chain_tree(&chain, &raise, "ValueError", &awaited);
+        assert!(tree.contains("└── app.load (async)\n"));
+        assert!(tree.contains("└── app.parse\n"));
+    }
+
+    #[test]
+    fn test_stats_trend_against_previous_snapshot() {
+        let mut db = test_db();
+        assert!(stats_trend(&db).is_none());
+        assert!(format_trend(None, false).contains("No earlier snapshot"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        db.save(&path).unwrap();
+        db.save(&path).unwrap();
+        assert_eq!(db.stats_history.len(), 1);
+
+        db.add_function(analysis("app.worse", 15, 0));
+        db.add_function(analysis("app.fine", 0, 0));
+        db.save(&path).unwrap();
+        assert_eq!(db.stats_history.len(), 2);
+
+        let trend = stats_trend(&db).unwrap();
+        assert_eq!(trend.functions, 2);
+        assert_eq!(trend.high_risk, 1);
+        assert_eq!(trend.unique_exceptions, 3);
+
+        let plain = format_trend(Some(&trend), false);
+        assert!(plain.contains("- ↑ Δ functions: +2"));
+        assert!(plain.contains("- ↑ Δ high risk: +1"));
+        assert!(plain.contains("-   Δ medium risk: 0"));
+        assert!(!plain.contains('\x1b'));
+
+        let colored = format_trend(Some(&trend), true);
+        assert!(colored.contains("\x1b[31m↑ Δ high risk: +1\x1b[0m"));
+        assert!(colored.contains("- ↑ Δ functions: +2"));
+
+        db.functions.remove("app.worse");
+        db.rebuild_exception_index();
+        let trend = stats_trend(&db).unwrap();
+        assert_eq!(trend.high_risk, -1);
+        assert!(format_trend(Some(&trend), true).contains("\x1b[32m↓ Δ high risk: -1\x1b[0m"));
+    }
+
+    fn test_db() -> ArborDatabase {
+        let mut db = ArborDatabase::new(Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        });
+        db.add_function(analysis("app.low", 1, 0));
+        db.add_function(analysis("app.medium", 5, 0));
+        db.add_function(analysis("app.high_exceptions", 12, 0));
+        db.add_function(analysis("app.high_none", 2, 6));
+        db
+    }
+
+    #[test]
+    fn test_uncaught_exceptions_at_package_boundary() {
+        let mut db = test_db();
+        let location = CodeLocation::new(PathBuf::from("mypackage/api.py"), 3);
+        let at = |line| CodeLocation::new(PathBuf::from("mypackage/api.py"), line);
+        let handler = ExceptionHandler {
+            caught_types: vec!["KeyError".to_string()],
+            location: location.clone(),
+            strategy: HandlerStrategy::Suppresses,
+            try_line_start: 2,
+            try_line_end: 3,
+            covered_calls: Vec::new(),
+            body: "except KeyError:\n    pass".to_string(),
+        };
+        // Client.__init__ reaches fetch's ValueError site and raises another
+        // of its own; the shared site counts once
+        for (id, raises) in [
+            ("mypackage.api.fetch", vec![("ValueError", 3), ("KeyError", 5)]),
+            ("mypackage.api.Client.__init__", vec![("ValueError", 3), ("ValueError", 9)]),
+            ("mypackage.api.Client.get", vec![("KeyError", 5), ("TimeoutError", 12)]),
+            ("mypackage._internal.parse", vec![("OSError", 20)]),
+            ("mypackage.api._helper", vec![("OSError", 21)]),
+        ] {
+            let mut analysis = analysis(id, 0, 0);
+            for (exc, line) in raises {
+                analysis
+                    .raises
+                    .push(RaiseStatement::new(exc.to_string(), exc.to_string(), at(line)));
+            }
+            db.add_function(analysis);
+        }
+        // fetch catches its KeyError; Client.get does not
+        db.functions.get_mut("mypackage.api.fetch").unwrap().raises[1].suppressed_by = Some(handler);
+
+        let uncaught = uncaught_exceptions(&db, "mypackage");
+        let types: Vec<&str> = uncaught.iter().map(|u| u.exception_type.as_str()).collect();
+        assert_eq!(types, vec!["ValueError", "KeyError", "TimeoutError"]);
+        assert_eq!(uncaught[0].occurrence_count, 2);
+        assert_eq!(uncaught[0].functions, vec!["mypackage.api.Client.__init__", "mypackage.api.fetch"]);
+        assert_eq!(uncaught[1].occurrence_count, 1);
+        assert_eq!(uncaught[1].functions, vec!["mypackage.api.Client.get"]);
+
+        let output = format_uncaught("mypackage", &uncaught);
+        assert!(output.contains("| `ValueError` | 2 | 2 | `mypackage.api.Client.__init__` |"));
+        assert!(!output.contains("OSError"));
+        assert!(format_uncaught("app", &uncaught_exceptions(&db, "other")).contains("No exceptions escape"));
+    }
+
+    #[test]
+    fn test_catchers_across_analyses() {
+        let mut db = test_db();
+        let clause = |function: &str, line: u32, caught: &str, strategy: HandlerStrategy| {
+            let mut location = CodeLocation::new(PathBuf::from("app.py"), line);
+            location.containing_function = Some(function.to_string());
+            CatchClause {
+                caught_type: caught.to_string(),
+                location,
+                strategy,
+            }
+        };
+        let shared = clause("app.load", 12, "requests.ConnectionError", HandlerStrategy::Reraise);
+        for id in ["app.run", "app.serve"] {
+            let mut analysis = analysis(id, 0, 0);
+            analysis.catches.insert(shared.caught_type.clone(), vec![shared.clone()]);
+            db.add_function(analysis);
+        }
+        let mut own = analysis("app.retry", 0, 0);
+        own.catches.insert(
+            "ConnectionError".to_string(),
+            vec![clause("app.retry", 30, "ConnectionError", HandlerStrategy::Raises("RetryError".to_string()))],
+        );
+        own.catches.insert(
+            "KeyError".to_string(),
+            vec![clause("app.retry", 40, "KeyError", HandlerStrategy::Suppresses)],
+        );
+        db.add_function(own);
+
+        let found = catchers(&db, "ConnectionError");
+        assert_eq!(found.len(), 2);
+        let output = format_catchers("ConnectionError", &found);
+        assert!(output.contains("**Handlers:** 2"));
+        assert!(output.contains("| `app.load` | `app.py:12` | `requests.ConnectionError` | yes |"));
+        assert!(output.contains("| `app.retry` | `app.py:30` | `ConnectionError` | as `RetryError` |"));
+        assert!(format_catchers("OSError", &catchers(&db, "OSError")).contains("No analyzed function catches"));
+    }
+
+    #[test]
+    fn test_period_parse() {
+        let now = DateTime::parse_from_rfc3339("2024-05-08T12:00:00Z").unwrap().with_timezone(&Utc);
+
+        let period = Period::parse_at("24h", now).unwrap();
+        assert_eq!(period.start, now - chrono::Duration::hours(24));
+        assert_eq!(period.end, now);
+        assert_eq!(period.label, "last 24h");
+        assert_eq!(Period::parse_at("1w", now).unwrap().start, now - chrono::Duration::days(7));
+        assert_eq!(Period::parse_at("7d", now).unwrap().start, now - chrono::Duration::days(7));
+
+        let period = Period::parse_at("2024-05-01T09:30:00", now).unwrap();
+        assert_eq!(period.label, "since 2024-05-01 09:30:00");
+        assert_eq!(Period::parse_at("2024-05-01T09:30:00Z", now).unwrap().start, period.start);
+        assert_eq!(Period::parse_at("2024-05-01", now).unwrap().label, "since 2024-05-01 00:00:00");
+
+        for invalid in ["", "h", "-1d", "3x", "yesterday", "2024-13-01"] {
+            assert!(Period::parse_at(invalid, now).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_list_since() {
+        let mut db = test_db();
+        let now = Utc::now();
+        let old = now - chrono::Duration::days(3);
+        db.functions.get_mut("app.low").unwrap().analyzed_at = Some(old);
+        db.functions.get_mut("app.medium").unwrap().analyzed_at = None;
+
+        let period = Period::parse_at("24h", now).unwrap();
+        db.retain_analyzed_since(period.start);
+        let mut ids: Vec<&String> = db.functions.keys().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["app.high_exceptions", "app.high_none"]);
+        assert!(db.functions_raising("Error11").next().is_some());
+
+        let output = format_list(&db, SortField::Name, None, Some(&period), &NoneSourceWeights::default());
+        assert!(output.contains("**Period:** last 24h\n"));
+        assert!(output.contains("**Total Functions:** 2\n"));
+
+        db.retain_analyzed_since(now + chrono::Duration::hours(1));
+        assert_eq!(
+            format_list(&db, SortField::Name, None, Some(&period), &NoneSourceWeights::default()),
+            "No functions analyzed in the period (last 24h)."
+        );
+    }
+
+    #[test]
+    fn test_package_top_risk() {
+        let db = test_db();
+        let weights = NoneSourceWeights::default();
+
+        let sort_by = package_sort_field(None, Some(2));
+        assert_eq!(sort_by, SortField::Risk);
+        let functions = package_functions(&db, "app", sort_by, &weights);
+        let ids: Vec<&str> = functions.iter().map(|f| f.function_id.as_str()).collect();
+        assert_eq!(ids, vec!["app.high_exceptions", "app.high_none", "app.medium", "app.low"]);
+        assert_eq!(functions[0].risk_score, 12.0);
+
+        let output = format_package(&db, "app", sort_by, Some(2), &weights);
+        assert!(output.contains("Showing top 2 of 4 functions by risk\n"));
+        assert!(output.contains("| `app.high_none` |"));
+        assert!(!output.contains("| `app.medium` |"));
+        assert!(output.contains("| Functions analyzed | 4 |"));
+
+        let output = format_package(&db, "app", package_sort_field(None, None), None, &weights);
+        assert!(!output.contains("Showing top"));
+        assert!(output.find("`app.high_exceptions`").unwrap() < output.find("`app.low`").unwrap());
+    }
+
+    #[test]
+    fn test_list_sort_by_risk_filter_high() {
+        let db = test_db();
+        let weights = NoneSourceWeights::default();
+        let functions = list_functions(&db, SortField::Risk, Some(RiskLevel::High), &weights);
+
+        let ids: Vec<&str> = functions.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["app.high_exceptions", "app.high_none"]);
+        assert!(functions.iter().all(|(_, a)| a.risk_level() == RiskLevel::High));
+        assert!(functions
+            .windows(2)
+            .all(|w| w[0].1.risk_score() >= w[1].1.risk_score()));
+
+        let output = format_list(&db, SortField::Risk, Some(RiskLevel::High), None, &weights);
+        assert!(output.contains("**Sorted By:** risk"));
+        assert!(output.contains("**Risk Filter:** 🔴 High"));
+        assert!(!output.contains("app.medium"));
+    }
+
+    #[test]
+    fn test_list_json_metadata() {
+        let db = test_db();
+        let weights = NoneSourceWeights::default();
+        let options = JsonFormatOptions::default();
+        let json = format_list_json(&db, SortField::Exceptions, Some(RiskLevel::High), &weights, options).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["sort_by"], "exceptions");
+        assert_eq!(value["filter_risk"], "High");
+        assert_eq!(value["total_functions"], 4);
+        assert_eq!(value["functions"][0]["function_id"], "app.high_exceptions");
+        assert_eq!(value["functions"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_list_sort_by_risk_uses_weights() {
+        let db = test_db();
+        let weights = NoneSourceWeights {
+            explicit_return: 3.0,
+            ..NoneSourceWeights::default()
+        };
+
+        let functions = list_functions(&db, SortField::Risk, None, &weights);
+        assert_eq!(functions[0].0, "app.high_none");
+    }
+
+    #[test]
+    fn test_handled_output() {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 10);
+        let mut analysis = analysis("app.run", 2, 0);
+        analysis.raises[0].suppressed_by = Some(ExceptionHandler {
+            caught_types: vec!["Error0".to_string()],
+            location: location.with_function("app.run"),
+            strategy: HandlerStrategy::Returns,
+            try_line_start: 8,
+            try_line_end: 9,
+            covered_calls: vec![],
+            body: String::new(),
+        });
+
+        let output = format_handled("app.run", &analysis);
+        assert!(output.contains("**Handled:** 1"));
+        assert!(output.contains("| `Error0` | `app.run` | `app.py:10` | returns |"));
+        assert!(!output.contains("| `Error1`"));
+
+        let json = format_handled_json(&analysis, JsonFormatOptions::default()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["handled"][0]["caught_in"], "app.run");
+        assert_eq!(value["unhandled"][0]["exception_type"], "Error1");
+    }
+
+    #[test]
+    fn test_transitive_callers_grouped_by_depth() {
+        let callers = vec![
+            ("api.handler".to_string(), 1),
+            ("cli.main".to_string(), 1),
+            ("api.router".to_string(), 2),
+        ];
+
+        let output = format_transitive_callers("db.fetch", &callers);
+        assert!(output.contains("## Depth 1 (direct) (2)"));
+        assert!(output.contains("## Depth 2 (1)"));
+        assert!(output.find("`cli.main`").unwrap() < output.find("`api.router`").unwrap());
+    }
+
+    #[test]
+    fn test_transitive_callers_summarized_by_package() {
+        let callers: Vec<(String, usize)> = (0..150)
+            .map(|i| (format!("pkg{}.caller{}", i % 3, i), 1 + i / 50))
+            .collect();
+
+        let output = format_transitive_callers("db.fetch", &callers);
+        assert!(output.contains("## By Package"));
+        assert!(output.contains("| `pkg0` | 50 |"));
+        assert!(!output.contains("## Depth"));
+    }
+
+    #[test]
+    fn test_exception_stats() {
+        let mut db = test_db();
+        db.grouping_suggestions.insert(
+            "InputErrors".to_string(),
+            crate::core::database::GroupingSuggestion {
+                group_name: "InputErrors".to_string(),
+                exceptions: vec!["Error0".to_string()],
+                rationale: String::new(),
+                handler_example: String::new(),
+                confidence: 0.9,
+                applicable_functions: Vec::new(),
+            },
+        );
+
+        let stats = exception_stats(&db, 1);
+        assert_eq!(stats[0].exception_type, "Error0");
+        assert_eq!(stats[0].occurrence_count, 4);
+        assert_eq!(stats[0].function_count, 4);
+        assert_eq!(stats[0].most_common_file, "app.py");
+        assert_eq!(stats[0].group.as_deref(), Some("InputErrors"));
+        assert!(stats
+            .windows(2)
+            .all(|w| w[0].occurrence_count >= w[1].occurrence_count));
+
+        let frequent = exception_stats(&db, 3);
+        assert!(frequent.iter().all(|s| s.occurrence_count >= 3));
+        assert!(frequent.iter().any(|s| s.exception_type == "Error1"));
+        assert!(!frequent.iter().any(|s| s.exception_type == "Error11"));
+
+        let output = format_exception_stats(&stats, 1);
+        assert!(output.contains("| `Error0` | 4 | 4 | `app.py` |"));
+    }
+
+    #[test]
+    fn test_sort_field_parse() {
+        assert_eq!("depth".parse::<SortField>().unwrap(), SortField::Depth);
+        assert!("size".parse::<SortField>().is_err());
+    }
+
+    #[test]
+    fn test_module_signatures_falls_back_to_index() {
+        let mut db = test_db();
+        db.symbol_index.add(
+            "app.unanalyzed".to_string(),
+            crate::core::database::SymbolLocation {
+                file_path: PathBuf::from("app.py"),
+                line_start: 40,
+                line_end: 45,
+                is_method: false,
+                parent_class: None,
+                is_property: false,
+                property_kind: None,
+                column_start: None,
+                column_end: None,
+                is_stub: false,
+            },
+        );
+        db.symbol_index.add(
+            "other.helper".to_string(),
+            crate::core::database::SymbolLocation {
+                file_path: PathBuf::from("other.py"),
+                line_start: 1,
+                line_end: 2,
+                is_method: false,
+                parent_class: None,
+                is_property: false,
+                property_kind: None,
+                column_start: None,
+                column_end: None,
+                is_stub: false,
+            },
+        );
+
+        let entries = module_signatures(&db, "app");
+        assert_eq!(entries.len(), 5);
+        assert!(entries.windows(2).all(|w| w[0].function_id < w[1].function_id));
+
+        let unanalyzed = entries.iter().find(|e| e.function_id == "app.unanalyzed").unwrap();
+        assert!(!unanalyzed.is_analyzed);
+        assert_eq!(unanalyzed.signature, None);
+        assert_eq!(unanalyzed.location, "app.py:40");
+
+        let output = format_signatures(&db, "app");
+        assert!(output.contains("**Functions:** 5 (4 analyzed)"));
+        assert!(output.contains("- `def app.low()` — `app.py:1`"));
+        assert!(output.contains("- `app.unanalyzed` — `app.py:40` (not analyzed)"));
+        assert!(!output.contains("other.helper"));
+    }
+
+    #[test]
+    fn test_none_kind_filter() {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut analysis = analysis("app.lookup", 0, 2);
+        analysis
+            .none_sources
+            .push(NoneSource::new(NoneSourceKind::ImplicitReturn, location.clone()));
+        analysis
+            .none_sources
+            .push(NoneSource::new(NoneSourceKind::CollectionAccess, location).with_variable_name("result"));
+
+        let kind = "implicit".parse::<NoneSourceKind>().unwrap();
+        let sources = filter_none_sources(&analysis, Some(kind));
+        assert_eq!(sources.len(), 1);
+        assert!(sources.iter().all(|(_, s)| s.kind == NoneSourceKind::ImplicitReturn));
+        assert_eq!(sources[0].0, 2);
+
+        let output = format_none("app.lookup", &analysis, Some(kind), None, None, &NoneSourceWeights::default());
+        assert!(output.contains("**Total None Sources:** 1 of 4"));
+        assert!(output.contains("### 3. implicit return"));
+        assert!(!output.contains("explicit return"));
+
+        let output = format_none("app.lookup", &analysis, None, None, None, &NoneSourceWeights::default());
+        assert!(output.contains("- **Variable:** `result`"));
+
+        let annotations = [None, None, None, Some(NoneAnnotationStatus::Unannotated)];
+        let output = format_none(
+            "app.lookup",
+            &analysis,
+            None,
+            None,
+            Some(&annotations),
+            &NoneSourceWeights::default(),
+        );
+        assert!(output.contains("### 4. collection access [unannotated]"));
+        assert!(output.contains("### 3. implicit return\n"));
+
+        let mut flow = NoneFlowPath::new(analysis.none_sources[3].clone());
+        flow.assignments.push(("result".to_string(), CodeLocation::new(PathBuf::from("app.py"), 4)));
+        assert_eq!(format_none_flow(&flow), "`collection access` → `result` (line 4) → not returned");
+
+        assert_eq!(filter_none_sources(&analysis, None).len(), 4);
+        assert_eq!(
+            none_source_breakdown(&analysis),
+            vec![
+                (NoneSourceKind::ExplicitReturn, 2),
+                (NoneSourceKind::ImplicitReturn, 1),
+                (NoneSourceKind::CollectionAccess, 1),
+            ]
+        );
+        assert!("bogus".parse::<NoneSourceKind>().is_err());
+    }
+
+    #[test]
+    fn test_match_exception_type() {
+        let mut db = test_db();
+        let mut fetch = analysis("app.fetch", 0, 0);
+        let location = CodeLocation::new(PathBuf::from("app.py"), 3);
+        for qualified in ["requests.exceptions.ConnectionError", "app.errors.DBConnectionError"] {
+            let short = qualified.rsplit('.').next().unwrap();
+            fetch
+                .raises
+                .push(RaiseStatement::new(short.to_string(), qualified.to_string(), location.clone()));
+        }
+        db.add_function(fetch);
+
+        let one = |name: &str| ExceptionMatch::One(name.to_string());
+        assert_eq!(match_exception_type(&db, "ConnectionError", false), one("ConnectionError"));
+        assert_eq!(match_exception_type(&db, "requests.exc", false), one("requests.exceptions.ConnectionError"));
+        assert_eq!(match_exception_type(&db, "dbconnection", false), one("app.errors.DBConnectionError"));
+        assert_eq!(match_exception_type(&db, "dbconnection", true), one("dbconnection"));
+        assert_eq!(match_exception_type(&db, "Missing", false), one("Missing"));
+
+        let ExceptionMatch::Many(matches) = match_exception_type(&db, "connection", false) else {
+            panic!("expected several matches");
+        };
+        assert_eq!(matches, vec!["app.errors.DBConnectionError", "requests.exceptions.ConnectionError"]);
+        let output = format_ambiguous_exception("connection", &matches);
+        assert!(output.starts_with("## Ambiguous Query — Multiple Matches"));
+        assert!(output.contains("- `requests.exceptions.ConnectionError`"));
+
+        assert_eq!(exception_locations(&db, "connection", false).locations.len(), 2);
+        assert!(exception_locations(&db, "connection", true).locations.is_empty());
+    }
+
+    #[test]
+    fn test_find_handlers() {
+        let handler = |function: &str, line: u32, caught: &str, strategy: HandlerStrategy| ExceptionHandler {
+            caught_types: vec![caught.to_string()],
+            location: CodeLocation::new(PathBuf::from("app.py"), line).with_function(function),
+            strategy,
+            try_line_start: line - 2,
+            try_line_end: line - 1,
+            covered_calls: vec![],
+            body: format!("except {}:\n    pass", caught),
+        };
+
+        let mut db = test_db();
+        let mut caller = analysis("app.caller", 0, 0);
+        caller.handlers = vec![
+            handler("app.caller", 10, "Error0", HandlerStrategy::Suppresses),
+            handler("app.caller", 20, "Error0", HandlerStrategy::Reraise),
+            handler("app.caller", 30, "OtherError", HandlerStrategy::Returns),
+        ];
+        let mut other = analysis("app.other", 0, 0);
+        other.handlers = vec![handler("app.caller", 10, "Error0", HandlerStrategy::Suppresses)];
+        db.add_function(caller);
+        db.add_function(other);
+
+        let handlers = find_handlers(&db, "Error0");
+        assert_eq!(handlers.len(), 1);
+        assert_eq!(handlers[0].function_id, "app.caller");
+        assert_eq!(handlers[0].line, 10);
+
+        let output = format_exception(&db, "Error0", true);
+        assert!(output.contains("## Where It's Caught"));
+        assert!(output.contains("### `app.caller` at `app.py:10`"));
+        assert!(output.contains("```python\nexcept Error0:\n    pass\n```"));
+        assert!(!format_exception(&db, "Error0", false).contains("Where It's Caught"));
+
+        assert_eq!(
+            format_cross_ref(&db, "app.caller", "Error0"),
+            "✓ `app.caller` catches `Error0` at app.py:10"
+        );
+        assert_eq!(
+            format_cross_ref(&db, "app.caller", "Error1"),
+            "✗ `app.caller` does not handle `Error1`"
+        );
+        assert_eq!(
+            format_cross_ref(&db, "app.other", "Error0"),
+            "✗ `app.other` does not handle `Error0`"
+        );
+    }
+
+    #[test]
+    fn test_suggest_new_groups_output() {
+        let mut db = test_db();
+        db.add_function(analysis("lib.parse", 3, 0));
+
+        let output = format_groups(&db, None, None, true, None);
+        assert!(output.contains("## Suggested New Groups"));
+        assert!(output.contains("### Abort exceptions"));
+        assert!(output.contains("**Confidence:** 🟡 medium (0.60)"));
+        assert!(!format_groups(&db, None, None, true, Some(0.8)).contains("### Abort exceptions"));
+        assert!(output.contains("arbor analyze app.high_exceptions app.high_none app.medium lib.parse"));
+
+        let scoped = new_group_suggestions(&db, Some("lib"), None);
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].1, vec!["lib.parse"]);
+
+        let new: Vec<GroupingSuggestion> = new_group_suggestions(&db, None, None)
+            .into_iter()
+            .map(|(suggestion, _)| suggestion)
+            .collect();
+        for suggestion in new {
+            db.grouping_suggestions.insert(suggestion.group_name.clone(), suggestion);
+        }
+        assert!(format_groups(&db, None, None, true, None).contains("Every exception is already covered"));
+        assert!(!format_groups(&db, None, None, false, None).contains("Suggested New Groups"));
+        assert!(format_groups(&db, None, None, false, Some(0.8)).contains("No grouping suggestions found"));
+    }
+
+    #[test]
+    fn test_groups_applicable_functions() {
+        let mut db = test_db();
+        for (function, exceptions) in [("app.medium", ["Error0", "Error1"]), ("app.high_none", ["Error0", "Error2"])] {
+            db.add_grouping_suggestion(GroupingSuggestion {
+                group_name: "app errors".to_string(),
+                exceptions: exceptions.iter().map(|e| e.to_string()).collect(),
+                rationale: String::new(),
+                handler_example: format!("except ({}) as e:", exceptions.join(", ")),
+                confidence: 0.9,
+                applicable_functions: vec![function.to_string()],
+            });
+        }
+        let group = &db.grouping_suggestions["app errors"];
+        assert_eq!(group.applicable_functions, vec!["app.high_none", "app.medium"]);
+        assert_eq!(group.exceptions, vec!["Error0", "Error1", "Error2"]);
+        assert_eq!(group.handler_example, "except (Error0, Error1, Error2) as e:");
+
+        assert!(format_groups(&db, None, None, false, None).contains("**Relevant to:** 2 functions"));
+        assert!(format_groups(&db, None, Some("app.medium"), false, None).contains("## app errors"));
+        assert!(format_groups(&db, None, Some("app.other"), false, None).contains("No grouping suggestions found"));
+        assert!(format_groups(&db, Some("app"), None, false, None).contains("## app errors"));
+        assert!(format_groups(&db, Some("ap"), None, false, None).contains("No grouping suggestions found"));
+
+        let groups: Vec<&GroupingSuggestion> = db.grouping_suggestions.values().collect();
+        let text = format_function(&db.functions["app.medium"], &groups, false, false);
+        assert!(text.contains("## Grouping Suggestions\n\n- **app errors**: Error0, Error1, Error2\n"));
+    }
+
+    #[test]
+    fn test_search_limit() {
+        let mut db = test_db();
+        for i in 0..5 {
+            db.symbol_index.add(
+                format!("app.helper_{}", i),
+                crate::core::database::SymbolLocation {
+                    file_path: PathBuf::from("app.py"),
+                    line_start: i,
+                    line_end: i + 1,
+                    is_method: false,
+                    parent_class: None,
+                    is_property: false,
+                    property_kind: None,
+                    column_start: None,
+                    column_end: None,
+                    is_stub: false,
+                },
+            );
+        }
+
+        let output = format_search(&db, "app.", 2);
+        assert!(output.contains("**Results:** 9 functions"));
+        assert!(output.contains("- `app.helper_0`"));
+        assert!(!output.contains("- `app.helper_2`"));
+        assert!(output.contains("*... and 3 more unanalyzed functions*"));
+        assert!(output.contains("*... and 2 more analyzed functions*"));
+    }
+
+    #[test]
+    fn test_exception_search() {
+        let mut db = test_db();
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut fetch = analysis("app.fetch", 0, 0);
+        let raised = ["requests.exceptions.Timeout", "requests.exceptions.ConnectionError", "app.errors.Timeout"];
+        for qualified in raised {
+            let short = qualified.rsplit('.').next().unwrap();
+            fetch
+                .raises
+                .push(RaiseStatement::new(short.to_string(), qualified.to_string(), location.clone()));
+        }
+        db.add_function(fetch);
+
+        let ids = |found: Vec<(&FunctionAnalysis, usize)>| -> Vec<(String, usize)> {
+            found.into_iter().map(|(a, n)| (a.function_id.clone(), n)).collect()
+        };
+        let fetch = vec![("app.fetch".to_string(), 2)];
+        assert_eq!(ids(functions_raising_matching(&db, "requests.*", None, None)), fetch);
+        assert_eq!(ids(functions_raising_matching(&db, "Timeout", None, None)), fetch);
+
+        let found = ids(functions_raising_matching(&db, "Error1*", None, None));
+        assert_eq!(found[0], ("app.high_exceptions".to_string(), 3));
+        assert_eq!(found[1], ("app.high_none".to_string(), 1));
+        assert_eq!(found.len(), 3);
+
+        let high = ids(functions_raising_matching(&db, "Error1*", None, Some(RiskLevel::High)));
+        assert_eq!(high.len(), 2);
+        assert_eq!(
+            ids(functions_raising_matching(&db, "Error1*", Some("MEDIUM"), None)),
+            vec![("app.medium".to_string(), 1)]
+        );
+
+        let output = format_exception_search(&db, "Error1*", None, None, 1);
+        assert!(output.contains("| `app.high_exceptions` | 3 | 12 |"));
+        assert!(output.contains("*... and 2 more functions*"));
+        assert!(format_exception_search(&db, "Missing*", None, None, 5).starts_with("No analyzed functions"));
+    }
+
+    #[test]
+    fn test_function_depth_profile() {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut analysis = FunctionAnalysis::new("app.run".to_string(), "def run()".to_string(), location.clone());
+        for (exc, depth) in [("KeyError", 2), ("KeyError", 1), ("ValueError", 0), ("OSError", 1)] {
+            let mut raise = RaiseStatement::new(exc.to_string(), exc.to_string(), location.clone());
+            raise.source_depth = depth;
+            analysis.raises.push(raise);
+        }
+
+        let plain = format_function(&analysis, &[], false, false);
+        assert!(!plain.contains("## Exceptions by Depth"));
+
+        let output = format_function(&analysis, &[], true, false);
+        assert!(output.contains("## Exceptions by Depth"));
+        assert!(output.contains("| `ValueError` | 0 |"));
+        assert!(output.contains("| `KeyError` | 1 |"));
+        assert!(output.contains("| `OSError` | 1 |"));
+        assert!(output.contains(&format!("depth 1 │ {} 2\n", "█".repeat(20))));
+        assert!(output.contains(&format!("depth 0 │ {} 1\n", "█".repeat(10))));
+    }
+
+    #[test]
+    fn test_function_show_chains() {
+        let mut analysis = FunctionAnalysis::new(
+            "app.run".to_string(),
+            "def run()".to_string(),
+            CodeLocation::new(PathBuf::from("app.py"), 1),
+        );
+        fn add(analysis: &mut FunctionAnalysis, exc: &str, line: u32, chain: &[&str]) {
+            let location = CodeLocation::new(PathBuf::from("app.py"), line);
+            analysis
+                .raises
+                .push(RaiseStatement::new(exc.to_string(), exc.to_string(), location.clone()));
+            analysis.call_chains.push(CallChainEntry {
+                exception_type: exc.to_string(),
+                location,
+                chain: chain.iter().map(|s| s.to_string()).collect(),
+            });
+        }
+        add(&mut analysis, "ValueError", 10, &["app.run", "app.validate", "app.check"]);
+        add(&mut analysis, "ValueError", 20, &["app.run", "app.parse"]);
+        add(&mut analysis, "KeyError", 21, &["app.run", "app.parse"]);
+        add(&mut analysis, "OSError", 30, &["app.run"]);
+
+        assert!(!format_function(&analysis, &[], false, false).contains("## Call Chains"));
+
+        let output = format_function(&analysis, &[], false, true);
+        assert!(output.contains("## Call Chains"));
+        assert!(output.contains("app.run\n└── app.parse\n    🔴 KeyError (app.py:21), ValueError (app.py:20)\n"));
+        assert!(output.contains("app.run\n🔴 OSError (app.py:30)\n"));
+        assert!(!output.contains("app.check"));
+        assert!(!output.contains("--all' for all"));
+
+        for i in 0..6 {
+            let callee = format!("app.step{}", i);
+            add(&mut analysis, &format!("Error{}", i), 40 + i, &["app.run", "app.deep", &callee]);
+        }
+        let output = format_function(&analysis, &[], false, true);
+        assert!(output.contains("*Showing 5 of 8 call chains. Run 'arbor query chain app.run <exc> --all' for all.*"));
+        assert!(!output.contains("OSError (app.py:30)"));
+    }
+
+    #[test]
+    fn test_call_graph_percentiles() {
+        let mut db = test_db();
+        assert!(call_graph_percentiles(&db).is_none());
+
+        for (i, analysis) in db.functions.values_mut().enumerate() {
+            analysis.call_graph_stats = crate::core::types::CallGraphStats {
+                width_at_depth: vec![1, i + 1],
+                branching_factor: i as f32 + 1.0,
+                leaf_count: i,
+                cycle_count: 0,
+            };
+        }
+        let percentiles = call_graph_percentiles(&db).unwrap();
+        assert_eq!(percentiles.functions, 4);
+        assert_eq!(
+            percentiles.branching_factor,
+            Percentiles { p50: 2.0, p90: 4.0, p99: 4.0, max: 4.0 }
+        );
+        assert_eq!(percentiles.max_width.max, 4.0);
+
+        let text = format_function(&db.functions["app.high_exceptions"], &[], false, false);
+        assert!(text.contains("| Call graph | shape: "));
+    }
+
+    #[test]
+    fn test_merge_group_entries() {
+        let mut db = test_db();
+        let groups = [
+            ("requests exceptions", ["ConnectionError", "Timeout"]),
+            ("urllib3 exceptions", ["ProtocolError", "Timeout"]),
+        ];
+        for (name, exceptions) in groups {
+            db.grouping_suggestions.insert(
+                name.to_string(),
+                GroupingSuggestion {
+                    group_name: name.to_string(),
+                    exceptions: exceptions.iter().map(|e| e.to_string()).collect(),
+                    rationale: format!("All exceptions from the {}", name.replace(" exceptions", " package")),
+                    handler_example: String::new(),
+                    confidence: 0.9,
+                    applicable_functions: Vec::new(),
+                },
+            );
+        }
+
+        let missing = merge_group_entries(&mut db, "requests exceptions", "nope", "HTTP client errors");
+        assert!(matches!(missing, Err(QueryError::GroupNotFound(name)) if name == "nope"));
+        assert_eq!(db.grouping_suggestions.len(), 2);
+
+        let merged = merge_group_entries(&mut db, "requests exceptions", "urllib3 exceptions", "HTTP client errors")
+            .unwrap();
+        assert_eq!(merged.exceptions, vec!["ConnectionError", "Timeout", "ProtocolError"]);
+        assert_eq!(db.grouping_suggestions.len(), 1);
+        assert!(db.grouping_suggestions.contains_key("HTTP client errors"));
+
+        let text = format_group_definition(&merged);
+        assert!(text.starts_with("## HTTP client errors\n"));
+        assert!(text.contains("**Reason:** All exceptions from the requests package and All exceptions"));
+        assert!(text.contains("- `ProtocolError`\n"));
+    }
+
+    #[test]
+    fn test_locations_only_output() {
+        let base = PathBuf::from("/project");
+        let mut analysis = FunctionAnalysis::new(
+            "myapp.api.run".to_string(),
+            "def run()".to_string(),
+            CodeLocation::new(base.join("myapp/api.py"), 1),
+        );
+        let mut inner = CodeLocation::new(base.join("myapp/api.py"), 42);
+        inner.containing_function = Some("myapp.api.process".to_string());
+        for location in [inner.clone(), inner, CodeLocation::new(base.join("myapp/util.py"), 7)] {
+            analysis
+                .raises
+                .push(RaiseStatement::new("ValueError".to_string(), "ValueError".to_string(), location));
+        }
+        analysis.raises.push(RaiseStatement::new(
+            "KeyError".to_string(),
+            "KeyError".to_string(),
+            CodeLocation::new(PathBuf::from("/elsewhere/lib.py"), 3),
+        ));
+
+        let output = LocationsOnlyOutput::new(analysis.raises.iter().map(|r| ("myapp.api.run", r)), &base);
+        assert_eq!(
+            output.to_text(),
+            "/elsewhere/lib.py:3: KeyError (in myapp.api.run)\n\
+             myapp/api.py:42: ValueError (in myapp.api.process)\n\
+             myapp/util.py:7: ValueError (in myapp.api.run)\n"
+        );
+
+        let json = output.to_json(JsonFormatOptions::default()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+        assert_eq!(json[1]["file"], "myapp/api.py");
+        assert_eq!(json[1]["line"], 42);
+        assert_eq!(json[1]["function"], "myapp.api.process");
+        assert_eq!(json[1]["exception"], "ValueError");
+    }
+
+    #[test]
+    fn test_block_bar_partial_blocks() {
+        assert_eq!(block_bar(0, 4), "");
+        assert_eq!(block_bar(4, 4), "█".repeat(20));
+        // 1/3 of 160 eighths rounds to 53: six full blocks and five eighths.
+        assert_eq!(block_bar(1, 3), format!("{}▋", "█".repeat(6)));
+    }
+
+    #[test]
+    fn test_callers_from_analysis_call_edges() {
+        let mut db = test_db();
+        let mut analysis = analysis("app.entry", 0, 0);
+        analysis.call_edges = vec![
+            ("app.entry".to_string(), "app.low".to_string()),
+            ("app.entry".to_string(), "app.medium".to_string()),
+            ("app.medium".to_string(), "app.low".to_string()),
+        ];
+        for (caller, callee) in &analysis.call_edges {
+            db.dependency_graph.add_call(caller, callee);
+        }
+        db.add_function(analysis);
+
+        let output = format_callers(&db, "app.low", false).unwrap();
+        assert_eq!(output, "Functions calling app.low:\n  - app.entry\n  - app.medium\n");
+        assert_eq!(format_callers(&db, "app.entry", false).unwrap(), "No callers found for app.entry");
+        assert!(matches!(
+            format_callers(&db, "app.unknown", false),
+            Err(QueryError::FunctionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_exception_count_distribution_section() {
+        let db = test_db();
+        let output = format_exception_distribution(&compute_distribution(&db.functions));
+
+        assert!(output.starts_with("## Exception Count Distribution\n"));
+        assert!(output.contains("0 exceptions │  0 functions\n"));
+        assert!(output.contains(&format!("1-2          │ {} 2 functions\n", "█".repeat(20))));
+        assert!(output.contains(&format!("3-5          │ {} 1 function\n", "█".repeat(10))));
+        assert!(output.contains(&format!("11-20        │ {} 1 function\n", "█".repeat(10))));
+        assert!(output.contains("21+          │  0 functions\n"));
+    }
+
+    #[test]
+    fn test_quickref_text_and_json_agree() {
+        let text = query_quickref();
+        assert!(text.contains("\nLOCAL (Entity-Level) Queries:\n  arbor query risk <function>           One-line risk summary\n"));
+        assert!(text.contains("  arbor query one-exception <fn> <exc>  Single exception details\n"));
+        assert!(text.contains("  arbor query stats --by-exception      Statistics per exception type\n"));
+
+        let json = query_quickref_json(JsonFormatOptions::default());
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(entries.len(), QUICKREF.len());
+        let risk = &entries[0];
+        assert_eq!(risk["command"], "risk");
+        assert_eq!(risk["args"], serde_json::json!(["<function>"]));
+        assert_eq!(risk["format_variants"], serde_json::json!(["markdown", "json"]));
+        for entry in &entries {
+            assert!(text.contains(entry["description"].as_str().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_query_json_schema() {
+        let schema = query_json_schema(JsonFormatOptions::default()).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&schema).unwrap();
+        assert_eq!(schema["title"], "FunctionAnalysis");
+        assert!(schema["properties"]["raises"].is_object());
+        for definition in ["RaiseStatement", "NoneSource", "CodeLocation"] {
+            assert!(schema["definitions"][definition].is_object(), "missing {}", definition);
+        }
+        assert_eq!(
+            schema["definitions"]["CodeLocation"]["properties"]["line"]["description"],
+            "1-based line number"
+        );
+    }
+
+    #[test]
+    fn test_list_csv() {
+        let mut db = ArborDatabase::new(Environment::default());
+        let mut quoted = analysis("app.fetch", 2, 0);
+        quoted.location = CodeLocation::new(PathBuf::from("src/a,\"b\".py"), 7);
+        db.add_function(quoted);
+        db.add_function(analysis("app.save", 0, 0));
+
+        let csv = format_list_csv(&db, SortField::Name, None, &NoneSourceWeights::default());
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "function_id,file,line,exception_count,none_source_count,risk_level,risk_score,call_depth,analyzed_at"
+        );
+        assert!(lines[1].starts_with("app.fetch,\"src/a,\"\"b\"\".py\",7,2,0,"));
+        assert!(lines[2].starts_with("app.save,app.py,1,0,0,Low,"));
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3], "");
+    }
+
+    #[test]
+    fn test_risk_all_json() {
+        let mut db = ArborDatabase::new(Environment::default());
+        db.add_function(analysis("app.quiet", 0, 0));
+        db.add_function(analysis("app.noisy", 6, 2));
+        db.add_function(analysis("app.some", 1, 0));
+
+        let weights = NoneSourceWeights::default();
+        let options = JsonFormatOptions::default();
+        let all: serde_json::Value =
+            serde_json::from_str(&format_risk_all_json(&db, None, &weights, options).unwrap()).unwrap();
+        let order: Vec<&str> = all.as_array().unwrap().iter().map(|r| r["function"].as_str().unwrap()).collect();
+        assert_eq!(order, vec!["app.noisy", "app.some", "app.quiet"]);
+
+        let low: serde_json::Value =
+            serde_json::from_str(&format_risk_all_json(&db, Some(RiskLevel::Low), &weights, options).unwrap()).unwrap();
+        assert!(low.as_array().unwrap().iter().all(|r| r["risk_level"] == "Low"));
+        assert!(low.as_array().unwrap().iter().any(|r| r["function"] == "app.quiet"));
+
+        let compact = format_risk_all_json(&db, None, &weights, JsonFormatOptions { pretty: false }).unwrap();
+        assert!(!compact.contains('\n'));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&compact).unwrap(), all);
+    }
 }
@@ -1,5 +1,7 @@
 use super::paths;
+use super::types::NoneSourceKind;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -11,6 +13,9 @@ pub enum ConfigError {
     #[error("TOML parse error: {0}")]
     Toml(#[from] toml::de::Error),
 
+    #[error("TOML parse error: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
+
     #[error("Config file not found at {0}")]
     NotFound(String),
 }
@@ -37,6 +42,25 @@ pub struct AnalysisConfig {
     pub max_depth: usize,
     pub include_stdlib: bool,
     pub timeout_seconds: u64,
+    /// Warn when an analyzed function has more None sources than this.
+    #[serde(alias = "none_source_threshold")]
+    pub max_none_sources: Option<usize>,
+    /// Store the lines around each raise in the database (about 1KB per raise).
+    pub include_source_context: bool,
+    /// Top-level directories that hold packages (`src/mypackage`), left out
+    /// of module paths even when they have an `__init__.py`.
+    pub src_layout_roots: Vec<String>,
+    /// How many directory levels deep the indexer walks; unbounded if unset.
+    pub index_depth: Option<usize>,
+    /// Python files larger than this many KB are skipped when indexing;
+    /// unlimited if unset.
+    pub max_file_size_kb: Option<usize>,
+    /// Write the database after every N analyzed functions; 0 saves only
+    /// at the end of the run.
+    pub checkpoint_every: usize,
+    /// Library exceptions treated like Python builtins: never resolved to a
+    /// definition. Exact names or `*` globs such as `django.core.exceptions.*`.
+    pub builtin_exceptions: Vec<String>,
 }
 
 impl Default for AnalysisConfig {
@@ -45,6 +69,13 @@ impl Default for AnalysisConfig {
             max_depth: 50,
             include_stdlib: false,
             timeout_seconds: 300,
+            max_none_sources: None,
+            include_source_context: true,
+            src_layout_roots: vec!["src".to_string(), "lib".to_string()],
+            index_depth: None,
+            max_file_size_kb: None,
+            checkpoint_every: 10,
+            builtin_exceptions: Vec::new(),
         }
     }
 }
@@ -63,6 +94,88 @@ pub struct IgnoreConfig {
     pub packages: Vec<String>,
     pub functions: Vec<String>,
     pub patterns: Vec<String>,
+    /// Exception types left out of analysis output and risk scoring.
+    pub exception_types: Vec<String>,
+}
+
+/// How much each kind of None source contributes to `risk_score`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoneSourceWeights {
+    pub explicit_return: f64,
+    pub implicit_return: f64,
+    pub function_call: f64,
+    pub collection_access: f64,
+    pub attribute_access: f64,
+    pub conditional_expr: f64,
+    pub match_arm: f64,
+    pub annotated_optional: f64,
+}
+
+impl Default for NoneSourceWeights {
+    fn default() -> Self {
+        Self {
+            explicit_return: 1.0,
+            implicit_return: 0.5,
+            function_call: 1.5,
+            collection_access: 2.0,
+            attribute_access: 1.0,
+            conditional_expr: 1.0,
+            match_arm: 1.0,
+            annotated_optional: 0.5,
+        }
+    }
+}
+
+impl NoneSourceWeights {
+    pub fn weight(&self, kind: &NoneSourceKind) -> f64 {
+        match kind {
+            NoneSourceKind::ExplicitReturn => self.explicit_return,
+            NoneSourceKind::ImplicitReturn => self.implicit_return,
+            NoneSourceKind::FunctionCall => self.function_call,
+            NoneSourceKind::CollectionAccess => self.collection_access,
+            NoneSourceKind::AttributeAccess => self.attribute_access,
+            NoneSourceKind::ConditionalExpr => self.conditional_expr,
+            NoneSourceKind::MatchArm => self.match_arm,
+            NoneSourceKind::AnnotatedOptional => self.annotated_optional,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RiskConfig {
+    pub none_source_weights: NoneSourceWeights,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Total line width markdown tables are fitted to (0 = unlimited).
+    /// `ARBOR_TABLE_WIDTH` overrides it.
+    pub max_table_width: usize,
+}
+
+/// User-defined recovery strategies, mapping an exception type (or `*` glob)
+/// to a strategy name such as `"circuit-break"`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RecoveryConfig {
+    pub strategies: BTreeMap<String, String>,
+}
+
+impl RecoveryConfig {
+    /// The strategy configured for `exc_type`. Exact entries win over globs;
+    /// among globs the first in sorted order applies.
+    pub fn strategy_for(&self, exc_type: &str) -> Option<&str> {
+        if let Some(strategy) = self.strategies.get(exc_type) {
+            return Some(strategy);
+        }
+        self.strategies
+            .iter()
+            .find(|(pattern, _)| pattern.contains('*') && glob_match(pattern, exc_type))
+            .map(|(_, strategy)| strategy.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -72,14 +185,23 @@ pub struct ArborConfig {
     pub analysis: AnalysisConfig,
     pub environment: EnvironmentConfig,
     pub ignore: IgnoreConfig,
+    pub risk: RiskConfig,
+    pub recovery: RecoveryConfig,
+    pub output: OutputConfig,
 }
 
 impl ArborConfig {
+    /// Loads a config file; a `pyproject.toml` is read through its
+    /// `[tool.arbor]` table.
     pub fn load(path: &Path) -> Result<Self, ConfigError> {
         if !path.exists() {
             return Err(ConfigError::NotFound(path.display().to_string()));
         }
 
+        if path.file_name().is_some_and(|name| name == paths::PYPROJECT_FILE) {
+            return PyprojectReader::load(path)?.into_config();
+        }
+
         let content = std::fs::read_to_string(path)?;
         let config: ArborConfig = toml::from_str(&content)?;
         Ok(config)
@@ -92,11 +214,17 @@ impl ArborConfig {
         }
     }
 
+    /// `.arbor/config.toml` if present, otherwise a `pyproject.toml` with a
+    /// `[tool.arbor]` table.
     pub fn find_config() -> Option<PathBuf> {
         let config_path = paths::config_path();
         if config_path.exists() {
             return Some(config_path);
         }
+        let pyproject_path = paths::pyproject_path();
+        if PyprojectReader::load(&pyproject_path).is_ok_and(|reader| reader.has_arbor_table()) {
+            return Some(pyproject_path);
+        }
         None
     }
 
@@ -116,23 +244,15 @@ impl ArborConfig {
     }
 
     pub fn should_ignore_package(&self, package: &str) -> bool {
-        self.ignore.packages.iter().any(|p| {
-            if p.contains('*') {
-                glob_match(p, package)
-            } else {
-                p == package
-            }
-        })
+        self.ignore.packages.iter().any(|p| matches_name_pattern(p, package))
     }
 
     pub fn should_ignore_function(&self, function: &str) -> bool {
-        self.ignore.functions.iter().any(|f| {
-            if f.contains('*') {
-                glob_match(f, function)
-            } else {
-                f == function
-            }
-        })
+        self.ignore.functions.iter().any(|f| matches_name_pattern(f, function))
+    }
+
+    pub fn should_ignore_exception(&self, exc_type: &str) -> bool {
+        self.ignore.exception_types.iter().any(|e| matches_name_pattern(e, exc_type))
     }
 
     pub fn default_toml() -> String {
@@ -147,6 +267,11 @@ auto_save = true
 max_depth = 50
 include_stdlib = false
 timeout_seconds = 300
+# max_none_sources = 10
+# index_depth = 3   # directory levels to index below each path (default: unbounded)
+# max_file_size_kb = 1024   # skip larger .py files when indexing (default: no limit)
+checkpoint_every = 10   # analyze saves after every N functions (0 = only at the end)
+# builtin_exceptions = ["django.core.exceptions.*"]   # never resolved to a definition
 
 [environment]
 python_path = ["."]
@@ -155,6 +280,20 @@ python_path = ["."]
 [ignore]
 packages = ["tests", "__pycache__", ".git"]
 functions = []
+# exception_types = ["Exception", "BaseException"]   # dropped from output and risk
+
+[risk.none_source_weights]
+explicit_return = 1.0
+implicit_return = 0.5
+function_call = 1.5
+collection_access = 2.0
+
+[output]
+max_table_width = 0   # 0 = unlimited; ARBOR_TABLE_WIDTH overrides
+
+[recovery.strategies]
+# "CircuitOpenError" = "circuit-break"
+# "*CacheMiss*" = "fall-back-to-cache"
 "#,
             paths::ARBOR_DIR,
             paths::DATABASE_FILE
@@ -162,6 +301,138 @@ functions = []
     }
 }
 
+/// Reads arbor settings from the `[tool.arbor]` table of a `pyproject.toml`.
+/// The table uses the same layout as `.arbor/config.toml`, e.g.
+/// `[tool.arbor.analysis]`.
+pub struct PyprojectReader {
+    document: toml::Table,
+    root: PathBuf,
+}
+
+impl PyprojectReader {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Err(ConfigError::NotFound(path.display().to_string()));
+        }
+        let content = std::fs::read_to_string(path)?;
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::parse(&content, root)
+    }
+
+    /// Parses `content`, resolving venv candidates relative to `root`.
+    pub fn parse(content: &str, root: PathBuf) -> Result<Self, ConfigError> {
+        let document: toml::Table = toml::from_str(content)?;
+        Ok(Self { document, root })
+    }
+
+    pub fn has_arbor_table(&self) -> bool {
+        self.arbor_table().is_some()
+    }
+
+    fn arbor_table(&self) -> Option<&toml::Table> {
+        self.document.get("tool")?.get("arbor")?.as_table()
+    }
+
+    /// The project declares dependencies through Poetry or PEP 621.
+    pub fn has_dependencies(&self) -> bool {
+        let poetry = self
+            .document
+            .get("tool")
+            .and_then(|tool| tool.get("poetry"))
+            .and_then(|poetry| poetry.get("dependencies"));
+        let project = self
+            .document
+            .get("project")
+            .and_then(|project| project.get("dependencies"));
+        poetry.is_some() || project.is_some()
+    }
+
+    /// An in-project `.venv` for a project with declared dependencies, as
+    /// created by `poetry install` (with `virtualenvs.in-project`), uv or pdm.
+    pub fn detect_venv(&self) -> Option<PathBuf> {
+        if !self.has_dependencies() {
+            return None;
+        }
+        let venv = self.root.join(".venv");
+        let is_venv = venv.join("pyvenv.cfg").exists()
+            || (venv.is_dir() && self.root.join("poetry.lock").exists());
+        is_venv.then(|| PathBuf::from(".venv"))
+    }
+
+    /// `[tool.arbor]` as an `ArborConfig`, with `environment.venv_path` filled
+    /// in from `detect_venv` when the table doesn't set it.
+    pub fn into_config(self) -> Result<ArborConfig, ConfigError> {
+        let mut config: ArborConfig = match self.arbor_table() {
+            Some(table) => table.clone().try_into()?,
+            None => ArborConfig::default(),
+        };
+        if config.environment.venv_path.is_none() {
+            config.environment.venv_path = self.detect_venv();
+        }
+        Ok(config)
+    }
+}
+
+/// Frameworks with bundled `builtin_exceptions` patterns, for `init --add-builtins`.
+pub const KNOWN_BUILTIN_FRAMEWORKS: &[&str] = &["django", "sqlalchemy", "requests"];
+
+/// Exception patterns that ship with `framework`, lowercase name as in
+/// `KNOWN_BUILTIN_FRAMEWORKS`.
+pub fn framework_builtin_exceptions(framework: &str) -> Option<&'static [&'static str]> {
+    let patterns: &'static [&'static str] = match framework.to_ascii_lowercase().as_str() {
+        "django" => &[
+            "django.core.exceptions.*",
+            "django.http.Http404",
+            "django.db.utils.*",
+            "django.db.transaction.TransactionManagementError",
+            "django.urls.exceptions.*",
+            "django.template.TemplateDoesNotExist",
+            "django.template.TemplateSyntaxError",
+            "*.DoesNotExist",
+            "*.MultipleObjectsReturned",
+        ],
+        "sqlalchemy" => &["sqlalchemy.exc.*", "sqlalchemy.orm.exc.*"],
+        "requests" => &["requests.exceptions.*"],
+        _ => return None,
+    };
+    Some(patterns)
+}
+
+/// Append the bundled `builtin_exceptions` patterns for `framework` to the
+/// `[analysis]` table of a config document, skipping any already present.
+/// Editing the document rather than re-serializing an `ArborConfig` keeps the
+/// file's comments. Returns how many were added, or `None` for a framework
+/// with no bundled patterns.
+pub fn add_framework_builtins(document: &mut toml_edit::DocumentMut, framework: &str) -> Option<usize> {
+    let patterns = framework_builtin_exceptions(framework)?;
+    let analysis = document
+        .entry("analysis")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()?;
+    if !analysis.get("builtin_exceptions").is_some_and(|item| item.is_array()) {
+        analysis.insert("builtin_exceptions", toml_edit::value(toml_edit::Array::new()));
+    }
+    let existing = analysis.get_mut("builtin_exceptions")?.as_array_mut()?;
+
+    let before = existing.len();
+    for pattern in patterns {
+        if !existing.iter().any(|e| e.as_str() == Some(pattern)) {
+            existing.push(*pattern);
+        }
+    }
+    Some(existing.len() - before)
+}
+
+/// Match a package or function name against an ignore entry, which is either
+/// an exact name or a `*` glob.
+pub fn matches_name_pattern(pattern: &str, name: &str) -> bool {
+    if pattern.contains('*') {
+        glob_match(pattern, name)
+    } else {
+        pattern == name
+    }
+}
+
 fn glob_match(pattern: &str, text: &str) -> bool {
     let pattern_parts: Vec<&str> = pattern.split('*').collect();
 
@@ -280,6 +551,7 @@ max_depth = 25
 [ignore]
 packages = ["tests", "__pycache__"]
 functions = ["*._private_*", "test_*"]
+exception_types = ["Exception", "*.Base*"]
 "#,
         )
         .unwrap();
@@ -291,6 +563,47 @@ functions = ["*._private_*", "test_*"]
         assert!(config.should_ignore_function("module._private_func"));
         assert!(config.should_ignore_function("test_something"));
         assert!(!config.should_ignore_function("public_func"));
+
+        assert!(config.should_ignore_exception("Exception"));
+        assert!(config.should_ignore_exception("myapp.errors.BaseError"));
+        assert!(!config.should_ignore_exception("ValueError"));
+    }
+
+    #[test]
+    fn test_add_framework_builtins() {
+        let mut document: toml_edit::DocumentMut = r#"
+# My settings
+[analysis]
+max_depth = 20   # deep enough
+builtin_exceptions = ["sqlalchemy.exc.*"]
+"#
+        .parse()
+        .unwrap();
+
+        assert_eq!(add_framework_builtins(&mut document, "Django"), Some(9));
+        assert_eq!(add_framework_builtins(&mut document, "django"), Some(0));
+        assert_eq!(add_framework_builtins(&mut document, "sqlalchemy"), Some(1));
+        assert_eq!(add_framework_builtins(&mut document, "flask"), None);
+
+        let content = document.to_string();
+        assert!(content.contains("# My settings"));
+        assert!(content.contains("max_depth = 20   # deep enough"));
+        let config: ArborConfig = toml::from_str(&content).unwrap();
+        let builtins = &config.analysis.builtin_exceptions;
+        assert_eq!(builtins.len(), 11);
+        assert!(builtins.iter().any(|e| matches_name_pattern(e, "shop.models.Order.DoesNotExist")));
+    }
+
+    #[test]
+    fn test_add_framework_builtins_to_default_template() {
+        let mut document: toml_edit::DocumentMut = ArborConfig::default_toml().parse().unwrap();
+        assert_eq!(add_framework_builtins(&mut document, "requests"), Some(1));
+
+        let content = document.to_string();
+        assert!(content.starts_with("# Arbor Configuration"));
+        assert!(content.contains("# venv_path = \".venv\""));
+        let config: ArborConfig = toml::from_str(&content).unwrap();
+        assert_eq!(config.analysis.builtin_exceptions, vec!["requests.exceptions.*"]);
     }
 
     #[test]
@@ -299,4 +612,95 @@ functions = ["*._private_*", "test_*"]
         let config: Result<ArborConfig, _> = toml::from_str(&toml_str);
         assert!(config.is_ok());
     }
+
+    #[test]
+    fn test_parse_risk_config() {
+        let toml_str = r#"
+[analysis]
+none_source_threshold = 4
+
+[risk.none_source_weights]
+implicit_return = 0.25
+collection_access = 3.0
+"#;
+
+        let config: ArborConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.analysis.max_none_sources, Some(4));
+
+        let weights = &config.risk.none_source_weights;
+        assert_eq!(weights.weight(&NoneSourceKind::ImplicitReturn), 0.25);
+        assert_eq!(weights.weight(&NoneSourceKind::CollectionAccess), 3.0);
+        assert_eq!(weights.weight(&NoneSourceKind::FunctionCall), 1.5);
+    }
+
+    #[test]
+    fn test_default_toml_risk_weights() {
+        let config: ArborConfig = toml::from_str(&ArborConfig::default_toml()).unwrap();
+        assert_eq!(config.risk.none_source_weights.explicit_return, 1.0);
+        assert_eq!(config.analysis.max_none_sources, None);
+    }
+
+    #[test]
+    fn test_pyproject_tool_arbor_table() {
+        let reader = PyprojectReader::parse(
+            r#"
+[project]
+name = "myapp"
+
+[tool.arbor.analysis]
+max_depth = 20
+
+[tool.arbor.ignore]
+packages = ["migrations"]
+
+[tool.black]
+line-length = 100
+"#,
+            PathBuf::from("/nonexistent"),
+        )
+        .unwrap();
+
+        assert!(reader.has_arbor_table());
+        assert!(!reader.has_dependencies());
+        let config = reader.into_config().unwrap();
+        assert_eq!(config.analysis.max_depth, 20);
+        assert_eq!(config.ignore.packages, vec!["migrations"]);
+        assert_eq!(config.analysis.timeout_seconds, 300);
+        assert_eq!(config.environment.venv_path, None);
+    }
+
+    #[test]
+    fn test_pyproject_detects_venv_from_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".venv")).unwrap();
+        std::fs::write(dir.path().join("poetry.lock"), "").unwrap();
+        let poetry = "[tool.poetry.dependencies]\npython = \"^3.11\"\n";
+
+        let reader = PyprojectReader::parse(poetry, dir.path().to_path_buf()).unwrap();
+        assert!(!reader.has_arbor_table());
+        assert_eq!(reader.detect_venv(), Some(PathBuf::from(".venv")));
+        assert_eq!(
+            reader.into_config().unwrap().environment.venv_path,
+            Some(PathBuf::from(".venv"))
+        );
+
+        // An explicit venv in [tool.arbor] wins over detection
+        let explicit = format!("{}[tool.arbor.environment]\nvenv_path = \"env\"\n", poetry);
+        let reader = PyprojectReader::parse(&explicit, dir.path().to_path_buf()).unwrap();
+        assert_eq!(reader.into_config().unwrap().environment.venv_path, Some(PathBuf::from("env")));
+
+        // Without declared dependencies the .venv is not assumed to belong to the project
+        let reader = PyprojectReader::parse("[project]\nname = \"x\"\n", dir.path().to_path_buf()).unwrap();
+        assert_eq!(reader.detect_venv(), None);
+    }
+
+    #[test]
+    fn test_load_dispatches_on_pyproject() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(paths::PYPROJECT_FILE);
+        std::fs::write(&path, "[tool.arbor.analysis]\ninclude_stdlib = true\n").unwrap();
+
+        let config = ArborConfig::load(&path).unwrap();
+        assert!(config.analysis.include_stdlib);
+    }
 }
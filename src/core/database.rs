@@ -1,10 +1,32 @@
-use crate::core::types::{CallGraph, FunctionAnalysis, ResolvedFunction};
+use crate::core::paths;
+use crate::core::types::{
+    absolute_path, is_stub_file, relative_path, CallGraph, CodeLocation, FunctionAnalysis, PropertyKind,
+    ResolvedFunction, RiskLevel,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::ops::Bound;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
 use thiserror::Error;
 
+/// The last database read by `load_cached`, keyed by path and the file's
+/// modified time so a write from another process is noticed.
+static DATABASE_CACHE: Mutex<Option<(PathBuf, ArborDatabase, SystemTime)>> = Mutex::new(None);
+
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn the in-process database cache on or off (`query --no-cache`).
+pub fn set_database_cache(enabled: bool) {
+    CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        clear_cache();
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum DatabaseError {
     #[error("IO error: {0}")]
@@ -18,14 +40,52 @@ pub enum DatabaseError {
 
     #[error("Database version mismatch: expected {expected}, found {found}")]
     VersionMismatch { expected: String, found: String },
+
+    #[error("Database is corrupt: {reason}\n{suggestion}")]
+    Corrupt { reason: String, suggestion: String },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+const RECREATE_SUGGESTION: &str = "Run 'arbor init --force' to recreate the database";
+
+impl DatabaseError {
+    /// `Corrupt` when the file is valid JSON of the wrong shape (missing
+    /// fields, wrong types, unparseable dates), otherwise `Json`.
+    fn from_load_error(error: serde_json::Error) -> Self {
+        match error.classify() {
+            serde_json::error::Category::Data => DatabaseError::Corrupt {
+                reason: error.to_string(),
+                suggestion: RECREATE_SUGGESTION.to_string(),
+            },
+            _ => DatabaseError::Json(error),
+        }
+    }
+}
+
+/// What `ArborDatabase::try_load_partial` could read from a damaged file.
+#[derive(Debug)]
+pub struct PartialDatabase {
+    pub database: ArborDatabase,
+    /// IDs of function entries that could not be read and were dropped.
+    pub skipped_functions: Vec<String>,
+    /// One message per part of the file that was lost.
+    pub errors: Vec<String>,
+}
+
+impl PartialDatabase {
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Environment {
     pub python_version: String,
     pub venv_path: Option<String>,
     pub site_packages: Vec<String>,
     pub python_path: Vec<String>,
+    /// Interpreter given to `arbor init --python`, if any.
+    #[serde(default)]
+    pub python_interpreter: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +94,36 @@ pub struct GroupingSuggestion {
     pub exceptions: Vec<String>,
     pub rationale: String,
     pub handler_example: String,
+    /// How reliable the grouping is, from 0.0 to 1.0.
+    #[serde(default = "default_group_confidence")]
+    pub confidence: f32,
+    /// Analyzed functions whose raises produced this group.
+    #[serde(default)]
+    pub applicable_functions: Vec<String>,
+}
+
+impl GroupingSuggestion {
+    pub fn applies_to(&self, function_id: &str) -> bool {
+        self.applicable_functions.iter().any(|f| f == function_id)
+    }
+
+    /// Whether any function the group applies to is `package` or lives under
+    /// it. Groups saved before applicable functions were recorded match when
+    /// their name or one of their exceptions starts with `package`.
+    pub fn applies_to_package(&self, package: &str) -> bool {
+        if self.applicable_functions.is_empty() {
+            return self.group_name.starts_with(package) || self.exceptions.iter().any(|e| e.starts_with(package));
+        }
+        let prefix = format!("{}.", package);
+        self.applicable_functions
+            .iter()
+            .any(|f| f == package || f.starts_with(&prefix))
+    }
+}
+
+/// Groups saved before confidence scores were recorded count as medium.
+fn default_group_confidence() -> f32 {
+    0.5
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,21 +133,48 @@ pub struct SymbolLocation {
     pub line_end: u32,
     pub is_method: bool,
     pub parent_class: Option<String>,
+    #[serde(default)]
+    pub is_property: bool,
+    #[serde(default)]
+    pub property_kind: Option<PropertyKind>,
+    /// Column span of the function or class name on `line_start`, so editors
+    /// land on the name rather than the `def`/`class` keyword.
+    #[serde(default)]
+    pub column_start: Option<u32>,
+    #[serde(default)]
+    pub column_end: Option<u32>,
+    /// Declared in a `.pyi` stub rather than in source.
+    #[serde(default)]
+    pub is_stub: bool,
 }
 
 impl From<ResolvedFunction> for SymbolLocation {
     fn from(rf: ResolvedFunction) -> Self {
         Self {
+            is_stub: is_stub_file(&rf.file_path),
             file_path: rf.file_path,
             line_start: rf.line_start,
             line_end: rf.line_end,
             is_method: rf.is_method,
             parent_class: rf.parent_class,
+            is_property: false,
+            property_kind: None,
+            column_start: None,
+            column_end: None,
         }
     }
 }
 
 impl SymbolLocation {
+    /// `file:line:column` when the name's column is known, else `file:line`.
+    pub fn to_code_location(&self) -> CodeLocation {
+        let location = CodeLocation::new(self.file_path.clone(), self.line_start);
+        match self.column_start {
+            Some(column) => location.with_column(column),
+            None => location,
+        }
+    }
+
     pub fn to_resolved(&self, name: &str) -> ResolvedFunction {
         ResolvedFunction {
             file_path: self.file_path.clone(),
@@ -72,9 +189,20 @@ impl SymbolLocation {
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SymbolIndex {
-    pub symbols: HashMap<String, SymbolLocation>,
+    pub symbols: BTreeMap<String, SymbolLocation>,
     pub indexed_at: Option<DateTime<Utc>>,
     pub file_hashes: HashMap<PathBuf, String>,
+    /// Files left out because they exceeded `analysis.max_file_size_kb`.
+    #[serde(default)]
+    pub skipped_large_files: Vec<PathBuf>,
+    /// Lowercase byte trigram -> symbols containing it, for substring search.
+    /// Not persisted; rebuilt on load.
+    #[serde(skip)]
+    pub trigram_index: HashMap<[u8; 3], Vec<String>>,
+    /// File -> symbols defined in it, so a file's symbols can be dropped
+    /// without scanning the whole index. Not persisted; rebuilt on load.
+    #[serde(skip)]
+    pub reverse_file_index: HashMap<PathBuf, Vec<String>>,
 }
 
 impl SymbolIndex {
@@ -83,7 +211,52 @@ impl SymbolIndex {
     }
 
     pub fn add(&mut self, qualified_name: String, location: SymbolLocation) {
-        self.symbols.insert(qualified_name, location);
+        let file = location.file_path.clone();
+        match self.symbols.insert(qualified_name.clone(), location) {
+            None => {
+                for trigram in trigrams(&qualified_name.to_lowercase()) {
+                    self.trigram_index.entry(trigram).or_default().push(qualified_name.clone());
+                }
+            }
+            Some(previous) if previous.file_path != file => {
+                if let Some(names) = self.reverse_file_index.get_mut(&previous.file_path) {
+                    names.retain(|name| name != &qualified_name);
+                }
+            }
+            Some(_) => return,
+        }
+        self.reverse_file_index.entry(file).or_default().push(qualified_name);
+    }
+
+    /// Removes every symbol defined in `path`, returning how many there were.
+    pub fn remove_file(&mut self, path: &Path) -> usize {
+        let Some(names) = self.reverse_file_index.remove(path) else {
+            return 0;
+        };
+        for name in &names {
+            self.symbols.remove(name);
+            for trigram in trigrams(&name.to_lowercase()) {
+                if let Some(entries) = self.trigram_index.get_mut(&trigram) {
+                    entries.retain(|entry| entry != name);
+                    if entries.is_empty() {
+                        self.trigram_index.remove(&trigram);
+                    }
+                }
+            }
+        }
+        names.len()
+    }
+
+    /// Removes every symbol defined in a file inside `archive`, returning
+    /// how many there were.
+    pub fn remove_archive(&mut self, archive: &Path) -> usize {
+        let files: Vec<PathBuf> = self
+            .reverse_file_index
+            .keys()
+            .filter(|file| file.starts_with(archive))
+            .cloned()
+            .collect();
+        files.iter().map(|file| self.remove_file(file)).sum()
     }
 
     pub fn get(&self, qualified_name: &str) -> Option<&SymbolLocation> {
@@ -102,6 +275,77 @@ impl SymbolIndex {
         self.symbols.is_empty()
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &SymbolLocation)> {
+        self.symbols.iter()
+    }
+
+    /// Symbols whose name starts with `prefix`, in order.
+    pub fn with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a String, &'a SymbolLocation)> {
+        self.symbols
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(name, _)| name.starts_with(prefix))
+    }
+
+    /// Case-insensitive search: names starting with `query` first, then names
+    /// containing it, each group sorted by name.
+    pub fn search(&self, query: &str) -> Vec<(&str, &SymbolLocation)> {
+        let query_lower = query.to_lowercase();
+        let query_trigrams = trigrams(&query_lower);
+
+        let candidates: Box<dyn Iterator<Item = (&String, &SymbolLocation)>> = if query_trigrams.is_empty() {
+            Box::new(self.symbols.iter())
+        } else {
+            let rarest = query_trigrams
+                .iter()
+                .map(|t| self.trigram_index.get(t).map_or(&[][..], Vec::as_slice))
+                .min_by_key(|names| names.len())
+                .unwrap_or(&[]);
+            Box::new(
+                rarest
+                    .iter()
+                    .filter_map(|name| self.symbols.get_key_value(name.as_str())),
+            )
+        };
+
+        let mut matches: Vec<(bool, &str, &SymbolLocation)> = candidates
+            .filter_map(|(name, location)| {
+                let lower = name.to_lowercase();
+                if lower.starts_with(&query_lower) {
+                    Some((false, name.as_str(), location))
+                } else if lower.contains(&query_lower) {
+                    Some((true, name.as_str(), location))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        matches
+            .into_iter()
+            .map(|(_, name, location)| (name, location))
+            .collect()
+    }
+
+    pub fn rebuild_trigram_index(&mut self) {
+        self.trigram_index.clear();
+        for name in self.symbols.keys() {
+            for trigram in trigrams(&name.to_lowercase()) {
+                self.trigram_index.entry(trigram).or_default().push(name.clone());
+            }
+        }
+    }
+
+    pub fn rebuild_reverse_file_index(&mut self) {
+        self.reverse_file_index.clear();
+        for (name, location) in &self.symbols {
+            self.reverse_file_index
+                .entry(location.file_path.clone())
+                .or_default()
+                .push(name.clone());
+        }
+    }
+
     pub fn mark_indexed(&mut self) {
         self.indexed_at = Some(Utc::now());
     }
@@ -118,6 +362,29 @@ impl SymbolIndex {
     }
 }
 
+/// How many `StatsSnapshot`s `ArborDatabase::stats_history` keeps.
+pub const STATS_HISTORY_LIMIT: usize = 10;
+
+/// Headline metrics recorded when the database is saved, for trends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub function_count: usize,
+    pub symbol_count: usize,
+    pub unique_exceptions: usize,
+    pub none_sources: usize,
+    pub high_risk: usize,
+    pub medium_risk: usize,
+    pub low_risk: usize,
+}
+
+impl StatsSnapshot {
+    /// Same metrics, whenever they were taken.
+    pub fn same_metrics(&self, other: &StatsSnapshot) -> bool {
+        StatsSnapshot { timestamp: other.timestamp, ..self.clone() } == *other
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArborDatabase {
     pub version: String,
@@ -128,6 +395,12 @@ pub struct ArborDatabase {
     pub functions: HashMap<String, FunctionAnalysis>,
     pub dependency_graph: CallGraph,
     pub grouping_suggestions: HashMap<String, GroupingSuggestion>,
+    /// Short exception type name -> sorted IDs of functions that raise it.
+    #[serde(default)]
+    pub exception_index: HashMap<String, Vec<String>>,
+    /// Metrics at each save that changed them, oldest first.
+    #[serde(default)]
+    pub stats_history: VecDeque<StatsSnapshot>,
 }
 
 impl ArborDatabase {
@@ -142,6 +415,8 @@ impl ArborDatabase {
             functions: HashMap::new(),
             dependency_graph: CallGraph::new(),
             grouping_suggestions: HashMap::new(),
+            exception_index: HashMap::new(),
+            stats_history: VecDeque::new(),
         }
     }
 
@@ -150,28 +425,450 @@ impl ArborDatabase {
             return Err(DatabaseError::NotFound(path.display().to_string()));
         }
         let content = std::fs::read_to_string(path)?;
-        let db: Self = serde_json::from_str(&content)?;
+        let mut db: Self = serde_json::from_str(&content).map_err(DatabaseError::from_load_error)?;
+        db.finish_load(path)?;
+        Ok(db)
+    }
+
+    /// Like `load`, but returns a copy of the cached database when `path` is
+    /// the file last loaded and its modified time is unchanged.
+    pub fn load_cached(path: &Path) -> Result<Self, DatabaseError> {
+        if !CACHE_ENABLED.load(Ordering::Relaxed) || !path.exists() {
+            return Self::load(path);
+        }
+        let modified = std::fs::metadata(path)?.modified()?;
+        if let Ok(cache) = DATABASE_CACHE.lock() {
+            if let Some((ref cached_path, ref db, cached_modified)) = *cache {
+                if cached_path == path && cached_modified == modified {
+                    return Ok(db.clone());
+                }
+            }
+        }
+        let db = Self::load(path)?;
+        if let Ok(mut cache) = DATABASE_CACHE.lock() {
+            *cache = Some((path.to_path_buf(), db.clone(), modified));
+        }
         Ok(db)
     }
 
-    pub fn save(&self, path: &Path) -> Result<(), DatabaseError> {
-        let content = serde_json::to_string_pretty(self)?;
+    /// Reads whatever `path` still holds: each top-level section that fails
+    /// to parse is left at its default and each unreadable function entry is
+    /// skipped, with the losses listed in the result.
+    pub fn try_load_partial(path: &Path) -> PartialDatabase {
+        let mut partial = PartialDatabase {
+            database: ArborDatabase::new(Environment::default()),
+            skipped_functions: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let root = match std::fs::read_to_string(path)
+            .map_err(DatabaseError::from)
+            .and_then(|content| serde_json::from_str(&content).map_err(DatabaseError::from))
+        {
+            Ok(serde_json::Value::Object(root)) => root,
+            Ok(_) => {
+                partial.errors.push("database root is not an object".to_string());
+                return partial;
+            }
+            Err(e) => {
+                partial.errors.push(e.to_string());
+                return partial;
+            }
+        };
+
+        fn section<T: serde::de::DeserializeOwned>(
+            root: &serde_json::Map<String, serde_json::Value>,
+            name: &str,
+            errors: &mut Vec<String>,
+        ) -> Option<T> {
+            let Some(value) = root.get(name) else {
+                errors.push(format!("missing field `{}`", name));
+                return None;
+            };
+            serde_json::from_value(value.clone())
+                .map_err(|e| errors.push(format!("`{}`: {}", name, e)))
+                .ok()
+        }
+
+        let db = &mut partial.database;
+        let errors = &mut partial.errors;
+        if let Some(version) = section(&root, "version", errors) {
+            db.version = version;
+        }
+        if let Some(created_at) = section(&root, "created_at", errors) {
+            db.created_at = created_at;
+        }
+        if let Some(updated_at) = section(&root, "updated_at", errors) {
+            db.updated_at = updated_at;
+        }
+        if let Some(environment) = section(&root, "environment", errors) {
+            db.environment = environment;
+        }
+        if let Some(symbol_index) = section(&root, "symbol_index", errors) {
+            db.symbol_index = symbol_index;
+        }
+        if let Some(dependency_graph) = section(&root, "dependency_graph", errors) {
+            db.dependency_graph = dependency_graph;
+        }
+        if let Some(grouping_suggestions) = section(&root, "grouping_suggestions", errors) {
+            db.grouping_suggestions = grouping_suggestions;
+        }
+        if root.contains_key("stats_history") {
+            if let Some(stats_history) = section(&root, "stats_history", errors) {
+                db.stats_history = stats_history;
+            }
+        }
+
+        match root.get("functions") {
+            Some(serde_json::Value::Object(functions)) => {
+                for (id, value) in functions {
+                    match serde_json::from_value::<FunctionAnalysis>(value.clone()) {
+                        Ok(analysis) => {
+                            db.functions.insert(id.clone(), analysis);
+                        }
+                        Err(e) => {
+                            errors.push(format!("function `{}`: {}", id, e));
+                            partial.skipped_functions.push(id.clone());
+                        }
+                    }
+                }
+            }
+            Some(_) => errors.push("`functions` is not an object".to_string()),
+            None => errors.push("missing field `functions`".to_string()),
+        }
+        partial.skipped_functions.sort();
+
+        db.exception_index.clear();
+        if let Err(e) = db.finish_load(path) {
+            errors.push(e.to_string());
+        }
+        partial
+    }
+
+    /// Resolves stored paths against the project root and rebuilds the
+    /// indexes that aren't persisted.
+    fn finish_load(&mut self, path: &Path) -> Result<(), DatabaseError> {
+        // Databases saved before paths were stored relative hold absolute
+        // paths, which are kept as they are; relative ones are resolved here.
+        let root = project_root(path)?;
+        self.rewrite_paths(&|p| absolute_path(p, &root));
+        self.symbol_index.rebuild_trigram_index();
+        self.symbol_index.rebuild_reverse_file_index();
+        if !self.exception_index_is_valid() {
+            self.rebuild_exception_index();
+        }
+        Ok(())
+    }
+
+    /// Writes the database with paths under the project root stored relative
+    /// to it, so the file can be committed and shared.
+    ///
+    /// Also records a `StatsSnapshot` if the metrics changed since the last one.
+    pub fn save(&mut self, path: &Path) -> Result<(), DatabaseError> {
+        self.record_stats_snapshot();
+        self.checkpoint(path)
+    }
+
+    /// Like `save`, but without recording a `StatsSnapshot`; for intermediate
+    /// writes during a long run, so the trend only sees finished runs.
+    pub fn checkpoint(&self, path: &Path) -> Result<(), DatabaseError> {
+        let root = project_root(path)?;
+        let mut portable = self.clone();
+        portable.rewrite_paths(&|p| relative_path(p, &root));
+        let content = serde_json::to_string_pretty(&portable)?;
+        // A write within the file system's mtime resolution would otherwise
+        // leave the old copy looking current
+        clear_cache();
         std::fs::write(path, content)?;
         Ok(())
     }
 
+    /// The current metrics, timestamped now.
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        let count_risk = |level: RiskLevel| self.functions.values().filter(|a| a.risk_level() == level).count();
+        StatsSnapshot {
+            timestamp: Utc::now(),
+            function_count: self.functions.len(),
+            symbol_count: self.symbol_index.len(),
+            unique_exceptions: self.exception_index.len(),
+            none_sources: self.functions.values().map(|a| a.none_source_count()).sum(),
+            high_risk: count_risk(RiskLevel::High),
+            medium_risk: count_risk(RiskLevel::Medium),
+            low_risk: count_risk(RiskLevel::Low),
+        }
+    }
+
+    fn record_stats_snapshot(&mut self) {
+        let snapshot = self.stats_snapshot();
+        if self.stats_history.back().is_some_and(|last| last.same_metrics(&snapshot)) {
+            return;
+        }
+        self.stats_history.push_back(snapshot);
+        while self.stats_history.len() > STATS_HISTORY_LIMIT {
+            self.stats_history.pop_front();
+        }
+    }
+
+    /// The latest snapshot whose metrics differ from `current`, i.e. the
+    /// state before the most recent change.
+    pub fn previous_stats_snapshot(&self, current: &StatsSnapshot) -> Option<&StatsSnapshot> {
+        self.stats_history.iter().rev().find(|snapshot| !snapshot.same_metrics(current))
+    }
+
+    /// Applies `rewrite` to every file path stored in the database.
+    fn rewrite_paths(&mut self, rewrite: &dyn Fn(&Path) -> PathBuf) {
+        let rewrite_location = |location: &mut CodeLocation| location.file = rewrite(&location.file);
+
+        for symbol in self.symbol_index.symbols.values_mut() {
+            symbol.file_path = rewrite(&symbol.file_path);
+        }
+        self.symbol_index.file_hashes = self
+            .symbol_index
+            .file_hashes
+            .drain()
+            .map(|(file, hash)| (rewrite(&file), hash))
+            .collect();
+        for file in &mut self.symbol_index.skipped_large_files {
+            *file = rewrite(file);
+        }
+
+        for analysis in self.functions.values_mut() {
+            rewrite_location(&mut analysis.location);
+            for raise in analysis.raises.iter_mut().chain(&mut analysis.ignored_raises) {
+                rewrite_location(&mut raise.raise_location);
+                if let Some(ref mut location) = raise.definition_location {
+                    rewrite_location(location);
+                }
+                if let Some(ref mut handler) = raise.suppressed_by {
+                    rewrite_location(&mut handler.location);
+                }
+            }
+            for source in &mut analysis.none_sources {
+                rewrite_location(&mut source.location);
+                if let Some(ref mut location) = source.source_definition {
+                    rewrite_location(location);
+                }
+            }
+            for handler in &mut analysis.handlers {
+                rewrite_location(&mut handler.location);
+            }
+            for suppressor in &mut analysis.suppressors {
+                rewrite_location(&mut suppressor.location);
+            }
+            for clause in analysis.catches.values_mut().flatten() {
+                rewrite_location(&mut clause.location);
+            }
+            for entry in &mut analysis.call_chains {
+                rewrite_location(&mut entry.location);
+            }
+        }
+    }
+
     pub fn add_function(&mut self, analysis: FunctionAnalysis) {
         self.updated_at = Utc::now();
+        if let Some(previous) = self.functions.remove(&analysis.function_id) {
+            unindex_exceptions(&mut self.exception_index, &previous);
+        }
+        index_exceptions(&mut self.exception_index, &analysis);
         self.functions.insert(analysis.function_id.clone(), analysis);
     }
 
+    /// Keeps only analyses made at or after `since`; those without a
+    /// timestamp are dropped. For filtered views, so `updated_at` is left as is.
+    pub fn retain_analyzed_since(&mut self, since: DateTime<Utc>) {
+        self.functions
+            .retain(|_, analysis| analysis.analyzed_at.is_some_and(|at| at >= since));
+        self.rebuild_exception_index();
+    }
+
     pub fn get_function(&self, id: &str) -> Option<&FunctionAnalysis> {
         self.functions.get(id)
     }
 
+    /// Removes the analysis of `id` and drops it from the functions grouping
+    /// suggestions apply to; a group left applying to nothing is removed.
     pub fn remove_function(&mut self, id: &str) -> Option<FunctionAnalysis> {
         self.updated_at = Utc::now();
-        self.functions.remove(id)
+        let removed = self.functions.remove(id);
+        if let Some(ref analysis) = removed {
+            unindex_exceptions(&mut self.exception_index, analysis);
+            self.grouping_suggestions.retain(|_, group| {
+                if !group.applies_to(id) {
+                    return true;
+                }
+                group.applicable_functions.retain(|f| f != id);
+                !group.applicable_functions.is_empty()
+            });
+        }
+        removed
+    }
+
+    /// Removes every analyzed function in `package` (its ID is the package
+    /// name or starts with `<package>.`) along with its call graph edges.
+    /// Grouping suggestions are dropped when all their exceptions were raised
+    /// only by the removed functions. Returns the removed IDs, sorted.
+    pub fn remove_package(&mut self, package: &str) -> Vec<String> {
+        let prefix = format!("{}.", package);
+        let ids: Vec<String> = self
+            .functions
+            .keys()
+            .filter(|id| *id == package || id.starts_with(&prefix))
+            .cloned()
+            .collect();
+
+        let mut raised: HashSet<String> = HashSet::new();
+        for id in &ids {
+            if let Some(analysis) = self.remove_function(id) {
+                raised.extend(
+                    analysis
+                        .raises
+                        .iter()
+                        .filter_map(|r| exception_index_key(&r.exception_type))
+                        .map(str::to_string),
+                );
+            }
+            self.dependency_graph.remove_function(id);
+        }
+
+        let orphaned = |exc: &String| {
+            let short = exc.rsplit('.').next().unwrap_or(exc);
+            raised.contains(short) && self.functions_raising(exc).next().is_none()
+        };
+        let stale: Vec<String> = self
+            .grouping_suggestions
+            .iter()
+            .filter(|(_, group)| group.exceptions.iter().all(orphaned))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in stale {
+            self.grouping_suggestions.remove(&name);
+        }
+
+        let mut ids = ids;
+        ids.sort();
+        ids
+    }
+
+    /// Saves `suggestion`. When a group of the same name is already saved,
+    /// its exceptions and the functions it applied to are kept alongside the
+    /// new ones, and the handler example names every exception.
+    pub fn add_grouping_suggestion(&mut self, mut suggestion: GroupingSuggestion) {
+        if let Some(existing) = self.grouping_suggestions.get(&suggestion.group_name) {
+            let listed = format!("({})", suggestion.exceptions.join(", "));
+            let mut exceptions = existing.exceptions.clone();
+            for exc in &suggestion.exceptions {
+                if !exceptions.contains(exc) {
+                    exceptions.push(exc.clone());
+                }
+            }
+            // Every generated handler names its exceptions as one `except (...)` tuple
+            suggestion.handler_example = suggestion
+                .handler_example
+                .replace(&listed, &format!("({})", exceptions.join(", ")));
+            suggestion.exceptions = exceptions;
+
+            for function in &existing.applicable_functions {
+                if !suggestion.applies_to(function) {
+                    suggestion.applicable_functions.push(function.clone());
+                }
+            }
+        }
+        self.grouping_suggestions.insert(suggestion.group_name.clone(), suggestion);
+    }
+
+    /// The union of two databases, e.g. from separate roots of a monorepo.
+    /// Where both analyzed the same function, the later `analyzed_at` wins
+    /// (one without a timestamp loses); where both indexed the same symbol or
+    /// file, the index built last wins. Ties keep `self`. Grouping suggestions
+    /// merge as in `add_grouping_suggestion`.
+    pub fn merge(mut self, other: ArborDatabase) -> ArborDatabase {
+        for path in other.environment.python_path {
+            if !self.environment.python_path.contains(&path) {
+                self.environment.python_path.push(path);
+            }
+        }
+        for path in other.environment.site_packages {
+            if !self.environment.site_packages.contains(&path) {
+                self.environment.site_packages.push(path);
+            }
+        }
+
+        let other_index_newer = other.symbol_index.indexed_at > self.symbol_index.indexed_at;
+        for (name, location) in other.symbol_index.symbols {
+            if other_index_newer || !self.symbol_index.contains(&name) {
+                self.symbol_index.add(name, location);
+            }
+        }
+        for (path, hash) in other.symbol_index.file_hashes {
+            if other_index_newer || !self.symbol_index.file_hashes.contains_key(&path) {
+                self.symbol_index.set_file_hash(path, hash);
+            }
+        }
+        for path in other.symbol_index.skipped_large_files {
+            if !self.symbol_index.skipped_large_files.contains(&path) {
+                self.symbol_index.skipped_large_files.push(path);
+            }
+        }
+        self.symbol_index.indexed_at = self.symbol_index.indexed_at.max(other.symbol_index.indexed_at);
+
+        for analysis in other.functions.into_values() {
+            let newer = self
+                .functions
+                .get(&analysis.function_id)
+                .map_or(true, |existing| analysis.analyzed_at > existing.analyzed_at);
+            if newer {
+                self.add_function(analysis);
+            }
+        }
+
+        for (caller, callees) in &other.dependency_graph.calls {
+            for callee in callees {
+                self.dependency_graph.add_call(caller, callee);
+            }
+        }
+
+        for suggestion in other.grouping_suggestions.into_values() {
+            self.add_grouping_suggestion(suggestion);
+        }
+
+        self.created_at = self.created_at.min(other.created_at);
+        self.updated_at = Utc::now();
+        self
+    }
+
+    /// IDs of analyzed functions raising `exc_type`, in sorted order.
+    /// Accepts a short name (`Timeout`) or a qualified one
+    /// (`requests.exceptions.Timeout`); qualified lookups go through the short
+    /// name and keep only functions whose raise matches the full path, either
+    /// as resolved or as written at the raise site.
+    pub fn functions_raising<'a>(&'a self, exc_type: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let short = exc_type.rsplit('.').next().unwrap_or(exc_type);
+        self.exception_index
+            .get(short)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(move |id| {
+                short == exc_type
+                    || self.functions.get(*id).is_some_and(|analysis| {
+                        analysis.raises.iter().any(|r| r.matches_type(exc_type))
+                    })
+            })
+    }
+
+    pub fn rebuild_exception_index(&mut self) {
+        self.exception_index.clear();
+        for analysis in self.functions.values() {
+            index_exceptions(&mut self.exception_index, analysis);
+        }
+    }
+
+    fn exception_index_is_valid(&self) -> bool {
+        let mut expected = HashMap::new();
+        for analysis in self.functions.values() {
+            index_exceptions(&mut expected, analysis);
+        }
+        expected == self.exception_index
     }
 
     pub fn function_count(&self) -> usize {
@@ -188,3 +885,514 @@ impl ArborDatabase {
             .map(|loc| loc.to_resolved(qualified_name))
     }
 }
+
+/// Distinct byte trigrams of `text`; empty when it is shorter than three bytes.
+fn trigrams(text: &str) -> HashSet<[u8; 3]> {
+    text.as_bytes()
+        .windows(3)
+        .map(|w| [w[0], w[1], w[2]])
+        .collect()
+}
+
+/// The key a raise is indexed under: the last dotted segment of its type, so
+/// `raise errors.NotFound()` is found as both `NotFound` and `errors.NotFound`.
+/// Bare re-raises name no type and are not indexed.
+fn exception_index_key(exception_type: &str) -> Option<&str> {
+    if exception_type == "(re-raise)" {
+        return None;
+    }
+    exception_type.rsplit('.').next()
+}
+
+fn clear_cache() {
+    if let Ok(mut cache) = DATABASE_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+fn index_exceptions(index: &mut HashMap<String, Vec<String>>, analysis: &FunctionAnalysis) {
+    for raise in &analysis.raises {
+        let Some(key) = exception_index_key(&raise.exception_type) else {
+            continue;
+        };
+        let ids = index.entry(key.to_string()).or_default();
+        if let Err(pos) = ids.binary_search(&analysis.function_id) {
+            ids.insert(pos, analysis.function_id.clone());
+        }
+    }
+}
+
+/// The directory that stored paths are relative to: the parent of the
+/// `.arbor` directory holding `db_path`, or `db_path`'s own directory when
+/// it lives elsewhere.
+fn project_root(db_path: &Path) -> Result<PathBuf, DatabaseError> {
+    let dir = match db_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => std::env::current_dir()?,
+    };
+    let dir = if dir.is_absolute() { dir } else { std::env::current_dir()?.join(dir) };
+    match dir.file_name() {
+        Some(name) if name == paths::ARBOR_DIR => Ok(dir.parent().map(Path::to_path_buf).unwrap_or(dir)),
+        _ => Ok(dir),
+    }
+}
+
+fn unindex_exceptions(index: &mut HashMap<String, Vec<String>>, analysis: &FunctionAnalysis) {
+    for raise in &analysis.raises {
+        let Some(key) = exception_index_key(&raise.exception_type) else {
+            continue;
+        };
+        if let Some(ids) = index.get_mut(key) {
+            ids.retain(|id| id != &analysis.function_id);
+            if ids.is_empty() {
+                index.remove(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{
+        CallChainEntry, CallGraphStats, CatchClause, CodeLocation, ExceptionHandler, ExceptionSuppressor,
+        HandlerStrategy, NoneSource, NoneSourceKind, PropertyKind, RaiseStatement,
+    };
+
+    fn analysis(id: &str, raises: &[(&str, &str)]) -> FunctionAnalysis {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 1);
+        let mut analysis = FunctionAnalysis::new(id.to_string(), format!("def {}()", id), location.clone());
+        for (short, qualified) in raises {
+            analysis
+                .raises
+                .push(RaiseStatement::new(short.to_string(), qualified.to_string(), location.clone()));
+        }
+        analysis
+    }
+
+    fn environment() -> Environment {
+        Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        }
+    }
+
+    #[test]
+    fn test_exception_index_tracks_add_and_remove() {
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.fetch", &[("Timeout", "requests.exceptions.Timeout")]));
+        db.add_function(analysis("app.parse", &[("ValueError", "ValueError"), ("Timeout", "app.Timeout")]));
+
+        let raising: Vec<&str> = db.functions_raising("Timeout").collect();
+        assert_eq!(raising, vec!["app.fetch", "app.parse"]);
+        let qualified: Vec<&str> = db.functions_raising("requests.exceptions.Timeout").collect();
+        assert_eq!(qualified, vec!["app.fetch"]);
+
+        db.add_function(analysis("app.parse", &[("ValueError", "ValueError")]));
+        assert_eq!(db.functions_raising("Timeout").collect::<Vec<_>>(), vec!["app.fetch"]);
+
+        db.remove_function("app.fetch");
+        assert_eq!(db.functions_raising("Timeout").count(), 0);
+        assert!(!db.exception_index.contains_key("Timeout"));
+    }
+
+    #[test]
+    fn test_exception_index_finds_dotted_raises() {
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.get", &[("errors.NotFound", "app.errors.NotFound")]));
+        db.add_function(analysis("app.retry", &[("(re-raise)", "(re-raise)")]));
+
+        assert_eq!(db.functions_raising("NotFound").collect::<Vec<_>>(), vec!["app.get"]);
+        assert_eq!(db.functions_raising("errors.NotFound").collect::<Vec<_>>(), vec!["app.get"]);
+        assert_eq!(db.functions_raising("app.errors.NotFound").collect::<Vec<_>>(), vec!["app.get"]);
+        assert!(!db.exception_index.contains_key("(re-raise)"));
+
+        db.remove_function("app.get");
+        assert!(db.exception_index.is_empty());
+    }
+
+    #[test]
+    fn test_remove_package() {
+        let group = |exceptions: &[&str]| GroupingSuggestion {
+            group_name: exceptions.join("+"),
+            exceptions: exceptions.iter().map(|e| e.to_string()).collect(),
+            rationale: String::new(),
+            handler_example: String::new(),
+            confidence: 0.5,
+            applicable_functions: Vec::new(),
+        };
+
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("vendor.client.fetch", &[("Timeout", "vendor.errors.Timeout")]));
+        db.add_function(analysis("vendor.parse", &[("ValueError", "ValueError")]));
+        db.add_function(analysis("vendored.run", &[]));
+        db.add_function(analysis("app.main", &[("ValueError", "ValueError")]));
+        db.dependency_graph.add_call("app.main", "vendor.parse");
+        db.dependency_graph.add_call("vendor.parse", "vendor.client.fetch");
+        for exceptions in [&["vendor.errors.Timeout"][..], &["Timeout", "ValueError"], &["ValueError"]] {
+            let group = group(exceptions);
+            db.grouping_suggestions.insert(group.group_name.clone(), group);
+        }
+
+        let removed = db.remove_package("vendor");
+        assert_eq!(removed, vec!["vendor.client.fetch", "vendor.parse"]);
+        assert_eq!(db.functions.len(), 2);
+        assert!(db.dependency_graph.get_callees("app.main").is_none());
+        assert!(db.dependency_graph.get_callers("vendor.client.fetch").is_none());
+        assert_eq!(db.functions_raising("Timeout").count(), 0);
+
+        let mut groups: Vec<&str> = db.grouping_suggestions.keys().map(String::as_str).collect();
+        groups.sort();
+        assert_eq!(groups, vec!["Timeout+ValueError", "ValueError"]);
+        assert!(db.grouping_suggestions["ValueError"].applies_to_package("Value"));
+
+        assert!(db.remove_package("missing").is_empty());
+    }
+
+    #[test]
+    fn test_remove_function_prunes_group_functions() {
+        let group = |name: &str, functions: &[&str]| GroupingSuggestion {
+            group_name: name.to_string(),
+            exceptions: vec!["ValueError".to_string()],
+            rationale: String::new(),
+            handler_example: String::new(),
+            confidence: 0.5,
+            applicable_functions: functions.iter().map(|f| f.to_string()).collect(),
+        };
+
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.parse", &[("ValueError", "ValueError")]));
+        db.add_function(analysis("lib.load", &[("ValueError", "ValueError")]));
+        db.add_grouping_suggestion(group("shared", &["app.parse", "lib.load"]));
+        db.add_grouping_suggestion(group("app only", &["app.parse"]));
+
+        db.remove_function("app.parse");
+        assert_eq!(db.grouping_suggestions["shared"].applicable_functions, vec!["lib.load"]);
+        assert!(!db.grouping_suggestions["shared"].applies_to_package("app"));
+        assert!(!db.grouping_suggestions.contains_key("app only"));
+    }
+
+    #[test]
+    fn test_load_rebuilds_stale_exception_index() {
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.fetch", &[("Timeout", "Timeout")]));
+        db.exception_index.clear();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        db.save(&path).unwrap();
+
+        let loaded = ArborDatabase::load(&path).unwrap();
+        assert_eq!(loaded.functions_raising("Timeout").collect::<Vec<_>>(), vec!["app.fetch"]);
+    }
+
+    fn symbol(line: u32) -> SymbolLocation {
+        SymbolLocation {
+            file_path: PathBuf::from("app.py"),
+            line_start: line,
+            line_end: line + 1,
+            is_method: false,
+            parent_class: None,
+            is_property: false,
+            property_kind: None,
+            column_start: None,
+            column_end: None,
+            is_stub: false,
+        }
+    }
+
+    #[test]
+    fn test_symbol_search_ranks_prefix_first() {
+        let mut index = SymbolIndex::new();
+        index.add("requests.api.get".to_string(), symbol(1));
+        index.add("myapp.fetch_requests".to_string(), symbol(2));
+        index.add("requests.api.post".to_string(), symbol(3));
+        index.add("Requests.Session".to_string(), symbol(4));
+        index.add("myapp.views.index".to_string(), symbol(5));
+
+        let names: Vec<&str> = index.search("requests").into_iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec!["Requests.Session", "requests.api.get", "requests.api.post", "myapp.fetch_requests"]
+        );
+
+        // Queries shorter than a trigram fall back to a full scan
+        assert_eq!(index.search("ge").len(), 1);
+        assert!(index.search("missing").is_empty());
+
+        let prefixed: Vec<&String> = index.with_prefix("requests.").map(|(name, _)| name).collect();
+        assert_eq!(prefixed, vec!["requests.api.get", "requests.api.post"]);
+    }
+
+    #[test]
+    fn test_load_rebuilds_trigram_index() {
+        let mut db = ArborDatabase::new(environment());
+        db.symbol_index.add("app.views.index".to_string(), symbol(1));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        db.save(&path).unwrap();
+
+        let loaded = ArborDatabase::load(&path).unwrap();
+        assert_eq!(loaded.symbol_index.search("views").len(), 1);
+    }
+
+    #[test]
+    fn test_save_stores_paths_relative_to_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join(".arbor")).unwrap();
+        let path = root.join(".arbor").join("database.json");
+
+        let file = root.join("src").join("api.py");
+        let location = CodeLocation::new(file.clone(), 42);
+        let mut analysis = FunctionAnalysis::new(
+            "src.api.fetch".to_string(),
+            "def fetch()".to_string(),
+            location.clone(),
+        );
+        analysis
+            .raises
+            .push(RaiseStatement::new("Timeout".to_string(), "Timeout".to_string(), location.clone()));
+        analysis.call_chains.push(CallChainEntry {
+            exception_type: "Timeout".to_string(),
+            location: location.clone(),
+            chain: vec!["src.api.fetch".to_string()],
+        });
+        analysis.ignored_raises.push(
+            RaiseStatement::new("Exception".to_string(), "Exception".to_string(), location.clone())
+                .with_definition(location.clone()),
+        );
+        analysis.suppressors.push(ExceptionSuppressor {
+            location: location.clone(),
+            caught_type: "*".to_string(),
+            first_statement: "pass".to_string(),
+        });
+        analysis.catches.insert(
+            "Timeout".to_string(),
+            vec![CatchClause {
+                caught_type: "Timeout".to_string(),
+                location: location.clone(),
+                strategy: HandlerStrategy::Reraise,
+            }],
+        );
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis);
+        db.symbol_index.set_file_hash(file.clone(), "abc".to_string());
+        db.symbol_index.skipped_large_files.push(root.join("src").join("generated.py"));
+        db.save(&path).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains(&root.display().to_string()));
+        assert!(saved.contains("\"file\": \"src/api.py\""));
+        assert_eq!(location.relative_to(root), "src/api.py:42");
+
+        let loaded = ArborDatabase::load(&path).unwrap();
+        let analysis = &loaded.functions["src.api.fetch"];
+        assert_eq!(analysis.location.file, file);
+        assert_eq!(analysis.raises[0].raise_location.file, file);
+        assert_eq!(analysis.call_chains[0].location.file, file);
+        assert_eq!(analysis.ignored_raises[0].raise_location.file, file);
+        assert_eq!(analysis.ignored_raises[0].definition_location.as_ref().unwrap().file, file);
+        assert_eq!(analysis.suppressors[0].location.file, file);
+        assert_eq!(analysis.catches["Timeout"][0].location.file, file);
+        assert!(loaded.symbol_index.file_hashes.contains_key(&file));
+        assert_eq!(loaded.symbol_index.skipped_large_files, vec![root.join("src").join("generated.py")]);
+    }
+
+    /// Every field is spelled out, so a new field on any of these types fails
+    /// to compile here until it is populated, and a path it adds is checked
+    /// by `test_checkpoint_leaves_no_absolute_paths`.
+    fn fully_populated_analysis(root: &Path) -> FunctionAnalysis {
+        let location = |name: &str| CodeLocation {
+            file: root.join("src").join(name),
+            line: 7,
+            column: Some(4),
+            containing_function: Some("src.api.fetch".to_string()),
+        };
+        let handler = ExceptionHandler {
+            caught_types: vec!["Timeout".to_string()],
+            location: location("handler.py"),
+            strategy: HandlerStrategy::Handles,
+            try_line_start: 5,
+            try_line_end: 6,
+            covered_calls: vec!["src.api.send".to_string()],
+            body: "except Timeout:\n    retry()".to_string(),
+        };
+        let raise = |name: &str| RaiseStatement {
+            exception_type: "Timeout".to_string(),
+            qualified_type: "src.errors.Timeout".to_string(),
+            raise_location: location(name),
+            definition_location: Some(location("errors.py")),
+            condition: Some("if slow".to_string()),
+            message: Some("timed out".to_string()),
+            suppressed_by: Some(handler.clone()),
+            source_context: Some(vec!["raise Timeout()".to_string()]),
+            source_depth: 1,
+            chained_from: Some("err".to_string()),
+            suppress_chain: false,
+            via_await: true,
+        };
+        FunctionAnalysis {
+            function_id: "src.api.fetch".to_string(),
+            signature: "def fetch()".to_string(),
+            location: location("api.py"),
+            raises: vec![raise("raise.py")],
+            none_sources: vec![NoneSource {
+                kind: NoneSourceKind::FunctionCall,
+                location: location("none.py"),
+                source_definition: Some(location("lookup.py")),
+                condition: Some("if missing".to_string()),
+                variable_name: Some("result".to_string()),
+                note: Some("may return None".to_string()),
+            }],
+            functions_traced: 3,
+            call_depth: 2,
+            call_chains: vec![CallChainEntry {
+                exception_type: "Timeout".to_string(),
+                location: location("chain.py"),
+                chain: vec!["src.api.fetch".to_string(), "src.api.send".to_string()],
+            }],
+            partial: false,
+            depth_limit_hits: 1,
+            skipped_functions: vec!["src.api.deep".to_string()],
+            handlers: vec![handler.clone()],
+            suppressors: vec![ExceptionSuppressor {
+                location: location("suppressor.py"),
+                caught_type: "*".to_string(),
+                first_statement: "pass".to_string(),
+            }],
+            catches: HashMap::from([(
+                "Timeout".to_string(),
+                vec![CatchClause {
+                    caught_type: "Timeout".to_string(),
+                    location: location("catch.py"),
+                    strategy: HandlerStrategy::Reraise,
+                }],
+            )]),
+            property_kind: Some(PropertyKind::Getter),
+            call_edges: vec![("src.api.fetch".to_string(), "src.api.send".to_string())],
+            awaited_edges: vec![("src.api.fetch".to_string(), "src.api.send".to_string())],
+            docstring_raises: vec!["Timeout".to_string()],
+            ignored_raises: vec![raise("ignored.py")],
+            call_graph_stats: CallGraphStats {
+                width_at_depth: vec![1, 2],
+                branching_factor: 1.5,
+                leaf_count: 1,
+                cycle_count: 0,
+            },
+            analyzed_at: Some(Utc::now()),
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_leaves_no_absolute_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join(".arbor")).unwrap();
+        let path = root.join(".arbor").join("database.json");
+
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(fully_populated_analysis(root));
+        db.checkpoint(&path).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let root = root.display().to_string();
+        let leaked: Vec<&str> = saved.lines().map(str::trim).filter(|line| line.contains(&root)).collect();
+        assert!(leaked.is_empty(), "absolute paths survived checkpoint: {:?}", leaked);
+    }
+
+    #[test]
+    fn test_load_cached_sees_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.fetch", &[("Timeout", "Timeout")]));
+        db.save(&path).unwrap();
+
+        let cached = ArborDatabase::load_cached(&path).unwrap();
+        assert_eq!(cached.functions.len(), 1);
+        assert_eq!(ArborDatabase::load_cached(&path).unwrap().functions.len(), 1);
+
+        db.add_function(analysis("app.parse", &[]));
+        db.save(&path).unwrap();
+        let cached = ArborDatabase::load_cached(&path).unwrap();
+        assert!(cached.functions.contains_key("app.parse"));
+        assert_eq!(cached.functions_raising("Timeout").collect::<Vec<_>>(), vec!["app.fetch"]);
+    }
+
+    #[test]
+    fn test_load_keeps_absolute_paths_outside_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let outside = PathBuf::from("/usr/lib/python3/site-packages/requests/api.py");
+
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(FunctionAnalysis::new(
+            "requests.api.get".to_string(),
+            "def get()".to_string(),
+            CodeLocation::new(outside.clone(), 1),
+        ));
+        db.save(&path).unwrap();
+
+        let loaded = ArborDatabase::load(&path).unwrap();
+        assert_eq!(loaded.functions["requests.api.get"].location.file, outside);
+    }
+
+    #[test]
+    fn test_load_reports_corrupt_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.fetch", &[("Timeout", "requests.exceptions.Timeout")]));
+        db.save(&path).unwrap();
+
+        let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        value.as_object_mut().unwrap().remove("version");
+        std::fs::write(&path, value.to_string()).unwrap();
+        match ArborDatabase::load(&path) {
+            Err(DatabaseError::Corrupt { reason, suggestion }) => {
+                assert!(reason.contains("version"));
+                assert!(suggestion.contains("arbor init --force"));
+            }
+            other => panic!("expected Corrupt, got {:?}", other.map(|_| ())),
+        }
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(matches!(ArborDatabase::load(&path), Err(DatabaseError::Json(_))));
+    }
+
+    #[test]
+    fn test_try_load_partial_skips_broken_functions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("database.json");
+        let mut db = ArborDatabase::new(environment());
+        db.add_function(analysis("app.fetch", &[("Timeout", "requests.exceptions.Timeout")]));
+        db.add_function(analysis("app.parse", &[("ValueError", "ValueError")]));
+        db.save(&path).unwrap();
+
+        let complete = ArborDatabase::try_load_partial(&path);
+        assert!(complete.is_complete());
+        assert_eq!(complete.database.functions.len(), 2);
+
+        let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        value["functions"]["app.parse"]["raises"] = serde_json::json!("not a list");
+        value["updated_at"] = serde_json::json!("yesterday");
+        std::fs::write(&path, value.to_string()).unwrap();
+
+        assert!(matches!(ArborDatabase::load(&path), Err(DatabaseError::Corrupt { .. })));
+        let partial = ArborDatabase::try_load_partial(&path);
+        assert!(!partial.is_complete());
+        assert_eq!(partial.skipped_functions, vec!["app.parse"]);
+        assert_eq!(partial.errors.len(), 2);
+        assert!(partial.database.functions.contains_key("app.fetch"));
+        assert_eq!(partial.database.environment.python_version, "3.12");
+        assert_eq!(partial.database.exception_index["Timeout"], vec!["app.fetch"]);
+
+        std::fs::write(&path, "[]").unwrap();
+        assert!(ArborDatabase::try_load_partial(&path).database.functions.is_empty());
+    }
+}
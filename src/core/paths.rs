@@ -4,6 +4,7 @@ pub const ARBOR_DIR: &str = ".arbor";
 pub const DATABASE_FILE: &str = "database.json";
 pub const CONFIG_FILE: &str = "config.toml";
 pub const COMMANDS_DIR: &str = "commands";
+pub const PYPROJECT_FILE: &str = "pyproject.toml";
 
 pub fn arbor_dir() -> PathBuf {
     PathBuf::from(ARBOR_DIR)
@@ -17,6 +18,10 @@ pub fn config_path() -> PathBuf {
     arbor_dir().join(CONFIG_FILE)
 }
 
+pub fn pyproject_path() -> PathBuf {
+    PathBuf::from(PYPROJECT_FILE)
+}
+
 pub fn commands_dir() -> PathBuf {
     arbor_dir().join(COMMANDS_DIR)
 }
@@ -1,12 +1,19 @@
+use crate::core::config::NoneSourceWeights;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct CodeLocation {
+    #[schemars(description = "Source file path")]
     pub file: PathBuf,
+    #[schemars(description = "1-based line number")]
     pub line: u32,
+    #[schemars(description = "Column of the location, when known")]
     pub column: Option<u32>,
+    #[schemars(description = "Qualified name of the enclosing function")]
     pub containing_function: Option<String>,
 }
 
@@ -36,16 +43,76 @@ impl CodeLocation {
             None => format!("{}:{}", self.file.display(), self.line),
         }
     }
+
+    /// Like `to_string_short`, with the file relative to `base` when it lies
+    /// under it: `src/api.py:42` rather than `/home/user/project/src/api.py:42`.
+    pub fn relative_to(&self, base: &Path) -> String {
+        let file = relative_path(&self.file, base);
+        match self.column {
+            Some(col) => format!("{}:{}:{}", file.display(), self.line, col),
+            None => format!("{}:{}", file.display(), self.line),
+        }
+    }
+}
+
+/// `path` with `base` stripped, or `path` unchanged when it is outside
+/// `base`. Leading `./` components are dropped either way.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let stripped = path.strip_prefix(base).unwrap_or(path);
+    stripped.components().filter(|c| !matches!(c, Component::CurDir)).collect()
+}
+
+/// `path` resolved against `base` when relative; absolute paths are kept.
+pub fn absolute_path(path: &Path, base: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(relative_path(path, Path::new("")))
+    }
+}
+
+/// Whether `path` is a `.pyi` type stub, which declares functions without
+/// bodies to analyze.
+pub fn is_stub_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "pyi")
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct RaiseStatement {
+    #[schemars(description = "Exception type as written at the raise site")]
     pub exception_type: String,
+    #[schemars(description = "Exception type resolved to its qualified name")]
     pub qualified_type: String,
+    #[schemars(description = "Where the exception is raised")]
     pub raise_location: CodeLocation,
+    #[schemars(description = "Where the exception class is defined; unset for builtins")]
     pub definition_location: Option<CodeLocation>,
+    #[schemars(description = "The condition guarding the raise")]
     pub condition: Option<String>,
+    #[schemars(description = "The exception message, when it is a literal")]
     pub message: Option<String>,
+    /// The handler that catches this exception before it reaches the analyzed
+    /// function's caller, if any.
+    #[serde(default)]
+    pub suppressed_by: Option<ExceptionHandler>,
+    /// Source lines around the raise: two either side when analyzed (unless
+    /// disabled), or the `--include-source-context` window on export.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_context: Option<Vec<String>>,
+    /// Call depth at which the raise was found: 0 for the analyzed function
+    /// itself, 1 for a direct callee, and so on.
+    #[serde(default)]
+    pub source_depth: usize,
+    /// The cause in `raise X from cause`, as written.
+    #[serde(default)]
+    pub chained_from: Option<String>,
+    /// Set for `raise X from None`, which hides the exception being handled.
+    #[serde(default)]
+    pub suppress_chain: bool,
+    /// Set when the raise is reached through an awaited coroutine somewhere
+    /// along the call chain.
+    #[serde(default)]
+    pub via_await: bool,
 }
 
 impl RaiseStatement {
@@ -57,6 +124,12 @@ impl RaiseStatement {
             definition_location: None,
             condition: None,
             message: None,
+            suppressed_by: None,
+            source_context: None,
+            source_depth: 0,
+            chained_from: None,
+            suppress_chain: false,
+            via_await: false,
         }
     }
 
@@ -74,9 +147,133 @@ impl RaiseStatement {
         self.message = Some(message.into());
         self
     }
+
+    /// Whether `exc_type` names this raise: its type as written
+    /// (`errors.NotFound`), as resolved, or by its short name (`NotFound`).
+    pub fn matches_type(&self, exc_type: &str) -> bool {
+        self.exception_type == exc_type
+            || self.qualified_type == exc_type
+            || (!exc_type.contains('.') && self.exception_type.rsplit('.').next() == Some(exc_type))
+    }
+}
+
+/// What an `except` block does with the exception it catches.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum HandlerStrategy {
+    Reraise,
+    Raises(String),
+    Returns,
+    Suppresses,
+    Handles,
+}
+
+impl HandlerStrategy {
+    pub fn describe(&self) -> String {
+        match self {
+            HandlerStrategy::Reraise => "re-raises".to_string(),
+            HandlerStrategy::Raises(exc) => format!("raises {}", exc),
+            HandlerStrategy::Returns => "returns".to_string(),
+            HandlerStrategy::Suppresses => "suppresses (pass)".to_string(),
+            HandlerStrategy::Handles => "handles".to_string(),
+        }
+    }
+}
+
+/// An `except` clause and the `try` body it guards.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ExceptionHandler {
+    /// Types named in the clause; empty for a bare `except:`.
+    pub caught_types: Vec<String>,
+    pub location: CodeLocation,
+    pub strategy: HandlerStrategy,
+    pub try_line_start: u32,
+    pub try_line_end: u32,
+    /// Qualified calls made inside the `try` body.
+    #[serde(default)]
+    pub covered_calls: Vec<String>,
+    /// Source text of the `except` clause, header included.
+    #[serde(default)]
+    pub body: String,
+}
+
+impl ExceptionHandler {
+    pub fn guards_line(&self, line: u32) -> bool {
+        line >= self.try_line_start && line <= self.try_line_end
+    }
+
+    /// Whether this handler stops `exception_type` from propagating. Handlers
+    /// that only re-raise do not.
+    pub fn catches(&self, exception_type: &str) -> bool {
+        if self.strategy == HandlerStrategy::Reraise {
+            return false;
+        }
+        if self.caught_types.is_empty() {
+            return true;
+        }
+
+        let short = |name: &str| match name.rsplit('.').next().unwrap_or(name) {
+            "IOError" | "EnvironmentError" => "OSError".to_string(),
+            other => other.to_string(),
+        };
+        let raised = short(exception_type);
+
+        self.caught_types.iter().any(|caught| {
+            let caught = short(caught);
+            caught == "Exception"
+                || caught == "BaseException"
+                || std::iter::successors(Some(raised.as_str()), |t| builtin_exception_parent(t))
+                    .any(|t| t == caught)
+        })
+    }
+}
+
+/// One type named in an `except` clause, with what the handler does with it.
+/// `location.containing_function` is the function holding the clause.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct CatchClause {
+    /// The type as written, or `*` for a bare `except:`.
+    pub caught_type: String,
+    pub location: CodeLocation,
+    pub strategy: HandlerStrategy,
+}
+
+impl CatchClause {
+    /// Whether the caught exception, or one raised in its place, keeps
+    /// propagating.
+    pub fn reraises(&self) -> bool {
+        matches!(self.strategy, HandlerStrategy::Reraise | HandlerStrategy::Raises(_))
+    }
+}
+
+/// A catch-all `except` clause (bare, `Exception` or `BaseException`) that
+/// neither re-raises nor logs, so whatever it catches disappears silently.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ExceptionSuppressor {
+    pub location: CodeLocation,
+    /// The catch-all type named in the clause, or `*` for a bare `except:`.
+    pub caught_type: String,
+    /// Source text of the first statement in the handler body.
+    pub first_statement: String,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+fn builtin_exception_parent(exception_type: &str) -> Option<&'static str> {
+    match exception_type {
+        "KeyError" | "IndexError" => Some("LookupError"),
+        "ZeroDivisionError" | "OverflowError" | "FloatingPointError" => Some("ArithmeticError"),
+        "FileNotFoundError" | "FileExistsError" | "PermissionError" | "IsADirectoryError"
+        | "NotADirectoryError" | "ConnectionError" | "TimeoutError" | "BlockingIOError" => Some("OSError"),
+        "ConnectionRefusedError" | "ConnectionResetError" | "ConnectionAbortedError"
+        | "BrokenPipeError" => Some("ConnectionError"),
+        "UnicodeDecodeError" | "UnicodeEncodeError" | "UnicodeTranslateError" => Some("UnicodeError"),
+        "UnicodeError" => Some("ValueError"),
+        "ModuleNotFoundError" => Some("ImportError"),
+        "RecursionError" | "NotImplementedError" => Some("RuntimeError"),
+        "UnboundLocalError" => Some("NameError"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum NoneSourceKind {
     ExplicitReturn,
     ImplicitReturn,
@@ -85,9 +282,22 @@ pub enum NoneSourceKind {
     AttributeAccess,
     ConditionalExpr,
     MatchArm,
+    /// No None found in the body, but the return annotation allows it.
+    AnnotatedOptional,
 }
 
 impl NoneSourceKind {
+    pub const ALL: [NoneSourceKind; 8] = [
+        NoneSourceKind::ExplicitReturn,
+        NoneSourceKind::ImplicitReturn,
+        NoneSourceKind::FunctionCall,
+        NoneSourceKind::CollectionAccess,
+        NoneSourceKind::AttributeAccess,
+        NoneSourceKind::ConditionalExpr,
+        NoneSourceKind::MatchArm,
+        NoneSourceKind::AnnotatedOptional,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             NoneSourceKind::ExplicitReturn => "explicit return",
@@ -97,16 +307,50 @@ impl NoneSourceKind {
             NoneSourceKind::AttributeAccess => "attribute access",
             NoneSourceKind::ConditionalExpr => "conditional expression",
             NoneSourceKind::MatchArm => "match arm",
+            NoneSourceKind::AnnotatedOptional => "annotated optional",
+        }
+    }
+}
+
+impl std::str::FromStr for NoneSourceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "implicit" => Ok(NoneSourceKind::ImplicitReturn),
+            "explicit" => Ok(NoneSourceKind::ExplicitReturn),
+            "function-call" => Ok(NoneSourceKind::FunctionCall),
+            "collection" => Ok(NoneSourceKind::CollectionAccess),
+            "attribute" => Ok(NoneSourceKind::AttributeAccess),
+            "conditional" => Ok(NoneSourceKind::ConditionalExpr),
+            "match" => Ok(NoneSourceKind::MatchArm),
+            "annotated" => Ok(NoneSourceKind::AnnotatedOptional),
+            other => Err(format!(
+                "unknown None source kind '{}' (expected implicit, explicit, function-call, collection, attribute, conditional, match or annotated)",
+                other
+            )),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct NoneSource {
+    #[schemars(description = "How the None value arises")]
     pub kind: NoneSourceKind,
+    #[schemars(description = "Where the None value originates")]
     pub location: CodeLocation,
+    #[schemars(description = "Definition of the callee or attribute that yields None")]
     pub source_definition: Option<CodeLocation>,
+    #[schemars(description = "The condition under which None is produced")]
     pub condition: Option<String>,
+    /// The variable or attribute the None value is assigned to, as in
+    /// `result` for `result = d.get(k)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable_name: Option<String>,
+    /// Why the source was reported when no expression produces the None, as
+    /// for an `AnnotatedOptional` source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 impl NoneSource {
@@ -116,6 +360,8 @@ impl NoneSource {
             location,
             source_definition: None,
             condition: None,
+            variable_name: None,
+            note: None,
         }
     }
 
@@ -128,18 +374,225 @@ impl NoneSource {
         self.condition = Some(condition.into());
         self
     }
+
+    pub fn with_variable_name(mut self, name: impl Into<String>) -> Self {
+        self.variable_name = Some(name.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A function's `-> ...` return annotation, as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReturnAnnotation {
+    /// `Optional[T]`, `Union[..., None]` or `T | None`.
+    Optional(String),
+    Other(String),
+}
+
+impl ReturnAnnotation {
+    pub fn parse(annotation: &str) -> Self {
+        let compact: String = annotation.chars().filter(|c| !c.is_whitespace()).collect();
+        let optional = compact.starts_with("Optional[")
+            || compact.contains(".Optional[")
+            || (compact.contains("Union[") && compact.contains("None"))
+            || (compact.contains('|') && compact.split('|').any(|part| part == "None"));
+        if optional {
+            ReturnAnnotation::Optional(annotation.to_string())
+        } else {
+            ReturnAnnotation::Other(annotation.to_string())
+        }
+    }
+
+    pub fn is_optional(&self) -> bool {
+        matches!(self, ReturnAnnotation::Optional(_))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ReturnAnnotation::Optional(text) | ReturnAnnotation::Other(text) => text,
+        }
+    }
+}
+
+/// How a None value moves from its source to the function's return.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoneFlowPath {
+    pub source: NoneSource,
+    /// Each variable the value passes through, starting with the one the
+    /// source assigns to.
+    pub assignments: Vec<(String, CodeLocation)>,
+    /// The first `return` that uses a tracked variable, or the source itself
+    /// when it is a return.
+    pub return_location: Option<CodeLocation>,
+}
+
+impl NoneFlowPath {
+    pub fn new(source: NoneSource) -> Self {
+        Self {
+            source,
+            assignments: Vec::new(),
+            return_location: None,
+        }
+    }
+}
+
+/// How the analyzed function reaches one raise or None source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CallChainEntry {
+    /// The raised type, or the `NoneSourceKind` name for a None source.
+    pub exception_type: String,
+    pub location: CodeLocation,
+    /// Function ids from the analyzed function to the one holding the site.
+    pub chain: Vec<String>,
+}
+
+/// Shape of the call graph traced from an analyzed function.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CallGraphStats {
+    /// `width_at_depth[d]` is how many functions were analyzed at depth `d`.
+    pub width_at_depth: Vec<usize>,
+    /// Average number of unique callees per analyzed function.
+    pub branching_factor: f32,
+    /// Analyzed functions that call nothing in the symbol index.
+    pub leaf_count: usize,
+    /// Calls back into a function already on the current call chain.
+    pub cycle_count: usize,
+}
+
+impl CallGraphStats {
+    /// At or above this branching factor a graph reads as fan-out.
+    pub const FAN_OUT_THRESHOLD: f32 = 2.0;
+
+    pub fn shape(&self) -> &'static str {
+        if self.branching_factor >= Self::FAN_OUT_THRESHOLD {
+            "fan-out"
+        } else {
+            "linear"
+        }
+    }
+
+    /// `shape: linear (bf=1.2)`
+    pub fn summary(&self) -> String {
+        format!("shape: {} (bf={:.1})", self.shape(), self.branching_factor)
+    }
+
+    /// Records one analyzed function at `depth` with `callees` unique callees,
+    /// of which `indexed_callees` are in the symbol index.
+    pub fn record(&mut self, depth: usize, callees: usize, indexed_callees: usize) {
+        if self.width_at_depth.len() <= depth {
+            self.width_at_depth.resize(depth + 1, 0);
+        }
+        self.width_at_depth[depth] += 1;
+
+        let analyzed = self.width_at_depth.iter().sum::<usize>() as f32;
+        self.branching_factor += (callees as f32 - self.branching_factor) / analyzed;
+
+        if indexed_callees == 0 {
+            self.leaf_count += 1;
+        }
+    }
+}
+
+/// Accepts both the current list and the `"Type@file:line" -> chain` map
+/// written by older versions.
+fn deserialize_call_chains<'de, D>(deserializer: D) -> Result<Vec<CallChainEntry>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredChains {
+        Entries(Vec<CallChainEntry>),
+        Keyed(HashMap<String, Vec<String>>),
+    }
+
+    Ok(match StoredChains::deserialize(deserializer)? {
+        StoredChains::Entries(entries) => entries,
+        StoredChains::Keyed(keyed) => {
+            let mut entries: Vec<CallChainEntry> = keyed
+                .into_iter()
+                .filter_map(|(key, chain)| {
+                    let (exception_type, location) = key.split_once('@')?;
+                    let (file, line) = location.rsplit_once(':')?;
+                    Some(CallChainEntry {
+                        exception_type: exception_type.to_string(),
+                        location: CodeLocation::new(PathBuf::from(file), line.parse().ok()?),
+                        chain,
+                    })
+                })
+                .collect();
+            entries.sort_by(|a, b| (&a.location.file, a.location.line).cmp(&(&b.location.file, b.location.line)));
+            entries
+        }
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FunctionAnalysis {
+    #[schemars(description = "Qualified name of the analyzed function")]
     pub function_id: String,
+    #[schemars(description = "The function's `def` line")]
     pub signature: String,
+    #[schemars(description = "Where the function is defined")]
     pub location: CodeLocation,
+    #[schemars(description = "Exceptions raised by the function or its callees")]
     pub raises: Vec<RaiseStatement>,
+    #[schemars(description = "Places where the function can produce None")]
     pub none_sources: Vec<NoneSource>,
+    #[schemars(description = "Number of functions visited while tracing")]
     pub functions_traced: usize,
+    #[schemars(description = "Deepest call depth reached")]
     pub call_depth: usize,
-    pub call_chains: HashMap<String, Vec<String>>,
+    /// One entry per raise or None source, with the path that reaches it.
+    #[serde(default, deserialize_with = "deserialize_call_chains")]
+    pub call_chains: Vec<CallChainEntry>,
+    #[serde(default)]
+    #[schemars(description = "Set when the analysis timed out before finishing")]
+    pub partial: bool,
+    /// Number of distinct callees not traced because they were past `max_depth`.
+    #[serde(default)]
+    pub depth_limit_hits: usize,
+    #[serde(default)]
+    #[schemars(description = "Callees not traced because they were past the depth limit")]
+    pub skipped_functions: Vec<String>,
+    /// Every `except` clause found in the traced functions.
+    #[serde(default)]
+    pub handlers: Vec<ExceptionHandler>,
+    /// Catch-all `except` clauses in the traced functions that swallow
+    /// exceptions without re-raising or logging.
+    #[serde(default)]
+    pub suppressors: Vec<ExceptionSuppressor>,
+    /// `except` clauses in the traced functions, keyed by caught type.
+    #[serde(default)]
+    pub catches: HashMap<String, Vec<CatchClause>>,
+    /// Set when the analyzed function is a `@property` accessor.
+    #[serde(default)]
+    pub property_kind: Option<PropertyKind>,
+    /// Caller/callee pairs seen while tracing. Not persisted here; the
+    /// database keeps them in its `dependency_graph`.
+    #[serde(skip)]
+    pub call_edges: Vec<(String, String)>,
+    /// Caller/callee pairs where the caller awaits the callee's coroutine,
+    /// kept so `query chain` can mark those hops.
+    #[serde(default)]
+    pub awaited_edges: Vec<(String, String)>,
+    /// Exception types declared in the analyzed function's docstring.
+    #[serde(default)]
+    pub docstring_raises: Vec<String>,
+    /// Raises dropped by `ignore.exception_types`; kept for `--show-ignored`
+    /// but left out of counts and risk.
+    #[serde(default)]
+    pub ignored_raises: Vec<RaiseStatement>,
+    #[serde(default)]
+    pub call_graph_stats: CallGraphStats,
+    /// When the analysis ran; unset for analyses saved by older versions.
+    #[serde(default)]
+    pub analyzed_at: Option<DateTime<Utc>>,
 }
 
 impl FunctionAnalysis {
@@ -152,18 +605,95 @@ impl FunctionAnalysis {
             none_sources: Vec::new(),
             functions_traced: 0,
             call_depth: 0,
-            call_chains: HashMap::new(),
+            call_chains: Vec::new(),
+            partial: false,
+            depth_limit_hits: 0,
+            skipped_functions: Vec::new(),
+            handlers: Vec::new(),
+            suppressors: Vec::new(),
+            catches: HashMap::new(),
+            property_kind: None,
+            call_edges: Vec::new(),
+            awaited_edges: Vec::new(),
+            docstring_raises: Vec::new(),
+            ignored_raises: Vec::new(),
+            call_graph_stats: CallGraphStats::default(),
+            analyzed_at: Some(Utc::now()),
         }
     }
 
+    /// Moves raises whose short or qualified type matches `is_ignored` into
+    /// `ignored_raises`. Returns how many were moved.
+    pub fn strip_ignored_raises(&mut self, is_ignored: impl Fn(&str) -> bool) -> usize {
+        let (ignored, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.raises)
+            .into_iter()
+            .partition(|raise| is_ignored(&raise.exception_type) || is_ignored(&raise.qualified_type));
+        self.raises = kept;
+        let count = ignored.len();
+        self.ignored_raises.extend(ignored);
+        count
+    }
+
     pub fn exception_count(&self) -> usize {
         self.raises.len()
     }
 
+    /// The chain that reaches the `exception_type` raise or None source at
+    /// `location`.
+    pub fn chain_to(&self, exception_type: &str, location: &CodeLocation) -> Option<&CallChainEntry> {
+        self.call_chains.iter().find(|entry| {
+            entry.exception_type == exception_type
+                && entry.location.file == location.file
+                && entry.location.line == location.line
+        })
+    }
+
+    /// The shortest chain to each raised exception type, keyed by type, with
+    /// the raise it reaches.
+    pub fn shortest_chains(&self) -> BTreeMap<&str, (&[String], &RaiseStatement)> {
+        let mut shortest: BTreeMap<&str, (&[String], &RaiseStatement)> = BTreeMap::new();
+        for raise in &self.raises {
+            let Some(entry) = self.chain_to(&raise.exception_type, &raise.raise_location) else {
+                continue;
+            };
+            let current = shortest
+                .entry(raise.exception_type.as_str())
+                .or_insert((&entry.chain, raise));
+            if entry.chain.len() < current.0.len() {
+                *current = (&entry.chain, raise);
+            }
+        }
+        shortest
+    }
+
+    /// Number of distinct chains among `shortest_chains`, i.e. how many
+    /// chains `query function --show-chains` draws.
+    pub fn unique_chain_count(&self) -> usize {
+        let mut chains: Vec<&[String]> = self.shortest_chains().into_values().map(|(chain, _)| chain).collect();
+        chains.sort();
+        chains.dedup();
+        chains.len()
+    }
+
     pub fn none_source_count(&self) -> usize {
         self.none_sources.len()
     }
 
+    /// Numeric risk used for ranking, with the default None source weights.
+    pub fn risk_score(&self) -> f64 {
+        self.risk_score_with(&NoneSourceWeights::default())
+    }
+
+    /// Each exception counts 1.0; each None source counts its kind's weight.
+    pub fn risk_score_with(&self, weights: &NoneSourceWeights) -> f64 {
+        let none_score: f64 = self
+            .none_sources
+            .iter()
+            .map(|source| weights.weight(&source.kind))
+            .sum();
+        self.exception_count() as f64 + none_score
+    }
+
     pub fn risk_level(&self) -> RiskLevel {
         let exc_count = self.exception_count();
         let none_count = self.none_source_count();
@@ -178,7 +708,7 @@ impl FunctionAnalysis {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -186,6 +716,15 @@ pub enum RiskLevel {
 }
 
 impl RiskLevel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "low" => Some(RiskLevel::Low),
+            "medium" => Some(RiskLevel::Medium),
+            "high" => Some(RiskLevel::High),
+            _ => None,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             RiskLevel::Low => "Low",
@@ -235,11 +774,14 @@ impl CallGraph {
         Self::default()
     }
 
+    /// Records that `caller` calls `callee`. Recording the same edge again is
+    /// a no-op, so re-analysis does not duplicate callers.
     pub fn add_call(&mut self, caller: &str, callee: &str) {
-        self.calls
-            .entry(caller.to_string())
-            .or_default()
-            .push(callee.to_string());
+        let callees = self.calls.entry(caller.to_string()).or_default();
+        if callees.iter().any(|c| c == callee) {
+            return;
+        }
+        callees.push(callee.to_string());
 
         self.called_by
             .entry(callee.to_string())
@@ -247,6 +789,26 @@ impl CallGraph {
             .push(caller.to_string());
     }
 
+    /// Drops every edge into or out of `function`.
+    pub fn remove_function(&mut self, function: &str) {
+        for callee in self.calls.remove(function).into_iter().flatten() {
+            if let Some(callers) = self.called_by.get_mut(&callee) {
+                callers.retain(|c| c != function);
+                if callers.is_empty() {
+                    self.called_by.remove(&callee);
+                }
+            }
+        }
+        for caller in self.called_by.remove(function).into_iter().flatten() {
+            if let Some(callees) = self.calls.get_mut(&caller) {
+                callees.retain(|c| c != function);
+                if callees.is_empty() {
+                    self.calls.remove(&caller);
+                }
+            }
+        }
+    }
+
     pub fn get_callees(&self, function: &str) -> Option<&Vec<String>> {
         self.calls.get(function)
     }
@@ -254,6 +816,64 @@ impl CallGraph {
     pub fn get_callers(&self, function: &str) -> Option<&Vec<String>> {
         self.called_by.get(function)
     }
+
+    /// Every direct and indirect caller with its distance from `function`
+    /// (1 = direct), sorted by depth then name.
+    pub fn transitive_callers(&self, function: &str) -> Vec<(String, usize)> {
+        let mut seen: std::collections::HashSet<&str> = std::collections::HashSet::new();
+        seen.insert(function);
+
+        let mut result = Vec::new();
+        let mut queue: std::collections::VecDeque<(&str, usize)> = std::collections::VecDeque::new();
+        queue.push_back((function, 0));
+
+        while let Some((current, depth)) = queue.pop_front() {
+            for caller in self.called_by.get(current).into_iter().flatten() {
+                if seen.insert(caller.as_str()) {
+                    result.push((caller.clone(), depth + 1));
+                    queue.push_back((caller.as_str(), depth + 1));
+                }
+            }
+        }
+
+        result.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        result
+    }
+}
+
+/// Which accessor of a `@property` a method implements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum PropertyKind {
+    Getter,
+    Setter,
+    Deleter,
+}
+
+impl PropertyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropertyKind::Getter => "getter",
+            PropertyKind::Setter => "setter",
+            PropertyKind::Deleter => "deleter",
+        }
+    }
+
+    /// Symbol name of this accessor for the property `name`. Getters keep the
+    /// property's own name; setters and deleters get a `.setter`/`.deleter`
+    /// suffix so all three can live in the symbol index.
+    pub fn symbol_name(&self, name: &str) -> String {
+        match self {
+            PropertyKind::Getter => name.to_string(),
+            other => format!("{}.{}", name, other.as_str()),
+        }
+    }
+}
+
+/// A call to a class that was analyzed as a call to its `__init__`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassCallResolution {
+    pub class_id: String,
+    pub init_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -261,6 +881,14 @@ pub struct SingleFunctionAnalysis {
     pub raises: Vec<RaiseStatement>,
     pub none_sources: Vec<NoneSource>,
     pub calls: Vec<String>,
+    /// The subset of `calls` whose coroutine is awaited.
+    pub awaited_calls: Vec<String>,
+    pub handlers: Vec<ExceptionHandler>,
+    pub suppressors: Vec<ExceptionSuppressor>,
+    pub catches: Vec<CatchClause>,
+    pub class_call: Option<ClassCallResolution>,
+    /// Exception types the function's docstring says it raises.
+    pub docstring_raises: Vec<String>,
 }
 
 impl SingleFunctionAnalysis {
@@ -269,6 +897,12 @@ impl SingleFunctionAnalysis {
             raises: Vec::new(),
             none_sources: Vec::new(),
             calls: Vec::new(),
+            awaited_calls: Vec::new(),
+            handlers: Vec::new(),
+            suppressors: Vec::new(),
+            catches: Vec::new(),
+            class_call: None,
+            docstring_raises: Vec::new(),
         }
     }
 }
@@ -278,3 +912,113 @@ impl Default for SingleFunctionAnalysis {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitive_callers() {
+        let mut graph = CallGraph::new();
+        graph.add_call("api.handler", "service.load");
+        graph.add_call("service.load", "db.fetch");
+        graph.add_call("cli.main", "db.fetch");
+        graph.add_call("api.router", "api.handler");
+        // A cycle must not loop forever or re-report callers
+        graph.add_call("db.fetch", "service.load");
+
+        let callers = graph.transitive_callers("db.fetch");
+        assert_eq!(
+            callers,
+            vec![
+                ("cli.main".to_string(), 1),
+                ("service.load".to_string(), 1),
+                ("api.handler".to_string(), 2),
+                ("api.router".to_string(), 3),
+            ]
+        );
+
+        assert!(graph.transitive_callers("api.router").is_empty());
+    }
+
+    #[test]
+    fn test_call_chains_accept_legacy_map() {
+        let json = r#"{
+            "function_id": "app.run",
+            "signature": "def run()",
+            "location": {"file": "app.py", "line": 1, "column": null, "containing_function": null},
+            "raises": [],
+            "none_sources": [],
+            "functions_traced": 2,
+            "call_depth": 1,
+            "call_chains": {"ValueError@src/app.py:12": ["app.run", "app.validate"]}
+        }"#;
+        let analysis: FunctionAnalysis = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            analysis.call_chains,
+            vec![CallChainEntry {
+                exception_type: "ValueError".to_string(),
+                location: CodeLocation::new(PathBuf::from("src/app.py"), 12),
+                chain: vec!["app.run".to_string(), "app.validate".to_string()],
+            }]
+        );
+
+        let saved = serde_json::to_string(&analysis).unwrap();
+        let reloaded: FunctionAnalysis = serde_json::from_str(&saved).unwrap();
+        assert_eq!(reloaded.call_chains, analysis.call_chains);
+    }
+
+    #[test]
+    fn test_strip_ignored_raises_lowers_risk() {
+        let mut analysis = FunctionAnalysis::new(
+            "myapp.process".to_string(),
+            "def process()".to_string(),
+            CodeLocation::new(PathBuf::from("myapp.py"), 1),
+        );
+        for line in 0..10 {
+            let exc_type = if line < 6 { "Exception" } else { "ValueError" };
+            analysis.raises.push(RaiseStatement::new(
+                exc_type.to_string(),
+                format!("builtins.{}", exc_type),
+                CodeLocation::new(PathBuf::from("myapp.py"), 10 + line),
+            ));
+        }
+        assert_eq!(analysis.risk_level(), RiskLevel::High);
+
+        let stripped = analysis.strip_ignored_raises(|exc_type| exc_type == "builtins.Exception");
+        assert_eq!(stripped, 6);
+        assert_eq!(analysis.exception_count(), 4);
+        assert_eq!(analysis.ignored_raises.len(), 6);
+        assert_eq!(analysis.risk_level(), RiskLevel::Low);
+    }
+
+    #[test]
+    fn test_unique_chain_count_counts_drawn_chains() {
+        let mut analysis = FunctionAnalysis::new(
+            "app.run".to_string(),
+            "def run()".to_string(),
+            CodeLocation::new(PathBuf::from("app.py"), 1),
+        );
+        assert_eq!(analysis.unique_chain_count(), 0);
+
+        let mut add = |exc: &str, line: u32, chain: &[&str]| {
+            let location = CodeLocation::new(PathBuf::from("app.py"), line);
+            analysis
+                .raises
+                .push(RaiseStatement::new(exc.to_string(), exc.to_string(), location.clone()));
+            analysis.call_chains.push(CallChainEntry {
+                exception_type: exc.to_string(),
+                location,
+                chain: chain.iter().map(|s| s.to_string()).collect(),
+            });
+        };
+        // Only the shortest chain to ValueError is drawn, and KeyError shares it
+        add("ValueError", 10, &["app.run", "app.validate", "app.check"]);
+        add("ValueError", 20, &["app.run", "app.parse"]);
+        add("KeyError", 21, &["app.run", "app.parse"]);
+        add("OSError", 30, &["app.run"]);
+
+        assert_eq!(analysis.shortest_chains()["ValueError"].0, ["app.run", "app.parse"]);
+        assert_eq!(analysis.unique_chain_count(), 2);
+    }
+}
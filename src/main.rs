@@ -1,8 +1,12 @@
-use arbor::cli::analyze::{run_analyze, AnalyzeArgs, OutputFormat};
-use arbor::cli::database::{run_init, run_refresh, run_remove, run_export, InitOptions, ExportOptions};
+use arbor::cli::analyze::{read_functions_from_reader, run_analyze, AnalyzeArgs, OutputFormat};
+use arbor::cli::database::{run_init, run_refresh, run_refresh_changed, run_remove, run_merge, run_export, InitOptions, RemoveTarget, ExportOptions, SourceContextLevel, STDOUT_PATH};
 use arbor::cli::query;
 use arbor::core::config::ArborConfig;
+use arbor::core::database::set_database_cache;
 use arbor::core::paths;
+use arbor::core::types::{NoneSourceKind, RiskLevel};
+use arbor::output::markdown::{configured_output_width, set_output_width};
+use arbor::output::JsonFormatOptions;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
@@ -17,7 +21,7 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Analyze {
-        #[arg(required_unless_present_any = ["all_public", "from_file"])]
+        #[arg(required_unless_present_any = ["all_public", "from_file", "from_stdin"])]
         functions: Vec<String>,
 
         #[arg(short = 'd', long = "max-depth", default_value = "50")]
@@ -32,8 +36,67 @@ enum Commands {
         #[arg(long)]
         all_public: Option<String>,
 
+        #[arg(long)]
+        include_private: bool,
+
+        #[arg(long)]
+        no_context: bool,
+
+        #[arg(long)]
+        no_progress: bool,
+
+        #[arg(long)]
+        check_docstrings: bool,
+
+        #[arg(long, requires = "check_docstrings")]
+        fail: bool,
+
+        #[arg(long)]
+        dry_run: bool,
+
+        #[arg(long)]
+        verbose_dry_run: bool,
+
+        #[arg(long)]
+        output_only: bool,
+
+        #[arg(long, value_name = "N")]
+        checkpoint_every: Option<usize>,
+
         #[arg(long)]
         from_file: Option<String>,
+
+        #[arg(long)]
+        from_stdin: bool,
+
+        #[arg(long)]
+        prefer_local: bool,
+
+        #[arg(short, long)]
+        quiet: bool,
+
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        #[arg(long)]
+        warn_depth_limit: bool,
+
+        #[arg(long = "exclude")]
+        exclude: Vec<String>,
+
+        #[arg(long)]
+        exclude_function: Vec<String>,
+
+        #[arg(long)]
+        max_none_sources: Option<usize>,
+
+        /// Diff the analyzed functions against another database afterwards
+        #[arg(long, value_name = "DB_PATH")]
+        compare_with: Option<String>,
+
+        /// Index only the target's file and its direct imports; nothing is saved
+        #[arg(long)]
+        no_index: bool,
     },
 
     Query {
@@ -42,6 +105,14 @@ enum Commands {
 
         #[arg(short, long, default_value = "markdown", global = true)]
         format: String,
+
+        /// Minify `--format json` output
+        #[arg(long, global = true)]
+        no_pretty: bool,
+
+        /// Always read the database from disk instead of the in-process cache
+        #[arg(long, global = true)]
+        no_cache: bool,
     },
 
     Init {
@@ -50,22 +121,75 @@ enum Commands {
 
         #[arg(long)]
         skip_site_packages: bool,
+
+        #[arg(short, long)]
+        quiet: bool,
+
+        #[arg(long, value_name = "FILE")]
+        from_requirements: Option<String>,
+
+        #[arg(long)]
+        scan_pyproject: bool,
+
+        #[arg(long, value_name = "N")]
+        index_depth: Option<usize>,
+
+        /// Skip Python files larger than N KB when indexing
+        #[arg(long, value_name = "N")]
+        max_file_size_kb: Option<usize>,
+
+        /// Treat a framework's exceptions as builtins (django, sqlalchemy, requests)
+        #[arg(long, value_name = "FRAMEWORK")]
+        add_builtins: Vec<String>,
+
+        /// Python interpreter to detect the version and site-packages with
+        #[arg(long, value_name = "INTERPRETER")]
+        python: Option<String>,
     },
 
     Refresh {
         functions: Vec<String>,
+
+        #[arg(long, conflicts_with = "functions")]
+        changed_only: bool,
     },
 
     Remove {
         functions: Vec<String>,
+
+        /// Remove every analyzed function in this package instead
+        #[arg(long, value_name = "PACKAGE", conflicts_with = "functions")]
+        all_from_package: Option<String>,
+    },
+
+    Merge {
+        /// Path to the other database, e.g. `services/api/.arbor/db.json`
+        other: String,
     },
 
     Export {
         #[arg(short, long)]
         output: Option<String>,
 
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<String>,
+
         #[arg(short, long, default_value = "json")]
         format: String,
+
+        #[arg(long, value_name = "N")]
+        include_source_context: Option<usize>,
+
+        #[arg(long, value_parser = ["high", "medium"])]
+        filter_risk: Option<String>,
+
+        /// Write JSON on one line instead of indented
+        #[arg(long)]
+        no_pretty: bool,
+
+        /// Open the exported file afterwards (e.g. an html report in the browser)
+        #[arg(long, conflicts_with = "output_dir")]
+        open: bool,
     },
 
     Config {
@@ -89,7 +213,16 @@ enum ConfigCommands {
 #[derive(Subcommand)]
 enum QueryCommands {
     Risk {
-        function: String,
+        #[arg(required_unless_present = "all")]
+        function: Option<String>,
+
+        /// Every analyzed function, highest risk first (same as `*`)
+        #[arg(long, conflicts_with = "function")]
+        all: bool,
+
+        /// Only list functions at this risk level (with --all or `*`)
+        #[arg(long, value_parser = ["high", "medium", "low"])]
+        filter_risk: Option<String>,
     },
 
     Has {
@@ -105,6 +238,10 @@ enum QueryCommands {
         function: String,
     },
 
+    Signatures {
+        module: String,
+    },
+
     OneException {
         function: String,
         exc_type: String,
@@ -117,6 +254,9 @@ enum QueryCommands {
 
     Callers {
         function: String,
+
+        #[arg(long)]
+        transitive: bool,
     },
 
     Callees {
@@ -129,40 +269,156 @@ enum QueryCommands {
 
     Exceptions {
         function: String,
+
+        #[arg(long)]
+        locations_only: bool,
+
+        #[arg(long, conflicts_with = "locations_only")]
+        show_ignored: bool,
+    },
+
+    Handled {
+        function: String,
+    },
+
+    Suppressors {
+        function: String,
     },
 
     None {
         function: String,
+
+        #[arg(long, value_name = "KIND")]
+        kind: Option<NoneSourceKind>,
+
+        #[arg(long)]
+        trace: bool,
+
+        /// Label function-call sources by the callee's return annotation
+        #[arg(long)]
+        annotate_source: bool,
     },
 
     Function {
-        function: String,
+        #[arg(required_unless_present = "json_schema")]
+        function: Option<String>,
+
+        #[arg(long)]
+        depth_profile: bool,
+
+        #[arg(long)]
+        show_chains: bool,
+
+        /// Print the JSON Schema of `--format json` output instead
+        #[arg(long, conflicts_with_all = ["function", "depth_profile", "show_chains"])]
+        json_schema: bool,
     },
 
     Chain {
         function: String,
         exception: String,
+
+        #[arg(long)]
+        all: bool,
     },
 
     Groups {
         package: Option<String>,
+
+        #[arg(long)]
+        suggest_new: bool,
+
+        #[arg(long)]
+        min_confidence: Option<f32>,
+
+        /// Only groups recorded while analyzing this function
+        #[arg(long, conflicts_with = "merge")]
+        function: Option<String>,
+
+        #[arg(long, num_args = 2, value_names = ["GROUP1", "GROUP2"], conflicts_with = "suggest_new")]
+        merge: Option<Vec<String>>,
+
+        #[arg(long, requires = "merge")]
+        name: Option<String>,
+
+        #[arg(long, requires = "merge")]
+        dry_run: bool,
     },
 
     Exception {
         exc_type: String,
+
+        #[arg(long)]
+        find_handlers: bool,
+
+        #[arg(long)]
+        cross_ref: Option<String>,
+
+        #[arg(long, conflicts_with_all = ["find_handlers", "cross_ref"])]
+        locations_only: bool,
+
+        /// Match the type name exactly instead of as a case-insensitive substring
+        #[arg(long)]
+        exact: bool,
+    },
+
+    Catchers {
+        exc_type: String,
     },
 
     Package {
         name: String,
+
+        #[arg(long, value_name = "N")]
+        top_risk: Option<usize>,
+
+        #[arg(long, value_parser = ["risk", "exceptions", "none", "depth", "name"])]
+        sort_by: Option<String>,
     },
 
-    List,
+    Uncaught {
+        package: String,
+    },
+
+    List {
+        #[arg(long, default_value = "name", value_parser = ["risk", "exceptions", "none", "depth", "name"])]
+        sort_by: String,
+
+        #[arg(long, value_parser = ["high", "medium", "low"])]
+        filter_risk: Option<String>,
+
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+    },
 
     Search {
-        query: String,
+        #[arg(required_unless_present = "exception")]
+        query: Option<String>,
+
+        #[arg(long, default_value = "25")]
+        limit: usize,
+
+        /// Only functions raising an exception whose short or qualified name matches this glob
+        #[arg(long, value_name = "PATTERN")]
+        exception: Option<String>,
+
+        #[arg(long, value_parser = ["high", "medium", "low"], requires = "exception")]
+        risk: Option<String>,
     },
 
-    Stats,
+    Stats {
+        #[arg(long)]
+        by_exception: bool,
+
+        #[arg(long, value_name = "N", default_value = "1", requires = "by_exception")]
+        min_occurrences: usize,
+
+        #[arg(long, conflicts_with = "by_exception")]
+        trend: bool,
+
+        #[arg(long, value_name = "WHEN", conflicts_with_all = ["by_exception", "trend"])]
+        since: Option<String>,
+    },
 
     #[command(name = "quickref", visible_alias = "ref")]
     QuickRef,
@@ -171,8 +427,12 @@ enum QueryCommands {
 fn main() {
     let cli = Cli::parse();
 
+    set_output_width(configured_output_width(
+        ArborConfig::load_or_default().output.max_table_width,
+    ));
+
     match cli.command {
-        Commands::Analyze { functions, depth, format, venv, all_public, from_file } => {
+        Commands::Analyze { functions, depth, format, venv, all_public, include_private, no_context, no_progress, check_docstrings, fail, dry_run, verbose_dry_run, output_only, checkpoint_every, from_file, from_stdin, prefer_local, quiet, timeout, warn_depth_limit, exclude, exclude_function, max_none_sources, compare_with, no_index } => {
             let output_format = match format.as_str() {
                 "json" => OutputFormat::Json,
                 _ => OutputFormat::Markdown,
@@ -181,14 +441,9 @@ fn main() {
             let mut all_functions = functions;
 
             if let Some(file_path) = from_file {
-                match std::fs::read_to_string(&file_path) {
-                    Ok(content) => {
-                        for line in content.lines() {
-                            let trimmed = line.trim();
-                            if !trimmed.is_empty() && !trimmed.starts_with('#') {
-                                all_functions.push(trimmed.to_string());
-                            }
-                        }
+                match std::fs::File::open(&file_path) {
+                    Ok(file) => {
+                        all_functions.extend(read_functions_from_reader(std::io::BufReader::new(file)));
                     }
                     Err(e) => {
                         eprintln!("Error reading file {}: {}", file_path, e);
@@ -197,9 +452,12 @@ fn main() {
                 }
             }
 
+            if from_stdin {
+                all_functions.extend(read_functions_from_reader(std::io::stdin().lock()));
+            }
+
             if let Some(module_name) = all_public {
-                // For now, just add the module as a function to analyze
-                // The analyze command will discover public functions
+                // Expanded against the symbol index by run_analyze
                 all_functions.push(format!("{}.*", module_name));
             }
 
@@ -213,6 +471,24 @@ fn main() {
                 depth,
                 output_format,
                 venv_path: venv.map(std::path::PathBuf::from),
+                prefer_local,
+                quiet,
+                timeout_seconds: timeout,
+                warn_depth_limit,
+                exclude_packages: exclude,
+                exclude_functions: exclude_function,
+                max_none_sources,
+                include_private,
+                no_context,
+                check_docstrings,
+                fail_on_mismatch: fail,
+                no_progress,
+                dry_run: dry_run || verbose_dry_run,
+                verbose_dry_run,
+                output_only,
+                checkpoint_every,
+                compare_with: compare_with.map(std::path::PathBuf::from),
+                no_index,
             };
             match run_analyze(args) {
                 Ok(()) => {}
@@ -222,79 +498,221 @@ fn main() {
                 }
             }
         }
-        Commands::Query { query: query_cmd, format } => {
+        Commands::Query { query: query_cmd, format, no_pretty, no_cache } => {
             let use_json = format == "json";
+            set_database_cache(!no_cache);
+            let json_options = JsonFormatOptions { pretty: !no_pretty };
 
             let result = match query_cmd {
-                QueryCommands::Risk { function } => {
-                    if use_json {
-                        query::query_risk_json(&function)
-                    } else {
-                        query::query_risk(&function)
+                QueryCommands::Risk { function, filter_risk, .. } => {
+                    let filter_risk = filter_risk.as_deref().and_then(RiskLevel::parse);
+                    // Without a function name clap has required --all
+                    match function.filter(|f| f != "*") {
+                        Some(_) if filter_risk.is_some() => Err(query::QueryError::InvalidQuery(
+                            "--filter-risk applies to every function; use --all or '*'".to_string(),
+                        )),
+                        None if use_json => query::query_risk_all_json(filter_risk, json_options),
+                        None => query::query_risk_all(filter_risk),
+                        Some(function) if use_json => query::query_risk_json(&function, json_options),
+                        Some(function) => query::query_risk(&function),
                     }
                 }
                 QueryCommands::Has { function, exception } => query::query_has(&function, &exception),
                 QueryCommands::Handle { function } => query::query_handle(&function),
                 QueryCommands::Signature { function } => query::query_signature(&function),
+                QueryCommands::Signatures { module } => {
+                    if use_json {
+                        query::query_signatures_json(&module, json_options)
+                    } else {
+                        query::query_signatures(&module)
+                    }
+                }
                 QueryCommands::OneException { function, exc_type } => {
                     query::query_one_exception(&function, &exc_type)
                 }
                 QueryCommands::OneNone { function, index } => {
                     query::query_one_none(&function, index)
                 }
-                QueryCommands::Callers { function } => query::query_callers(&function),
+                QueryCommands::Callers { function, transitive } => {
+                    if use_json {
+                        query::query_callers_json(&function, transitive, json_options)
+                    } else {
+                        query::query_callers(&function, transitive)
+                    }
+                }
                 QueryCommands::Callees { function } => query::query_callees(&function),
                 QueryCommands::Diff { function } => query::query_diff(&function),
-                QueryCommands::Exceptions { function } => {
+                QueryCommands::Handled { function } => {
+                    if use_json {
+                        query::query_handled_json(&function, json_options)
+                    } else {
+                        query::query_handled(&function)
+                    }
+                }
+                QueryCommands::Suppressors { function } => {
+                    if use_json {
+                        query::query_suppressors_json(&function, json_options)
+                    } else {
+                        query::query_suppressors(&function)
+                    }
+                }
+                QueryCommands::Exceptions { function, locations_only: true, .. } => {
+                    if use_json {
+                        query::query_exceptions_locations_json(&function, json_options)
+                    } else {
+                        query::query_exceptions_locations(&function)
+                    }
+                }
+                QueryCommands::Exceptions { function, show_ignored, .. } => {
+                    if use_json {
+                        query::query_exceptions_json(&function, show_ignored, json_options)
+                    } else {
+                        query::query_exceptions(&function, show_ignored)
+                    }
+                }
+                QueryCommands::None { function, kind, trace, annotate_source } => {
                     if use_json {
-                        query::query_exceptions_json(&function)
+                        query::query_none_json(&function, kind, trace, annotate_source, json_options)
                     } else {
-                        query::query_exceptions(&function)
+                        query::query_none(&function, kind, trace, annotate_source)
                     }
                 }
-                QueryCommands::None { function } => {
+                QueryCommands::Function { json_schema: true, .. } => query::query_json_schema(json_options),
+                QueryCommands::Function { function, depth_profile, show_chains, .. } => {
+                    // Required by clap unless --json-schema is given
+                    let function = function.unwrap_or_default();
                     if use_json {
-                        query::query_none_json(&function)
+                        query::query_function_json(&function, json_options)
                     } else {
-                        query::query_none(&function)
+                        query::query_function(&function, depth_profile, show_chains)
                     }
                 }
-                QueryCommands::Function { function } => {
+                QueryCommands::Chain { function, exception, all } => {
+                    if format == "mermaid" {
+                        if all {
+                            query::query_chain_all_mermaid(&function, &exception)
+                        } else {
+                            query::query_chain_mermaid(&function, &exception)
+                        }
+                    } else if !all {
+                        query::query_chain(&function, &exception)
+                    } else if use_json {
+                        query::query_chain_all_json(&function, &exception, json_options)
+                    } else {
+                        query::query_chain_all(&function, &exception)
+                    }
+                }
+                QueryCommands::Groups { merge: Some(groups), name, dry_run, .. } => {
                     if use_json {
-                        query::query_function_json(&function)
+                        query::query_groups_merge_json(
+                            &groups[0],
+                            &groups[1],
+                            name.as_deref(),
+                            dry_run,
+                            json_options,
+                        )
                     } else {
-                        query::query_function(&function)
+                        query::query_groups_merge(&groups[0], &groups[1], name.as_deref(), dry_run)
                     }
                 }
-                QueryCommands::Chain { function, exception } => {
-                    query::query_chain(&function, &exception)
+                QueryCommands::Groups { package, suggest_new, min_confidence, function, .. } => {
+                    if use_json {
+                        query::query_groups_json(
+                            package.as_deref(),
+                            function.as_deref(),
+                            suggest_new,
+                            min_confidence,
+                            json_options,
+                        )
+                    } else {
+                        query::query_groups(package.as_deref(), function.as_deref(), suggest_new, min_confidence)
+                    }
+                }
+                QueryCommands::Exception { exc_type, locations_only: true, exact, .. } => {
+                    if use_json {
+                        query::query_exception_locations_json(&exc_type, exact, json_options)
+                    } else {
+                        query::query_exception_locations(&exc_type, exact)
+                    }
                 }
-                QueryCommands::Groups { package } => {
+                QueryCommands::Exception { exc_type, find_handlers, cross_ref, exact, .. } => {
                     if use_json {
-                        query::query_groups_json(package.as_deref())
+                        query::query_exception_json(
+                            &exc_type,
+                            exact,
+                            find_handlers,
+                            cross_ref.as_deref(),
+                            json_options,
+                        )
                     } else {
-                        query::query_groups(package.as_deref())
+                        query::query_exception(&exc_type, exact, find_handlers, cross_ref.as_deref())
                     }
                 }
-                QueryCommands::Exception { exc_type } => query::query_exception(&exc_type),
-                QueryCommands::Package { name } => query::query_package(&name),
-                QueryCommands::List => {
+                QueryCommands::Catchers { exc_type } => {
                     if use_json {
-                        query::query_list_json()
+                        query::query_catchers_json(&exc_type, json_options)
                     } else {
-                        query::query_list()
+                        query::query_catchers(&exc_type)
                     }
                 }
-                QueryCommands::Search { query: q } => query::query_search(&q),
-                QueryCommands::Stats => {
+                QueryCommands::Uncaught { package } => {
+                    if use_json {
+                        query::query_uncaught_json(&package, json_options)
+                    } else {
+                        query::query_uncaught(&package)
+                    }
+                }
+                QueryCommands::Package { name, top_risk, sort_by } => {
+                    match sort_by.map(|s| s.parse::<query::SortField>()).transpose() {
+                        Ok(sort_by) => {
+                            let sort_by = query::package_sort_field(sort_by, top_risk);
+                            if use_json {
+                                query::query_package_json(&name, sort_by, top_risk, json_options)
+                            } else {
+                                query::query_package(&name, sort_by, top_risk)
+                            }
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                QueryCommands::List { sort_by, filter_risk, since } => {
+                    let filter_risk = filter_risk.as_deref().and_then(RiskLevel::parse);
+                    let period = since.as_deref().map(query::Period::parse).transpose();
+                    match (sort_by.parse::<query::SortField>(), period) {
+                        (Ok(sort_by), Ok(period)) if use_json => {
+                            query::query_list_json(sort_by, filter_risk, period.as_ref(), json_options)
+                        }
+                        (Ok(sort_by), Ok(period)) if format == "csv" => {
+                            query::query_list_csv(sort_by, filter_risk, period.as_ref())
+                        }
+                        (Ok(sort_by), Ok(period)) => query::query_list(sort_by, filter_risk, period.as_ref()),
+                        (Err(e), _) | (_, Err(e)) => Err(e),
+                    }
+                }
+                QueryCommands::Search { query: q, limit, exception, risk } => {
+                    let risk = risk.as_deref().and_then(RiskLevel::parse);
+                    query::query_search(q.as_deref(), exception.as_deref(), risk, limit)
+                }
+                QueryCommands::Stats { by_exception: true, min_occurrences, .. } => {
                     if use_json {
-                        query::query_stats_json()
+                        query::query_exception_stats_json(min_occurrences, json_options)
                     } else {
-                        query::query_stats()
+                        query::query_exception_stats(min_occurrences)
+                    }
+                }
+                QueryCommands::Stats { trend, since, .. } => {
+                    match since.as_deref().map(query::Period::parse).transpose() {
+                        Ok(period) if use_json => query::query_stats_json(trend, period.as_ref(), json_options),
+                        Ok(period) => query::query_stats(trend, period.as_ref()),
+                        Err(e) => Err(e),
                     }
                 }
                 QueryCommands::QuickRef => {
-                    println!("{}", query::query_quickref());
+                    if use_json {
+                        println!("{}", query::query_quickref_json(json_options));
+                    } else {
+                        println!("{}", query::query_quickref());
+                    }
                     return;
                 }
             };
@@ -307,10 +725,27 @@ fn main() {
                 }
             }
         }
-        Commands::Init { force, skip_site_packages } => {
+        Commands::Init {
+            force,
+            skip_site_packages,
+            quiet,
+            from_requirements,
+            scan_pyproject,
+            index_depth,
+            max_file_size_kb,
+            add_builtins,
+            python,
+        } => {
             let options = InitOptions {
                 force,
                 index_site_packages: !skip_site_packages,
+                quiet,
+                requirements: from_requirements.map(std::path::PathBuf::from),
+                scan_pyproject,
+                index_depth,
+                max_file_size_kb,
+                add_builtins,
+                python_interpreter: python,
             };
             match run_init(options) {
                 Ok(path) => println!("\nDatabase ready: {}", path.display()),
@@ -320,8 +755,13 @@ fn main() {
                 }
             }
         }
-        Commands::Refresh { functions } => {
-            match run_refresh(if functions.is_empty() { None } else { Some(functions) }) {
+        Commands::Refresh { functions, changed_only } => {
+            let result = if changed_only {
+                run_refresh_changed()
+            } else {
+                run_refresh(if functions.is_empty() { None } else { Some(functions) })
+            };
+            match result {
                 Ok(count) => {
                     if count == 0 {
                         println!("\nNo functions refreshed (no changes detected)");
@@ -335,32 +775,45 @@ fn main() {
                 }
             }
         }
-        Commands::Remove { functions } => {
-            if functions.is_empty() {
-                match run_remove(None) {
-                    Ok(()) => println!("\nDatabase removed"),
-                    Err(e) => {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            } else {
-                match run_remove(Some(functions.clone())) {
-                    Ok(()) => println!("\nRemoved {} function(s) from database", functions.len()),
-                    Err(e) => {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    }
+        Commands::Remove { functions, all_from_package } => {
+            let target = match all_from_package {
+                Some(package) => RemoveTarget::Package(package),
+                None if functions.is_empty() => RemoveTarget::Database,
+                None => RemoveTarget::Functions(functions),
+            };
+            let whole_database = matches!(target, RemoveTarget::Database);
+            match run_remove(target) {
+                Ok(_) if whole_database => println!("\nDatabase removed"),
+                Ok(count) => println!("\nRemoved {} function(s) from database", count),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
                 }
             }
         }
-        Commands::Export { output, format } => {
+        Commands::Merge { other } => match run_merge(std::path::Path::new(&other)) {
+            Ok(count) => println!("\nMerged {} new function(s) from {}", count, other),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Commands::Export { output, output_dir, format, include_source_context, filter_risk, no_pretty, open } => {
             let options = ExportOptions {
                 output_path: output.map(std::path::PathBuf::from),
+                output_dir: output_dir.map(std::path::PathBuf::from),
                 format: format.clone(),
+                source_context: include_source_context.map(SourceContextLevel::symmetric),
+                filter_risk: filter_risk.as_deref().and_then(RiskLevel::parse),
+                pretty_print: !no_pretty,
+                open,
             };
             match run_export(options) {
-                Ok(path) => println!("Exported to: {}", path.display()),
+                Ok(path) => {
+                    if path.as_os_str() != STDOUT_PATH {
+                        println!("Exported to: {}", path.display());
+                    }
+                }
                 Err(e) => {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
@@ -0,0 +1,176 @@
+use crate::core::database::ArborDatabase;
+use crate::core::types::{FunctionAnalysis, RiskLevel};
+
+const REPORT_TEMPLATE: &str = include_str!("../assets/report.html");
+
+pub trait HtmlOutput {
+    fn to_html(&self) -> String;
+}
+
+/// One `<tbody>` per function: a summary row, and a details row listing the
+/// call chain to each exception that opens when the summary is clicked. The
+/// `data-*` attributes are what the report's column sorting reads.
+impl HtmlOutput for FunctionAnalysis {
+    fn to_html(&self) -> String {
+        let risk = self.risk_level();
+        let mut html = format!(
+            "<tbody data-risk=\"{}\" data-exceptions=\"{}\" data-none=\"{}\">\n",
+            risk as u8,
+            self.exception_count(),
+            self.none_source_count()
+        );
+        html.push_str(&format!(
+            "<tr class=\"summary\"><td><code>{}</code></td><td class=\"{}\">{} {}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            escape_html(&self.function_id),
+            risk_class(risk),
+            risk.emoji(),
+            risk.as_str(),
+            self.exception_count(),
+            self.none_source_count()
+        ));
+
+        html.push_str("<tr class=\"details\"><td colspan=\"4\">");
+        html.push_str(&format!(
+            "<code>{}</code> at <code>{}</code>",
+            escape_html(&self.signature),
+            escape_html(&self.location.to_string_short())
+        ));
+        if self.raises.is_empty() {
+            html.push_str("<p>No exceptions.</p>");
+        } else {
+            html.push_str("<ul class=\"chains\">");
+            for raise in &self.raises {
+                let chain = match self.chain_to(&raise.exception_type, &raise.raise_location) {
+                    Some(entry) if entry.chain.len() > 1 => entry.chain.join(" → "),
+                    _ => "raised directly".to_string(),
+                };
+                html.push_str(&format!(
+                    "<li><code>{}</code> at <code>{}</code>: {}</li>",
+                    escape_html(&raise.exception_type),
+                    escape_html(&raise.raise_location.to_string_short()),
+                    escape_html(&chain)
+                ));
+            }
+            html.push_str("</ul>");
+        }
+        html.push_str("</td></tr>\n</tbody>\n");
+        html
+    }
+}
+
+/// The whole report: the embedded template filled with a risk breakdown and
+/// every analyzed function, riskiest first.
+impl HtmlOutput for ArborDatabase {
+    fn to_html(&self) -> String {
+        let mut functions: Vec<&FunctionAnalysis> = self.functions.values().collect();
+        functions.sort_by(|a, b| {
+            b.risk_level()
+                .cmp(&a.risk_level())
+                .then(b.exception_count().cmp(&a.exception_count()))
+                .then(a.function_id.cmp(&b.function_id))
+        });
+
+        let meta = format!(
+            "{} functions analyzed · Python {} · updated {}",
+            self.functions.len(),
+            escape_html(&self.environment.python_version),
+            self.updated_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        let totals: String = [RiskLevel::High, RiskLevel::Medium, RiskLevel::Low]
+            .iter()
+            .map(|level| {
+                let count = functions.iter().filter(|f| f.risk_level() == *level).count();
+                format!(
+                    "<div class=\"{}\">{} {}: {}</div>",
+                    risk_class(*level),
+                    level.emoji(),
+                    level.as_str(),
+                    count
+                )
+            })
+            .collect();
+        let rows: String = functions.iter().map(|f| f.to_html()).collect();
+
+        REPORT_TEMPLATE
+            .replace("{{meta}}", &meta)
+            .replace("{{totals}}", &totals)
+            .replace("{{rows}}", &rows)
+    }
+}
+
+fn risk_class(risk: RiskLevel) -> &'static str {
+    match risk {
+        RiskLevel::High => "risk-high",
+        RiskLevel::Medium => "risk-medium",
+        RiskLevel::Low => "risk-low",
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::Environment;
+    use crate::core::types::{CallChainEntry, CodeLocation, RaiseStatement};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_function_html() {
+        let location = CodeLocation::new(PathBuf::from("app/api.py"), 7);
+        let mut analysis = FunctionAnalysis::new(
+            "app.api.fetch".to_string(),
+            "def fetch(d: dict[str, int]) -> int".to_string(),
+            CodeLocation::new(PathBuf::from("app/api.py"), 1),
+        );
+        analysis.raises.push(RaiseStatement::new(
+            "KeyError".to_string(),
+            "builtins.KeyError".to_string(),
+            location.clone(),
+        ));
+        analysis.call_chains.push(CallChainEntry {
+            exception_type: "KeyError".to_string(),
+            location,
+            chain: vec!["app.api.fetch".to_string(), "app.api.lookup".to_string()],
+        });
+
+        let html = analysis.to_html();
+        assert!(html.starts_with("<tbody data-risk=\"0\" data-exceptions=\"1\" data-none=\"0\">"));
+        assert!(html.contains("<td class=\"risk-low\">🟢 Low</td>"));
+        assert!(html.contains("def fetch(d: dict[str, int]) -&gt; int"));
+        assert!(html.contains("<code>KeyError</code> at <code>app/api.py:7</code>: app.api.fetch → app.api.lookup"));
+    }
+
+    #[test]
+    fn test_database_report_is_self_contained() {
+        let mut db = ArborDatabase::new(Environment::default());
+        for id in ["app.b", "app.a"] {
+            db.add_function(FunctionAnalysis::new(
+                id.to_string(),
+                format!("def {}()", id),
+                CodeLocation::new(PathBuf::from("app.py"), 1),
+            ));
+        }
+
+        let html = db.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("{{"));
+        assert!(!html.contains("src=\"http"));
+        assert!(html.contains("<div class=\"risk-low\">🟢 Low: 2</div>"));
+        assert!(html.find("app.a").unwrap() < html.find("app.b").unwrap());
+        assert_eq!(escape_html("<a href=\"x\">&'"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+    }
+}
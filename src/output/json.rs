@@ -1,4 +1,27 @@
 use serde::Serialize;
+use std::io::Write;
+
+/// Layout of `--format json` query output, chosen on the command line
+/// (`query --no-pretty` minifies it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonFormatOptions {
+    pub pretty: bool,
+}
+
+impl Default for JsonFormatOptions {
+    fn default() -> Self {
+        Self { pretty: true }
+    }
+}
+
+/// `value` as indented JSON when `pretty`, otherwise on one line.
+pub fn json_serialize<T: Serialize + ?Sized>(value: &T, pretty: bool) -> Result<String, serde_json::Error> {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+}
 
 pub trait JsonOutput {
     fn to_json(&self) -> Result<String, serde_json::Error>;
@@ -14,3 +37,96 @@ impl<T: Serialize> JsonOutput for T {
         serde_json::to_string_pretty(self)
     }
 }
+
+/// Writes a JSON array one item at a time, flushing after each, so results
+/// show up as they are produced rather than all at the end.
+/// Dropping the streamer closes the array too, so output stays valid JSON
+/// when the producer bails out early with an error.
+pub struct JsonArrayStreamer<W: Write> {
+    writer: W,
+    first_item: bool,
+    finished: bool,
+}
+
+impl<W: Write> JsonArrayStreamer<W> {
+    /// Writes the opening `[`.
+    pub fn begin(mut writer: W) -> std::io::Result<Self> {
+        writeln!(writer, "[")?;
+        Ok(Self {
+            writer,
+            first_item: true,
+            finished: false,
+        })
+    }
+
+    pub fn write_item<T: Serialize>(&mut self, item: &T) -> std::io::Result<()> {
+        if !self.first_item {
+            writeln!(self.writer, ",")?;
+        }
+        serde_json::to_writer_pretty(&mut self.writer, item)?;
+        self.first_item = false;
+        self.writer.flush()
+    }
+
+    /// Writes the closing `]`.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.close()
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+        if !self.first_item {
+            writeln!(self.writer)?;
+        }
+        writeln!(self.writer, "]")?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for JsonArrayStreamer<W> {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_serialize() {
+        let value = serde_json::json!({"id": 1, "tags": ["a"]});
+        assert_eq!(json_serialize(&value, false).unwrap(), r#"{"id":1,"tags":["a"]}"#);
+        assert!(json_serialize(&value, true).unwrap().contains("\n  \"id\": 1"));
+    }
+
+    #[test]
+    fn test_json_array_streamer() {
+        let mut out = Vec::new();
+        let mut array = JsonArrayStreamer::begin(&mut out).unwrap();
+        array.write_item(&serde_json::json!({"id": 1})).unwrap();
+        array.write_item(&serde_json::json!({"id": 2})).unwrap();
+        array.finish().unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed, serde_json::json!([{"id": 1}, {"id": 2}]));
+
+        let mut out = Vec::new();
+        JsonArrayStreamer::begin(&mut out).unwrap().finish().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[\n]\n");
+
+        // An early return drops the streamer, which still closes the array
+        let mut out = Vec::new();
+        let write_then_fail = |out: &mut Vec<u8>| -> std::io::Result<()> {
+            let mut array = JsonArrayStreamer::begin(out)?;
+            array.write_item(&serde_json::json!({"id": 1}))?;
+            Err(std::io::Error::other("analysis failed"))
+        };
+        assert!(write_then_fail(&mut out).is_err());
+        let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed, serde_json::json!([{"id": 1}]));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::core::database::{ArborDatabase, GroupingSuggestion};
+use crate::core::types::{FunctionAnalysis, RaiseStatement};
+use serde::Serialize;
+use std::io::Write;
+
+/// One line of a JSON Lines export, tagged by `type`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JsonlRecord<'a> {
+    Meta {
+        version: &'a str,
+        exported_at: String,
+    },
+    Function {
+        data: &'a FunctionAnalysis,
+    },
+    Exception {
+        data: ExceptionRecord<'a>,
+    },
+    Group {
+        data: &'a GroupingSuggestion,
+    },
+}
+
+#[derive(Serialize)]
+pub struct ExceptionRecord<'a> {
+    pub function_id: &'a str,
+    #[serde(flatten)]
+    pub raise: &'a RaiseStatement,
+}
+
+/// Write the database as JSON Lines: a `meta` header, then one line per
+/// function, per raised exception and per grouping suggestion.
+pub fn write_jsonl<W: Write>(db: &ArborDatabase, writer: &mut W) -> std::io::Result<()> {
+    write_record(
+        writer,
+        &JsonlRecord::Meta {
+            version: &db.version,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )?;
+
+    let mut function_ids: Vec<&String> = db.functions.keys().collect();
+    function_ids.sort();
+
+    for id in &function_ids {
+        write_record(writer, &JsonlRecord::Function { data: &db.functions[*id] })?;
+    }
+
+    for id in &function_ids {
+        for raise in &db.functions[*id].raises {
+            write_record(
+                writer,
+                &JsonlRecord::Exception {
+                    data: ExceptionRecord { function_id: id, raise },
+                },
+            )?;
+        }
+    }
+
+    let mut group_names: Vec<&String> = db.grouping_suggestions.keys().collect();
+    group_names.sort();
+
+    for name in group_names {
+        write_record(writer, &JsonlRecord::Group { data: &db.grouping_suggestions[name] })?;
+    }
+
+    writer.flush()
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &JsonlRecord) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::Environment;
+    use crate::core::types::CodeLocation;
+    use std::path::PathBuf;
+
+    fn test_db() -> ArborDatabase {
+        let mut db = ArborDatabase::new(Environment {
+            python_version: "3.12".to_string(),
+            venv_path: None,
+            site_packages: vec![],
+            python_path: vec![],
+            python_interpreter: None,
+        });
+
+        let location = CodeLocation::new(PathBuf::from("api.py"), 3);
+        let mut analysis = FunctionAnalysis::new(
+            "api.fetch".to_string(),
+            "def fetch(...)".to_string(),
+            location.clone(),
+        );
+        analysis.raises.push(RaiseStatement::new(
+            "ValueError".to_string(),
+            "ValueError".to_string(),
+            location,
+        ));
+        db.add_function(analysis);
+        db
+    }
+
+    #[test]
+    fn test_write_jsonl_lines() {
+        let db = test_db();
+        let mut buffer = Vec::new();
+        write_jsonl(&db, &mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "meta");
+        assert_eq!(lines[0]["version"], db.version.as_str());
+        assert_eq!(lines[1]["type"], "function");
+        assert_eq!(lines[1]["data"]["function_id"], "api.fetch");
+        assert_eq!(lines[2]["type"], "exception");
+        assert_eq!(lines[2]["data"]["function_id"], "api.fetch");
+        assert_eq!(lines[2]["data"]["exception_type"], "ValueError");
+    }
+}
@@ -1,7 +1,78 @@
 use crate::analysis::grouping::RecoveryStrategy;
 use crate::core::database::GroupingSuggestion;
 use crate::core::paths;
+use crate::plugins::python::extractor::SOURCE_CONTEXT_LINES;
 use crate::core::types::{FunctionAnalysis, NoneSource, RaiseStatement, RiskLevel};
+use std::cell::Cell;
+
+/// Environment variable overriding `output.max_table_width`.
+pub const TABLE_WIDTH_ENV: &str = "ARBOR_TABLE_WIDTH";
+
+/// Narrowest a column is squeezed to when distributing the output width.
+const MIN_COLUMN_WIDTH: usize = 5;
+
+thread_local! {
+    static OUTPUT_WIDTH: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Sets the line width that tables and the `format_*` helpers on this thread
+/// fit their output to. `None` disables truncation.
+pub fn set_output_width(width: Option<usize>) {
+    OUTPUT_WIDTH.with(|w| w.set(width));
+}
+
+pub fn output_width() -> Option<usize> {
+    OUTPUT_WIDTH.with(Cell::get)
+}
+
+/// The output width from `ARBOR_TABLE_WIDTH`, falling back to the configured
+/// `max_table_width`. Zero means unlimited in both.
+pub fn configured_output_width(config_width: usize) -> Option<usize> {
+    resolve_output_width(std::env::var(TABLE_WIDTH_ENV).ok().as_deref(), config_width)
+}
+
+fn resolve_output_width(env_width: Option<&str>, config_width: usize) -> Option<usize> {
+    let width = env_width
+        .and_then(|w| w.trim().parse::<usize>().ok())
+        .unwrap_or(config_width);
+    (width > 0).then_some(width)
+}
+
+/// Shortens `text` to at most `width` characters, ending it with `…`. A
+/// truncated `code span` keeps its closing backtick.
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let is_code = text.len() > 1 && text.starts_with('`') && text.ends_with('`');
+    if is_code && width >= 3 {
+        let kept: String = text.chars().take(width - 2).collect();
+        return format!("{}…`", kept);
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// Splits `total` line width across columns: narrow columns keep their
+/// natural width and the rest share what is left equally.
+fn distribute_width(natural: &[usize], total: usize) -> Vec<usize> {
+    let overhead = 3 * natural.len() + 1;
+    let mut available = total.saturating_sub(overhead);
+    let mut widths = vec![0; natural.len()];
+
+    let mut order: Vec<usize> = (0..natural.len()).collect();
+    order.sort_by_key(|&i| natural[i]);
+
+    let mut remaining = natural.len();
+    for i in order {
+        let share = (available / remaining).max(MIN_COLUMN_WIDTH);
+        widths[i] = natural[i].min(share);
+        available = available.saturating_sub(widths[i]);
+        remaining -= 1;
+    }
+
+    widths
+}
 
 pub trait MarkdownOutput {
     fn to_markdown(&self) -> String;
@@ -18,6 +89,9 @@ pub trait MarkdownOutput {
 pub struct MarkdownTable {
     headers: Vec<String>,
     rows: Vec<Vec<String>>,
+    /// Per-column character limits; longer cells are truncated with `…`.
+    /// When unset, the thread's `output_width` is split across columns.
+    max_column_widths: Option<Vec<usize>>,
 }
 
 impl MarkdownTable {
@@ -25,9 +99,15 @@ impl MarkdownTable {
         Self {
             headers: headers.into_iter().map(String::from).collect(),
             rows: Vec::new(),
+            max_column_widths: None,
         }
     }
 
+    pub fn with_max_widths(mut self, widths: Vec<usize>) -> Self {
+        self.max_column_widths = Some(widths);
+        self
+    }
+
     pub fn add_row(&mut self, row: Vec<&str>) {
         self.rows.push(row.into_iter().map(String::from).collect());
     }
@@ -37,26 +117,50 @@ impl MarkdownTable {
             return String::new();
         }
 
+        let limits = self.column_limits();
+        let render_row = |cells: &[String]| -> String {
+            let cells: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| match limits.as_ref().and_then(|l| l.get(i)) {
+                    Some(&limit) => truncate_to_width(cell, limit),
+                    None => cell.clone(),
+                })
+                .collect();
+            format!("| {} |\n", cells.join(" | "))
+        };
+
         let mut output = String::new();
 
-        output.push_str("| ");
-        output.push_str(&self.headers.join(" | "));
-        output.push_str(" |\n");
+        output.push_str(&render_row(&self.headers));
 
-        output.push_str("|");
+        output.push('|');
         for _ in &self.headers {
             output.push_str("------|");
         }
         output.push('\n');
 
         for row in &self.rows {
-            output.push_str("| ");
-            output.push_str(&row.join(" | "));
-            output.push_str(" |\n");
+            output.push_str(&render_row(row));
         }
 
         output
     }
+
+    fn column_limits(&self) -> Option<Vec<usize>> {
+        if let Some(ref widths) = self.max_column_widths {
+            return Some(widths.clone());
+        }
+        let total = output_width()?;
+
+        let mut natural: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate().take(natural.len()) {
+                natural[i] = natural[i].max(cell.chars().count());
+            }
+        }
+        Some(distribute_width(&natural, total))
+    }
 }
 
 pub fn format_risk(risk: RiskLevel) -> String {
@@ -73,17 +177,61 @@ pub fn format_recovery(strategy: RecoveryStrategy) -> String {
 }
 
 pub fn format_code_block(code: &str, lang: &str) -> String {
+    let code = match output_width() {
+        Some(width) => code
+            .lines()
+            .map(|line| truncate_to_width(line, width))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => code.to_string(),
+    };
     format!("```{}\n{}\n```", lang, code)
 }
 
 pub fn format_header(level: usize, text: &str) -> String {
+    let text = match output_width() {
+        Some(width) => truncate_to_width(text, width.saturating_sub(level + 1).max(MIN_COLUMN_WIDTH)),
+        None => text.to_string(),
+    };
     format!("{} {}\n", "#".repeat(level), text)
 }
 
 pub fn format_key_value(key: &str, value: &str) -> String {
+    let value = match output_width() {
+        Some(width) => {
+            let prefix = key.chars().count() + "**:** ".len();
+            truncate_to_width(value, width.saturating_sub(prefix).max(MIN_COLUMN_WIDTH))
+        }
+        None => value.to_string(),
+    };
     format!("**{}:** {}\n", key, value)
 }
 
+/// `context` as a python block with the raise line marked `>>>`.
+pub fn format_source_context(context: &[String], raise_line: u32) -> String {
+    let marked = context_raise_index(context, raise_line);
+    let code: Vec<String> = context
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let marker = if Some(i) == marked { ">>> " } else { "    " };
+            format!("{}{}", marker, line)
+        })
+        .collect();
+    format_code_block(&code.join("\n"), "python")
+}
+
+/// Position of the raise within `context`, assuming the analysis window;
+/// wider export windows fall back to the first line containing `raise`.
+fn context_raise_index(context: &[String], raise_line: u32) -> Option<usize> {
+    let expected = (raise_line as usize).saturating_sub(1).min(SOURCE_CONTEXT_LINES);
+    context
+        .get(expected)
+        .filter(|line| line.contains("raise"))
+        .map(|_| expected)
+        .or_else(|| context.iter().position(|line| line.contains("raise")))
+}
+
 pub fn format_list_item(text: &str) -> String {
     format!("- {}\n", text)
 }
@@ -150,6 +298,18 @@ impl MarkdownOutput for FunctionAnalysis {
             }
             output.push_str(&exc_table.render());
             output.push('\n');
+
+            for raise in &self.raises {
+                if let Some(ref context) = raise.source_context {
+                    output.push_str(&format!(
+                        "<details>\n<summary>{} at {}</summary>\n\n",
+                        raise.exception_type,
+                        raise.raise_location.to_string_short()
+                    ));
+                    output.push_str(&format_code_block(&context.join("\n"), "python"));
+                    output.push_str("\n</details>\n\n");
+                }
+            }
         }
 
         if !self.none_sources.is_empty() {
@@ -223,6 +383,11 @@ impl MarkdownOutput for RaiseStatement {
             ),
         ));
 
+        if let Some(ref context) = self.source_context {
+            output.push('\n');
+            output.push_str(&format_source_context(context, self.raise_location.line));
+        }
+
         output
     }
 }
@@ -255,6 +420,10 @@ impl MarkdownOutput for NoneSource {
             &format!("`{}`", self.location.to_string_short()),
         ));
 
+        if let Some(ref variable) = self.variable_name {
+            output.push_str(&format_key_value("Variable", &format!("`{}`", variable)));
+        }
+
         if let Some(ref def) = self.source_definition {
             output.push_str(&format_key_value("Source", &format!("`{}`", def.to_string_short())));
         }
@@ -326,6 +495,8 @@ pub struct DatabaseStats {
     pub high_risk: usize,
     pub medium_risk: usize,
     pub low_risk: usize,
+    /// Shown under the title when the functions were filtered, e.g. by risk.
+    pub filter_note: Option<String>,
 }
 
 impl MarkdownOutput for DatabaseStats {
@@ -339,6 +510,10 @@ impl MarkdownOutput for DatabaseStats {
         output.push_str(&format_header(1, "Arbor Database Statistics"));
         output.push('\n');
 
+        if let Some(ref note) = self.filter_note {
+            output.push_str(&format!("**{}**\n\n", note));
+        }
+
         output.push_str(&format_key_value("Database", &format!("`{}/{}`", paths::ARBOR_DIR, paths::DATABASE_FILE)));
         output.push_str(&format_key_value("Version", &self.version));
         output.push_str(&format_key_value("Created", &self.created_at));
@@ -368,17 +543,17 @@ impl MarkdownOutput for DatabaseStats {
             risk_table.add_row(vec![
                 "🔴 High",
                 &self.high_risk.to_string(),
-                &format!("{}%", (self.high_risk * 100) / total),
+                &format!("{}%", (self.high_risk * 100).checked_div(total).unwrap_or(0)),
             ]);
             risk_table.add_row(vec![
                 "🟡 Medium",
                 &self.medium_risk.to_string(),
-                &format!("{}%", (self.medium_risk * 100) / total),
+                &format!("{}%", (self.medium_risk * 100).checked_div(total).unwrap_or(0)),
             ]);
             risk_table.add_row(vec![
                 "🟢 Low",
                 &self.low_risk.to_string(),
-                &format!("{}%", (self.low_risk * 100) / total),
+                &format!("{}%", (self.low_risk * 100).checked_div(total).unwrap_or(0)),
             ]);
         }
         output.push_str(&risk_table.render());
@@ -390,6 +565,8 @@ impl MarkdownOutput for DatabaseStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::CodeLocation;
+    use std::path::PathBuf;
 
     #[test]
     fn test_markdown_table() {
@@ -424,6 +601,47 @@ mod tests {
         assert!(output.ends_with("```"));
     }
 
+    #[test]
+    fn test_table_max_widths_truncate_cells() {
+        let mut table = MarkdownTable::new(vec!["Type", "Condition"]).with_max_widths(vec![10, 8]);
+        table.add_row(vec!["`VeryLongExceptionName`", "x is None"]);
+        table.add_row(vec!["`KeyError`", "-"]);
+
+        let output = table.render();
+        assert!(output.contains("| Type | Conditi… |"));
+        assert!(output.contains("| `VeryLon…` | x is No… |"));
+        assert!(output.contains("| `KeyError` | - |"));
+    }
+
+    #[test]
+    fn test_output_width_is_distributed_across_columns() {
+        assert_eq!(distribute_width(&[4, 40, 60], 50), vec![4, 18, 18]);
+        assert_eq!(distribute_width(&[4, 10], 80), vec![4, 10]);
+
+        set_output_width(Some(30));
+        let mut table = MarkdownTable::new(vec!["Kind", "Location"]);
+        table.add_row(vec!["implicit", "src/very/long/path/to/module.py:120"]);
+        let output = table.render();
+        assert!(output.lines().all(|line| line.chars().count() <= 30));
+        assert!(output.contains("| implicit | src/very/long/… |"));
+
+        assert_eq!(format_key_value("Reason", &"x".repeat(40)).trim_end().chars().count(), 30);
+        assert_eq!(format_header(2, &"y".repeat(40)).trim_end().chars().count(), 30);
+        assert!(format_code_block(&"z".repeat(40), "python").contains(&format!("{}…", "z".repeat(29))));
+        set_output_width(None);
+
+        assert!(table.render().contains("src/very/long/path/to/module.py:120"));
+    }
+
+    #[test]
+    fn test_resolve_output_width() {
+        assert_eq!(resolve_output_width(Some("100"), 80), Some(100));
+        assert_eq!(resolve_output_width(None, 80), Some(80));
+        assert_eq!(resolve_output_width(Some("junk"), 80), Some(80));
+        assert_eq!(resolve_output_width(Some("0"), 80), None);
+        assert_eq!(resolve_output_width(None, 0), None);
+    }
+
     #[test]
     fn test_format_risk() {
         assert!(format_risk(RiskLevel::High).contains("High"));
@@ -436,4 +654,35 @@ mod tests {
         assert!(format_recovery(RecoveryStrategy::Retry).contains("retryable"));
         assert!(format_recovery(RecoveryStrategy::Abort).contains("not retryable"));
     }
+
+    #[test]
+    fn test_source_context_details_block() {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 5);
+        let mut analysis = FunctionAnalysis::new(
+            "app.run".to_string(),
+            "def run()".to_string(),
+            location.clone(),
+        );
+        let mut raise = RaiseStatement::new("ValueError".to_string(), "ValueError".to_string(), location);
+        raise.source_context = Some(vec!["if x:".to_string(), "    raise ValueError()".to_string()]);
+        analysis.raises.push(raise);
+
+        let output = analysis.to_markdown_detailed();
+        assert!(output.contains("<details>\n<summary>ValueError at app.py:5</summary>"));
+        assert!(output.contains("```python\nif x:\n    raise ValueError()\n```"));
+    }
+
+    #[test]
+    fn test_raise_detailed_marks_raise_line() {
+        let location = CodeLocation::new(PathBuf::from("app.py"), 2);
+        let mut raise = RaiseStatement::new("ValueError".to_string(), "ValueError".to_string(), location);
+        raise.source_context = Some(vec![
+            "def run(x):".to_string(),
+            "    raise ValueError()".to_string(),
+            "".to_string(),
+        ]);
+
+        let output = raise.to_markdown_detailed();
+        assert!(output.contains("```python\n    def run(x):\n>>>     raise ValueError()\n    \n```"));
+    }
 }
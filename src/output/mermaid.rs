@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+/// Which kind of Mermaid diagram `call_chain_to_mermaid` draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MermaidDiagramType {
+    Flowchart,
+    SequenceDiagram,
+}
+
+/// Mermaid source for call chains that end in raising `exception`. Each chain
+/// runs from the analyzed function to the one that raises. A flowchart merges
+/// the steps chains share; a sequence diagram plays them one after another.
+pub fn call_chain_to_mermaid(chains: &[&[String]], exception: &str, diagram_type: MermaidDiagramType) -> String {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut functions: Vec<&str> = Vec::new();
+    for function in chains.iter().flat_map(|chain| chain.iter()) {
+        ids.entry(function.as_str()).or_insert_with(|| {
+            functions.push(function);
+            functions.len() - 1
+        });
+    }
+
+    match diagram_type {
+        MermaidDiagramType::SequenceDiagram => {
+            let mut result = String::from("sequenceDiagram\n");
+            for (i, function) in functions.iter().enumerate() {
+                result.push_str(&format!("    participant F{} as {}\n", i, function));
+            }
+            for chain in chains {
+                for step in chain.windows(2) {
+                    let callee = step[1].rsplit('.').next().unwrap_or(&step[1]);
+                    result.push_str(&format!(
+                        "    F{}->>F{}: {}()\n",
+                        ids[step[0].as_str()],
+                        ids[step[1].as_str()],
+                        callee
+                    ));
+                }
+                if let Some(last) = chain.last() {
+                    result.push_str(&format!("    Note over F{}: raise {}(...)\n", ids[last.as_str()], exception));
+                }
+            }
+            result
+        }
+        MermaidDiagramType::Flowchart => {
+            let mut result = String::from("flowchart TD\n");
+            for (i, function) in functions.iter().enumerate() {
+                result.push_str(&format!("    F{}[\"{}\"]\n", i, function));
+            }
+            result.push_str(&format!("    R([\"raise {}\"])\n", exception));
+
+            let mut edges: Vec<String> = Vec::new();
+            for chain in chains {
+                for step in chain.windows(2) {
+                    edges.push(format!("F{} --> F{}", ids[step[0].as_str()], ids[step[1].as_str()]));
+                }
+                if let Some(last) = chain.last() {
+                    edges.push(format!("F{} --> R", ids[last.as_str()]));
+                }
+            }
+            let mut seen = std::collections::HashSet::new();
+            for edge in edges.iter().filter(|e| seen.insert(e.as_str())) {
+                result.push_str(&format!("    {}\n", edge));
+            }
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_sequence_diagram() {
+        let path = chain(&["app.handler", "app.db.fetch"]);
+        let diagram = call_chain_to_mermaid(&[&path], "KeyError", MermaidDiagramType::SequenceDiagram);
+        assert_eq!(diagram.lines().next(), Some("sequenceDiagram"));
+        assert_eq!(
+            diagram,
+            "sequenceDiagram\n\
+             \x20   participant F0 as app.handler\n\
+             \x20   participant F1 as app.db.fetch\n\
+             \x20   F0->>F1: fetch()\n\
+             \x20   Note over F1: raise KeyError(...)\n"
+        );
+    }
+
+    #[test]
+    fn test_flowchart_merges_shared_steps() {
+        let first = chain(&["app.handler", "app.load", "app.parse"]);
+        let second = chain(&["app.handler", "app.load"]);
+        let diagram = call_chain_to_mermaid(&[&first, &second], "ValueError", MermaidDiagramType::Flowchart);
+        assert_eq!(diagram.lines().next(), Some("flowchart TD"));
+        assert_eq!(diagram.matches("F0 --> F1").count(), 1);
+        assert!(diagram.contains("    F2 --> R\n"));
+        assert!(diagram.contains("    F1 --> R\n"));
+        assert!(diagram.contains("R([\"raise ValueError\"])"));
+    }
+}
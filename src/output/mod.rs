@@ -1,8 +1,18 @@
+pub mod html;
 pub mod json;
+pub mod jsonl;
 pub mod markdown;
+pub mod mermaid;
+pub mod sarif;
+pub mod schema;
 
-pub use json::JsonOutput;
+pub use html::{escape_html, HtmlOutput};
+pub use json::{json_serialize, JsonArrayStreamer, JsonFormatOptions, JsonOutput};
+pub use jsonl::write_jsonl;
 pub use markdown::{
     format_code_block, format_header, format_key_value, format_list_item, format_recovery,
     format_risk, DatabaseStats, MarkdownOutput, MarkdownTable,
 };
+pub use mermaid::{call_chain_to_mermaid, MermaidDiagramType};
+pub use sarif::{to_sarif, SarifOutput};
+pub use schema::SchemaOutput;
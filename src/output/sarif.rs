@@ -0,0 +1,267 @@
+use crate::core::database::ArborDatabase;
+use crate::core::types::{CodeLocation, FunctionAnalysis, RiskLevel};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A SARIF 2.1.0 log, as GitHub code scanning and SARIF viewers read it.
+pub trait SarifOutput {
+    fn to_sarif(&self) -> Value;
+}
+
+impl SarifOutput for HashMap<String, FunctionAnalysis> {
+    fn to_sarif(&self) -> Value {
+        to_sarif(self)
+    }
+}
+
+impl SarifOutput for ArborDatabase {
+    fn to_sarif(&self) -> Value {
+        to_sarif(&self.functions)
+    }
+}
+
+/// One result per raise site (`raise.<ExceptionType>`) and per None source
+/// (`none.<kind>`). A site reached from several analyzed functions is
+/// reported once, naming them all, at the level of the riskiest: `error`
+/// for high risk, `warning` for medium, `note` for low. Raises caught on the
+/// way are marked as suppressed in source.
+pub fn to_sarif(analyses: &HashMap<String, FunctionAnalysis>) -> Value {
+    let mut ids: Vec<&String> = analyses.keys().collect();
+    ids.sort();
+
+    let mut sites: BTreeMap<SiteKey, Site> = BTreeMap::new();
+    for id in ids {
+        let analysis = &analyses[id];
+        let risk = analysis.risk_level();
+        for raise in &analysis.raises {
+            let key = SiteKey::new(format!("raise.{}", raise.exception_type), &raise.raise_location);
+            let site = sites.entry(key).or_insert_with(|| Site {
+                description: format!("`{}` is raised", raise.exception_type),
+                risk,
+                functions: Vec::new(),
+                suppressed: true,
+            });
+            site.add(id, risk);
+            site.suppressed &= raise.suppressed_by.is_some();
+        }
+        for source in &analysis.none_sources {
+            let rule = format!("none.{}", source.kind.as_str().replace(' ', "-"));
+            let key = SiteKey::new(rule, &source.location);
+            let site = sites.entry(key).or_insert_with(|| Site {
+                description: format!("None can come from this {}", source.kind.as_str()),
+                risk,
+                functions: Vec::new(),
+                suppressed: false,
+            });
+            site.add(id, risk);
+        }
+    }
+
+    let mut rules: Vec<&str> = sites.keys().map(|key| key.rule.as_str()).collect();
+    rules.sort();
+    rules.dedup();
+
+    let results: Vec<Value> = sites
+        .iter()
+        .map(|(key, site)| {
+            let mut result = json!({
+                "ruleId": key.rule,
+                "ruleIndex": rules.binary_search(&key.rule.as_str()).unwrap_or_default(),
+                "level": sarif_level(site.risk),
+                "message": {
+                    "text": format!("{}; reaches {}", site.description, site.functions.join(", ")),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": key.uri },
+                        "region": region(key.line, key.column),
+                    },
+                }],
+            });
+            if site.suppressed {
+                result["suppressions"] = json!([{ "kind": "inSource" }]);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "arbor",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|rule| sarif_rule(rule)).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct SiteKey {
+    uri: String,
+    line: u32,
+    column: Option<u32>,
+    rule: String,
+}
+
+impl SiteKey {
+    fn new(rule: String, location: &CodeLocation) -> Self {
+        Self {
+            uri: artifact_uri(&location.file),
+            line: location.line,
+            column: location.column,
+            rule,
+        }
+    }
+}
+
+struct Site {
+    description: String,
+    risk: RiskLevel,
+    functions: Vec<String>,
+    /// Every function reaching the site has it caught on the way.
+    suppressed: bool,
+}
+
+impl Site {
+    fn add(&mut self, function: &str, risk: RiskLevel) {
+        self.risk = self.risk.max(risk);
+        self.functions.push(function.to_string());
+    }
+}
+
+fn sarif_level(risk: RiskLevel) -> &'static str {
+    match risk {
+        RiskLevel::High => "error",
+        RiskLevel::Medium => "warning",
+        RiskLevel::Low => "note",
+    }
+}
+
+fn sarif_rule(rule: &str) -> Value {
+    let text = match rule.split_once('.') {
+        Some(("raise", exception)) => format!("Raises {}", exception),
+        Some((_, kind)) => format!("Produces None ({})", kind.replace('-', " ")),
+        None => rule.to_string(),
+    };
+    json!({ "id": rule, "shortDescription": { "text": text } })
+}
+
+/// SARIF columns are 1-based; ours are 0-based.
+fn region(line: u32, column: Option<u32>) -> Value {
+    let mut region = json!({ "startLine": line.max(1) });
+    if let Some(column) = column {
+        region["startColumn"] = json!(column + 1);
+    }
+    region
+}
+
+/// `path` relative to the current directory where possible, with forward
+/// slashes, so code scanning can match it to files in the repository.
+fn artifact_uri(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{ExceptionHandler, HandlerStrategy, NoneSource, NoneSourceKind, RaiseStatement};
+    use std::path::PathBuf;
+
+    fn fixture() -> HashMap<String, FunctionAnalysis> {
+        let file = PathBuf::from("app/api.py");
+        let mut analysis = FunctionAnalysis::new(
+            "app.api.fetch".to_string(),
+            "def fetch(url)".to_string(),
+            CodeLocation::new(file.clone(), 3),
+        );
+        analysis.raises.push(RaiseStatement::new(
+            "ValueError".to_string(),
+            "builtins.ValueError".to_string(),
+            CodeLocation::new(file.clone(), 5).with_column(8),
+        ));
+        let mut caught = RaiseStatement::new(
+            "KeyError".to_string(),
+            "builtins.KeyError".to_string(),
+            CodeLocation::new(file.clone(), 9),
+        );
+        caught.suppressed_by = Some(ExceptionHandler {
+            caught_types: vec!["KeyError".to_string()],
+            location: CodeLocation::new(file.clone(), 10),
+            strategy: HandlerStrategy::Suppresses,
+            try_line_start: 8,
+            try_line_end: 9,
+            covered_calls: Vec::new(),
+            body: "except KeyError:\n    pass".to_string(),
+        });
+        analysis.raises.push(caught);
+        analysis
+            .none_sources
+            .push(NoneSource::new(NoneSourceKind::ImplicitReturn, CodeLocation::new(file, 12)));
+
+        let mut caller = analysis.clone();
+        caller.function_id = "app.main".to_string();
+        caller.none_sources.clear();
+
+        HashMap::from([(analysis.function_id.clone(), analysis), (caller.function_id.clone(), caller)])
+    }
+
+    #[test]
+    fn test_to_sarif_structure() {
+        let sarif = fixture().to_sarif();
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["$schema"], SARIF_SCHEMA);
+
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "arbor");
+        let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|rule| rule["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, vec!["none.implicit-return", "raise.KeyError", "raise.ValueError"]);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        for result in results {
+            let rule = result["ruleId"].as_str().unwrap();
+            assert_eq!(rules[result["ruleIndex"].as_u64().unwrap() as usize], rule);
+            assert!(["error", "warning", "note"].contains(&result["level"].as_str().unwrap()));
+        }
+
+        let value_error = results.iter().find(|r| r["ruleId"] == "raise.ValueError").unwrap();
+        let location = &value_error["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "app/api.py");
+        assert_eq!(location["region"], json!({ "startLine": 5, "startColumn": 9 }));
+        assert_eq!(
+            value_error["message"]["text"],
+            "`ValueError` is raised; reaches app.api.fetch, app.main"
+        );
+        assert!(value_error.get("suppressions").is_none());
+
+        let key_error = results.iter().find(|r| r["ruleId"] == "raise.KeyError").unwrap();
+        assert_eq!(key_error["suppressions"][0]["kind"], "inSource");
+    }
+
+    #[test]
+    fn test_to_sarif_round_trips_through_json() {
+        let sarif = to_sarif(&fixture());
+        let text = serde_json::to_string_pretty(&sarif).unwrap();
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed, sarif);
+        assert_eq!(to_sarif(&HashMap::new())["runs"][0]["results"], json!([]));
+    }
+}
@@ -0,0 +1,20 @@
+use crate::core::types::FunctionAnalysis;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use serde::Serialize;
+
+/// JSON Schema for the analyses `arbor query function --format json` prints,
+/// with `RaiseStatement`, `NoneSource` and `CodeLocation` under `definitions`.
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct SchemaOutput {
+    schema: RootSchema,
+}
+
+impl SchemaOutput {
+    pub fn function_analysis() -> Self {
+        Self {
+            schema: schema_for!(FunctionAnalysis),
+        }
+    }
+}
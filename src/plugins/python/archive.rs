@@ -0,0 +1,127 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Whether `path` names a `.whl` or `.egg` archive, by extension.
+pub fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "whl" || ext == "egg")
+}
+
+/// The archives directly inside `dir`, sorted by name.
+pub fn archives_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut archives: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| is_archive(p) && p.is_file())
+        .collect();
+    archives.sort();
+    archives
+}
+
+/// Whether an entry name belongs to an importable package: every directory
+/// above it is a Python identifier, which leaves out `*.dist-info`,
+/// `EGG-INFO` and the like.
+pub fn is_package_entry(name: &str) -> bool {
+    let mut parts: Vec<&str> = name.split('/').collect();
+    parts.pop();
+    parts.iter().all(|part| is_identifier(part))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn open(archive: &Path) -> io::Result<zip::ZipArchive<File>> {
+    zip::ZipArchive::new(File::open(archive)?).map_err(io::Error::from)
+}
+
+/// Splits a path inside an archive into the archive and the entry name. Like
+/// `zipimport`, a file in an archive is addressed by joining its entry name
+/// onto the archive path: `site-packages/pkg-1.0.egg/pkg/api.py`.
+fn split_archive_path(path: &Path) -> Option<(&Path, String)> {
+    let archive = path.ancestors().skip(1).find(|p| is_archive(p) && p.is_file())?;
+    let entry = path.strip_prefix(archive).ok()?;
+    let parts: Vec<String> = entry.iter().map(|c| c.to_string_lossy().into_owned()).collect();
+    Some((archive, parts.join("/")))
+}
+
+/// Whether `path` is a source file on disk or inside an archive.
+pub fn source_exists(path: &Path) -> bool {
+    if path.is_file() {
+        return true;
+    }
+    match split_archive_path(path) {
+        Some((archive, entry)) => open(archive).is_ok_and(|mut zip| zip.by_name(&entry).is_ok()),
+        None => false,
+    }
+}
+
+/// Reads a source file on disk or inside an archive.
+pub fn read_source(path: &Path) -> io::Result<String> {
+    if path.is_file() {
+        return std::fs::read_to_string(path);
+    }
+    let (archive, entry) = split_archive_path(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+    let mut zip = open(archive)?;
+    let mut file = zip.by_name(&entry).map_err(io::Error::from)?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// The file in `archive` defining module `parts`: the package's
+/// `__init__.py` or the module's `.py` file.
+pub fn find_module(archive: &Path, parts: &[&str]) -> Option<PathBuf> {
+    let zip = open(archive).ok()?;
+    let module = parts.join("/");
+    let candidates = [format!("{}/__init__.py", module), format!("{}.py", module)];
+    let mut names = zip.file_names();
+    let found = names.find(|name| candidates.iter().any(|c| c == name))?;
+    Some(archive.join(found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_archive(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_archive_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let egg = dir.path().join("pkg-1.0-py3.8.egg");
+        write_archive(&egg, &[
+            ("pkg/__init__.py", ""),
+            ("pkg/api.py", "def get():\n    pass\n"),
+            ("EGG-INFO/PKG-INFO", "Name: pkg\n"),
+        ]);
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        assert_eq!(archives_in(dir.path()), vec![egg.clone()]);
+        assert_eq!(find_module(&egg, &["pkg"]), Some(egg.join("pkg/__init__.py")));
+        assert_eq!(find_module(&egg, &["pkg", "api"]), Some(egg.join("pkg/api.py")));
+        assert_eq!(find_module(&egg, &["other"]), None);
+
+        assert!(source_exists(&egg.join("pkg/api.py")));
+        assert!(!source_exists(&egg.join("pkg/missing.py")));
+        assert_eq!(read_source(&egg.join("pkg/api.py")).unwrap(), "def get():\n    pass\n");
+
+        assert!(is_package_entry("pkg/api.py"));
+        assert!(is_package_entry("setup.py"));
+        assert!(!is_package_entry("EGG-INFO/scripts/run.py"));
+        assert!(!is_package_entry("pkg-1.0.dist-info/x.py"));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::core::types::{CodeLocation, NoneSource, NoneSourceKind, RaiseStatement};
+use crate::core::types::{
+    CatchClause, CodeLocation, ExceptionHandler, HandlerStrategy, NoneFlowPath, NoneSource, NoneSourceKind,
+    PropertyKind, RaiseStatement, ReturnAnnotation,
+};
 use std::collections::HashMap;
 use std::path::Path;
 use thiserror::Error;
@@ -12,6 +15,9 @@ pub enum ExtractorError {
     ExtractionFailed(String),
 }
 
+/// Lines of code kept before and after a raise in `RaiseStatement::source_context`.
+pub const SOURCE_CONTEXT_LINES: usize = 2;
+
 #[derive(Debug, Clone, Default)]
 pub struct CallContext {
     pub current_module: String,
@@ -69,6 +75,20 @@ fn extract_raises_from_node(
     }
 }
 
+/// Up to `SOURCE_CONTEXT_LINES` lines either side of `line` (1-based),
+/// fewer at the start or end of the file.
+fn source_context_lines(content: &str, line: u32) -> Option<Vec<String>> {
+    let index = (line as usize).checked_sub(1)?;
+    let start = index.saturating_sub(SOURCE_CONTEXT_LINES);
+    let lines: Vec<String> = content
+        .lines()
+        .skip(start)
+        .take(index - start + SOURCE_CONTEXT_LINES + 1)
+        .map(str::to_string)
+        .collect();
+    (lines.len() > index - start).then_some(lines)
+}
+
 fn parse_raise_statement(node: tree_sitter::Node, content: &str, path: &Path) -> Option<RaiseStatement> {
     let line = node.start_position().row as u32 + 1;
     let column = node.start_position().column as u32;
@@ -85,6 +105,8 @@ fn parse_raise_statement(node: tree_sitter::Node, content: &str, path: &Path) ->
         let child = cursor.node();
         match child.kind() {
             "raise" => {}
+            // What follows is the cause, handled below
+            "from" => break,
             "call" => {
                 if let Some(func) = child.child_by_field_name("function") {
                     exception_type = get_node_text(func, content);
@@ -118,46 +140,262 @@ fn parse_raise_statement(node: tree_sitter::Node, content: &str, path: &Path) ->
         stmt = stmt.with_message(msg);
     }
 
+    match node.child_by_field_name("cause") {
+        Some(cause) if cause.kind() == "none" => stmt.suppress_chain = true,
+        Some(cause) => stmt.chained_from = Some(get_node_text(cause, content)),
+        None => {}
+    }
+
     if let Some(condition) = find_guarding_condition(node, content) {
         stmt = stmt.with_condition(condition);
     }
 
+    stmt.source_context = source_context_lines(content, line);
+
     Some(stmt)
 }
 
 fn extract_first_string_arg(args_node: tree_sitter::Node, content: &str) -> Option<String> {
     for i in 0..args_node.child_count() {
         if let Some(child) = args_node.child(i) {
-            if child.kind() == "string" {
-                let text = get_node_text(child, content);
-                let trimmed = text.trim_matches(|c| c == '"' || c == '\'');
-                return Some(trimmed.to_string());
+            if let Some(value) = extract_string_value(child, content) {
+                return Some(value);
             }
         }
     }
     None
 }
 
+/// Extract the literal value of a `string` or `concatenated_string` node.
+///
+/// Prefixes (`f`, `b`, `r`, ...) and quotes, including triple quotes, are
+/// stripped. F-string placeholders are kept as written, e.g. `"Error: {value}"`,
+/// and adjacent literals are joined.
+pub fn extract_string_value(node: tree_sitter::Node, content: &str) -> Option<String> {
+    match node.kind() {
+        "string" => {
+            let mut body_start = node.start_byte();
+            let mut body_end = node.end_byte();
+
+            for i in 0..node.child_count() {
+                if let Some(child) = node.child(i) {
+                    match child.kind() {
+                        "string_start" => body_start = child.end_byte(),
+                        "string_end" => body_end = child.start_byte(),
+                        _ => {}
+                    }
+                }
+            }
+
+            if body_start > body_end {
+                return None;
+            }
+            Some(content[body_start..body_end].to_string())
+        }
+        "concatenated_string" => {
+            let mut value = String::new();
+            for i in 0..node.child_count() {
+                if let Some(child) = node.child(i) {
+                    if let Some(part) = extract_string_value(child, content) {
+                        value.push_str(&part);
+                    }
+                }
+            }
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+/// Exception types listed in the docstring of the function defined on
+/// `line` (1-based), per `parse_docstring_raises`. Empty without a docstring.
+pub fn extract_docstring_raises(tree: &tree_sitter::Tree, content: &str, line: u32) -> Vec<String> {
+    let row = (line as usize).saturating_sub(1);
+    find_function_at(tree.root_node(), row)
+        .and_then(|function| function.child_by_field_name("body"))
+        .and_then(|body| body.named_child(0))
+        .filter(|statement| statement.kind() == "expression_statement")
+        .and_then(|statement| statement.named_child(0))
+        .and_then(|string| extract_string_value(string, content))
+        .map(|docstring| parse_docstring_raises(&docstring))
+        .unwrap_or_default()
+}
+
+/// The return annotation of the function defined at 1-based `line`, if it
+/// has one.
+pub fn extract_type_annotations(tree: &tree_sitter::Tree, content: &str, line: u32) -> Option<ReturnAnnotation> {
+    let row = (line as usize).saturating_sub(1);
+    find_function_at(tree.root_node(), row)
+        .and_then(|function| function.child_by_field_name("return_type"))
+        .map(|annotation| ReturnAnnotation::parse(&get_node_text(annotation, content)))
+}
+
+fn find_function_at(node: tree_sitter::Node, row: usize) -> Option<tree_sitter::Node> {
+    if node.kind() == "function_definition" && node.start_position().row == row {
+        return Some(node);
+    }
+    if node.start_position().row > row || node.end_position().row < row {
+        return None;
+    }
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .find_map(|child| find_function_at(child, row))
+}
+
+/// Exception types declared in a docstring, in order and without duplicates.
+/// Understands Google (`Raises:` followed by indented `Type: why` lines),
+/// NumPy (`Raises` underlined with dashes, one type per line) and Sphinx
+/// (`:raises Type: why`) styles.
+pub fn parse_docstring_raises(docstring: &str) -> Vec<String> {
+    let lines: Vec<&str> = docstring.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let is_underline = |line: &str| {
+        let trimmed = line.trim();
+        !trimmed.is_empty() && trimmed.chars().all(|c| c == '-')
+    };
+    let mut found: Vec<String> = Vec::new();
+    let push_types = |text: &str, found: &mut Vec<String>| {
+        for name in text.split(',').map(str::trim) {
+            let valid = !name.is_empty()
+                && name.split('.').all(|part| {
+                    part.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                        && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+                });
+            if valid && !found.iter().any(|f| f == name) {
+                found.push(name.to_string());
+            }
+        }
+    };
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if let Some(rest) = trimmed.strip_prefix(":raises").or_else(|| trimmed.strip_prefix(":raise")) {
+            if let Some((types, _)) = rest.split_once(':') {
+                push_types(types, &mut found);
+            }
+        } else if trimmed == "Raises:" {
+            let header = indent(line);
+            let mut entry_indent = None;
+            while i + 1 < lines.len() {
+                let next = lines[i + 1];
+                if !next.trim().is_empty() {
+                    if indent(next) <= header {
+                        break;
+                    }
+                    let level = *entry_indent.get_or_insert(indent(next));
+                    if indent(next) == level {
+                        let entry = next.trim();
+                        push_types(entry.split_once(':').map_or(entry, |(types, _)| types), &mut found);
+                    }
+                }
+                i += 1;
+            }
+        } else if trimmed == "Raises" && lines.get(i + 1).is_some_and(|next| is_underline(next)) {
+            let header = indent(line);
+            i += 1;
+            while i + 1 < lines.len() {
+                let next = lines[i + 1];
+                let starts_section = lines.get(i + 2).is_some_and(|after| is_underline(after));
+                if starts_section || (!next.trim().is_empty() && indent(next) < header) {
+                    break;
+                }
+                if !next.trim().is_empty() && indent(next) == header {
+                    let entry = next.trim();
+                    push_types(entry.split_once(':').map_or(entry, |(types, _)| types), &mut found);
+                }
+                i += 1;
+            }
+        }
+        i += 1;
+    }
+
+    found
+}
+
+/// The condition of the innermost branch containing `node`: an `if` or
+/// `elif` condition, a `case` arm, or for an `else` branch the negation of the
+/// conditions before it. Conditions outside the enclosing function don't count.
 fn find_guarding_condition(node: tree_sitter::Node, content: &str) -> Option<String> {
     let mut current = node.parent();
     while let Some(parent) = current {
-        if parent.kind() == "if_statement" {
-            if let Some(condition) = parent.child_by_field_name("condition") {
-                return Some(get_node_text(condition, content));
+        match parent.kind() {
+            "if_statement" | "elif_clause" => {
+                if let Some(condition) = parent.child_by_field_name("condition") {
+                    return Some(get_node_text(condition, content));
+                }
+            }
+            "else_clause" => {
+                if let Some(if_statement) = parent.parent().filter(|p| p.kind() == "if_statement") {
+                    return Some(else_condition(if_statement, content));
+                }
             }
+            "case_clause" => return Some(case_condition(parent, content)),
+            "function_definition" | "lambda" | "class_definition" => return None,
+            _ => {}
         }
         current = parent.parent();
     }
     None
 }
 
+/// `not (a or b)` for the `else` of `if a: ... elif b: ...`.
+fn else_condition(if_statement: tree_sitter::Node, content: &str) -> String {
+    let mut conditions: Vec<String> = if_statement
+        .child_by_field_name("condition")
+        .map(|c| get_node_text(c, content))
+        .into_iter()
+        .collect();
+
+    let mut cursor = if_statement.walk();
+    for alternative in if_statement.children_by_field_name("alternative", &mut cursor) {
+        if alternative.kind() == "elif_clause" {
+            if let Some(condition) = alternative.child_by_field_name("condition") {
+                conditions.push(get_node_text(condition, content));
+            }
+        }
+    }
+
+    format!("not ({})", conditions.join(" or "))
+}
+
+/// Describe a `case` arm by its pattern(s) and guard, e.g. `case [x, y] if x > 0`.
+fn case_condition(case_clause: tree_sitter::Node, content: &str) -> String {
+    let patterns: Vec<String> = (0..case_clause.named_child_count())
+        .filter_map(|i| case_clause.named_child(i))
+        .filter(|n| n.kind() == "case_pattern")
+        .map(|n| get_node_text(n, content))
+        .collect();
+
+    let mut condition = format!("case {}", patterns.join(", "));
+    if let Some(guard) = case_clause.child_by_field_name("guard") {
+        condition.push(' ');
+        condition.push_str(&get_node_text(guard, content));
+    }
+    condition
+}
+
+fn enclosing_case_clause(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        match parent.kind() {
+            "case_clause" => return Some(parent),
+            "function_definition" | "lambda" | "class_definition" => return None,
+            _ => current = parent.parent(),
+        }
+    }
+    None
+}
+
 pub fn extract_none_sources(
     tree: &tree_sitter::Tree,
     content: &str,
     path: &Path,
 ) -> Result<Vec<NoneSource>, ExtractorError> {
     let mut sources = Vec::new();
-    extract_none_from_node(tree.root_node(), content, path, &mut sources, None);
+    extract_none_from_node(tree.root_node(), content, path, &mut sources, None, None);
     Ok(sources)
 }
 
@@ -169,16 +407,18 @@ pub fn extract_none_sources_in_range(
     line_end: u32,
 ) -> Result<Vec<NoneSource>, ExtractorError> {
     let mut sources = Vec::new();
-    extract_none_from_node(tree.root_node(), content, path, &mut sources, Some((line_start, line_end)));
+    extract_none_from_node(tree.root_node(), content, path, &mut sources, Some((line_start, line_end)), None);
     Ok(sources)
 }
 
+/// `assignment` is the assignment whose right-hand side is `node`, if any.
 fn extract_none_from_node(
     node: tree_sitter::Node,
     content: &str,
     path: &Path,
     sources: &mut Vec<NoneSource>,
     line_range: Option<(u32, u32)>,
+    assignment: Option<tree_sitter::Node>,
 ) {
     let line = node.start_position().row as u32 + 1;
 
@@ -187,12 +427,17 @@ fn extract_none_from_node(
     if in_range {
         match node.kind() {
             "return_statement" => {
-                if let Some(source) = parse_return_none(node, content, path) {
+                if let Some(source) = parse_return_none(node, content, path, assignment) {
                     sources.push(source);
                 }
             }
             "call" => {
-                if let Some(source) = check_none_returning_call(node, content, path) {
+                if let Some(source) = check_none_returning_call(node, content, path, assignment) {
+                    sources.push(source);
+                }
+            }
+            "case_clause" => {
+                if let Some(source) = parse_fallthrough_case(node, content, path) {
                     sources.push(source);
                 }
             }
@@ -200,17 +445,127 @@ fn extract_none_from_node(
         }
     }
 
+    let right = match node.kind() {
+        "assignment" => node.child_by_field_name("right").map(|n| n.id()),
+        _ => None,
+    };
     for i in 0..node.child_count() {
         if let Some(child) = node.child(i) {
-            extract_none_from_node(child, content, path, sources, line_range);
+            let child_assignment = (Some(child.id()) == right).then_some(node);
+            extract_none_from_node(child, content, path, sources, line_range, child_assignment);
+        }
+    }
+}
+
+/// The target of `assignment` when it is a plain name or attribute:
+/// `x` for `x = ...`, `self.result` for `self.result = ...`.
+fn assignment_target(assignment: tree_sitter::Node, content: &str) -> Option<String> {
+    let left = assignment.child_by_field_name("left")?;
+    matches!(left.kind(), "identifier" | "attribute").then(|| get_node_text(left, content))
+}
+
+/// Follows the None from `source` through its function: every assignment
+/// whose right side uses a tracked variable adds its target to the path, up
+/// to the first `return` that uses one. A linear scan in source order, so
+/// branches are merged and reassignments are not treated as kills.
+pub fn trace_none_flow(tree: &tree_sitter::Tree, content: &str, source: &NoneSource) -> NoneFlowPath {
+    let mut flow = NoneFlowPath::new(source.clone());
+    if matches!(source.kind, NoneSourceKind::ExplicitReturn | NoneSourceKind::ImplicitReturn) {
+        flow.return_location = Some(source.location.clone());
+        return flow;
+    }
+    let Some(ref variable) = source.variable_name else {
+        return flow;
+    };
+    flow.assignments.push((variable.clone(), source.location.clone()));
+
+    let row = (source.location.line as usize).saturating_sub(1);
+    let body = find_enclosing_function(tree.root_node(), row).and_then(|f| f.child_by_field_name("body"));
+    if let Some(body) = body {
+        let mut tracked = vec![variable.clone()];
+        trace_flow_from_node(body, content, row, &source.location.file, &mut tracked, &mut flow);
+    }
+    flow
+}
+
+fn find_enclosing_function(node: tree_sitter::Node, row: usize) -> Option<tree_sitter::Node> {
+    if node.start_position().row > row || node.end_position().row < row {
+        return None;
+    }
+    let inner = (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .find_map(|child| find_enclosing_function(child, row));
+    inner.or_else(|| (node.kind() == "function_definition").then_some(node))
+}
+
+fn trace_flow_from_node(
+    node: tree_sitter::Node,
+    content: &str,
+    source_row: usize,
+    path: &Path,
+    tracked: &mut Vec<String>,
+    flow: &mut NoneFlowPath,
+) {
+    if flow.return_location.is_some() {
+        return;
+    }
+    let after_source = node.start_position().row > source_row;
+    let location = || {
+        CodeLocation::new(path.to_path_buf(), node.start_position().row as u32 + 1)
+            .with_column(node.start_position().column as u32)
+    };
+
+    match node.kind() {
+        "function_definition" | "class_definition" | "lambda" => return,
+        "assignment" if after_source => {
+            let target = assignment_target(node, content);
+            let right = node.child_by_field_name("right");
+            if let (Some(target), Some(right)) = (target, right) {
+                if mentions_any(right, content, tracked) {
+                    flow.assignments.push((target.clone(), location()));
+                    if !tracked.contains(&target) {
+                        tracked.push(target);
+                    }
+                }
+            }
+            return;
+        }
+        "return_statement" if after_source => {
+            if node.named_child(0).is_some_and(|value| mentions_any(value, content, tracked)) {
+                flow.return_location = Some(location());
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    for i in 0..node.named_child_count() {
+        if let Some(child) = node.named_child(i) {
+            trace_flow_from_node(child, content, source_row, path, tracked, flow);
         }
     }
 }
 
-fn parse_return_none(node: tree_sitter::Node, content: &str, path: &Path) -> Option<NoneSource> {
+/// Whether `node` reads any of `names` (plain names or dotted attributes).
+fn mentions_any(node: tree_sitter::Node, content: &str, names: &[String]) -> bool {
+    if matches!(node.kind(), "identifier" | "attribute") && names.contains(&get_node_text(node, content)) {
+        return true;
+    }
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|child| mentions_any(child, content, names))
+}
+
+fn parse_return_none(
+    node: tree_sitter::Node,
+    content: &str,
+    path: &Path,
+    assignment: Option<tree_sitter::Node>,
+) -> Option<NoneSource> {
     let line = node.start_position().row as u32 + 1;
     let column = node.start_position().column as u32;
     let location = CodeLocation::new(path.to_path_buf(), line).with_column(column);
+    let variable_name = assignment.and_then(|a| assignment_target(a, content));
 
     let mut has_value = false;
     let mut is_explicit_none = false;
@@ -226,24 +581,69 @@ fn parse_return_none(node: tree_sitter::Node, content: &str, path: &Path) -> Opt
         }
     }
 
-    if is_explicit_none {
+    let source = if enclosing_case_clause(node).is_some() && (is_explicit_none || !has_value) {
+        let mut source = NoneSource::new(NoneSourceKind::MatchArm, location);
+        if let Some(condition) = find_guarding_condition(node, content) {
+            source = source.with_condition(condition);
+        }
+        source
+    } else if is_explicit_none {
         let mut source = NoneSource::new(NoneSourceKind::ExplicitReturn, location);
         if let Some(condition) = find_guarding_condition(node, content) {
             source = source.with_condition(condition);
         }
-        Some(source)
+        source
     } else if !has_value {
         let mut source = NoneSource::new(NoneSourceKind::ImplicitReturn, location);
         if let Some(condition) = find_guarding_condition(node, content) {
             source = source.with_condition(condition);
         }
-        Some(source)
+        source
     } else {
-        None
+        return None;
+    };
+
+    Some(match variable_name {
+        Some(name) => source.with_variable_name(name),
+        None => source,
+    })
+}
+
+/// A `case` arm that neither returns nor raises, in a `match` that ends its
+/// function, falls off the end and implicitly returns None.
+fn parse_fallthrough_case(node: tree_sitter::Node, content: &str, path: &Path) -> Option<NoneSource> {
+    let consequence = node.child_by_field_name("consequence")?;
+    let exits = (0..consequence.named_child_count())
+        .filter_map(|i| consequence.named_child(i))
+        .any(|n| matches!(n.kind(), "return_statement" | "raise_statement"));
+    if exits {
+        return None;
+    }
+
+    let match_statement = node.parent()?.parent()?;
+    let function_body = match_statement.parent()?;
+    if match_statement.kind() != "match_statement"
+        || function_body.parent().map(|n| n.kind()) != Some("function_definition")
+    {
+        return None;
     }
+    let last_statement = function_body.named_child(function_body.named_child_count().checked_sub(1)?)?;
+    if last_statement.id() != match_statement.id() {
+        return None;
+    }
+
+    let line = node.start_position().row as u32 + 1;
+    let column = node.start_position().column as u32;
+    let location = CodeLocation::new(path.to_path_buf(), line).with_column(column);
+    Some(NoneSource::new(NoneSourceKind::MatchArm, location).with_condition(case_condition(node, content)))
 }
 
-fn check_none_returning_call(node: tree_sitter::Node, content: &str, path: &Path) -> Option<NoneSource> {
+fn check_none_returning_call(
+    node: tree_sitter::Node,
+    content: &str,
+    path: &Path,
+    assignment: Option<tree_sitter::Node>,
+) -> Option<NoneSource> {
     let func = node.child_by_field_name("function")?;
 
     if func.kind() == "attribute" {
@@ -263,7 +663,11 @@ fn check_none_returning_call(node: tree_sitter::Node, content: &str, path: &Path
                 NoneSourceKind::FunctionCall
             };
 
-            return Some(NoneSource::new(kind, location));
+            let source = NoneSource::new(kind, location);
+            return Some(match assignment.and_then(|a| assignment_target(a, content)) {
+                Some(name) => source.with_variable_name(name),
+                None => source,
+            });
         }
     }
 
@@ -275,7 +679,7 @@ pub fn extract_calls(
     content: &str,
 ) -> Result<Vec<String>, ExtractorError> {
     let mut calls = Vec::new();
-    extract_calls_from_node(tree.root_node(), content, &mut calls, None, None);
+    extract_calls_from_node(tree.root_node(), content, &mut calls, &mut Vec::new(), None, None);
     Ok(calls)
 }
 
@@ -286,7 +690,7 @@ pub fn extract_calls_in_range(
     line_end: u32,
 ) -> Result<Vec<String>, ExtractorError> {
     let mut calls = Vec::new();
-    extract_calls_from_node(tree.root_node(), content, &mut calls, Some((line_start, line_end)), None);
+    extract_calls_from_node(tree.root_node(), content, &mut calls, &mut Vec::new(), Some((line_start, line_end)), None);
     Ok(calls)
 }
 
@@ -298,14 +702,45 @@ pub fn extract_calls_in_range_with_context(
     context: &CallContext,
 ) -> Result<Vec<String>, ExtractorError> {
     let mut calls = Vec::new();
-    extract_calls_from_node(tree.root_node(), content, &mut calls, Some((line_start, line_end)), Some(context));
+    extract_calls_from_node(
+        tree.root_node(),
+        content,
+        &mut calls,
+        &mut Vec::new(),
+        Some((line_start, line_end)),
+        Some(context),
+    );
     Ok(calls)
 }
 
+/// Like `extract_calls_in_range_with_context`, also returning the subset of
+/// calls whose coroutine is awaited: `await fetch()`, or passed to
+/// `asyncio.gather`, `asyncio.create_task` or a `TaskGroup`'s `create_task`.
+pub fn extract_calls_and_awaits_in_range_with_context(
+    tree: &tree_sitter::Tree,
+    content: &str,
+    line_start: u32,
+    line_end: u32,
+    context: &CallContext,
+) -> Result<(Vec<String>, Vec<String>), ExtractorError> {
+    let mut calls = Vec::new();
+    let mut awaited = Vec::new();
+    extract_calls_from_node(
+        tree.root_node(),
+        content,
+        &mut calls,
+        &mut awaited,
+        Some((line_start, line_end)),
+        Some(context),
+    );
+    Ok((calls, awaited))
+}
+
 fn extract_calls_from_node(
     node: tree_sitter::Node,
     content: &str,
     calls: &mut Vec<String>,
+    awaited: &mut Vec<String>,
     line_range: Option<(u32, u32)>,
     context: Option<&CallContext>,
 ) {
@@ -318,8 +753,17 @@ fn extract_calls_from_node(
             if let Some(func) = node.child_by_field_name("function") {
                 let call_name = get_node_text(func, content);
                 let qualified = qualify_call(&call_name, context);
-                if !calls.contains(&qualified) {
-                    calls.push(qualified);
+                // The spawner itself is asyncio plumbing; the coroutines
+                // passed to it are picked up as awaited calls below
+                if !is_task_spawner(&call_name, &qualified) {
+                    let is_awaited = node.parent().is_some_and(|p| p.kind() == "await")
+                        || is_spawned_coroutine(node, content, context);
+                    if is_awaited && !awaited.contains(&qualified) {
+                        awaited.push(qualified.clone());
+                    }
+                    if !calls.contains(&qualified) {
+                        calls.push(qualified);
+                    }
                 }
             }
         }
@@ -327,7 +771,107 @@ fn extract_calls_from_node(
 
     for i in 0..node.child_count() {
         if let Some(child) = node.child(i) {
-            extract_calls_from_node(child, content, calls, line_range, context);
+            extract_calls_from_node(child, content, calls, awaited, line_range, context);
+        }
+    }
+}
+
+/// Calls that take coroutines and run them as tasks, so exceptions raised in
+/// the coroutines surface where the gather or task is awaited.
+fn is_task_spawner(call_name: &str, qualified: &str) -> bool {
+    matches!(qualified, "asyncio.gather" | "asyncio.create_task") || call_name.ends_with(".create_task")
+}
+
+/// Whether `call` is a coroutine argument of a task spawner, as in
+/// `asyncio.gather(fetch(a), fetch(b))`.
+fn is_spawned_coroutine(call: tree_sitter::Node, content: &str, context: Option<&CallContext>) -> bool {
+    let Some(spawner) = call
+        .parent()
+        .filter(|p| p.kind() == "argument_list")
+        .and_then(|args| args.parent())
+        .filter(|p| p.kind() == "call")
+    else {
+        return false;
+    };
+    spawner.child_by_field_name("function").is_some_and(|func| {
+        let call_name = get_node_text(func, content);
+        is_task_spawner(&call_name, &qualify_call(&call_name, context))
+    })
+}
+
+/// The property accessor a `decorated_definition` defines, if any:
+/// `@property` and `@cached_property` are getters, `@name.setter` and
+/// `@name.deleter` the other accessors.
+pub fn property_kind(decorated: tree_sitter::Node, content: &str) -> Option<PropertyKind> {
+    (0..decorated.named_child_count())
+        .filter_map(|i| decorated.named_child(i))
+        .filter(|n| n.kind() == "decorator")
+        .find_map(|decorator| {
+            let text = get_node_text(decorator, content);
+            let name = text.trim_start_matches('@').trim();
+            match name {
+                "property" | "cached_property" | "functools.cached_property" => Some(PropertyKind::Getter),
+                _ if name.ends_with(".getter") => Some(PropertyKind::Getter),
+                _ if name.ends_with(".setter") => Some(PropertyKind::Setter),
+                _ if name.ends_with(".deleter") => Some(PropertyKind::Deleter),
+                _ => None,
+            }
+        })
+}
+
+/// Attribute reads, assignments and deletions in the line range, qualified
+/// like calls, with the property accessor each would invoke. Attributes that
+/// are themselves called are left to the call extraction.
+pub fn extract_attribute_accesses_in_range_with_context(
+    tree: &tree_sitter::Tree,
+    content: &str,
+    line_start: u32,
+    line_end: u32,
+    context: &CallContext,
+) -> Vec<(String, PropertyKind)> {
+    let mut accesses = Vec::new();
+    extract_attribute_accesses_from_node(tree.root_node(), content, &mut accesses, (line_start, line_end), context);
+    accesses
+}
+
+fn extract_attribute_accesses_from_node(
+    node: tree_sitter::Node,
+    content: &str,
+    accesses: &mut Vec<(String, PropertyKind)>,
+    line_range: (u32, u32),
+    context: &CallContext,
+) {
+    if node.kind() == "attribute" {
+        let line = node.start_position().row as u32 + 1;
+        let parent = node.parent();
+        let is_field = |field: &str| {
+            parent
+                .and_then(|p| p.child_by_field_name(field))
+                .is_some_and(|n| n.id() == node.id())
+        };
+
+        let kinds: &[PropertyKind] = match parent.map(|p| p.kind()) {
+            Some("call") if is_field("function") => &[],
+            Some("assignment") if is_field("left") => &[PropertyKind::Setter],
+            Some("augmented_assignment") if is_field("left") => &[PropertyKind::Getter, PropertyKind::Setter],
+            Some("delete_statement") => &[PropertyKind::Deleter],
+            _ => &[PropertyKind::Getter],
+        };
+
+        if line >= line_range.0 && line <= line_range.1 {
+            let qualified = qualify_call(&get_node_text(node, content), Some(context));
+            for kind in kinds {
+                let access = (qualified.clone(), *kind);
+                if !accesses.contains(&access) {
+                    accesses.push(access);
+                }
+            }
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            extract_attribute_accesses_from_node(child, content, accesses, line_range, context);
         }
     }
 }
@@ -370,36 +914,347 @@ fn qualify_call(call_name: &str, context: Option<&CallContext>) -> String {
     call_name.to_string()
 }
 
+/// Extract the `except` clauses of every `try` statement starting in the line
+/// range. `covered_calls` is left empty; the caller resolves those with its
+/// own `CallContext`.
+pub fn extract_handlers_in_range(
+    tree: &tree_sitter::Tree,
+    content: &str,
+    path: &Path,
+    line_start: u32,
+    line_end: u32,
+) -> Result<Vec<ExceptionHandler>, ExtractorError> {
+    let mut handlers = Vec::new();
+    extract_handlers_from_node(tree.root_node(), content, path, &mut handlers, (line_start, line_end));
+    Ok(handlers)
+}
+
+fn extract_handlers_from_node(
+    node: tree_sitter::Node,
+    content: &str,
+    path: &Path,
+    handlers: &mut Vec<ExceptionHandler>,
+    line_range: (u32, u32),
+) {
+    if node.kind() == "try_statement" {
+        let line = node.start_position().row as u32 + 1;
+
+        if line >= line_range.0 && line <= line_range.1 {
+            if let Some(body) = node.child_by_field_name("body") {
+                let try_line_start = body.start_position().row as u32 + 1;
+                let try_line_end = body.end_position().row as u32 + 1;
+
+                for i in 0..node.named_child_count() {
+                    let Some(clause) = node.named_child(i) else { continue };
+                    if clause.kind() != "except_clause" {
+                        continue;
+                    }
+
+                    let location = CodeLocation::new(path.to_path_buf(), clause.start_position().row as u32 + 1)
+                        .with_column(clause.start_position().column as u32);
+
+                    handlers.push(ExceptionHandler {
+                        caught_types: parse_caught_types(clause, content),
+                        location,
+                        strategy: classify_handler(clause, content),
+                        try_line_start,
+                        try_line_end,
+                        covered_calls: Vec::new(),
+                        body: get_node_text(clause, content),
+                    });
+                }
+            }
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            extract_handlers_from_node(child, content, path, handlers, line_range);
+        }
+    }
+}
+
+/// One `CatchClause` per type named in every `except` clause of the file (one
+/// with type `*` for a bare `except:`). The containing function is the local
+/// dotted name, as in `Client.get`; callers that know the module qualify it.
+pub fn extract_catches(tree: &tree_sitter::Tree, content: &str, path: &Path) -> Vec<CatchClause> {
+    let mut catches = Vec::new();
+    extract_catches_from_node(tree.root_node(), content, path, &mut catches);
+    catches
+}
+
+fn extract_catches_from_node(node: tree_sitter::Node, content: &str, path: &Path, catches: &mut Vec<CatchClause>) {
+    if node.kind() == "except_clause" {
+        let mut location = CodeLocation::new(path.to_path_buf(), node.start_position().row as u32 + 1)
+            .with_column(node.start_position().column as u32);
+        location.containing_function = local_function_name(node, content);
+        let strategy = classify_handler(node, content);
+
+        let mut caught_types = parse_caught_types(node, content);
+        if caught_types.is_empty() {
+            caught_types.push("*".to_string());
+        }
+        for caught_type in caught_types {
+            catches.push(CatchClause {
+                caught_type,
+                location: location.clone(),
+                strategy: strategy.clone(),
+            });
+        }
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            extract_catches_from_node(child, content, path, catches);
+        }
+    }
+}
+
+/// `Class.method` or `function` for the innermost function enclosing `node`,
+/// through any enclosing classes and functions.
+fn local_function_name(node: tree_sitter::Node, content: &str) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        if matches!(ancestor.kind(), "function_definition" | "class_definition") {
+            if let Some(name) = ancestor.child_by_field_name("name") {
+                names.push(get_node_text(name, content));
+            }
+        }
+        current = ancestor.parent();
+    }
+    if names.is_empty() {
+        return None;
+    }
+    names.reverse();
+    Some(names.join("."))
+}
+
+/// Types named in an `except` clause; empty for a bare `except:`.
+pub fn parse_caught_types(clause: tree_sitter::Node, content: &str) -> Vec<String> {
+    let Some(mut value) = clause.child_by_field_name("value") else {
+        return Vec::new();
+    };
+
+    // `except ValueError as e` wraps the type in an as_pattern
+    if value.kind() == "as_pattern" {
+        match value.named_child(0) {
+            Some(inner) => value = inner,
+            None => return Vec::new(),
+        }
+    }
+
+    if value.kind() == "tuple" || value.kind() == "parenthesized_expression" {
+        (0..value.named_child_count())
+            .filter_map(|i| value.named_child(i))
+            .map(|n| get_node_text(n, content))
+            .collect()
+    } else {
+        vec![get_node_text(value, content)]
+    }
+}
+
+fn classify_handler(clause: tree_sitter::Node, content: &str) -> HandlerStrategy {
+    let Some(block) = (0..clause.named_child_count())
+        .filter_map(|i| clause.named_child(i))
+        .find(|n| n.kind() == "block")
+    else {
+        return HandlerStrategy::Handles;
+    };
+
+    let mut raises = Vec::new();
+    extract_raises_from_node(block, content, Path::new(""), &mut raises, None);
+    if let Some(raise) = raises.first() {
+        return if raise.exception_type == "(re-raise)" {
+            HandlerStrategy::Reraise
+        } else {
+            HandlerStrategy::Raises(raise.exception_type.clone())
+        };
+    }
+
+    let statements: Vec<_> = (0..block.named_child_count())
+        .filter_map(|i| block.named_child(i))
+        .filter(|n| n.kind() != "comment")
+        .collect();
+
+    if statements.iter().any(|n| n.kind() == "return_statement") {
+        HandlerStrategy::Returns
+    } else if statements.iter().all(|n| {
+        n.kind() == "pass_statement"
+            || (n.kind() == "expression_statement" && get_node_text(*n, content) == "...")
+    }) {
+        HandlerStrategy::Suppresses
+    } else {
+        HandlerStrategy::Handles
+    }
+}
+
 fn get_node_text(node: tree_sitter::Node, content: &str) -> String {
     content[node.byte_range()].to_string()
 }
 
+/// The names in the module-level `__all__`: set by `__all__ = [...]` or
+/// `(...)`, then extended by `__all__ += [...]`, `__all__.extend([...])` and
+/// `__all__.append("...")`. `None` when the module has no `__all__`, or when
+/// any of those statements uses something other than string literals, since
+/// the exported names can't then be known.
+pub fn extract_dunder_all(tree: &tree_sitter::Tree, content: &str) -> Option<Vec<String>> {
+    let root = tree.root_node();
+    let mut names: Option<Vec<String>> = None;
+
+    let mut cursor = root.walk();
+    for statement in root.named_children(&mut cursor) {
+        if statement.kind() != "expression_statement" {
+            continue;
+        }
+        let Some(expression) = statement.named_child(0) else {
+            continue;
+        };
+        match expression.kind() {
+            "assignment" | "augmented_assignment" => {
+                let is_dunder_all = expression
+                    .child_by_field_name("left")
+                    .is_some_and(|left| left.kind() == "identifier" && get_node_text(left, content) == "__all__");
+                if !is_dunder_all {
+                    continue;
+                }
+                // `__all__: list[str]` alone binds nothing
+                let Some(right) = expression.child_by_field_name("right") else {
+                    continue;
+                };
+                let entries = string_sequence(right, content)?;
+                if expression.kind() == "assignment" {
+                    names = Some(entries);
+                } else {
+                    let operator = expression.child_by_field_name("operator")?;
+                    if get_node_text(operator, content) != "+=" {
+                        return None;
+                    }
+                    names.get_or_insert_with(Vec::new).extend(entries);
+                }
+            }
+            "call" => {
+                let Some(function) = expression.child_by_field_name("function") else {
+                    continue;
+                };
+                let on_dunder_all = function.kind() == "attribute"
+                    && function
+                        .child_by_field_name("object")
+                        .is_some_and(|object| get_node_text(object, content) == "__all__");
+                if !on_dunder_all {
+                    continue;
+                }
+                let arguments = expression.child_by_field_name("arguments")?;
+                let mut arg_cursor = arguments.walk();
+                let args: Vec<_> = arguments
+                    .named_children(&mut arg_cursor)
+                    .filter(|arg| arg.kind() != "comment")
+                    .collect();
+                let [arg] = args.as_slice() else {
+                    return None;
+                };
+                let method = function.child_by_field_name("attribute")?;
+                match get_node_text(method, content).as_str() {
+                    "extend" => names.get_or_insert_with(Vec::new).extend(string_sequence(*arg, content)?),
+                    "append" => names.get_or_insert_with(Vec::new).push(string_literal(*arg, content)?),
+                    _ => return None,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    names
+}
+
+/// The entries of a list or tuple made only of string literals.
+fn string_sequence(node: tree_sitter::Node, content: &str) -> Option<Vec<String>> {
+    if !matches!(node.kind(), "list" | "tuple") {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let items: Vec<_> = node.named_children(&mut cursor).filter(|item| item.kind() != "comment").collect();
+    items.into_iter().map(|item| string_literal(item, content)).collect()
+}
+
+/// The value of a plain string literal; None for an f-string with
+/// placeholders or any other expression.
+fn string_literal(node: tree_sitter::Node, content: &str) -> Option<String> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let mut value = String::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "string_start" | "string_end" => {}
+            "string_content" => value.push_str(&get_node_text(child, content)),
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
 /// Extract imports from a Python file, returning a map from local name to qualified name
 /// e.g., "from requests.exceptions import ConnectionError" -> {"ConnectionError": "requests.exceptions.ConnectionError"}
+///
+/// Only runtime imports are returned: names imported under `if TYPE_CHECKING:`
+/// don't exist when the code runs, so calls must not resolve through them.
 pub fn extract_imports(tree: &tree_sitter::Tree, content: &str) -> HashMap<String, String> {
     let mut imports = HashMap::new();
-    extract_imports_from_node(tree.root_node(), content, &mut imports);
+    let mut type_checking_imports = HashMap::new();
+    extract_imports_from_node(tree.root_node(), content, &mut imports, &mut type_checking_imports, false);
     imports
 }
 
+/// The names imported under `if TYPE_CHECKING:`, in the same form as
+/// `extract_imports`.
+pub fn extract_type_checking_imports(tree: &tree_sitter::Tree, content: &str) -> HashMap<String, String> {
+    let mut imports = HashMap::new();
+    let mut type_checking_imports = HashMap::new();
+    extract_imports_from_node(tree.root_node(), content, &mut imports, &mut type_checking_imports, false);
+    type_checking_imports
+}
+
+/// Whether `node` is `if TYPE_CHECKING:` or `if typing.TYPE_CHECKING:`.
+pub fn is_type_checking_guard(node: tree_sitter::Node, content: &str) -> bool {
+    if node.kind() != "if_statement" {
+        return false;
+    }
+    node.child_by_field_name("condition").is_some_and(|condition| {
+        let text = get_node_text(condition, content);
+        (condition.kind() == "identifier" && text == "TYPE_CHECKING")
+            || (condition.kind() == "attribute" && text == "typing.TYPE_CHECKING")
+    })
+}
+
 fn extract_imports_from_node(
     node: tree_sitter::Node,
     content: &str,
     imports: &mut HashMap<String, String>,
+    type_checking_imports: &mut HashMap<String, String>,
+    type_checking_only: bool,
 ) {
+    let target = if type_checking_only { &mut *type_checking_imports } else { &mut *imports };
     match node.kind() {
         "import_from_statement" => {
-            parse_import_from(node, content, imports);
+            parse_import_from(node, content, target);
         }
         "import_statement" => {
-            parse_import(node, content, imports);
+            parse_import(node, content, target);
         }
         _ => {}
     }
 
+    let guarded_block = if is_type_checking_guard(node, content) {
+        node.child_by_field_name("consequence")
+    } else {
+        None
+    };
     for i in 0..node.child_count() {
         if let Some(child) = node.child(i) {
-            extract_imports_from_node(child, content, imports);
+            let in_guard = type_checking_only || guarded_block == Some(child);
+            extract_imports_from_node(child, content, imports, type_checking_imports, in_guard);
         }
     }
 }
@@ -451,7 +1306,7 @@ fn parse_import(node: tree_sitter::Node, content: &str, imports: &mut HashMap<St
             match child.kind() {
                 "dotted_name" => {
                     let name = get_node_text(child, content);
-                    let local_name = name.split('.').last().unwrap_or(&name).to_string();
+                    let local_name = name.split('.').next_back().unwrap_or(&name).to_string();
                     imports.insert(local_name, name);
                 }
                 "aliased_import" => {
@@ -546,6 +1401,29 @@ mod tests {
         parser.parse(code, None).unwrap()
     }
 
+    #[test]
+    fn test_extract_dunder_all() {
+        let all = |code: &str| extract_dunder_all(&parse_python(code), code);
+        let names = |list: &[&str]| Some(list.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(all("import os\n__all__ = ['a', \"b\",\n    # 'hidden'\n    'c']\n"), names(&["a", "b", "c"]));
+        assert_eq!(
+            all("__all__ = ('a',)\n__all__ += ['b']\n__all__.extend(['c'])\n__all__.append('d')\n"),
+            names(&["a", "b", "c", "d"])
+        );
+        assert_eq!(all("__all__: list[str] = ['a']\n"), names(&["a"]));
+        assert_eq!(all("# __all__ = ['a']\nDOC = \"__all__ = ['b']\"\n"), None);
+        assert_eq!(all("def f():\n    __all__ = ['a']\n"), None);
+        assert_eq!(all("x = obj.__all__\n"), None);
+
+        // Anything that isn't a string literal makes the list unknowable
+        assert_eq!(all("__all__ = [*base.__all__, 'a']\n"), None);
+        assert_eq!(all("__all__ = ['a']\n__all__.extend(base.__all__)\n"), None);
+        assert_eq!(all("__all__ = ['a']\n__all__.remove('a')\n"), None);
+        assert_eq!(all("__all__ = [name for name in dir()]\n"), None);
+        assert_eq!(all("__all__ = [f'{x}']\n"), None);
+    }
+
     #[test]
     fn test_extract_simple_raise() {
         let code = r#"
@@ -576,6 +1454,45 @@ def foo():
         assert_eq!(raises[0].message, None);
     }
 
+    #[test]
+    fn test_extract_chained_raise() {
+        let code = r#"
+def foo():
+    try:
+        load()
+    except OSError as original_err:
+        raise ValueError("x") from original_err
+"#;
+        let tree = parse_python(code);
+        let raises = extract_raises(&tree, code, Path::new("test.py")).unwrap();
+
+        assert_eq!(raises.len(), 1);
+        assert_eq!(raises[0].exception_type, "ValueError");
+        assert_eq!(raises[0].message, Some("x".to_string()));
+        assert_eq!(raises[0].chained_from, Some("original_err".to_string()));
+        assert!(!raises[0].suppress_chain);
+    }
+
+    #[test]
+    fn test_extract_raise_from_none() {
+        let code = r#"
+def foo():
+    raise RuntimeError() from None
+
+def bar(exc):
+    raise errors.Wrapped from exc.__cause__
+"#;
+        let tree = parse_python(code);
+        let raises = extract_raises(&tree, code, Path::new("test.py")).unwrap();
+
+        assert_eq!(raises[0].exception_type, "RuntimeError");
+        assert_eq!(raises[0].chained_from, None);
+        assert!(raises[0].suppress_chain);
+
+        assert_eq!(raises[1].exception_type, "errors.Wrapped");
+        assert_eq!(raises[1].chained_from, Some("exc.__cause__".to_string()));
+    }
+
     #[test]
     fn test_extract_bare_raise() {
         let code = r#"
@@ -607,6 +1524,53 @@ def foo(x):
         assert_eq!(raises[0].condition, Some("x < 0".to_string()));
     }
 
+    #[test]
+    fn test_guarding_condition_is_innermost_branch() {
+        let code = r#"
+def foo(a, b, c, mode):
+    if a and b:
+        if c:
+            raise KeyError("nested")
+        raise IndexError("outer body")
+    if mode == "r":
+        pass
+    elif mode == "w":
+        if not a:
+            raise TypeError("nested in elif")
+        raise ValueError("elif")
+    elif mode == "x":
+        raise OSError("second elif")
+    else:
+        raise RuntimeError("else")
+
+    def inner():
+        raise LookupError("nested function")
+"#;
+        let tree = parse_python(code);
+        let path = Path::new("test.py");
+        let raises = extract_raises(&tree, code, path).unwrap();
+
+        let found: Vec<(&str, Option<&str>)> = raises
+            .iter()
+            .map(|r| (r.exception_type.as_str(), r.condition.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("KeyError", Some("c")),
+                ("IndexError", Some("a and b")),
+                ("TypeError", Some("not a")),
+                ("ValueError", Some("mode == \"w\"")),
+                ("OSError", Some("mode == \"x\"")),
+                (
+                    "RuntimeError",
+                    Some("not (mode == \"r\" or mode == \"w\" or mode == \"x\")")
+                ),
+                ("LookupError", None),
+            ]
+        );
+    }
+
     #[test]
     fn test_extract_qualified_raise() {
         let code = r#"
@@ -682,6 +1646,95 @@ def foo():
         assert!(calls.contains(&"module.func".to_string()));
     }
 
+    #[test]
+    fn test_extract_awaited_calls() {
+        let code = r#"
+async def foo(tg):
+    await bar()
+    baz()
+    await asyncio.gather(one(), two(x))
+    tg.create_task(three())
+    task = asyncio.create_task(four())
+    await task
+"#;
+        let tree = parse_python(code);
+        let context = CallContext {
+            current_module: String::new(),
+            current_class: None,
+            imports: HashMap::new(),
+        };
+        let (calls, awaited) = extract_calls_and_awaits_in_range_with_context(&tree, code, 2, 8, &context).unwrap();
+
+        assert_eq!(calls, vec!["bar", "baz", "one", "two", "three", "four"]);
+        assert_eq!(awaited, vec!["bar", "one", "two", "three", "four"]);
+    }
+
+    #[test]
+    fn test_extract_type_annotations() {
+        let code = r#"
+def a() -> Optional[User]:
+    pass
+
+def b() -> typing.Union[User, None]:
+    pass
+
+def c() -> User | None:
+    pass
+
+def d() -> dict[str, User]:
+    pass
+
+def e():
+    pass
+"#;
+        let tree = parse_python(code);
+        let annotation = |line| extract_type_annotations(&tree, code, line);
+
+        assert_eq!(annotation(2), Some(ReturnAnnotation::Optional("Optional[User]".to_string())));
+        assert_eq!(annotation(5), Some(ReturnAnnotation::Optional("typing.Union[User, None]".to_string())));
+        assert_eq!(annotation(8), Some(ReturnAnnotation::Optional("User | None".to_string())));
+        assert_eq!(annotation(11), Some(ReturnAnnotation::Other("dict[str, User]".to_string())));
+        assert_eq!(annotation(14), None);
+    }
+
+    #[test]
+    fn test_extract_catches() {
+        let code = r#"
+class Client:
+    def get(self):
+        try:
+            fetch()
+        except (KeyError, requests.Timeout) as e:
+            raise
+        except:
+            pass
+
+try:
+    import ujson
+except ImportError:
+    ujson = None
+"#;
+        let tree = parse_python(code);
+        let catches = extract_catches(&tree, code, Path::new("client.py"));
+        let summary: Vec<(&str, u32, Option<&str>, bool)> = catches
+            .iter()
+            .map(|c| {
+                (c.caught_type.as_str(), c.location.line, c.location.containing_function.as_deref(), c.reraises())
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("KeyError", 6, Some("Client.get"), true),
+                ("requests.Timeout", 6, Some("Client.get"), true),
+                ("*", 8, Some("Client.get"), false),
+                ("ImportError", 13, None, false),
+            ]
+        );
+        assert_eq!(catches[2].strategy, HandlerStrategy::Suppresses);
+    }
+
     #[test]
     fn test_extract_calls_in_range() {
         let code = r#"
@@ -729,4 +1782,31 @@ import json as j
         assert_eq!(imports.get("path"), Some(&"os.path".to_string()));
         assert_eq!(imports.get("j"), Some(&"json".to_string()));
     }
+
+    #[test]
+    fn test_extract_imports_skips_type_checking() {
+        let code = r#"
+from typing import TYPE_CHECKING
+import typing
+
+if TYPE_CHECKING:
+    from heavy.lib import HeavyLib
+if typing.TYPE_CHECKING:
+    import numpy as np
+else:
+    import json as np
+if DEBUG:
+    from debug import trace
+"#;
+        let tree = parse_python(code);
+        let imports = extract_imports(&tree, code);
+        assert_eq!(imports.get("HeavyLib"), None);
+        assert_eq!(imports.get("np"), Some(&"json".to_string()));
+        assert_eq!(imports.get("trace"), Some(&"debug.trace".to_string()));
+
+        let type_checking = extract_type_checking_imports(&tree, code);
+        assert_eq!(type_checking.get("HeavyLib"), Some(&"heavy.lib.HeavyLib".to_string()));
+        assert_eq!(type_checking.get("np"), Some(&"numpy".to_string()));
+        assert_eq!(type_checking.len(), 2);
+    }
 }
@@ -1,3 +1,4 @@
+pub mod archive;
 pub mod extractor;
 pub mod parser;
 pub mod resolver;
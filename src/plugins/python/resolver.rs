@@ -1,4 +1,6 @@
-use crate::core::types::ResolvedFunction;
+use crate::core::types::{PropertyKind, ResolvedFunction};
+use crate::plugins::python::{archive, extractor};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -21,11 +23,51 @@ pub enum ResolveError {
     ParserError(String),
 }
 
+/// Layout of a Python environment directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VenvType {
+    /// Created by `venv`/`virtualenv`; has a `pyvenv.cfg`.
+    Standard,
+    /// A conda environment; has a `conda-meta/` directory.
+    Conda,
+    Unknown,
+}
+
+pub fn detect_venv_type(path: &Path) -> VenvType {
+    if path.join("conda-meta").is_dir() {
+        VenvType::Conda
+    } else if path.join("pyvenv.cfg").is_file() {
+        VenvType::Standard
+    } else {
+        VenvType::Unknown
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ImportInfo {
     pub name: String,
     pub source_module: String,
     pub original_name: Option<String>,
+    /// Imported inside `if TYPE_CHECKING:`, so the name only exists for
+    /// annotations and not at runtime.
+    pub is_type_checking_only: bool,
+}
+
+/// Module-level statements that may hold imports, flagged when they sit in an
+/// `if TYPE_CHECKING:` block.
+fn import_statements<'t>(root: tree_sitter::Node<'t>, content: &str) -> Vec<(tree_sitter::Node<'t>, bool)> {
+    let mut statements = Vec::new();
+    for i in 0..root.child_count() {
+        let Some(child) = root.child(i) else { continue };
+        if extractor::is_type_checking_guard(child, content) {
+            if let Some(block) = child.child_by_field_name("consequence") {
+                statements.extend((0..block.child_count()).filter_map(|j| block.child(j)).map(|n| (n, true)));
+            }
+        } else {
+            statements.push((child, false));
+        }
+    }
+    statements
 }
 
 pub struct PythonResolver {
@@ -33,8 +75,9 @@ pub struct PythonResolver {
     pub site_packages: Vec<PathBuf>,
     pub venv_path: Option<PathBuf>,
     parser: Option<tree_sitter::Parser>,
-    #[allow(dead_code)]
     import_cache: HashMap<PathBuf, Vec<ImportInfo>>,
+    /// `.whl`/`.egg` archives found in each search path, listed on first use.
+    archive_cache: RefCell<HashMap<PathBuf, Vec<PathBuf>>>,
 }
 
 impl PythonResolver {
@@ -50,6 +93,7 @@ impl PythonResolver {
             venv_path: None,
             parser: Some(parser),
             import_cache: HashMap::new(),
+            archive_cache: RefCell::new(HashMap::new()),
         }
     }
 
@@ -65,6 +109,40 @@ impl PythonResolver {
         Ok(Self::new(python_path, site_packages))
     }
 
+    /// Adds a project search path, searched after the existing ones.
+    pub fn add_python_path(&mut self, path: PathBuf) {
+        if !self.python_path.contains(&path) {
+            self.python_path.push(path);
+            self.import_cache.clear();
+        }
+    }
+
+    /// Adds a site-packages directory, e.g. for a package installed mid-session.
+    pub fn add_site_packages(&mut self, path: PathBuf) {
+        if !self.site_packages.contains(&path) {
+            self.site_packages.push(path);
+            self.import_cache.clear();
+        }
+    }
+
+    pub fn remove_python_path(&mut self, path: &Path) {
+        let before = self.python_path.len();
+        self.python_path.retain(|p| p != path);
+        if self.python_path.len() != before {
+            self.import_cache.clear();
+        }
+    }
+
+    /// Re-detects the search paths as `from_environment` does, replacing the
+    /// current ones, e.g. after the venv's `pyvenv.cfg` changed.
+    pub fn reload_environment(&mut self) -> Result<(), ResolveError> {
+        self.site_packages = Self::detect_site_packages()?;
+        self.python_path = Self::detect_python_path();
+        self.import_cache.clear();
+        self.archive_cache.borrow_mut().clear();
+        Ok(())
+    }
+
     fn detect_python_path() -> Vec<PathBuf> {
         let mut paths = Vec::new();
 
@@ -115,33 +193,185 @@ impl PythonResolver {
     }
 
     pub fn find_site_packages(venv: &Path) -> Result<PathBuf, ResolveError> {
+        if detect_venv_type(venv) == VenvType::Conda {
+            if let Some(site_packages) = Self::find_conda_site_packages(venv) {
+                return Ok(site_packages);
+            }
+        }
+
         let lib = venv.join("lib");
-        if !lib.exists() {
-            return Err(ResolveError::ModuleNotFound(format!(
-                "No lib directory in venv: {}",
+        if lib.exists() {
+            for entry in std::fs::read_dir(&lib)? {
+                let entry = entry?;
+                let name = entry.file_name();
+                let name_str = name.to_string_lossy();
+                if name_str.starts_with("python") {
+                    let site_packages = entry.path().join("site-packages");
+                    if site_packages.exists() {
+                        return Ok(site_packages);
+                    }
+                }
+            }
+        }
+
+        Self::search_site_packages(venv).ok_or_else(|| {
+            ResolveError::ModuleNotFound(format!(
+                "No site-packages found in venv: {}",
                 venv.display()
-            )));
+            ))
+        })
+    }
+
+    /// Site-packages of a conda environment. `conda info --json` reports the
+    /// Python version of the base environment; for other environments the
+    /// version comes from the `python-X.Y.Z-*.json` record in `conda-meta/`.
+    pub fn find_conda_site_packages(env: &Path) -> Option<PathBuf> {
+        let from_conda = std::process::Command::new("conda")
+            .args(["info", "--json"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| {
+                conda_info_python_version(&String::from_utf8_lossy(&output.stdout), env)
+            });
+
+        from_conda
+            .or_else(|| conda_meta_python_version(env))
+            .map(|version| conda_site_packages_dir(env, &version))
+            .filter(|dir| dir.is_dir())
+    }
+
+    /// Last-resort search for a `site-packages` directory anywhere under the
+    /// environment's `lib/` (or `Lib/` on Windows).
+    pub fn search_site_packages(venv: &Path) -> Option<PathBuf> {
+        ["lib", "Lib"]
+            .iter()
+            .map(|lib| venv.join(lib))
+            .filter(|lib| lib.is_dir())
+            .flat_map(|lib| {
+                walkdir::WalkDir::new(lib)
+                    .max_depth(3)
+                    .sort_by_file_name()
+                    .into_iter()
+                    .filter_map(|entry| entry.ok())
+            })
+            .find(|entry| entry.file_type().is_dir() && entry.file_name() == "site-packages")
+            .map(|entry| entry.into_path())
+    }
+
+    /// Falls back to a `.pyi` stub when no source defines `qualified_name`.
+    pub fn resolve(&mut self, qualified_name: &str) -> Result<ResolvedFunction, ResolveError> {
+        let bases: Vec<PathBuf> = self.search_paths().into_iter().cloned().collect();
+        self.resolve_in(qualified_name, &bases)
+            .or_else(|e| self.resolve_stub(qualified_name).ok_or(e))
+    }
+
+    /// Resolve `name` from `.pyi` stubs only: `module.pyi` or
+    /// `module/__init__.pyi` next to the source, or the same inside a PEP 561
+    /// `<package>-stubs` directory.
+    pub fn resolve_stub(&mut self, name: &str) -> Option<ResolvedFunction> {
+        let parts: Vec<&str> = name.split('.').collect();
+        for i in (1..parts.len()).rev() {
+            let function_name = parts[i..].join(".");
+            for stub in self.find_stub_modules(&parts[..i]) {
+                if let Some(resolved) = self.find_function_in_stub(&stub, &function_name) {
+                    return Some(resolved);
+                }
+            }
         }
+        None
+    }
 
-        for entry in std::fs::read_dir(&lib)? {
-            let entry = entry?;
-            let name = entry.file_name();
-            let name_str = name.to_string_lossy();
-            if name_str.starts_with("python") {
-                let site_packages = entry.path().join("site-packages");
-                if site_packages.exists() {
-                    return Ok(site_packages);
+    fn find_stub_modules(&self, parts: &[&str]) -> Vec<PathBuf> {
+        let module_subpath: PathBuf = parts.iter().collect();
+        let stub_package: PathBuf = std::iter::once(format!("{}-stubs", parts[0]))
+            .chain(parts[1..].iter().map(|p| p.to_string()))
+            .collect();
+
+        let mut stubs = Vec::new();
+        for base in self.search_paths() {
+            for subpath in [&module_subpath, &stub_package] {
+                let module = base.join(subpath);
+                for candidate in [module.with_extension("pyi"), module.join("__init__.pyi")] {
+                    if candidate.is_file() {
+                        stubs.push(candidate);
+                    }
                 }
             }
         }
+        stubs
+    }
 
-        Err(ResolveError::ModuleNotFound(format!(
-            "No site-packages found in venv: {}",
-            venv.display()
-        )))
+    /// `find_function_in_file` for a stub. Stub bodies are just `...`, and
+    /// `@overload` variants all resolve to the last declaration.
+    pub fn find_function_in_stub(&mut self, path: &Path, name: &str) -> Option<ResolvedFunction> {
+        self.find_function_in_file(path, name).ok().flatten()
     }
 
-    pub fn resolve(&mut self, qualified_name: &str) -> Result<ResolvedFunction, ResolveError> {
+    /// Resolve against every search path separately and return each distinct match,
+    /// in search order (python_path entries first, then site_packages).
+    pub fn resolve_all(&mut self, qualified_name: &str) -> Vec<ResolvedFunction> {
+        let bases: Vec<PathBuf> = self.search_paths().into_iter().cloned().collect();
+        let mut candidates: Vec<ResolvedFunction> = Vec::new();
+
+        for base in bases {
+            if let Ok(resolved) = self.resolve_in(qualified_name, std::slice::from_ref(&base)) {
+                let duplicate = candidates.iter().any(|c| {
+                    c.file_path == resolved.file_path && c.line_start == resolved.line_start
+                });
+                if !duplicate {
+                    candidates.push(resolved);
+                }
+            }
+        }
+
+        candidates
+    }
+
+    /// Resolve to the best candidate, preferring project code over site-packages,
+    /// and warn when the name is ambiguous.
+    pub fn resolve_with_fallback(&mut self, qualified_name: &str) -> Result<ResolvedFunction, ResolveError> {
+        if qualified_name.is_empty() {
+            return Err(ResolveError::InvalidQualifiedName(
+                "Empty qualified name".to_string(),
+            ));
+        }
+
+        let mut candidates = self.resolve_all(qualified_name);
+        if candidates.is_empty() {
+            return self.resolve_stub(qualified_name).ok_or_else(|| {
+                ResolveError::FunctionNotFound(qualified_name.to_string(), "all search paths".to_string())
+            });
+        }
+
+        let best = candidates
+            .iter()
+            .position(|c| self.is_local(&c.file_path))
+            .unwrap_or(0);
+
+        if candidates.len() > 1 {
+            eprintln!(
+                "Warning: {} is ambiguous ({} candidates), using {}",
+                qualified_name,
+                candidates.len(),
+                candidates[best].file_path.display()
+            );
+            for (i, candidate) in candidates.iter().enumerate() {
+                if i != best {
+                    eprintln!("  also found: {}:{}", candidate.file_path.display(), candidate.line_start);
+                }
+            }
+        }
+
+        Ok(candidates.swap_remove(best))
+    }
+
+    fn is_local(&self, file_path: &Path) -> bool {
+        let in_site_packages = self.site_packages.iter().any(|sp| file_path.starts_with(sp));
+        !in_site_packages && self.python_path.iter().any(|p| file_path.starts_with(p))
+    }
+
+    fn resolve_in(&mut self, qualified_name: &str, bases: &[PathBuf]) -> Result<ResolvedFunction, ResolveError> {
         if qualified_name.is_empty() {
             return Err(ResolveError::InvalidQualifiedName(
                 "Empty qualified name".to_string(),
@@ -157,7 +387,7 @@ impl PythonResolver {
             let module_parts = &parts[..i];
             let remaining = &parts[i..];
 
-            if let Some(module_path) = self.resolve_module_path(module_parts) {
+            if let Some(module_path) = self.resolve_module_path_in(module_parts, bases) {
                 let function_name = if remaining.is_empty() {
                     parts.last().unwrap().to_string()
                 } else {
@@ -172,7 +402,7 @@ impl PythonResolver {
                     }
                 }
 
-                if module_path.is_file() {
+                if archive::source_exists(&module_path) {
                     if let Some(resolved) = self.find_function_in_file(&module_path, &function_name)? {
                         return Ok(resolved);
                     }
@@ -184,7 +414,7 @@ impl PythonResolver {
                     module_path.clone()
                 };
 
-                if init_path.exists() {
+                if archive::source_exists(&init_path) {
                     if let Some(resolved) =
                         self.find_in_init_reexport(&init_path, &function_name)?
                     {
@@ -197,14 +427,14 @@ impl PythonResolver {
         let module_parts = &parts[..parts.len() - 1];
         let function_name = parts.last().unwrap();
 
-        if let Some(module_path) = self.resolve_module_path(module_parts) {
+        if let Some(module_path) = self.resolve_module_path_in(module_parts, bases) {
             let file_path = if module_path.is_dir() {
                 module_path.join("__init__.py")
             } else {
                 module_path
             };
 
-            if file_path.exists() {
+            if archive::source_exists(&file_path) {
                 if let Some(resolved) = self.find_function_in_file(&file_path, function_name)? {
                     return Ok(resolved);
                 }
@@ -221,14 +451,19 @@ impl PythonResolver {
         ))
     }
 
-    fn resolve_module_path(&self, parts: &[&str]) -> Option<PathBuf> {
-        let module_subpath = parts.join("/");
+    /// Where module `parts` lives in the first search path that has it: its
+    /// `.py` file, or the directory of a package.
+    pub fn resolve_module_path(&self, parts: &[&str]) -> Option<PathBuf> {
+        let search_paths: Vec<PathBuf> = self.search_paths().into_iter().cloned().collect();
+        self.resolve_module_path_in(parts, &search_paths)
+    }
 
-        let search_paths: Vec<&PathBuf> = self
-            .python_path
-            .iter()
-            .chain(self.site_packages.iter())
-            .collect();
+    fn resolve_module_path_in(&self, parts: &[&str], search_paths: &[PathBuf]) -> Option<PathBuf> {
+        if parts.is_empty() {
+            return None;
+        }
+
+        let module_subpath: PathBuf = parts.iter().collect();
 
         for base in search_paths {
             let dir_path = base.join(&module_subpath);
@@ -239,7 +474,7 @@ impl PythonResolver {
                 }
             }
 
-            let file_path = base.join(format!("{}.py", module_subpath));
+            let file_path = dir_path.with_extension("py");
             if file_path.exists() {
                 return Some(file_path);
             }
@@ -247,7 +482,7 @@ impl PythonResolver {
             if parts.len() > 1 {
                 let parent_parts = &parts[..parts.len() - 1];
                 let last = parts.last().unwrap();
-                let parent_path = base.join(parent_parts.join("/"));
+                let parent_path = base.join(parent_parts.iter().collect::<PathBuf>());
 
                 if parent_path.is_dir() {
                     let file_in_parent = parent_path.join(format!("{}.py", last));
@@ -256,6 +491,12 @@ impl PythonResolver {
                     }
                 }
             }
+
+            let mut archive_cache = self.archive_cache.borrow_mut();
+            let archives = archive_cache.entry(base.clone()).or_insert_with(|| archive::archives_in(base));
+            if let Some(found) = archives.iter().find_map(|zip| archive::find_module(zip, parts)) {
+                return Some(found);
+            }
         }
 
         None
@@ -266,7 +507,7 @@ impl PythonResolver {
         file_path: &Path,
         name: &str,
     ) -> Result<Option<ResolvedFunction>, ResolveError> {
-        let content = std::fs::read_to_string(file_path)?;
+        let content = archive::read_source(file_path)?;
 
         let parser = self.parser.as_mut().ok_or_else(|| {
             ResolveError::ParserError("Parser not initialized".to_string())
@@ -419,7 +660,11 @@ impl PythonResolver {
                                 let method_node = if member.kind() == "function_definition" {
                                     Some(member)
                                 } else if member.kind() == "decorated_definition" {
-                                    member.child_by_field_name("definition")
+                                    // `Class.name` means the property getter, not its setter or deleter
+                                    match extractor::property_kind(member, content) {
+                                        Some(PropertyKind::Setter | PropertyKind::Deleter) => None,
+                                        _ => member.child_by_field_name("definition"),
+                                    }
                                 } else {
                                     None
                                 };
@@ -474,14 +719,19 @@ impl PythonResolver {
             return Ok(None);
         }
 
-        if !init_path.exists() {
+        if !archive::source_exists(init_path) {
             return Ok(None);
         }
 
-        let content = std::fs::read_to_string(init_path)?;
+        let content = archive::read_source(init_path)?;
         let imports = self.parse_imports(&content, init_path)?;
 
         for import in imports {
+            // A TYPE_CHECKING import is never bound at runtime, so it doesn't
+            // re-export anything a call could reach.
+            if import.is_type_checking_only {
+                continue;
+            }
             if import.name == name || import.original_name.as_deref() == Some(name) {
                 let target_name = import.original_name.as_deref().unwrap_or(&import.name);
 
@@ -499,7 +749,7 @@ impl PythonResolver {
                         path.clone()
                     };
 
-                    if file_path.exists() {
+                    if archive::source_exists(&file_path) {
                         if let Some(resolved) = self.find_function_in_file(&file_path, target_name)? {
                             return Ok(Some(ResolvedFunction {
                                 function_name: name.to_string(),
@@ -537,73 +787,73 @@ impl PythonResolver {
         let mut imports = Vec::new();
         let root = tree.root_node();
 
-        for i in 0..root.child_count() {
-            if let Some(child) = root.child(i) {
-                if child.kind() == "import_from_statement" {
-                    let mut module_name = String::new();
-                    let mut prefix = String::new();
-                    let mut in_names = false;
-
-                    for j in 0..child.child_count() {
-                        if let Some(c) = child.child(j) {
-                            match c.kind() {
-                                "relative_import" => {
-                                    for k in 0..c.child_count() {
-                                        if let Some(rel_child) = c.child(k) {
-                                            match rel_child.kind() {
-                                                "import_prefix" => {
-                                                    for d in 0..rel_child.child_count() {
-                                                        if let Some(dot) = rel_child.child(d) {
-                                                            if dot.kind() == "." {
-                                                                prefix.push('.');
-                                                            }
+        for (child, is_type_checking_only) in import_statements(root, content) {
+            if child.kind() == "import_from_statement" {
+                let mut module_name = String::new();
+                let mut prefix = String::new();
+                let mut in_names = false;
+
+                for j in 0..child.child_count() {
+                    if let Some(c) = child.child(j) {
+                        match c.kind() {
+                            "relative_import" => {
+                                for k in 0..c.child_count() {
+                                    if let Some(rel_child) = c.child(k) {
+                                        match rel_child.kind() {
+                                            "import_prefix" => {
+                                                for d in 0..rel_child.child_count() {
+                                                    if let Some(dot) = rel_child.child(d) {
+                                                        if dot.kind() == "." {
+                                                            prefix.push('.');
                                                         }
                                                     }
                                                 }
-                                                "dotted_name" => {
-                                                    module_name = content[rel_child.byte_range()].to_string();
-                                                }
-                                                _ => {}
                                             }
+                                            "dotted_name" => {
+                                                module_name = content[rel_child.byte_range()].to_string();
+                                            }
+                                            _ => {}
                                         }
                                     }
                                 }
-                                "dotted_name" => {
-                                    if !in_names {
-                                        if module_name.is_empty() {
-                                            module_name = content[c.byte_range()].to_string();
-                                        }
-                                    } else {
-                                        let name = content[c.byte_range()].to_string();
-                                        if !name.is_empty() {
-                                            imports.push(ImportInfo {
-                                                name,
-                                                source_module: format!("{}{}", prefix, module_name),
-                                                original_name: None,
-                                            });
-                                        }
+                            }
+                            "dotted_name" => {
+                                if !in_names {
+                                    if module_name.is_empty() {
+                                        module_name = content[c.byte_range()].to_string();
                                     }
-                                }
-                                "aliased_import" => {
-                                    let orig = c
-                                        .child_by_field_name("name")
-                                        .map(|n| content[n.byte_range()].to_string());
-                                    let alias = c
-                                        .child_by_field_name("alias")
-                                        .map(|n| content[n.byte_range()].to_string());
-                                    if let Some(name) = alias.or(orig.clone()) {
+                                } else {
+                                    let name = content[c.byte_range()].to_string();
+                                    if !name.is_empty() {
                                         imports.push(ImportInfo {
                                             name,
                                             source_module: format!("{}{}", prefix, module_name),
-                                            original_name: orig,
+                                            original_name: None,
+                                            is_type_checking_only,
                                         });
                                     }
                                 }
-                                "import" => {
-                                    in_names = true;
+                            }
+                            "aliased_import" => {
+                                let orig = c
+                                    .child_by_field_name("name")
+                                    .map(|n| content[n.byte_range()].to_string());
+                                let alias = c
+                                    .child_by_field_name("alias")
+                                    .map(|n| content[n.byte_range()].to_string());
+                                if let Some(name) = alias.or(orig.clone()) {
+                                    imports.push(ImportInfo {
+                                        name,
+                                        source_module: format!("{}{}", prefix, module_name),
+                                        original_name: orig,
+                                        is_type_checking_only,
+                                    });
                                 }
-                                _ => {}
                             }
+                            "import" => {
+                                in_names = true;
+                            }
+                            _ => {}
                         }
                     }
                 }
@@ -627,21 +877,14 @@ impl PythonResolver {
         if module_rest.is_empty() {
             Some(base.join("__init__.py"))
         } else {
-            let subpath = module_rest.replace('.', "/");
-            let file_path = base.join(format!("{}.py", subpath));
-            if file_path.exists() {
+            let subpath: PathBuf = module_rest.split('.').collect();
+            let file_path = base.join(&subpath).with_extension("py");
+            if archive::source_exists(&file_path) {
                 return Some(file_path);
             }
 
-            let dir_path = base.join(&subpath);
-            if dir_path.is_dir() {
-                let init = dir_path.join("__init__.py");
-                if init.exists() {
-                    return Some(init);
-                }
-            }
-
-            None
+            let init = base.join(&subpath).join("__init__.py");
+            archive::source_exists(&init).then_some(init)
         }
     }
 
@@ -653,6 +896,49 @@ impl PythonResolver {
     }
 }
 
+/// `X.Y` from `conda info --json` output, when `env` is the environment that
+/// conda itself runs in.
+fn conda_info_python_version(json: &str, env: &Path) -> Option<String> {
+    let info: serde_json::Value = serde_json::from_str(json).ok()?;
+    let prefix = info.get("sys.prefix").or_else(|| info.get("root_prefix"))?.as_str()?;
+    let same_env = match (Path::new(prefix).canonicalize(), env.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => Path::new(prefix) == env,
+    };
+    if !same_env {
+        return None;
+    }
+    major_minor(info.get("python_version")?.as_str()?)
+}
+
+/// `X.Y` from the `conda-meta/python-X.Y.Z-<build>.json` package record.
+fn conda_meta_python_version(env: &Path) -> Option<String> {
+    std::fs::read_dir(env.join("conda-meta"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .find_map(|name| {
+            let version = name.strip_prefix("python-")?.strip_suffix(".json")?;
+            let version = version.split('-').next()?;
+            version.starts_with(|c: char| c.is_ascii_digit()).then(|| major_minor(version))?
+        })
+}
+
+fn major_minor(version: &str) -> Option<String> {
+    let mut parts = version.split('.');
+    let major = parts.next()?;
+    let minor = parts.next()?;
+    Some(format!("{}.{}", major, minor))
+}
+
+fn conda_site_packages_dir(env: &Path, version: &str) -> PathBuf {
+    if cfg!(windows) {
+        env.join("Lib").join("site-packages")
+    } else {
+        env.join("lib").join(format!("python{}", version)).join("site-packages")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -684,12 +970,66 @@ mod tests {
         assert_eq!(imports.len(), 2);
 
         let get_data_import = imports.iter().find(|i| i.name == "get_data").unwrap();
+        assert!(!get_data_import.is_type_checking_only);
         assert_eq!(get_data_import.source_module, ".api");
 
         let post_data_import = imports.iter().find(|i| i.name == "post_data").unwrap();
         assert_eq!(post_data_import.source_module, ".api");
     }
 
+    #[test]
+    fn test_search_paths_are_mutable() {
+        let mut resolver = PythonResolver::new(vec![PathBuf::from("/project")], vec![]);
+        resolver
+            .import_cache
+            .insert(PathBuf::from("/project/app.py"), Vec::new());
+
+        resolver.add_python_path(PathBuf::from("/project/src"));
+        resolver.add_python_path(PathBuf::from("/project/src"));
+        assert_eq!(resolver.python_path, vec![PathBuf::from("/project"), PathBuf::from("/project/src")]);
+        assert!(resolver.import_cache.is_empty());
+
+        resolver.add_site_packages(PathBuf::from("/venv/lib/python3.12/site-packages"));
+        assert_eq!(resolver.site_packages.len(), 1);
+
+        resolver.remove_python_path(Path::new("/project"));
+        assert_eq!(resolver.python_path, vec![PathBuf::from("/project/src")]);
+        resolver.remove_python_path(Path::new("/missing"));
+        assert_eq!(resolver.python_path.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_type_checking_imports() {
+        let content = "from .api import get_data\n\nif TYPE_CHECKING:\n    from .models import Model as M\n";
+
+        let mut resolver = PythonResolver::new(vec![], vec![]);
+        let imports = resolver.parse_imports(content, Path::new("/fake/__init__.py")).unwrap();
+
+        assert_eq!(imports.len(), 2);
+        assert!(!imports[0].is_type_checking_only);
+        assert_eq!(imports[1].name, "M");
+        assert_eq!(imports[1].source_module, ".models");
+        assert!(imports[1].is_type_checking_only);
+    }
+
+    #[test]
+    fn test_reexport_skips_type_checking_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("pkg");
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(
+            package.join("__init__.py"),
+            "from .api import get_data\n\nif TYPE_CHECKING:\n    from .models import build\n",
+        )
+        .unwrap();
+        std::fs::write(package.join("api.py"), "def get_data():\n    pass\n").unwrap();
+        std::fs::write(package.join("models.py"), "def build():\n    pass\n").unwrap();
+
+        let mut resolver = PythonResolver::new(vec![dir.path().to_path_buf()], vec![]);
+        assert!(resolver.resolve("pkg.get_data").is_ok());
+        assert!(resolver.resolve("pkg.build").is_err());
+    }
+
     #[test]
     fn test_resolve_relative_module() {
         let resolver = PythonResolver::new(vec![], vec![]);
@@ -698,4 +1038,55 @@ mod tests {
         let result = resolver.resolve_relative_import(&init_path, ".api");
         println!("Resolved .api from {:?}: {:?}", init_path, result);
     }
+
+    #[test]
+    fn test_detect_venv_type() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_venv_type(dir.path()), VenvType::Unknown);
+
+        std::fs::write(dir.path().join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+        assert_eq!(detect_venv_type(dir.path()), VenvType::Standard);
+
+        std::fs::create_dir(dir.path().join("conda-meta")).unwrap();
+        assert_eq!(detect_venv_type(dir.path()), VenvType::Conda);
+    }
+
+    #[test]
+    fn test_conda_site_packages_from_conda_meta() {
+        let env = tempfile::tempdir().unwrap();
+        let meta = env.path().join("conda-meta");
+        std::fs::create_dir(&meta).unwrap();
+        std::fs::write(meta.join("python-dateutil-2.9.0-pyhd8ed1ab_0.json"), "{}").unwrap();
+        std::fs::write(meta.join("python-3.11.9-hb806964_0_cpython.json"), "{}").unwrap();
+        assert_eq!(conda_meta_python_version(env.path()), Some("3.11".to_string()));
+
+        let site_packages = conda_site_packages_dir(env.path(), "3.11");
+        std::fs::create_dir_all(&site_packages).unwrap();
+        assert_eq!(PythonResolver::find_site_packages(env.path()).unwrap(), site_packages);
+    }
+
+    #[test]
+    fn test_conda_info_python_version_matches_env() {
+        let env = tempfile::tempdir().unwrap();
+        let json = format!(
+            r#"{{"root_prefix": {:?}, "python_version": "3.12.4.final.0"}}"#,
+            env.path().display().to_string()
+        );
+        assert_eq!(conda_info_python_version(&json, env.path()), Some("3.12".to_string()));
+
+        let other = tempfile::tempdir().unwrap();
+        assert_eq!(conda_info_python_version(&json, other.path()), None);
+        assert_eq!(conda_info_python_version("not json", env.path()), None);
+    }
+
+    #[test]
+    fn test_search_site_packages_fallback() {
+        let env = tempfile::tempdir().unwrap();
+        let nested = env.path().join("lib").join("pypy3.10").join("site-packages");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(PythonResolver::search_site_packages(env.path()), Some(nested.clone()));
+        assert_eq!(PythonResolver::find_site_packages(env.path()).unwrap(), nested);
+        assert!(PythonResolver::search_site_packages(&env.path().join("missing")).is_none());
+    }
 }
@@ -0,0 +1,124 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn arbor(dir: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_arbor"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "arbor {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("app.py"),
+        "def fetch():\n    raise ValueError(\"bad\")\n\n\ndef run():\n    fetch()\n    raise KeyError(\"missing\")\n",
+    )
+    .unwrap();
+    arbor(dir.path(), &["init"]);
+    dir
+}
+
+#[test]
+fn test_analyze_json_stdout_is_valid_json() {
+    let dir = project();
+
+    let output = arbor(
+        dir.path(),
+        &["analyze", "--format", "json", "--exclude-function", "app.skip", "app.fetch", "app.skip", "app.run"],
+    );
+
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<&str> = results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["function_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["app.fetch", "app.run"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Loading database..."));
+    assert!(stderr.contains("Skipping app.skip"));
+    assert!(stderr.contains("Results saved to"));
+}
+
+#[test]
+fn test_init_quiet_hides_environment() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = arbor(dir.path(), &["init", "--quiet"]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Detecting Python environment"));
+    assert!(!stdout.contains("Python version:"));
+    assert!(!stdout.contains("Site-packages:"));
+    assert!(dir.path().join(".arbor/database.json").exists());
+}
+
+#[test]
+fn test_query_risk_filter_requires_all() {
+    let dir = project();
+    arbor(dir.path(), &["analyze", "app.run"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_arbor"))
+        .args(["query", "risk", "app.run", "--filter-risk", "high"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--filter-risk applies to every function"));
+
+    arbor(dir.path(), &["query", "risk", "--all", "--filter-risk", "high"]);
+    let all = arbor(dir.path(), &["query", "risk", "*", "--filter-risk", "low"]);
+    assert!(String::from_utf8_lossy(&all.stdout).contains("app.run"));
+    let high = arbor(dir.path(), &["query", "risk", "*", "--filter-risk", "high"]);
+    assert!(!String::from_utf8_lossy(&high.stdout).contains("app.run"));
+}
+
+#[test]
+fn test_stats_markdown_and_json_count_the_same_exceptions() {
+    let dir = project();
+    std::fs::write(
+        dir.path().join("errors.py"),
+        "import requests\n\n\ndef call():\n    raise requests.exceptions.Timeout()\n\n\ndef retry():\n    try:\n        call()\n    except ValueError:\n        raise\n",
+    )
+    .unwrap();
+    arbor(dir.path(), &["refresh"]);
+    arbor(dir.path(), &["analyze", "app.run", "errors.retry"]);
+
+    let markdown = arbor(dir.path(), &["query", "stats"]);
+    let markdown = String::from_utf8_lossy(&markdown.stdout);
+    let json = arbor(dir.path(), &["query", "stats", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+
+    let unique = json["unique_exceptions"].as_u64().unwrap();
+    assert_eq!(unique, 3);
+    assert!(markdown.contains(&format!("| Unique exceptions | {} |", unique)));
+}
+
+#[test]
+fn test_chain_all_json_reports_missing_exception() {
+    let dir = project();
+    arbor(dir.path(), &["analyze", "app.run"]);
+
+    let found = arbor(dir.path(), &["query", "chain", "app.run", "ValueError", "--all", "--format", "json"]);
+    let paths: serde_json::Value = serde_json::from_slice(&found.stdout).unwrap();
+    assert_eq!(paths[0], serde_json::json!(["app.run", "app.fetch"]));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_arbor"))
+        .args(["query", "chain", "app.run", "TypeError", "--all", "--format", "json"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
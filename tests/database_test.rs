@@ -0,0 +1,85 @@
+use arbor::analysis::indexer::Indexer;
+use arbor::core::database::{ArborDatabase, Environment};
+use arbor::core::types::{CodeLocation, FunctionAnalysis};
+use chrono::{Duration, Utc};
+use std::path::Path;
+
+/// A database for one project root: `shared.py` plus one module of its own,
+/// indexed, with `shared.helper` analyzed at `analyzed_at`.
+fn project_db(root: &Path, own_module: &str, analyzed_at: chrono::DateTime<Utc>) -> ArborDatabase {
+    std::fs::write(root.join("shared.py"), "def helper():\n    pass\n").unwrap();
+    std::fs::write(root.join(format!("{}.py", own_module)), "def run():\n    helper()\n").unwrap();
+
+    let mut db = ArborDatabase::new(Environment {
+        python_path: vec![root.to_string_lossy().to_string()],
+        ..Environment::default()
+    });
+    db.symbol_index = Indexer::new().unwrap().index_directories(&[root.to_path_buf()]).unwrap();
+
+    let mut shared = FunctionAnalysis::new(
+        "shared.helper".to_string(),
+        format!("def helper()  # from {}", own_module),
+        CodeLocation::new(root.join("shared.py"), 1),
+    );
+    shared.analyzed_at = Some(analyzed_at);
+    db.add_function(shared);
+
+    let run_id = format!("{}.run", own_module);
+    db.add_function(FunctionAnalysis::new(
+        run_id.clone(),
+        "def run()".to_string(),
+        CodeLocation::new(root.join(format!("{}.py", own_module)), 1),
+    ));
+    db.dependency_graph.add_call(&run_id, "shared.helper");
+    db
+}
+
+#[test]
+fn test_merge_databases_prefers_newer_analysis() {
+    let api_root = tempfile::tempdir().unwrap();
+    let worker_root = tempfile::tempdir().unwrap();
+    let now = Utc::now();
+
+    let api = project_db(api_root.path(), "api", now - Duration::hours(1));
+    let worker = project_db(worker_root.path(), "worker", now);
+
+    let merged = api.clone().merge(worker.clone());
+    assert_eq!(merged.function_count(), 3);
+    assert_eq!(merged.functions["shared.helper"].signature, "def helper()  # from worker");
+    assert!(merged.symbol_index.contains("api.run"));
+    assert!(merged.symbol_index.contains("worker.run"));
+    assert_eq!(merged.environment.python_path.len(), 2);
+    assert_eq!(
+        merged.dependency_graph.get_callers("shared.helper").unwrap(),
+        &vec!["api.run".to_string(), "worker.run".to_string()]
+    );
+    assert_eq!(merged.symbol_index.file_hashes.len(), 4);
+
+    // Order doesn't matter: the newer analysis still wins
+    let reversed = worker.merge(api);
+    assert_eq!(reversed.functions["shared.helper"].signature, "def helper()  # from worker");
+}
+
+#[test]
+fn test_merge_saved_databases() {
+    let api_root = tempfile::tempdir().unwrap();
+    let worker_root = tempfile::tempdir().unwrap();
+    let now = Utc::now();
+
+    let mut api = project_db(api_root.path(), "api", now);
+    let mut worker = project_db(worker_root.path(), "worker", now - Duration::hours(1));
+    let api_path = api_root.path().join("db.json");
+    let worker_path = worker_root.path().join("db.json");
+    api.save(&api_path).unwrap();
+    worker.save(&worker_path).unwrap();
+
+    let mut merged = ArborDatabase::load(&api_path)
+        .unwrap()
+        .merge(ArborDatabase::load(&worker_path).unwrap());
+    merged.save(&api_path).unwrap();
+
+    let reloaded = ArborDatabase::load(&api_path).unwrap();
+    assert_eq!(reloaded.functions["shared.helper"].signature, "def helper()  # from api");
+    assert!(reloaded.get_function("worker.run").is_some());
+    assert_eq!(reloaded.symbol_index.search("worker.run").len(), 1);
+}
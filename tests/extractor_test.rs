@@ -0,0 +1,260 @@
+use arbor::core::types::{HandlerStrategy, NoneSourceKind};
+use arbor::plugins::python::extractor::{
+    extract_docstring_raises, extract_handlers_in_range, extract_none_sources, extract_raises,
+    parse_docstring_raises, trace_none_flow,
+};
+use arbor::plugins::python::parser::PythonParser;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> (PathBuf, String) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let content = std::fs::read_to_string(&path).unwrap();
+    (path, content)
+}
+
+fn first_message(code: &str) -> Option<String> {
+    let path = Path::new("test.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(code, path).unwrap();
+    let raises = extract_raises(&tree, code, path).unwrap();
+    assert_eq!(raises.len(), 1);
+    raises[0].message.clone()
+}
+
+#[test]
+fn test_message_simple_string() {
+    assert_eq!(
+        first_message("raise ValueError('single quoted')\n"),
+        Some("single quoted".to_string())
+    );
+    assert_eq!(
+        first_message("raise ValueError(\"it's fine\")\n"),
+        Some("it's fine".to_string())
+    );
+}
+
+#[test]
+fn test_message_fstring_keeps_placeholders() {
+    assert_eq!(
+        first_message("raise ValueError(f\"Error: {value}\")\n"),
+        Some("Error: {value}".to_string())
+    );
+    assert_eq!(
+        first_message("raise ValueError(f'{a!r} is not {b:>10}')\n"),
+        Some("{a!r} is not {b:>10}".to_string())
+    );
+}
+
+#[test]
+fn test_message_triple_quoted() {
+    assert_eq!(
+        first_message("raise ValueError(\"\"\"multi\nline\"\"\")\n"),
+        Some("multi\nline".to_string())
+    );
+    assert_eq!(
+        first_message("raise ValueError('''triple single''')\n"),
+        Some("triple single".to_string())
+    );
+}
+
+#[test]
+fn test_message_prefixed_strings() {
+    assert_eq!(
+        first_message("raise ValueError(b'bytes message')\n"),
+        Some("bytes message".to_string())
+    );
+    assert_eq!(
+        first_message("raise ValueError(r\"raw \\d+\")\n"),
+        Some("raw \\d+".to_string())
+    );
+    assert_eq!(
+        first_message("raise ValueError(rf\"raw {x}\")\n"),
+        Some("raw {x}".to_string())
+    );
+}
+
+#[test]
+fn test_message_concatenated_strings() {
+    assert_eq!(
+        first_message("raise ValueError(\"part1\" \" part2\")\n"),
+        Some("part1 part2".to_string())
+    );
+    assert_eq!(
+        first_message("raise ValueError(\n    \"value \"\n    f\"{x} too large\"\n)\n"),
+        Some("value {x} too large".to_string())
+    );
+}
+
+#[test]
+fn test_message_empty_and_missing() {
+    assert_eq!(first_message("raise ValueError('')\n"), Some(String::new()));
+    assert_eq!(first_message("raise ValueError(code)\n"), None);
+}
+
+#[test]
+fn test_extract_handlers() {
+    let code = r#"
+def f():
+    try:
+        g()
+    except ValueError as e:
+        raise
+    except (KeyError, errors.NotFound):
+        return None
+    except:
+        pass
+"#;
+    let mut parser = PythonParser::new().unwrap();
+    let path = Path::new("test.py");
+    let tree = parser.parse_str(code, path).unwrap();
+    let handlers = extract_handlers_in_range(&tree, code, path, 1, 10).unwrap();
+
+    assert_eq!(handlers.len(), 3);
+    assert_eq!(handlers[0].caught_types, vec!["ValueError"]);
+    assert_eq!(handlers[0].strategy, HandlerStrategy::Reraise);
+    assert_eq!(handlers[1].caught_types, vec!["KeyError", "errors.NotFound"]);
+    assert_eq!(handlers[1].strategy, HandlerStrategy::Returns);
+    assert!(handlers[2].caught_types.is_empty());
+    assert_eq!(handlers[2].strategy, HandlerStrategy::Suppresses);
+    assert_eq!((handlers[0].try_line_start, handlers[0].try_line_end), (4, 4));
+}
+
+#[test]
+fn test_match_arm_none_sources() {
+    let (path, content) = fixture("match_statements.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(&content, &path).unwrap();
+    let sources = extract_none_sources(&tree, &content, &path).unwrap();
+
+    let arms: Vec<(u32, Option<&str>)> = sources
+        .iter()
+        .filter(|s| s.kind == NoneSourceKind::MatchArm)
+        .map(|s| (s.location.line, s.condition.as_deref()))
+        .collect();
+
+    assert_eq!(
+        arms,
+        vec![
+            (7, Some(r#"case {"action": "get", "key": key} if key"#)),
+            (11, Some(r#"case "quit" | "exit""#)),
+            (22, Some("case int()")),
+        ]
+    );
+    assert!(!sources.iter().any(|s| s.kind == NoneSourceKind::ExplicitReturn));
+}
+
+#[test]
+fn test_raises_inside_case_clauses() {
+    let (path, content) = fixture("match_statements.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(&content, &path).unwrap();
+    let raises = extract_raises(&tree, &content, &path).unwrap();
+
+    let found: Vec<(&str, Option<&str>)> = raises
+        .iter()
+        .map(|r| (r.exception_type.as_str(), r.condition.as_deref()))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            ("ValueError", Some("case [first, second]")),
+            ("TypeError", Some("case _")),
+        ]
+    );
+}
+
+#[test]
+fn test_raise_source_context_window() {
+    let code = "def f(x):\n    a = 1\n    b = 2\n    if x:\n        raise ValueError()\n    return a\n";
+    let path = Path::new("test.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(code, path).unwrap();
+    let raises = extract_raises(&tree, code, path).unwrap();
+
+    let context = raises[0].source_context.as_ref().unwrap();
+    assert_eq!(context.len(), 4);
+    assert_eq!(context[0], "    b = 2");
+    assert_eq!(context[2], "        raise ValueError()");
+    assert_eq!(context[3], "    return a");
+
+    let tree = parser.parse_str("raise KeyError()\n", path).unwrap();
+    let raises = extract_raises(&tree, "raise KeyError()\n", path).unwrap();
+    assert_eq!(raises[0].source_context, Some(vec!["raise KeyError()".to_string()]));
+}
+
+#[test]
+fn test_none_source_variable_names() {
+    let code = "def f(d, k):\n    result = d.get(k)\n    self.cached = d.pop(k)\n    a, b = d.get(k)\n    use(d.get(k))\n";
+    let path = Path::new("test.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(code, path).unwrap();
+    let sources = extract_none_sources(&tree, code, path).unwrap();
+
+    let names: Vec<Option<&str>> = sources.iter().map(|s| s.variable_name.as_deref()).collect();
+    assert_eq!(names, vec![Some("result"), Some("self.cached"), None, None]);
+}
+
+#[test]
+fn test_trace_none_flow() {
+    let code = "\
+def f(d, k):
+    x = d.get(k)
+    def inner():
+        return x
+    y = x.strip() if x else ''
+    z = 1
+    w = y
+    return w
+
+def g(d):
+    v = d.get('a')
+    log(v)
+    return None
+";
+    let path = Path::new("test.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(code, path).unwrap();
+    let sources = extract_none_sources(&tree, code, path).unwrap();
+
+    let source = sources.iter().find(|s| s.variable_name.as_deref() == Some("x")).unwrap();
+    let flow = trace_none_flow(&tree, code, source);
+    let steps: Vec<(&str, u32)> = flow.assignments.iter().map(|(name, loc)| (name.as_str(), loc.line)).collect();
+    assert_eq!(steps, vec![("x", 2), ("y", 5), ("w", 7)]);
+    assert_eq!(flow.return_location.map(|loc| loc.line), Some(8));
+
+    // Used but never returned
+    let source = sources.iter().find(|s| s.variable_name.as_deref() == Some("v")).unwrap();
+    let flow = trace_none_flow(&tree, code, source);
+    assert_eq!(flow.assignments.len(), 1);
+    assert_eq!(flow.return_location, None);
+
+    // A `return None` is its own return
+    let source = sources.iter().find(|s| s.kind == NoneSourceKind::ExplicitReturn).unwrap();
+    let flow = trace_none_flow(&tree, code, source);
+    assert_eq!(flow.return_location.map(|loc| loc.line), Some(13));
+}
+
+#[test]
+fn test_parse_docstring_raises_styles() {
+    let google = "Load it.\n\nRaises:\n    ValueError: if bad.\n    errors.ApiError: on failure\n        that spans lines.\n\nReturns:\n    x";
+    assert_eq!(parse_docstring_raises(google), vec!["ValueError", "errors.ApiError"]);
+
+    let numpy = "Load it.\n\nRaises\n------\nKeyError\n    If missing.\nOSError\n\nReturns\n-------\nint";
+    assert_eq!(parse_docstring_raises(numpy), vec!["KeyError", "OSError"]);
+
+    let sphinx = "Load it.\n\n:param x: input\n:raises ValueError: if bad\n:raise TypeError, KeyError: otherwise";
+    assert_eq!(parse_docstring_raises(sphinx), vec!["ValueError", "TypeError", "KeyError"]);
+
+    assert!(parse_docstring_raises("Nothing raised here.").is_empty());
+}
+
+#[test]
+fn test_extract_docstring_raises() {
+    let code = "def process(path):\n    \"\"\"Process.\n\n    Raises:\n        OSError: if unreadable\n    \"\"\"\n    open(path)\n\ndef bare():\n    pass\n";
+    let path = Path::new("test.py");
+    let mut parser = PythonParser::new().unwrap();
+    let tree = parser.parse_str(code, path).unwrap();
+    assert_eq!(extract_docstring_raises(&tree, code, 1), vec!["OSError"]);
+    assert!(extract_docstring_raises(&tree, code, 9).is_empty());
+}
@@ -0,0 +1,26 @@
+"""Functions whose return annotations allow None without returning it."""
+from typing import Optional, Union
+
+USERS = {"admin": 1}
+
+
+def find_optional(name) -> Optional[int]:
+    return USERS[name]
+
+
+def find_union(name) -> Union[int, None]:
+    return USERS[name]
+
+
+def find_pipe(name) -> int | None:
+    return USERS[name]
+
+
+def find_plain(name) -> int:
+    return USERS[name]
+
+
+def find_returns_none(name) -> Optional[int]:
+    if name not in USERS:
+        return None
+    return USERS[name]
@@ -0,0 +1,36 @@
+"""Coroutines whose exceptions propagate through await and task spawners."""
+import asyncio
+
+
+async def fetch(url):
+    if not url:
+        raise ValueError("empty url")
+    return url
+
+
+async def store(item):
+    raise KeyError(item)
+
+
+def validate(item):
+    if item is None:
+        raise TypeError("item is required")
+
+
+async def sync_step(item):
+    validate(item)
+
+
+async def fetch_one(url):
+    return await fetch(url)
+
+
+async def fetch_all(urls):
+    return await asyncio.gather(fetch(urls[0]), store(urls[1]))
+
+
+async def run_tasks(item):
+    async with asyncio.TaskGroup() as tg:
+        tg.create_task(store(item))
+    task = asyncio.create_task(sync_step(item))
+    await task
@@ -0,0 +1,28 @@
+"""Classes whose constructors raise."""
+
+
+class Account:
+    def __init__(self, balance):
+        if balance < 0:
+            raise ValueError("balance must be non-negative")
+        self.balance = balance
+
+    def withdraw(self, amount):
+        if amount > self.balance:
+            raise KeyError("insufficient funds")
+        self.balance -= amount
+
+
+def open_account(balance):
+    return Account(balance)
+
+
+class ledger:
+    def __init__(self, owner):
+        if not owner:
+            raise TypeError("ledger needs an owner")
+        self.owner = owner
+
+
+def open_ledger(owner):
+    return ledger(owner)
@@ -0,0 +1,38 @@
+"""Exceptions caught at different points of a call chain."""
+
+
+def parse_port(value):
+    if not value.isdigit():
+        raise ValueError("port must be numeric")
+    return int(value)
+
+
+def lookup(settings, key):
+    if key not in settings:
+        raise KeyError(key)
+    return settings[key]
+
+
+def load_port(settings):
+    try:
+        return parse_port(lookup(settings, "port"))
+    except ValueError:
+        return 8080
+
+
+def load_timeout(settings):
+    try:
+        if "timeout" not in settings:
+            raise RuntimeError("timeout missing")
+    except RuntimeError:
+        pass
+    try:
+        return lookup(settings, "timeout")
+    except LookupError:
+        raise
+
+
+def configure(settings):
+    port = load_port(settings)
+    timeout = load_timeout(settings)
+    return port, timeout
@@ -0,0 +1,23 @@
+"""Python 3.10 structural pattern matching."""
+
+
+def handle_command(command):
+    match command:
+        case {"action": "get", "key": key} if key:
+            return None
+        case [first, second]:
+            raise ValueError("pairs are not supported")
+        case "quit" | "exit":
+            return
+        case str(name):
+            return name.upper()
+        case _:
+            raise TypeError("unknown command")
+
+
+def describe(value):
+    match value:
+        case 0:
+            return "zero"
+        case int():
+            print("integer")
@@ -0,0 +1,31 @@
+"""Property accessors that raise or return None."""
+
+
+class Config:
+    def __init__(self, values):
+        self._values = values
+
+    @property
+    def port(self):
+        if "port" not in self._values:
+            return None
+        return self._values["port"]
+
+    @port.setter
+    def port(self, value):
+        if value < 0:
+            raise ValueError("port must be positive")
+        self._values["port"] = value
+
+    @port.deleter
+    def port(self):
+        raise AttributeError("port cannot be deleted")
+
+    def describe(self):
+        return "port=%s" % self.port
+
+    def reset(self, value):
+        self.port = value
+
+    def clear(self):
+        del self.port
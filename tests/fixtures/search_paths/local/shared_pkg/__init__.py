@@ -0,0 +1 @@
+"""Project-local copy of shared_pkg."""
@@ -0,0 +1,5 @@
+"""Local helpers."""
+
+
+def shared_function():
+    raise ValueError("local implementation")
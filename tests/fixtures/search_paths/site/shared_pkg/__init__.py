@@ -0,0 +1 @@
+"""Installed copy of shared_pkg."""
@@ -0,0 +1,9 @@
+"""Installed helpers."""
+
+
+def other_function():
+    return None
+
+
+def shared_function():
+    raise RuntimeError("installed implementation")
@@ -0,0 +1,19 @@
+"""Module with non-ASCII identifiers."""
+
+
+def 处理数据(值):
+    if 值 is None:
+        raise ValueError("值不能为空")
+    return 值
+
+
+def café():
+    return None
+
+
+class 数据类:
+    def 方法(self):
+        raise KeyError("缺失")
+
+    def 调用(self):
+        return self.方法()
@@ -0,0 +1,5 @@
+"""Module whose file name is non-ASCII."""
+
+
+def 函数():
+    raise RuntimeError("模块错误")
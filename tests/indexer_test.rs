@@ -1,4 +1,5 @@
 use arbor::analysis::indexer::Indexer;
+use arbor::core::types::PropertyKind;
 use std::path::PathBuf;
 
 fn fixtures_path() -> PathBuf {
@@ -54,3 +55,238 @@ fn test_index_file_hashes() {
     assert!(!index.file_hashes.is_empty());
     assert!(index.indexed_at.is_some());
 }
+
+#[test]
+fn test_index_unicode_identifiers() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    assert!(index.contains("unicode_names.处理数据"));
+    assert!(index.contains("unicode_names.café"));
+    assert!(index.contains("unicode_names.数据类"));
+    assert!(index.contains("unicode_names.数据类.方法"));
+    assert!(index.contains("模块.函数"));
+
+    let method = index.get("unicode_names.数据类.方法").unwrap();
+    assert!(method.is_method);
+    assert_eq!(method.parent_class, Some("数据类".to_string()));
+}
+
+#[test]
+fn test_index_property_accessors() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let getter = index.get("properties.Config.port").unwrap();
+    assert!(getter.is_property);
+    assert_eq!(getter.property_kind, Some(PropertyKind::Getter));
+    assert_eq!(getter.line_start, 9);
+
+    let setter = index.get("properties.Config.port.setter").unwrap();
+    assert_eq!(setter.property_kind, Some(PropertyKind::Setter));
+    assert_eq!(setter.line_start, 15);
+
+    let deleter = index.get("properties.Config.port.deleter").unwrap();
+    assert_eq!(deleter.property_kind, Some(PropertyKind::Deleter));
+
+    let method = index.get("properties.Config.describe").unwrap();
+    assert!(!method.is_property);
+    assert_eq!(method.property_kind, None);
+}
+
+#[test]
+fn test_index_name_columns() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    // `class Config:` - the column points at the name, not the keyword
+    let class = index.get("properties.Config").unwrap();
+    assert_eq!((class.column_start, class.column_end), (Some(6), Some(12)));
+
+    // `    def port(self):`
+    let getter = index.get("properties.Config.port").unwrap();
+    assert_eq!((getter.column_start, getter.column_end), (Some(8), Some(12)));
+    let location = getter.to_code_location();
+    assert_eq!((location.line, location.column), (9, Some(8)));
+    assert!(location.to_string_short().ends_with("properties.py:9:8"));
+}
+
+#[test]
+fn test_reindex_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("app.py");
+    std::fs::write(&file, "def one():\n    pass\n\ndef two():\n    pass\n").unwrap();
+
+    let mut indexer = Indexer::new().unwrap();
+    let mut index = indexer.index_directories(&[dir.path().to_path_buf()]).unwrap();
+    assert_eq!(index.len(), 2);
+    let old_hash = index.file_hashes[&file].clone();
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_python::LANGUAGE.into()).unwrap();
+
+    std::fs::write(&file, "def one():\n    pass\n").unwrap();
+    assert_eq!(index.reindex_file(&file, dir.path(), &mut parser).unwrap(), -1);
+    assert!(index.contains("app.one"));
+    assert!(!index.contains("app.two"));
+    assert!(index.search("two").is_empty());
+    assert_ne!(index.file_hashes[&file], old_hash);
+
+    std::fs::write(&file, "def one():\n    pass\n\nclass Three:\n    def four(self):\n        pass\n").unwrap();
+    assert_eq!(index.reindex_file(&file, dir.path(), &mut parser).unwrap(), 2);
+    assert!(index.contains("app.Three.four"));
+
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(index.reindex_file(&file, dir.path(), &mut parser).unwrap(), -3);
+    assert!(index.is_empty());
+    assert!(!index.file_hashes.contains_key(&file));
+}
+
+#[test]
+fn test_index_directories_incremental() {
+    let dir = tempfile::tempdir().unwrap();
+    let edited = dir.path().join("edited.py");
+    let untouched = dir.path().join("untouched.py");
+    let deleted = dir.path().join("deleted.py");
+    std::fs::write(&edited, "def old():\n    pass\n").unwrap();
+    std::fs::write(&untouched, "def stable():\n    pass\n").unwrap();
+    std::fs::write(&deleted, "def gone():\n    pass\n").unwrap();
+
+    let dirs = [dir.path().to_path_buf()];
+    let mut existing = Indexer::new().unwrap().index_directories(&dirs).unwrap();
+    // Re-parsing untouched.py would reset this, so it shows the entry was reused
+    existing.symbols.get_mut("untouched.stable").unwrap().line_start = 99;
+
+    std::fs::write(&edited, "def new():\n    pass\n").unwrap();
+    std::fs::write(dir.path().join("added.py"), "def fresh():\n    pass\n").unwrap();
+    std::fs::remove_file(&deleted).unwrap();
+
+    let index = Indexer::new().unwrap().index_directories_incremental(&dirs, &existing).unwrap();
+    assert_eq!(index.get("untouched.stable").unwrap().line_start, 99);
+    assert!(index.contains("edited.new"));
+    assert!(!index.contains("edited.old"));
+    assert!(index.contains("added.fresh"));
+    assert!(!index.contains("deleted.gone"));
+    assert!(!index.file_hashes.contains_key(&deleted));
+    assert_ne!(index.file_hashes[&edited], existing.file_hashes[&edited]);
+    assert_eq!(index.file_hashes[&untouched], existing.file_hashes[&untouched]);
+    assert!(index.search("old").is_empty());
+}
+
+#[test]
+fn test_index_max_depth() {
+    let root = tempfile::tempdir().unwrap();
+    let deep = root.path().join("a").join("b");
+    std::fs::create_dir_all(&deep).unwrap();
+    std::fs::write(root.path().join("top.py"), "def top():\n    pass\n").unwrap();
+    std::fs::write(root.path().join("a/mid.py"), "def mid():\n    pass\n").unwrap();
+    std::fs::write(deep.join("deep.py"), "def deep():\n    pass\n").unwrap();
+
+    let dirs = [root.path().to_path_buf()];
+    let limited = Indexer::new().unwrap().with_max_depth(Some(2)).index_directories(&dirs).unwrap();
+    assert!(limited.get("top.top").is_some());
+    assert!(limited.get("a.mid.mid").is_some());
+    assert!(limited.get("a.b.deep.deep").is_none());
+
+    let unbounded = Indexer::new().unwrap().index_directories(&dirs).unwrap();
+    assert!(unbounded.get("a.b.deep.deep").is_some());
+}
+
+#[test]
+fn test_index_max_file_size() {
+    let root = tempfile::tempdir().unwrap();
+    let generated = root.path().join("generated.py");
+    std::fs::write(root.path().join("small.py"), "def small():\n    pass\n").unwrap();
+    std::fs::write(&generated, format!("def big():\n    pass\n{}", "# filler\n".repeat(300))).unwrap();
+
+    let dirs = [root.path().to_path_buf()];
+    let limited = Indexer::new().unwrap().with_max_file_size_kb(Some(1)).index_directories(&dirs).unwrap();
+    assert!(limited.get("small.small").is_some());
+    assert!(limited.get("generated.big").is_none());
+    assert_eq!(limited.skipped_large_files, vec![generated]);
+
+    let unlimited = Indexer::new().unwrap().index_directories(&dirs).unwrap();
+    assert!(unlimited.get("generated.big").is_some());
+    assert!(unlimited.skipped_large_files.is_empty());
+}
+
+fn write_wheel(path: &std::path::Path, files: &[(&str, &str)]) {
+    use std::io::Write;
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, content) in files {
+        zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_index_zip_archives() {
+    let site_packages = tempfile::tempdir().unwrap();
+    let wheel = site_packages.path().join("zipped-1.0-py3-none-any.whl");
+    write_wheel(&wheel, &[
+        ("zipped/__init__.py", "def init_helper():\n    pass\n"),
+        ("zipped/client.py", "class Client:\n    def send(self):\n        raise ValueError()\n"),
+        ("zipped-1.0.dist-info/METADATA", "Name: zipped\n"),
+    ]);
+
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[site_packages.path().to_path_buf()]).unwrap();
+    assert!(index.contains("zipped.init_helper"));
+    assert!(index.contains("zipped.client.Client"));
+    let send = index.get("zipped.client.Client.send").unwrap();
+    assert_eq!(send.file_path, wheel.join("zipped/client.py"));
+    assert_eq!(send.line_start, 2);
+
+    let mut prefixed = arbor::core::database::SymbolIndex::new();
+    indexer.index_zip(&wheel, "vendor", &mut prefixed).unwrap();
+    assert!(prefixed.contains("vendor.zipped.client.Client.send"));
+}
+
+#[test]
+fn test_index_directories_incremental_archives() {
+    let site_packages = tempfile::tempdir().unwrap();
+    let kept = site_packages.path().join("kept-1.0-py3-none-any.whl");
+    let upgraded = site_packages.path().join("upgraded-1.0-py3-none-any.whl");
+    let removed = site_packages.path().join("removed-1.0.egg");
+    write_wheel(&kept, &[("kept/__init__.py", "def stable():\n    pass\n")]);
+    write_wheel(&upgraded, &[("upgraded/__init__.py", "def old():\n    pass\n")]);
+    write_wheel(&removed, &[("removed/__init__.py", "def gone():\n    pass\n")]);
+
+    let dirs = [site_packages.path().to_path_buf()];
+    let mut existing = Indexer::new().unwrap().index_directories(&dirs).unwrap();
+    assert!(existing.file_hashes.contains_key(&kept));
+    // Re-reading the archive would reset this, so it shows the entry was reused
+    existing.symbols.get_mut("kept.stable").unwrap().line_start = 99;
+
+    write_wheel(&upgraded, &[("upgraded/__init__.py", "def new():\n    pass\n")]);
+    std::fs::remove_file(&removed).unwrap();
+
+    let index = Indexer::new().unwrap().index_directories_incremental(&dirs, &existing).unwrap();
+    assert_eq!(index.get("kept.stable").unwrap().line_start, 99);
+    assert!(index.contains("upgraded.new"));
+    assert!(!index.contains("upgraded.old"));
+    assert!(!index.contains("removed.gone"));
+    assert!(!index.file_hashes.contains_key(&removed));
+    assert_ne!(index.file_hashes[&upgraded], existing.file_hashes[&upgraded]);
+}
+
+#[test]
+fn test_index_stub_files() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("both.py"), "def run():\n    pass\n").unwrap();
+    std::fs::write(root.path().join("both.pyi"), "def run() -> None: ...\n").unwrap();
+    std::fs::write(root.path().join("only.pyi"), "def declared() -> int: ...\n").unwrap();
+    let stubs = root.path().join("typed-stubs");
+    std::fs::create_dir(&stubs).unwrap();
+    std::fs::write(stubs.join("__init__.pyi"), "def top() -> None: ...\n").unwrap();
+
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[root.path().to_path_buf()]).unwrap();
+
+    let run = index.get("both.run").unwrap();
+    assert!(run.file_path.ends_with("both.py"));
+    assert!(!run.is_stub);
+    assert!(index.get("only.declared").unwrap().is_stub);
+    assert!(index.get("typed.top").unwrap().is_stub);
+}
@@ -102,3 +102,140 @@ fn test_resolve_reexported_class() {
     assert_eq!(resolved.function_name, "User");
     assert!(resolved.file_path.ends_with("models.py"));
 }
+
+#[test]
+fn test_resolve_unicode_function() {
+    let fixtures = fixtures_path();
+    let mut resolver = PythonResolver::new(vec![fixtures.clone()], vec![]);
+
+    let resolved = resolver.resolve("unicode_names.处理数据").unwrap();
+    assert_eq!(resolved.function_name, "处理数据");
+    assert_eq!(resolved.line_start, 4);
+
+    let resolved = resolver.resolve("unicode_names.café").unwrap();
+    assert_eq!(resolved.function_name, "café");
+}
+
+#[test]
+fn test_resolve_unicode_method_and_module() {
+    let fixtures = fixtures_path();
+    let mut resolver = PythonResolver::new(vec![fixtures.clone()], vec![]);
+
+    let resolved = resolver.resolve("unicode_names.数据类.方法").unwrap();
+    assert_eq!(resolved.function_name, "数据类.方法");
+    assert_eq!(resolved.parent_class, Some("数据类".to_string()));
+
+    let resolved = resolver.resolve("模块.函数").unwrap();
+    assert!(resolved.file_path.ends_with("模块.py"));
+}
+
+#[test]
+fn test_resolve_all_returns_every_candidate() {
+    let search_paths = fixtures_path().join("search_paths");
+    let mut resolver = PythonResolver::new(
+        vec![search_paths.join("local")],
+        vec![search_paths.join("site")],
+    );
+
+    let candidates = resolver.resolve_all("shared_pkg.helpers.shared_function");
+    assert_eq!(candidates.len(), 2);
+    assert!(candidates[0].file_path.starts_with(search_paths.join("local")));
+    assert!(candidates[1].file_path.starts_with(search_paths.join("site")));
+}
+
+#[test]
+fn test_resolve_with_fallback_prefers_local() {
+    let search_paths = fixtures_path().join("search_paths");
+    let mut resolver = PythonResolver::new(
+        vec![search_paths.join("local")],
+        vec![search_paths.join("site")],
+    );
+
+    let resolved = resolver
+        .resolve_with_fallback("shared_pkg.helpers.shared_function")
+        .unwrap();
+    assert!(resolved.file_path.starts_with(search_paths.join("local")));
+    assert_eq!(resolved.line_start, 4);
+}
+
+#[test]
+fn test_resolve_with_fallback_single_candidate() {
+    let search_paths = fixtures_path().join("search_paths");
+    let mut resolver = PythonResolver::new(
+        vec![search_paths.join("local")],
+        vec![search_paths.join("site")],
+    );
+
+    let resolved = resolver
+        .resolve_with_fallback("shared_pkg.helpers.other_function")
+        .unwrap();
+    assert!(resolved.file_path.starts_with(search_paths.join("site")));
+
+    assert!(resolver.resolve_with_fallback("shared_pkg.helpers.missing").is_err());
+}
+
+#[test]
+fn test_resolve_property_to_getter() {
+    let fixtures = fixtures_path();
+    let mut resolver = PythonResolver::new(vec![fixtures.clone()], vec![]);
+
+    let resolved = resolver.resolve("properties.Config.port").unwrap();
+    assert_eq!(resolved.function_name, "Config.port");
+    assert_eq!(resolved.line_start, 9);
+    assert!(resolved.is_method);
+}
+
+#[test]
+fn test_resolve_function_in_egg_archive() {
+    use std::io::Write;
+    let site_packages = tempfile::tempdir().unwrap();
+    let egg = site_packages.path().join("eggy-2.0-py3.8.egg");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&egg).unwrap());
+    for (name, content) in [
+        ("eggy/__init__.py", "from .core import run\n"),
+        ("eggy/core.py", "import os\n\ndef run():\n    raise OSError()\n"),
+    ] {
+        zip.start_file(name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+
+    let mut resolver = PythonResolver::new(vec![], vec![site_packages.path().to_path_buf()]);
+    let resolved = resolver.resolve("eggy.core.run").unwrap();
+    assert_eq!(resolved.file_path, egg.join("eggy/core.py"));
+    assert_eq!(resolved.line_start, 3);
+
+    let reexported = resolver.resolve("eggy.run").unwrap();
+    assert_eq!(reexported.file_path, egg.join("eggy/core.py"));
+}
+
+#[test]
+fn test_resolve_falls_back_to_stubs() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("fastlib.pyi"), "def fetch(url: str) -> bytes: ...\n").unwrap();
+    std::fs::write(root.path().join("partial.py"), "def present():\n    pass\n").unwrap();
+    std::fs::write(
+        root.path().join("partial.pyi"),
+        "def present() -> None: ...\n@overload\ndef extra(x: int) -> int: ...\n@overload\ndef extra(x: str) -> str: ...\n",
+    )
+    .unwrap();
+    let stubs = root.path().join("typed-stubs");
+    std::fs::create_dir(&stubs).unwrap();
+    std::fs::write(stubs.join("api.pyi"), "class Client:\n    def get(self) -> str: ...\n").unwrap();
+
+    let mut resolver = PythonResolver::new(vec![root.path().to_path_buf()], vec![]);
+
+    let fetch = resolver.resolve("fastlib.fetch").unwrap();
+    assert!(fetch.file_path.ends_with("fastlib.pyi"));
+    assert_eq!(fetch.line_start, 1);
+
+    assert!(resolver.resolve("partial.present").unwrap().file_path.ends_with("partial.py"));
+    let extra = resolver.resolve("partial.extra").unwrap();
+    assert!(extra.file_path.ends_with("partial.pyi"));
+    assert_eq!(extra.line_start, 5);
+
+    let get = resolver.resolve_stub("typed.api.Client.get").unwrap();
+    assert!(get.file_path.ends_with("typed-stubs/api.pyi"));
+    assert!(get.is_method);
+    assert!(resolver.resolve_stub("fastlib.missing").is_none());
+}
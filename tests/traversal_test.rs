@@ -1,7 +1,12 @@
-use arbor::analysis::traversal::Traverser;
+use arbor::analysis::traversal::{TraversalError, Traverser};
 use arbor::analysis::indexer::Indexer;
+use arbor::analysis::progress::ProgressReporter;
+use arbor::core::types::{CallGraph, NoneSourceKind, PropertyKind};
 use arbor::plugins::python::resolver::PythonResolver;
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
 
 fn fixtures_path() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
@@ -87,3 +92,549 @@ fn test_exception_definition_lookup() {
     assert!(def_loc.file.to_string_lossy().contains("custom_exceptions.py"));
     assert_eq!(def_loc.line, 4); // CustomError is defined on line 4
 }
+
+#[test]
+fn test_configured_builtin_exceptions_are_not_resolved() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index)
+        .with_builtin_exceptions(vec!["custom_exceptions.Custom*".to_string()]);
+
+    let custom = traverser.analyze_function("custom_exceptions.raise_custom").unwrap();
+    assert_eq!(custom.raises[0].exception_type, "CustomError");
+    assert!(custom.raises[0].definition_location.is_none());
+
+    let network = traverser.analyze_function("custom_exceptions.raise_network").unwrap();
+    assert!(network.raises[0].definition_location.is_some());
+}
+
+#[test]
+fn test_analyze_unicode_function() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("unicode_names.处理数据").unwrap();
+
+    assert_eq!(analysis.raises.len(), 1);
+    assert_eq!(analysis.raises[0].exception_type, "ValueError");
+    assert_eq!(analysis.raises[0].message, Some("值不能为空".to_string()));
+}
+
+#[test]
+fn test_analyze_unicode_self_call() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    // `数据类` has no uppercase form, so the class must come from the index
+    let analysis = traverser.analyze_function("unicode_names.数据类.调用").unwrap();
+
+    assert_eq!(analysis.raises.len(), 1);
+    assert_eq!(analysis.raises[0].exception_type, "KeyError");
+}
+
+struct RecordingReporter {
+    events: Rc<RefCell<Vec<String>>>,
+}
+
+impl ProgressReporter for RecordingReporter {
+    fn on_function_start(&self, function_id: &str, depth: usize) {
+        self.events.borrow_mut().push(format!("start {} {}", function_id, depth));
+    }
+
+    fn on_function_done(&self, function_id: &str, raise_count: usize) {
+        self.events.borrow_mut().push(format!("done {} {}", function_id, raise_count));
+    }
+
+    fn on_file_indexed(&self, _path: &Path) {
+        self.events.borrow_mut().push("file".to_string());
+    }
+
+    fn on_complete(&self, total: usize) {
+        self.events.borrow_mut().push(format!("complete {}", total));
+    }
+
+    fn on_queue_size_changed(&self, queue_size: usize, visited_size: usize) {
+        self.events.borrow_mut().push(format!("queue {} {}", queue_size, visited_size));
+    }
+}
+
+#[test]
+fn test_progress_reporter_events() {
+    let index_events = Rc::new(RefCell::new(Vec::new()));
+    let mut indexer = Indexer::new().unwrap().with_progress(Box::new(RecordingReporter {
+        events: index_events.clone(),
+    }));
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    assert!(index_events.borrow().iter().any(|e| e == "file"));
+    assert_eq!(
+        index_events.borrow().last(),
+        Some(&format!("complete {}", index.len()))
+    );
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index)
+        .with_progress(Box::new(RecordingReporter { events: events.clone() }));
+
+    traverser.analyze_function("exceptions_and_none.simple_raise").unwrap();
+
+    let events = events.borrow();
+    assert_eq!(events[0], "start exceptions_and_none.simple_raise 0");
+    assert_eq!(events[1], "queue 0 1");
+    assert!(events.contains(&"done exceptions_and_none.simple_raise 1".to_string()));
+    assert!(events.last().unwrap().starts_with("complete "));
+}
+
+#[test]
+fn test_analyze_timeout() {
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_timeout(Duration::ZERO);
+
+    let result = traverser.analyze_function("exceptions_and_none.call_other_function");
+    assert!(matches!(result, Err(TraversalError::Timeout(1))));
+
+    let analysis = traverser
+        .analyze_function_partial("exceptions_and_none.call_other_function")
+        .unwrap();
+    assert!(analysis.partial);
+    assert_eq!(analysis.functions_traced, 1);
+}
+
+#[test]
+fn test_analyze_without_timeout_is_complete() {
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_timeout(Duration::from_secs(300));
+
+    let analysis = traverser.analyze_function("exceptions_and_none.simple_raise").unwrap();
+    assert!(!analysis.partial);
+}
+
+#[test]
+fn test_depth_limit_records_skipped_functions() {
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 0).unwrap();
+
+    let analysis = traverser.analyze_function("exceptions_and_none.call_other_function").unwrap();
+    assert_eq!(analysis.functions_traced, 1);
+    assert!(analysis.depth_limit_hits > 0);
+    assert_eq!(analysis.depth_limit_hits, analysis.skipped_functions.len());
+    assert!(analysis
+        .skipped_functions
+        .iter()
+        .any(|f| f.ends_with("some_function")));
+}
+
+#[test]
+fn test_no_depth_limit_hits_within_depth() {
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10).unwrap();
+
+    let analysis = traverser.analyze_function("exceptions_and_none.simple_raise").unwrap();
+    assert_eq!(analysis.depth_limit_hits, 0);
+    assert!(analysis.skipped_functions.is_empty());
+}
+
+#[test]
+fn test_class_call_analyzes_constructor() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("constructors.open_account").unwrap();
+
+    let types: Vec<&str> = analysis.raises.iter().map(|r| r.exception_type.as_str()).collect();
+    assert_eq!(types, vec!["ValueError"]);
+
+    let chain = analysis
+        .call_chains
+        .iter()
+        .find(|entry| entry.exception_type == "ValueError")
+        .map(|entry| entry.chain.clone())
+        .unwrap();
+    assert_eq!(
+        chain,
+        vec![
+            "constructors.open_account".to_string(),
+            "constructors.Account(...)".to_string(),
+            "constructors.Account.__init__".to_string(),
+        ]
+    );
+
+    let plan = traverser.plan_function("constructors.open_account");
+    let planned: Vec<&str> = plan.functions.iter().map(|(id, _)| id.as_str()).collect();
+    assert!(planned.contains(&"constructors.Account"));
+    assert!(!planned.contains(&"constructors.Account.__init__"));
+    assert_eq!(plan.max_depth, analysis.call_depth);
+}
+
+#[test]
+fn test_lowercase_class_call_analyzes_constructor() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("constructors.open_ledger").unwrap();
+
+    let types: Vec<&str> = analysis.raises.iter().map(|r| r.exception_type.as_str()).collect();
+    assert_eq!(types, vec!["TypeError"]);
+}
+
+#[test]
+fn test_handlers_mark_suppressed_raises() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    let find = |exc: &str| {
+        analysis
+            .raises
+            .iter()
+            .find(|r| r.exception_type == exc)
+            .unwrap_or_else(|| panic!("{} not found", exc))
+    };
+
+    // Caught by load_port around the call to parse_port
+    let value_error = find("ValueError").suppressed_by.as_ref().unwrap();
+    assert_eq!(
+        value_error.location.containing_function.as_deref(),
+        Some("handlers.load_port")
+    );
+
+    // Raised and caught inside load_timeout
+    let runtime_error = find("RuntimeError").suppressed_by.as_ref().unwrap();
+    assert_eq!(
+        runtime_error.location.containing_function.as_deref(),
+        Some("handlers.load_timeout")
+    );
+
+    // load_port does not catch KeyError and load_timeout re-raises it
+    assert!(analysis
+        .raises
+        .iter()
+        .filter(|r| r.exception_type == "KeyError")
+        .all(|r| r.suppressed_by.is_none()));
+
+    // Every except clause in the traced functions is kept on the analysis
+    assert_eq!(analysis.handlers.len(), 3);
+    assert!(analysis
+        .handlers
+        .iter()
+        .any(|h| h.body.starts_with("except ValueError:") && h.body.contains("return 8080")));
+}
+
+#[test]
+fn test_catches_are_keyed_by_caught_type() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    let mut types: Vec<&String> = analysis.catches.keys().collect();
+    types.sort();
+    assert_eq!(types, vec!["LookupError", "RuntimeError", "ValueError"]);
+
+    let lookup = &analysis.catches["LookupError"];
+    assert_eq!(lookup.len(), 1);
+    assert_eq!(lookup[0].location.line, 31);
+    assert_eq!(lookup[0].location.containing_function.as_deref(), Some("handlers.load_timeout"));
+    assert!(lookup[0].reraises());
+    assert!(!analysis.catches["ValueError"][0].reraises());
+}
+
+#[test]
+fn test_property_accesses_are_traced() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    // Reading the property runs the getter, which can return None
+    let describe = traverser.analyze_function("properties.Config.describe").unwrap();
+    assert!(describe
+        .none_sources
+        .iter()
+        .any(|s| s.kind == NoneSourceKind::ExplicitReturn && s.location.line == 11));
+    assert!(describe.raises.is_empty());
+
+    // Assigning runs the setter, deleting runs the deleter
+    let reset = traverser.analyze_function("properties.Config.reset").unwrap();
+    assert!(reset.raises.iter().any(|r| r.exception_type == "ValueError"));
+    assert!(!reset.raises.iter().any(|r| r.exception_type == "AttributeError"));
+
+    let clear = traverser.analyze_function("properties.Config.clear").unwrap();
+    assert!(clear.raises.iter().any(|r| r.exception_type == "AttributeError"));
+
+    let getter = traverser.analyze_function("properties.Config.port").unwrap();
+    assert_eq!(getter.property_kind, Some(PropertyKind::Getter));
+}
+
+#[test]
+fn test_raises_record_source_depth() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    let depth = |exc: &str| {
+        analysis
+            .raises
+            .iter()
+            .find(|r| r.exception_type == exc)
+            .map(|r| r.source_depth)
+            .unwrap_or_else(|| panic!("{} not found", exc))
+    };
+
+    // configure -> load_timeout raises directly; parse_port and lookup are one call further
+    assert_eq!(depth("RuntimeError"), 1);
+    assert_eq!(depth("ValueError"), 2);
+    assert_eq!(depth("KeyError"), 2);
+}
+
+#[test]
+fn test_source_context_can_be_disabled() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index.clone());
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    assert!(analysis.raises.iter().all(|r| r.source_context.is_some()));
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index)
+        .with_source_context(false);
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    assert!(analysis.raises.iter().all(|r| r.source_context.is_none()));
+}
+
+#[test]
+fn test_plan_function_lists_reachable_functions() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index.clone());
+    let plan = traverser.plan_function("handlers.configure");
+    let names: Vec<&str> = plan.functions.iter().map(|(f, _)| f.as_str()).collect();
+
+    assert_eq!(names.first(), Some(&"handlers.configure"));
+    assert!(names.contains(&"handlers.load_timeout"));
+    assert!(plan.skipped_functions.is_empty());
+
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    assert_eq!(plan.max_depth, analysis.call_depth);
+    assert_eq!(plan.functions.len(), analysis.call_graph_stats.width_at_depth.iter().sum::<usize>());
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut shallow = Traverser::new(resolver, 1)
+        .unwrap()
+        .with_symbol_index(index);
+    let plan = shallow.plan_function("handlers.configure");
+    assert_eq!(plan.max_depth, 1);
+    assert!(!plan.skipped_functions.is_empty());
+}
+
+#[test]
+fn test_call_edges_populate_dependency_graph() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("handlers.configure").unwrap();
+    assert!(analysis
+        .call_edges
+        .contains(&("handlers.configure".to_string(), "handlers.load_port".to_string())));
+
+    let mut graph = CallGraph::new();
+    for (caller, callee) in &analysis.call_edges {
+        graph.add_call(caller, callee);
+    }
+    // Recording the same analysis twice must not duplicate callers
+    for (caller, callee) in &analysis.call_edges {
+        graph.add_call(caller, callee);
+    }
+
+    let mut callers = graph.get_callers("handlers.lookup").cloned().unwrap_or_default();
+    callers.sort();
+    assert_eq!(callers, vec!["handlers.load_port", "handlers.load_timeout"]);
+    assert_eq!(
+        graph.get_callers("handlers.load_port").unwrap(),
+        &vec!["handlers.configure".to_string()]
+    );
+    assert_eq!(
+        graph.transitive_callers("handlers.parse_port"),
+        vec![
+            ("handlers.load_port".to_string(), 1),
+            ("handlers.configure".to_string(), 2),
+        ]
+    );
+}
+
+#[test]
+fn test_call_graph_stats() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("shape.py"),
+        "def root():\n    a()\n    b()\n    root()\n\n\
+         def a():\n    leaf()\n\n\
+         def b():\n    leaf()\n    leaf()\n\n\
+         def leaf():\n    pass\n",
+    )
+    .unwrap();
+
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[dir.path().to_path_buf()]).unwrap();
+    let resolver = PythonResolver::new(vec![dir.path().to_path_buf()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10).unwrap().with_symbol_index(index);
+
+    let stats = traverser.analyze_function("shape.root").unwrap().call_graph_stats;
+    assert_eq!(stats.width_at_depth, vec![1, 2, 1]);
+    // root has 3 unique callees, a and b one each, leaf none
+    assert_eq!(stats.branching_factor, 1.25);
+    assert_eq!(stats.leaf_count, 1);
+    assert_eq!(stats.cycle_count, 1);
+    assert_eq!(stats.summary(), "shape: linear (bf=1.2)");
+}
+
+#[test]
+fn test_stub_functions_are_not_traversed() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("app.py"), "import stubbed\n\ndef run():\n    stubbed.go()\n").unwrap();
+    std::fs::write(
+        root.path().join("stubbed.pyi"),
+        "def go() -> None:\n    helper()\n\ndef helper() -> None:\n    raise ValueError()\n",
+    )
+    .unwrap();
+
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[root.path().to_path_buf()]).unwrap();
+    let resolver = PythonResolver::new(vec![root.path().to_path_buf()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10).unwrap().with_symbol_index(index);
+
+    let analysis = traverser.analyze_function("app.run").unwrap();
+    assert_eq!(analysis.functions_traced, 2);
+    assert!(analysis.raises.is_empty());
+
+    let plan = traverser.plan_function("app.run");
+    let planned: Vec<&str> = plan.functions.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(planned, vec!["app.run", "stubbed.go"]);
+}
+
+#[test]
+fn test_awaited_calls_propagate_raises() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    let one = traverser.analyze_function("async_calls.fetch_one").unwrap();
+    let value_error = one.raises.iter().find(|r| r.exception_type == "ValueError").unwrap();
+    assert!(value_error.via_await);
+    assert!(one
+        .awaited_edges
+        .contains(&("async_calls.fetch_one".to_string(), "async_calls.fetch".to_string())));
+
+    // Each coroutine passed to gather is a callee; gather itself is not
+    let all = traverser.analyze_function("async_calls.fetch_all").unwrap();
+    assert!(all.raises.iter().any(|r| r.exception_type == "ValueError" && r.via_await));
+    assert!(all.raises.iter().any(|r| r.exception_type == "KeyError" && r.via_await));
+    assert!(!all.call_edges.iter().any(|(_, callee)| callee.ends_with("gather")));
+
+    // TaskGroup.create_task and asyncio.create_task; the synchronous hop
+    // below an awaited coroutine is still reached via await
+    let tasks = traverser.analyze_function("async_calls.run_tasks").unwrap();
+    assert!(tasks.raises.iter().any(|r| r.exception_type == "KeyError" && r.via_await));
+    assert!(tasks.raises.iter().any(|r| r.exception_type == "TypeError" && r.via_await));
+    assert_eq!(tasks.awaited_edges.len(), 2);
+
+    let sync = traverser.analyze_function("async_calls.sync_step").unwrap();
+    assert!(sync.raises.iter().all(|r| !r.via_await));
+}
+
+#[test]
+fn test_optional_return_annotation_is_a_none_source() {
+    let mut indexer = Indexer::new().unwrap();
+    let index = indexer.index_directories(&[fixtures_path()]).unwrap();
+
+    let resolver = PythonResolver::new(vec![fixtures_path()], vec![]);
+    let mut traverser = Traverser::new(resolver, 10)
+        .unwrap()
+        .with_symbol_index(index);
+
+    for (function, line) in [("find_optional", 7), ("find_union", 11), ("find_pipe", 15)] {
+        let analysis = traverser.analyze_function(&format!("annotated_returns.{}", function)).unwrap();
+        assert_eq!(analysis.none_sources.len(), 1, "{}", function);
+        let source = &analysis.none_sources[0];
+        assert_eq!(source.kind, NoneSourceKind::AnnotatedOptional);
+        assert_eq!(source.location.line, line);
+        assert!(source.note.as_deref().unwrap().contains("contract allows None"));
+    }
+
+    let plain = traverser.analyze_function("annotated_returns.find_plain").unwrap();
+    assert!(plain.none_sources.is_empty());
+
+    // A real None source makes the annotation redundant
+    let returns_none = traverser.analyze_function("annotated_returns.find_returns_none").unwrap();
+    assert!(returns_none
+        .none_sources
+        .iter()
+        .all(|s| s.kind != NoneSourceKind::AnnotatedOptional));
+}